@@ -1,10 +1,31 @@
-use log::{error, info};
+use log::{error, info, warn};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
-use tauri::State;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use database_structure_sync_lib::diff::{compare_schemas, compare_schemas_cross};
-use database_structure_sync_lib::models::{Connection, ConnectionInput, DiffResult};
+use database_structure_sync_lib::db::{
+    GeneratorRegistry, PostgresSqlGenerator, SqlGenerator, apply_item, apply_with_rollback,
+    execute_with_report, ping, read_tables_tolerant,
+};
+use database_structure_sync_lib::diff;
+use database_structure_sync_lib::diff::{
+    DriftDetector, analyze_impact, compare_grants, compare_routines, compare_schemas,
+    compare_schemas_cross_with_options, compare_schemas_with_options, cross_engine_warning,
+    database_charset_warning, drop_all_statements, duplicate_name_warnings, filter_destructive,
+    generate_script, minimize_statements, order_for_execution, select_safe_changes, to_report,
+    undo_sql, version_compatibility_warnings, write_ndjson,
+};
+use database_structure_sync_lib::logging;
+use database_structure_sync_lib::models::{
+    CompareOptions, Connection, ConnectionInput, ConnectionSecrets, DatabaseInfo,
+    DatabasePairResult, DbType, DiffItem, DiffResult, DriftEvent, ImpactReport, Inconsistency,
+    PingResult, ReportFormat, RollbackReport, SavedComparePreset, SchemaSnapshot, ScriptOptions,
+    SshConfig, StatementResult, SyncEstimate, TableSchema,
+};
+use database_structure_sync_lib::ssh;
 
 use crate::driver::{AppState, DatabaseDriver, create_driver, load_connection};
 
@@ -54,10 +75,12 @@ pub(crate) async fn update_connection(
 ) -> Result<Connection, String> {
     info!("Updating connection: {} ({})", id, input.name);
     let store = state.config_store.lock().await;
-    store.update_connection(&id, input).await.map_err(|e| {
+    let updated = store.update_connection(&id, input).await.map_err(|e| {
         error!("Failed to update connection {}: {}", id, e);
         e.to_string()
-    })
+    })?;
+    state.driver_cache.invalidate(&id).await;
+    Ok(updated)
 }
 
 #[tauri::command]
@@ -70,14 +93,13 @@ pub(crate) async fn delete_connection(
     store.delete_connection(&id).await.map_err(|e| {
         error!("Failed to delete connection {}: {}", id, e);
         e.to_string()
-    })
+    })?;
+    state.driver_cache.invalidate(&id).await;
+    Ok(())
 }
 
 #[tauri::command]
-pub(crate) async fn test_connection(
-    state: State<'_, AppState>,
-    input: ConnectionInput,
-) -> Result<(), String> {
+pub(crate) async fn test_connection(input: ConnectionInput) -> Result<(), String> {
     info!("Testing connection: {} ({})", input.name, input.host);
 
     let temp_conn = Connection {
@@ -91,11 +113,16 @@ pub(crate) async fn test_connection(
         database: input.database,
         ssh_config: input.ssh_config,
         ssl_config: input.ssl_config,
+        session_params: input.session_params,
+        schema_file: input.schema_file,
+        statement_timeout_secs: input.statement_timeout_secs,
+        prompt_for_secrets: false,
         created_at: String::new(),
         updated_at: String::new(),
     };
 
-    let driver = DatabaseDriver::create(&temp_conn, &state.active_tunnels)
+    // The form carries the secrets, whether or not they will be stored.
+    let driver = DatabaseDriver::create(&temp_conn, None)
         .await
         .map_err(|e| {
             error!("Failed to create driver for test: {}", e);
@@ -111,10 +138,56 @@ pub(crate) async fn test_connection(
     Ok(())
 }
 
+/// Open and close an SSH tunnel without touching the database behind it, so a
+/// failing tunneled connection can be narrowed down to the SSH hop or not.
+#[tauri::command]
+pub(crate) async fn test_ssh_tunnel(
+    ssh_config: SshConfig,
+    remote_host: String,
+    remote_port: u16,
+) -> Result<u16, String> {
+    info!(
+        "Testing SSH tunnel via {}:{} to {}:{}",
+        ssh_config.host, ssh_config.port, remote_host, remote_port
+    );
+    let ssh_config = ssh_config.resolve_secrets().map_err(|e| e.to_string())?;
+    ssh::test_ssh_tunnel(&ssh_config, &remote_host, remote_port)
+        .await
+        .map_err(|e| {
+            error!("SSH tunnel test failed: {}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+pub(crate) async fn ping_connection(
+    state: State<'_, AppState>,
+    connection_id: String,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<PingResult, String> {
+    info!("Pinging connection: {}", connection_id);
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &connection_id, "Connection").await?;
+    drop(store);
+
+    let driver = create_driver(&state, &mut conn, None, secrets.as_ref()).await?;
+    let result = ping(driver.as_reader()).await;
+
+    info!(
+        "Ping {}: ok={} in {} ms",
+        connection_id, result.ok, result.latency_ms
+    );
+    Ok(result)
+}
+
 #[tauri::command]
 pub(crate) async fn list_databases(
     state: State<'_, AppState>,
     connection_id: String,
+    name_filter: Option<String>,
+    limit: Option<u32>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
 ) -> Result<Vec<String>, String> {
     info!("Listing databases for connection: {}", connection_id);
 
@@ -122,87 +195,988 @@ pub(crate) async fn list_databases(
     let mut conn = load_connection(&store, &connection_id, "Connection").await?;
     drop(store);
 
-    let driver = create_driver(&mut conn, None, &state.active_tunnels).await?;
-    let databases = driver.as_reader().list_databases().await.map_err(|e| {
-        error!("Failed to list databases: {}", e);
-        e.to_string()
-    })?;
+    let driver = create_driver(&state, &mut conn, None, secrets.as_ref()).await?;
+    let databases = driver
+        .as_reader()
+        .list_databases(name_filter.as_deref(), limit)
+        .await
+        .map_err(|e| {
+            error!("Failed to list databases: {}", e);
+            e.to_string()
+        })?;
 
     info!("Found {} databases", databases.len());
     Ok(databases)
 }
 
+/// Table names only, for populating a table picker before any comparison.
 #[tauri::command]
-pub(crate) async fn compare_databases(
+pub(crate) async fn list_tables(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: Option<String>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<Vec<String>, String> {
+    info!("Listing tables for connection: {}", connection_id);
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &connection_id, "Connection").await?;
+    drop(store);
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    let tables = driver.as_reader().list_tables().await.map_err(|e| {
+        error!("Failed to list tables: {}", e);
+        e.to_string()
+    })?;
+
+    info!("Found {} tables", tables.len());
+    Ok(tables)
+}
+
+/// Round-trip every table through the generator and the DDL parser and report
+/// what comes back different. A test aid for catching generator and parser bugs.
+#[tauri::command]
+pub(crate) async fn self_consistency_check(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: Option<String>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<Vec<Inconsistency>, String> {
+    info!(
+        "Checking generator round trip for connection: {}",
+        connection_id
+    );
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &connection_id, "Connection").await?;
+    drop(store);
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    let tables = driver.as_reader().get_tables().await.map_err(|e| {
+        error!("Failed to read schema: {}", e);
+        e.to_string()
+    })?;
+    let report = diff::self_consistency_check(&tables, conn.db_type).map_err(|e| {
+        error!("Failed to parse generated DDL: {}", e);
+        e.to_string()
+    })?;
+
+    info!(
+        "Round trip of {} tables: {} inconsistencies",
+        tables.len(),
+        report.len()
+    );
+    Ok(report)
+}
+
+/// Save the connection's current tables as a named baseline to diff against later.
+#[tauri::command]
+pub(crate) async fn save_schema_snapshot(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: Option<String>,
+    name: String,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<SchemaSnapshot, String> {
+    info!(
+        "Saving schema snapshot {} of connection: {}",
+        name, connection_id
+    );
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &connection_id, "Connection").await?;
+    drop(store);
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    let tables = driver.as_reader().get_tables().await.map_err(|e| {
+        error!("Failed to read schema: {}", e);
+        e.to_string()
+    })?;
+
+    let store = state.config_store.lock().await;
+    store
+        .save_schema_snapshot(&name, &conn.db_type, &tables)
+        .await
+        .map_err(|e| {
+            error!("Failed to save schema snapshot: {}", e);
+            e.to_string()
+        })
+}
+
+/// Diff the connection's live tables against a saved snapshot. The snapshot is
+/// the source, so the SQL brings the live database back to the baseline.
+#[tauri::command]
+pub(crate) async fn compare_against_snapshot(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: Option<String>,
+    snapshot_id: String,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<DiffResult, String> {
+    info!(
+        "Comparing connection {} against snapshot {}",
+        connection_id, snapshot_id
+    );
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &connection_id, "Connection").await?;
+    let snapshot = store
+        .get_schema_snapshot(&snapshot_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Schema snapshot not found: {}", snapshot_id))?;
+    drop(store);
+    if snapshot.db_type != conn.db_type {
+        return Err(format!(
+            "Snapshot {} is of a {} database, not {}",
+            snapshot.name, snapshot.db_type, conn.db_type
+        ));
+    }
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    let read = read_tables_tolerant(driver.as_reader(), None)
+        .await
+        .map_err(|e| {
+            error!("Failed to read schema: {}", e);
+            e.to_string()
+        })?;
+    // A table the live side couldn't read would otherwise look removed from it.
+    let baseline: Vec<TableSchema> = snapshot
+        .tables
+        .into_iter()
+        .filter(|t| !read.skipped.contains(&t.name))
+        .collect();
+    let registered = state.generators.generator(&conn.db_type);
+    let sql_gen: &dyn SqlGenerator = registered.as_deref().unwrap_or(driver.as_sql_generator());
+    let items = compare_schemas(&baseline, &read.tables, sql_gen);
+    let items = order_for_execution(items, &baseline, sql_gen);
+
+    let mut warnings: Vec<String> = read
+        .warnings
+        .iter()
+        .map(|w| format!("Target: {}", w))
+        .collect();
+    warnings.extend(duplicate_name_warnings("Snapshot", &baseline));
+    warnings.extend(duplicate_name_warnings("Target", &read.tables));
+
+    info!(
+        "Comparison against snapshot {}: {} differences",
+        snapshot.name,
+        items.len()
+    );
+    Ok(DiffResult {
+        items,
+        source_tables: baseline.len(),
+        target_tables: read.tables.len(),
+        warnings,
+        cross_engine: false,
+        source_schema: None,
+        target_schema: None,
+    })
+}
+
+/// SQL that drops every view, table, routine, unowned sequence and enum type in
+/// the database, views and foreign keys first, for wiping a target before
+/// re-creating it. Destructive: the caller must type
+/// the database name back as `confirm_database`. Nothing is executed here.
+#[tauri::command]
+pub(crate) async fn generate_drop_all(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: Option<String>,
+    confirm_database: String,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<Vec<String>, String> {
+    info!(
+        "Generating drop-all script for connection: {}",
+        connection_id
+    );
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &connection_id, "Connection").await?;
+    drop(store);
+    let target_database = database.clone().unwrap_or_else(|| conn.database.clone());
+    if confirm_database != target_database {
+        return Err(format!(
+            "Type the database name ({}) to confirm dropping everything in it",
+            target_database
+        ));
+    }
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    let reader = driver.as_reader();
+    let tables = reader.get_tables().await.map_err(|e| {
+        error!("Failed to read schema: {}", e);
+        e.to_string()
+    })?;
+    let routines = reader.get_routines().await.map_err(|e| {
+        error!("Failed to read routines: {}", e);
+        e.to_string()
+    })?;
+    let objects = reader.list_schema_objects().await.map_err(|e| {
+        error!("Failed to list views, sequences and types: {}", e);
+        e.to_string()
+    })?;
+    let sql_gen = state
+        .generators
+        .generator(&conn.db_type)
+        .ok_or_else(|| format!("No SQL generator is registered for {}", conn.db_type))?;
+
+    let statements = drop_all_statements(&tables, &routines, &objects, sql_gen.as_ref());
+    warn!(
+        "Drop-all script for {}: {} statements over {} tables",
+        target_database,
+        statements.len(),
+        tables.len()
+    );
+    Ok(statements)
+}
+
+#[tauri::command]
+pub(crate) async fn get_database_info(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: Option<String>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<DatabaseInfo, String> {
+    info!("Reading database info for connection: {}", connection_id);
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &connection_id, "Connection").await?;
+    drop(store);
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    driver.as_reader().get_database_info().await.map_err(|e| {
+        error!("Failed to read database info: {}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+pub(crate) async fn get_native_create_table(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: Option<String>,
+    table: String,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<String, String> {
+    info!("Reading native DDL of {} on {}", table, connection_id);
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &connection_id, "Connection").await?;
+    drop(store);
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    driver
+        .as_reader()
+        .native_create_table(&table)
+        .await
+        .map_err(|e| {
+            error!("Failed to read native DDL of {}: {}", table, e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+pub(crate) async fn analyze_diff_item(
+    state: State<'_, AppState>,
+    target_id: String,
+    database: Option<String>,
+    item: DiffItem,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<ImpactReport, String> {
+    info!("Analyzing impact of diff item {} on {}", item.id, target_id);
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &target_id, "Target connection").await?;
+    drop(store);
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    analyze_impact(driver.as_reader(), &item)
+        .await
+        .map_err(|e| {
+            error!("Failed to analyze diff item: {}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+pub(crate) async fn estimate_sync(
     state: State<'_, AppState>,
-    source_id: String,
     target_id: String,
+    database: Option<String>,
+    items: Vec<DiffItem>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<SyncEstimate, String> {
+    info!(
+        "Estimating sync duration of {} item(s) on {}",
+        items.len(),
+        target_id
+    );
+
+    let store = state.config_store.lock().await;
+    let mut conn = load_connection(&store, &target_id, "Target connection").await?;
+    drop(store);
+
+    let driver = create_driver(&state, &mut conn, database, secrets.as_ref()).await?;
+    diff::estimate_sync(driver.as_reader(), &items)
+        .await
+        .map_err(|e| {
+            error!("Failed to estimate sync duration: {}", e);
+            e.to_string()
+        })
+}
+
+/// Both sides of a comparison: connections, live drivers and their schemas.
+struct SchemaPair {
+    source_conn: Connection,
+    source_driver: Arc<DatabaseDriver>,
+    source_tables: Vec<TableSchema>,
+    target_conn: Connection,
+    target_driver: Arc<DatabaseDriver>,
+    target_tables: Vec<TableSchema>,
+    /// Tables that couldn't be read on either side and were left out.
+    read_warnings: Vec<String>,
+}
+
+/// Read all tables, or only the named ones, skipping tables either side can't read.
+async fn fetch_schema_pair(
+    state: &AppState,
+    source_id: &str,
+    target_id: &str,
     source_database: Option<String>,
     target_database: Option<String>,
-) -> Result<DiffResult, String> {
-    info!("Comparing databases: {} -> {}", source_id, target_id);
-
+    tables: Option<&[String]>,
+    secrets: Option<&HashMap<String, ConnectionSecrets>>,
+) -> Result<SchemaPair, String> {
     let store = state.config_store.lock().await;
-    let mut source_conn = load_connection(&store, &source_id, "Source connection").await?;
-    let mut target_conn = load_connection(&store, &target_id, "Target connection").await?;
+    let mut source_conn = load_connection(&store, source_id, "Source connection").await?;
+    let mut target_conn = load_connection(&store, target_id, "Target connection").await?;
     drop(store);
 
     info!(
         "Connecting to source: {} ({})",
         source_conn.name, source_conn.db_type
     );
-    let source_driver =
-        create_driver(&mut source_conn, source_database, &state.active_tunnels).await?;
+    let source_driver = create_driver(state, &mut source_conn, source_database, secrets).await?;
 
     info!(
         "Connecting to target: {} ({})",
         target_conn.name, target_conn.db_type
     );
-    let target_driver =
-        create_driver(&mut target_conn, target_database, &state.active_tunnels).await?;
+    let target_driver = create_driver(state, &mut target_conn, target_database, secrets).await?;
 
+    read_schema_pair(
+        source_conn,
+        source_driver,
+        target_conn,
+        target_driver,
+        tables,
+    )
+    .await
+}
+
+/// Read both sides' tables through already-connected drivers.
+async fn read_schema_pair(
+    source_conn: Connection,
+    source_driver: Arc<DatabaseDriver>,
+    target_conn: Connection,
+    target_driver: Arc<DatabaseDriver>,
+    tables: Option<&[String]>,
+) -> Result<SchemaPair, String> {
     info!("Fetching source schema...");
-    let source_tables = source_driver.as_reader().get_tables().await.map_err(|e| {
-        error!("Failed to get source tables: {}", e);
-        e.to_string()
-    })?;
+    let source = read_tables_tolerant(source_driver.as_reader(), tables)
+        .await
+        .map_err(|e| {
+            error!("Failed to get source tables: {}", e);
+            e.to_string()
+        })?;
 
     info!("Fetching target schema...");
-    let target_tables = target_driver.as_reader().get_tables().await.map_err(|e| {
-        error!("Failed to get target tables: {}", e);
+    let target = read_tables_tolerant(target_driver.as_reader(), tables)
+        .await
+        .map_err(|e| {
+            error!("Failed to get target tables: {}", e);
+            e.to_string()
+        })?;
+
+    // A table skipped on one side would otherwise look added or removed, so it
+    // is left out of the comparison on both.
+    let skipped: HashSet<&str> = source
+        .skipped
+        .iter()
+        .chain(&target.skipped)
+        .map(String::as_str)
+        .collect();
+    let keep = |tables: Vec<TableSchema>| -> Vec<TableSchema> {
+        tables
+            .into_iter()
+            .filter(|t| !skipped.contains(t.name.as_str()))
+            .collect()
+    };
+    let source_tables = keep(source.tables);
+    let target_tables = keep(target.tables);
+    let read_warnings = source
+        .warnings
+        .iter()
+        .map(|w| format!("Source: {}", w))
+        .chain(target.warnings.iter().map(|w| format!("Target: {}", w)))
+        .collect();
+
+    Ok(SchemaPair {
+        source_conn,
+        source_driver,
+        source_tables,
+        target_conn,
+        target_driver,
+        target_tables,
+        read_warnings,
+    })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn compare_databases(
+    state: State<'_, AppState>,
+    source_id: String,
+    target_id: String,
+    source_database: Option<String>,
+    target_database: Option<String>,
+    options: Option<CompareOptions>,
+    include_schemas: Option<bool>,
+    preset_id: Option<String>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<DiffResult, String> {
+    info!("Comparing databases: {} -> {}", source_id, target_id);
+
+    let preset = match preset_id {
+        Some(id) => Some(load_compare_preset(&state, &id).await?),
+        None => None,
+    };
+    let options = match &preset {
+        Some(preset) => preset.options.clone(),
+        None => options.unwrap_or_default(),
+    };
+
+    let pair = fetch_schema_pair(
+        &state,
+        &source_id,
+        &target_id,
+        source_database,
+        target_database,
+        None,
+        secrets.as_ref(),
+    )
+    .await?;
+
+    let mut result = diff_schema_pair(&pair, &state.generators, &options, true).await;
+    if pair.source_conn.db_type == DbType::MySQL && pair.target_conn.db_type == DbType::MySQL {
+        match (
+            pair.source_driver.as_reader().server_version().await,
+            pair.target_driver.as_reader().server_version().await,
+        ) {
+            (Ok(source_version), Ok(target_version)) => {
+                result.warnings.extend(version_compatibility_warnings(
+                    &result.items,
+                    &source_version,
+                    &target_version,
+                ));
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Server versions not compared: {}", e);
+            }
+        }
+    }
+    if preset.as_ref().is_some_and(|p| p.safe_only) {
+        select_safe_changes(&mut result, &pair.source_tables);
+    }
+    if include_schemas.unwrap_or(false) {
+        return Ok(result.with_schemas(pair.source_tables, pair.target_tables));
+    }
+    Ok(result)
+}
+
+/// Compare several databases on the same two servers in one call, e.g. each
+/// tenant's database. Each side reads all its databases through one connection
+/// to its server (per database on Postgres, which can't switch databases).
+#[tauri::command]
+pub(crate) async fn compare_multi_database(
+    state: State<'_, AppState>,
+    source_id: String,
+    target_id: String,
+    db_pairs: Vec<(String, String)>,
+    options: Option<CompareOptions>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<Vec<DatabasePairResult>, String> {
+    info!(
+        "Comparing {} database pair(s): {} -> {}",
+        db_pairs.len(),
+        source_id,
+        target_id
+    );
+    let options = options.unwrap_or_default();
+
+    let store = state.config_store.lock().await;
+    let mut source_conn = load_connection(&store, &source_id, "Source connection").await?;
+    let mut target_conn = load_connection(&store, &target_id, "Target connection").await?;
+    drop(store);
+    let source_server = create_driver(&state, &mut source_conn, None, secrets.as_ref())
+        .await?
+        .server_session()
+        .await
+        .map_err(|e| e.to_string())?;
+    let target_server = create_driver(&state, &mut target_conn, None, secrets.as_ref())
+        .await?
+        .server_session()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut results = Vec::with_capacity(db_pairs.len());
+    for (source_db, target_db) in db_pairs {
+        info!("Comparing database {} -> {}", source_db, target_db);
+        let source_driver = source_server
+            .use_database(&source_db)
+            .await
+            .map_err(|e| e.to_string())?;
+        let target_driver = target_server
+            .use_database(&target_db)
+            .await
+            .map_err(|e| e.to_string())?;
+        let pair = read_schema_pair(
+            Connection {
+                database: source_db.clone(),
+                ..source_conn.clone()
+            },
+            source_driver,
+            Connection {
+                database: target_db.clone(),
+                ..target_conn.clone()
+            },
+            target_driver,
+            None,
+        )
+        .await?;
+        let result = diff_schema_pair(&pair, &state.generators, &options, true).await;
+        results.push(DatabasePairResult {
+            source_db,
+            target_db,
+            result,
+        });
+    }
+    Ok(results)
+}
+
+async fn load_compare_preset(state: &AppState, id: &str) -> Result<SavedComparePreset, String> {
+    let store = state.config_store.lock().await;
+    store
+        .get_compare_preset(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| {
+            error!("Compare preset not found: {}", id);
+            "Compare preset not found".to_string()
+        })
+}
+
+#[tauri::command]
+pub(crate) async fn save_compare_preset(
+    state: State<'_, AppState>,
+    name: String,
+    options: CompareOptions,
+    safe_only: Option<bool>,
+) -> Result<SavedComparePreset, String> {
+    info!("Saving compare preset: {}", name);
+    let store = state.config_store.lock().await;
+    store
+        .save_compare_preset(&name, &options, safe_only.unwrap_or(false))
+        .await
+        .map_err(|e| {
+            error!("Failed to save compare preset: {}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+pub(crate) async fn list_compare_presets(
+    state: State<'_, AppState>,
+) -> Result<Vec<SavedComparePreset>, String> {
+    let store = state.config_store.lock().await;
+    store.list_compare_presets().await.map_err(|e| {
+        error!("Failed to list compare presets: {}", e);
         e.to_string()
-    })?;
+    })
+}
+
+#[tauri::command]
+pub(crate) async fn delete_compare_preset(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    info!("Deleting compare preset: {}", id);
+    let store = state.config_store.lock().await;
+    store.delete_compare_preset(&id).await.map_err(|e| {
+        error!("Failed to delete compare preset: {}", e);
+        e.to_string()
+    })
+}
+
+/// `compare_databases` with only the safe, additive changes selected, for
+/// bringing a target up to date without locking or failing on existing rows.
+#[tauri::command]
+pub(crate) async fn compare_databases_safe(
+    state: State<'_, AppState>,
+    source_id: String,
+    target_id: String,
+    source_database: Option<String>,
+    target_database: Option<String>,
+    options: Option<CompareOptions>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<DiffResult, String> {
+    info!(
+        "Comparing databases (safe changes): {} -> {}",
+        source_id, target_id
+    );
+
+    let pair = fetch_schema_pair(
+        &state,
+        &source_id,
+        &target_id,
+        source_database,
+        target_database,
+        None,
+        secrets.as_ref(),
+    )
+    .await?;
+
+    let mut result =
+        diff_schema_pair(&pair, &state.generators, &options.unwrap_or_default(), true).await;
+    select_safe_changes(&mut result, &pair.source_tables);
+    Ok(result)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn compare_tables_subset(
+    state: State<'_, AppState>,
+    source_id: String,
+    target_id: String,
+    table_names: Vec<String>,
+    source_database: Option<String>,
+    target_database: Option<String>,
+    options: Option<CompareOptions>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<DiffResult, String> {
+    info!(
+        "Comparing {} table(s): {} -> {}",
+        table_names.len(),
+        source_id,
+        target_id
+    );
+
+    let pair = fetch_schema_pair(
+        &state,
+        &source_id,
+        &target_id,
+        source_database,
+        target_database,
+        Some(&table_names),
+        secrets.as_ref(),
+    )
+    .await?;
+
+    Ok(diff_schema_pair(
+        &pair,
+        &state.generators,
+        &options.unwrap_or_default(),
+        false,
+    )
+    .await)
+}
 
+/// Diff an already-read schema pair, picking the same- or cross-engine comparison.
+/// With `include_routines`, same-engine pairs also diff stored procedures and
+/// functions; routine bodies aren't portable across engines.
+async fn diff_schema_pair(
+    pair: &SchemaPair,
+    generators: &GeneratorRegistry,
+    options: &CompareOptions,
+    include_routines: bool,
+) -> DiffResult {
     info!(
         "Comparing schemas: {} source tables, {} target tables",
-        source_tables.len(),
-        target_tables.len()
+        pair.source_tables.len(),
+        pair.target_tables.len()
     );
-    let items = if source_conn.db_type == target_conn.db_type {
-        compare_schemas(
-            &source_tables,
-            &target_tables,
-            target_driver.as_sql_generator(),
-        )
+    let cross_engine = pair.source_conn.db_type != pair.target_conn.db_type;
+    let concurrent = PostgresSqlGenerator {
+        concurrent_indexes: true,
+    };
+    let registered = generators.generator(&pair.target_conn.db_type);
+    let sql_gen: &dyn SqlGenerator =
+        if options.concurrent_indexes && pair.target_conn.db_type == DbType::PostgreSQL {
+            &concurrent
+        } else {
+            registered
+                .as_deref()
+                .unwrap_or(pair.target_driver.as_sql_generator())
+        };
+    let items = if !cross_engine {
+        compare_schemas_with_options(&pair.source_tables, &pair.target_tables, sql_gen, options)
     } else {
-        let source_mapper = source_driver.as_type_mapper(&source_conn.db_type);
-        let target_mapper = target_driver.as_type_mapper(&target_conn.db_type);
-        compare_schemas_cross(
-            &source_tables,
-            &target_tables,
-            target_driver.as_sql_generator(),
+        let source_mapper = pair.source_driver.as_type_mapper(&pair.source_conn.db_type);
+        let target_mapper = pair.target_driver.as_type_mapper(&pair.target_conn.db_type);
+        compare_schemas_cross_with_options(
+            &pair.source_tables,
+            &pair.target_tables,
+            sql_gen,
             source_mapper.as_ref(),
             target_mapper.as_ref(),
+            options,
         )
     };
+    let mut items = order_for_execution(items, &pair.source_tables, sql_gen);
+
+    let mut warnings = pair.read_warnings.clone();
+    warnings.extend(duplicate_name_warnings("Source", &pair.source_tables));
+    warnings.extend(duplicate_name_warnings("Target", &pair.target_tables));
+    warnings.extend(cross_engine_warning(
+        &pair.source_conn.db_type,
+        &pair.target_conn.db_type,
+    ));
+    if include_routines && !cross_engine {
+        match (
+            pair.source_driver.as_reader().get_routines().await,
+            pair.target_driver.as_reader().get_routines().await,
+        ) {
+            (Ok(source_routines), Ok(target_routines)) => {
+                // Routines run after the table changes they may depend on.
+                let mut id_counter = items
+                    .iter()
+                    .filter_map(|i| i.id.parse::<u32>().ok())
+                    .max()
+                    .unwrap_or(0);
+                items.extend(compare_routines(
+                    &source_routines,
+                    &target_routines,
+                    sql_gen,
+                    &mut id_counter,
+                ));
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to read routines: {}", e);
+                warnings.push(format!(
+                    "Stored procedures and functions were not compared: {}",
+                    e
+                ));
+            }
+        }
+    }
+    // Grantee names differ between engines, so grants are same-engine only too.
+    if options.include_grants && !cross_engine {
+        match (
+            pair.source_driver.as_reader().get_grants().await,
+            pair.target_driver.as_reader().get_grants().await,
+        ) {
+            (Ok(source_grants), Ok(target_grants)) => {
+                // Grants go last, once the tables they name exist.
+                let mut id_counter = items
+                    .iter()
+                    .filter_map(|i| i.id.parse::<u32>().ok())
+                    .max()
+                    .unwrap_or(0);
+                items.extend(compare_grants(
+                    &source_grants,
+                    &target_grants,
+                    sql_gen,
+                    &mut id_counter,
+                ));
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to read grants: {}", e);
+                warnings.push(format!("Grants were not compared: {}", e));
+            }
+        }
+    }
 
     info!("Comparison complete: {} differences found", items.len());
 
-    Ok(DiffResult {
+    if !cross_engine {
+        match (
+            pair.source_driver.as_reader().get_database_info().await,
+            pair.target_driver.as_reader().get_database_info().await,
+        ) {
+            (Ok(source_info), Ok(target_info)) => {
+                warnings.extend(database_charset_warning(&source_info, &target_info));
+            }
+            (Err(e), _) | (_, Err(e)) => warn!("Failed to read database info: {}", e),
+        }
+    }
+
+    DiffResult {
         items,
-        source_tables: source_tables.len(),
-        target_tables: target_tables.len(),
-    })
+        source_tables: pair.source_tables.len(),
+        target_tables: pair.target_tables.len(),
+        warnings,
+        cross_engine,
+        source_schema: None,
+        target_schema: None,
+    }
+}
+
+/// Check one drift monitor's pair: read both schemas and, when they have
+/// drifted in a way not reported yet, diff them.
+async fn check_drift(
+    state: &AppState,
+    source_id: &str,
+    target_id: &str,
+    database: Option<String>,
+    secrets: Option<&HashMap<String, ConnectionSecrets>>,
+    detector: &mut DriftDetector,
+) -> Result<Option<DiffResult>, String> {
+    let pair = fetch_schema_pair(
+        state,
+        source_id,
+        target_id,
+        database.clone(),
+        database,
+        None,
+        secrets,
+    )
+    .await?;
+    if !detector.observe(&pair.source_tables, &pair.target_tables) {
+        return Ok(None);
+    }
+    let result =
+        diff_schema_pair(&pair, &state.generators, &CompareOptions::default(), false).await;
+    // Fingerprints also differ on what the comparison deliberately ignores.
+    Ok(Some(result).filter(|r| !r.items.is_empty()))
+}
+
+/// Compare a source and target every `interval_secs` and emit `drift-detected`
+/// with the diff whenever the target drifts from the source. Returns the
+/// monitor's id for `stop_drift_monitor`. Connections that don't store their
+/// secrets need them in `secrets` up front; the monitor keeps them for its checks.
+#[tauri::command]
+pub(crate) async fn start_drift_monitor(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    source_id: String,
+    target_id: String,
+    database: Option<String>,
+    interval_secs: u64,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<String, String> {
+    let store = state.config_store.lock().await;
+    let source_conn = load_connection(&store, &source_id, "Source connection").await?;
+    let target_conn = load_connection(&store, &target_id, "Target connection").await?;
+    drop(store);
+    // Fail now rather than on every check in the background.
+    for conn in [&source_conn, &target_conn] {
+        conn.with_secrets(secrets.as_ref().and_then(|s| s.get(&conn.id)))
+            .map_err(|e| e.to_string())?;
+    }
+
+    let monitor_id = uuid::Uuid::new_v4().to_string();
+    info!(
+        "Starting drift monitor {}: {} -> {} every {}s",
+        monitor_id, source_id, target_id, interval_secs
+    );
+
+    let id = monitor_id.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        let mut detector = DriftDetector::default();
+        loop {
+            interval.tick().await;
+            let state = app.state::<AppState>();
+            match check_drift(
+                &state,
+                &source_id,
+                &target_id,
+                database.clone(),
+                secrets.as_ref(),
+                &mut detector,
+            )
+            .await
+            {
+                Ok(Some(result)) => {
+                    warn!(
+                        "Drift monitor {}: target {} drifted ({} differences)",
+                        id,
+                        target_id,
+                        result.items.len()
+                    );
+                    let event = DriftEvent {
+                        monitor_id: id.clone(),
+                        source_id: source_id.clone(),
+                        target_id: target_id.clone(),
+                        result,
+                    };
+                    if let Err(e) = app.emit("drift-detected", event) {
+                        error!("Failed to emit drift event: {}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Drift monitor {} check failed: {}", id, e),
+            }
+        }
+    });
+    state
+        .drift_monitors
+        .lock()
+        .await
+        .insert(monitor_id.clone(), handle);
+    Ok(monitor_id)
+}
+
+#[tauri::command]
+pub(crate) async fn stop_drift_monitor(
+    state: State<'_, AppState>,
+    monitor_id: String,
+) -> Result<(), String> {
+    let handle = state
+        .drift_monitors
+        .lock()
+        .await
+        .remove(&monitor_id)
+        .ok_or_else(|| format!("No drift monitor with id {}", monitor_id))?;
+    handle.abort();
+    info!("Stopped drift monitor {}", monitor_id);
+    Ok(())
+}
+
+/// Whether comparing the two databases with `options` would find no differences,
+/// routines and grants included.
+#[tauri::command]
+pub(crate) async fn are_schemas_in_sync(
+    state: State<'_, AppState>,
+    source_id: String,
+    target_id: String,
+    source_database: Option<String>,
+    target_database: Option<String>,
+    options: Option<CompareOptions>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<bool, String> {
+    info!("Checking sync status: {} -> {}", source_id, target_id);
+
+    let pair = fetch_schema_pair(
+        &state,
+        &source_id,
+        &target_id,
+        source_database,
+        target_database,
+        None,
+        secrets.as_ref(),
+    )
+    .await?;
+
+    let in_sync = diff_schema_pair(&pair, &state.generators, &options.unwrap_or_default(), true)
+        .await
+        .items
+        .is_empty();
+
+    info!("Schemas in sync: {}", in_sync);
+    Ok(in_sync)
 }
 
 #[tauri::command]
@@ -211,6 +1185,7 @@ pub(crate) async fn execute_sync(
     target_id: String,
     sql_statements: Vec<String>,
     target_database: Option<String>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
 ) -> Result<(), String> {
     info!(
         "Executing sync on target {}: {} statements",
@@ -221,12 +1196,15 @@ pub(crate) async fn execute_sync(
     let store = state.config_store.lock().await;
     let mut target_conn = load_connection(&store, &target_id, "Target connection").await?;
     drop(store);
+    if target_conn.schema_file.is_some() {
+        return Err("Cannot sync to a schema file connection".to_string());
+    }
 
-    let driver = create_driver(&mut target_conn, target_database, &state.active_tunnels).await?;
+    let driver = create_driver(&state, &mut target_conn, target_database, secrets.as_ref()).await?;
 
     for (i, sql) in sql_statements.iter().enumerate() {
         info!("Executing statement {}/{}", i + 1, sql_statements.len());
-        driver.execute_sql(sql).await.map_err(|e| {
+        driver.as_executor().execute_sql(sql).await.map_err(|e| {
             error!("Failed to execute SQL: {}\nError: {}", sql, e);
             format!("Failed to execute: {}\nError: {}", sql, e)
         })?;
@@ -236,6 +1214,176 @@ pub(crate) async fn execute_sync(
     Ok(())
 }
 
+/// Best-effort counterpart of `execute_sync`: reports each statement's outcome
+/// instead of only the first error, and keeps going past failures unless
+/// `stop_on_error` is set.
+#[tauri::command]
+pub(crate) async fn execute_sync_report(
+    state: State<'_, AppState>,
+    target_id: String,
+    sql_statements: Vec<String>,
+    target_database: Option<String>,
+    stop_on_error: bool,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<Vec<StatementResult>, String> {
+    info!(
+        "Executing sync with report on target {}: {} statements",
+        target_id,
+        sql_statements.len()
+    );
+
+    let store = state.config_store.lock().await;
+    let mut target_conn = load_connection(&store, &target_id, "Target connection").await?;
+    drop(store);
+    if target_conn.schema_file.is_some() {
+        return Err("Cannot sync to a schema file connection".to_string());
+    }
+
+    let driver = create_driver(&state, &mut target_conn, target_database, secrets.as_ref()).await?;
+    let results = execute_with_report(driver.as_executor(), &sql_statements, stop_on_error).await;
+
+    info!(
+        "Sync execution finished: {}/{} statements succeeded",
+        results.iter().filter(|r| r.ok).count(),
+        sql_statements.len()
+    );
+    Ok(results)
+}
+
+/// Apply diff items so that a failure leaves the target as it was: in one
+/// transaction on Postgres, and on MySQL, whose DDL commits as it runs, by
+/// undoing what was already applied with compensating statements built from the
+/// target as it was beforehand.
+#[tauri::command]
+pub(crate) async fn apply_diff_items_with_rollback(
+    state: State<'_, AppState>,
+    target_id: String,
+    database: Option<String>,
+    items: Vec<DiffItem>,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<RollbackReport, String> {
+    info!(
+        "Applying {} diff item(s) with rollback on target {}",
+        items.len(),
+        target_id
+    );
+
+    let store = state.config_store.lock().await;
+    let mut target_conn = load_connection(&store, &target_id, "Target connection").await?;
+    drop(store);
+    if target_conn.schema_file.is_some() {
+        return Err("Cannot sync to a schema file connection".to_string());
+    }
+
+    let driver = create_driver(&state, &mut target_conn, database, secrets.as_ref()).await?;
+    let mut names: Vec<String> = items.iter().map(|i| i.table_name.clone()).collect();
+    names.sort();
+    names.dedup();
+    let before = driver
+        .as_reader()
+        .get_tables_named(&names)
+        .await
+        .map_err(|e| {
+            error!("Failed to read target schema: {}", e);
+            e.to_string()
+        })?;
+    let registered = state.generators.generator(&target_conn.db_type);
+    let sql_gen: &dyn SqlGenerator = registered.as_deref().unwrap_or(driver.as_sql_generator());
+    let transactional_ddl = target_conn.db_type == DbType::PostgreSQL;
+
+    let report = apply_with_rollback(
+        driver.as_executor(),
+        &items,
+        transactional_ddl,
+        |item, ran| undo_sql(item, ran, &before, sql_gen),
+    )
+    .await;
+    if !report.clean_rollback {
+        warn!(
+            "Rollback left {} item(s) applied on target {}",
+            report.left_applied.len(),
+            target_id
+        );
+    }
+    Ok(report)
+}
+
+/// Apply one diff item on its own, optionally in a transaction; the granular
+/// counterpart of `execute_sync`.
+#[tauri::command]
+pub(crate) async fn apply_diff_item(
+    state: State<'_, AppState>,
+    target_id: String,
+    database: Option<String>,
+    item: DiffItem,
+    use_transaction: bool,
+    secrets: Option<HashMap<String, ConnectionSecrets>>,
+) -> Result<StatementResult, String> {
+    info!("Applying diff item {} on target {}", item.id, target_id);
+
+    let store = state.config_store.lock().await;
+    let mut target_conn = load_connection(&store, &target_id, "Target connection").await?;
+    drop(store);
+    if target_conn.schema_file.is_some() {
+        return Err("Cannot sync to a schema file connection".to_string());
+    }
+
+    let driver = create_driver(&state, &mut target_conn, database, secrets.as_ref()).await?;
+    Ok(apply_item(driver.as_executor(), &item, use_transaction).await)
+}
+
+/// Diff two serialized schema snapshots offline, with no database connection.
+/// The items come in execution order, as from a live comparison.
+#[tauri::command]
+pub(crate) fn compare_schema_snapshots(
+    state: State<'_, AppState>,
+    source: Vec<TableSchema>,
+    target: Vec<TableSchema>,
+    db_type: DbType,
+) -> Result<Vec<DiffItem>, String> {
+    info!(
+        "Comparing {} snapshots: {} source tables, {} target tables",
+        db_type,
+        source.len(),
+        target.len()
+    );
+    let sql_gen = state
+        .generators
+        .generator(&db_type)
+        .ok_or_else(|| format!("No SQL generator is registered for {}", db_type))?;
+    let items = compare_schemas(&source, &target, sql_gen.as_ref());
+    Ok(order_for_execution(items, &source, sql_gen.as_ref()))
+}
+
+#[tauri::command]
+pub(crate) fn generate_sql_script(items: Vec<DiffItem>, options: Option<ScriptOptions>) -> String {
+    generate_script(&items, &options.unwrap_or_default())
+}
+
+/// The selected items' SQL with duplicate and superseded statements left out.
+#[tauri::command]
+pub(crate) fn minimize_sql_statements(items: Vec<DiffItem>) -> Vec<String> {
+    minimize_statements(&items)
+}
+
+/// Only the diffs that drop data or objects, for reviewing the risky part of a sync.
+#[tauri::command]
+pub(crate) fn filter_destructive_diffs(items: Vec<DiffItem>) -> Vec<DiffItem> {
+    filter_destructive(items)
+}
+
+#[tauri::command]
+pub(crate) fn format_diff_report(result: DiffResult, format: ReportFormat) -> String {
+    to_report(&result, format)
+}
+
+#[tauri::command]
+pub(crate) fn set_log_level(level: String) -> Result<(), String> {
+    let filter = logging::set_log_level(&level).map_err(|e| e.to_string())?;
+    info!("Log level set to {}", filter);
+    Ok(())
+}
+
 #[tauri::command]
 pub(crate) async fn save_sql_file(file_path: String, content: String) -> Result<(), String> {
     info!("Saving SQL file to: {}", file_path);
@@ -258,3 +1406,31 @@ pub(crate) async fn save_sql_file(file_path: String, content: String) -> Result<
     info!("SQL file saved successfully");
     Ok(())
 }
+
+#[tauri::command]
+pub(crate) async fn export_diffs_ndjson(
+    items: Vec<DiffItem>,
+    file_path: String,
+) -> Result<(), String> {
+    info!("Exporting {} diff item(s) to: {}", items.len(), file_path);
+
+    let path = Path::new(&file_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            error!("Failed to create directory: {}", e);
+            e.to_string()
+        })?;
+    }
+
+    let file = fs::File::create(path).map_err(|e| {
+        error!("Failed to create NDJSON file: {}", e);
+        e.to_string()
+    })?;
+    write_ndjson(&items, std::io::BufWriter::new(file)).map_err(|e| {
+        error!("Failed to write NDJSON file: {}", e);
+        e.to_string()
+    })?;
+
+    info!("Diff items exported successfully");
+    Ok(())
+}
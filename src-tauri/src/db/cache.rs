@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+/// (connection id, database)
+type CacheKey = (String, String);
+
+struct CacheEntry<T> {
+    value: Arc<T>,
+    last_used: Instant,
+}
+
+/// Live connections keyed by connection id and database, so consecutive commands
+/// against the same connection reuse one pool (and SSH tunnel) instead of
+/// reconnecting. Entries unused for longer than the idle timeout are dropped,
+/// and with them whatever the value owns once the last `Arc` goes away.
+pub struct ConnectionCache<T> {
+    entries: Mutex<HashMap<CacheKey, CacheEntry<T>>>,
+    idle_timeout: Duration,
+}
+
+impl<T> ConnectionCache<T> {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            idle_timeout,
+        }
+    }
+
+    /// Return the cached value for `(connection_id, database)` if `is_healthy`
+    /// accepts it, otherwise build a new one with `create` and cache it.
+    pub async fn get_or_create<F, Fut, E>(
+        &self,
+        connection_id: &str,
+        database: &str,
+        is_healthy: impl Fn(&T) -> bool,
+        create: F,
+    ) -> Result<Arc<T>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let key = (connection_id.to_string(), database.to_string());
+        {
+            let mut entries = self.entries.lock().await;
+            let now = Instant::now();
+            entries.retain(|_, e| now.duration_since(e.last_used) < self.idle_timeout);
+            if let Some(entry) = entries.get_mut(&key) {
+                if is_healthy(&entry.value) {
+                    entry.last_used = now;
+                    return Ok(entry.value.clone());
+                }
+                entries.remove(&key);
+            }
+        }
+
+        // Connect without holding the lock so other connections aren't blocked.
+        let value = Arc::new(create().await?);
+        self.entries.lock().await.insert(
+            key,
+            CacheEntry {
+                value: value.clone(),
+                last_used: Instant::now(),
+            },
+        );
+        Ok(value)
+    }
+
+    /// Drop every cached entry for a connection, e.g. after it was edited or deleted.
+    pub async fn invalidate(&self, connection_id: &str) {
+        self.entries
+            .lock()
+            .await
+            .retain(|(id, _), _| id != connection_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct FakePool {
+        closed: bool,
+    }
+
+    async fn get(
+        cache: &ConnectionCache<FakePool>,
+        id: &str,
+        database: &str,
+        created: &AtomicU32,
+    ) -> Arc<FakePool> {
+        cache
+            .get_or_create(
+                id,
+                database,
+                |p| !p.closed,
+                || async {
+                    created.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, ()>(FakePool { closed: false })
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn back_to_back_operations_reuse_pool() {
+        let cache = ConnectionCache::new(Duration::from_secs(60));
+        let created = AtomicU32::new(0);
+
+        let first = get(&cache, "conn-1", "app", &created).await;
+        let second = get(&cache, "conn-1", "app", &created).await;
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn database_is_part_of_the_key() {
+        let cache = ConnectionCache::new(Duration::from_secs(60));
+        let created = AtomicU32::new(0);
+
+        let app = get(&cache, "conn-1", "app", &created).await;
+        let logs = get(&cache, "conn-1", "logs", &created).await;
+
+        assert!(!Arc::ptr_eq(&app, &logs));
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn invalidate_and_unhealthy_entries_reconnect() {
+        let cache = ConnectionCache::new(Duration::from_secs(60));
+        let created = AtomicU32::new(0);
+
+        get(&cache, "conn-1", "app", &created).await;
+        cache.invalidate("conn-1").await;
+        get(&cache, "conn-1", "app", &created).await;
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+
+        let reused = cache
+            .get_or_create(
+                "conn-1",
+                "app",
+                |_| false,
+                || async { Ok::<_, ()>(FakePool { closed: true }) },
+            )
+            .await
+            .unwrap();
+        assert!(reused.closed, "unhealthy entry should be replaced");
+    }
+
+    #[tokio::test]
+    async fn idle_entries_are_evicted() {
+        let cache = ConnectionCache::new(Duration::ZERO);
+        let created = AtomicU32::new(0);
+
+        get(&cache, "conn-1", "app", &created).await;
+        get(&cache, "conn-1", "app", &created).await;
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,1509 @@
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+
+use crate::db::mysql::expression_default;
+use crate::db::postgres::sequence_from_default;
+use crate::db::split_statements;
+use crate::models::*;
+
+/// Parse the `CREATE TABLE`, `CREATE INDEX`, `CREATE TYPE ... AS ENUM` and
+/// `ALTER TABLE ... ADD` statements of a schema dump (`mysqldump --no-data`,
+/// `pg_dump --schema-only` or hand-written DDL) into table schemas, spelled the
+/// way the `dialect`'s reader would report them. Other statements are ignored.
+pub fn parse_schema_sql(sql: &str, dialect: &DbType) -> Result<Vec<TableSchema>> {
+    let mut schema = ParsedSchema {
+        dialect: dialect.clone(),
+        tables: Vec::new(),
+        enums: HashMap::new(),
+    };
+    for statement in split_statements(sql) {
+        let tokens = tokenize(&statement);
+        let p = Parser {
+            sql: &statement,
+            toks: &tokens,
+        };
+        schema.statement(&p)?;
+    }
+
+    // Postgres partitions are tables of their own and read back sorted by
+    // name; MySQL keeps them in declaration order.
+    let sort_partitions = schema.is_postgres();
+    let mut tables = schema.tables;
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    for table in &mut tables {
+        if let Some(spec) = table.partitioning.as_mut().filter(|_| sort_partitions) {
+            spec.partitions.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        table.indexes.sort_by(|a, b| a.name.cmp(&b.name));
+        table.foreign_keys.sort_by(|a, b| a.name.cmp(&b.name));
+        table.unique_constraints.sort_by(|a, b| a.name.cmp(&b.name));
+        table.policies.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    Ok(tables)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Word,
+    /// Quoted identifier; `text` is unquoted.
+    Ident,
+    /// String literal; `text` is the unescaped content.
+    Str,
+    Num,
+    Punct,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(sql: &str) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(sql.len(), |(pos, _)| *pos);
+    let mut tokens = Vec::new();
+    let mut versioned_comments = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i].1;
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let start = i;
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i].1 != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            if chars.get(i + 2).map(|(_, c)| *c) == Some('!') {
+                // MySQL `/*!80023 INVISIBLE */`: the content is live SQL.
+                i += 3;
+                while i < chars.len() && chars[i].1.is_ascii_digit() {
+                    i += 1;
+                }
+                versioned_comments += 1;
+            } else {
+                i += 2;
+                while i < chars.len()
+                    && !(chars[i].1 == '*' && chars.get(i + 1).map(|x| x.1) == Some('/'))
+                {
+                    i += 1;
+                }
+                i += 2;
+            }
+        } else if c == '*' && next == Some('/') && versioned_comments > 0 {
+            versioned_comments -= 1;
+            i += 2;
+        } else if c == '\'' || c == '"' || c == '`' {
+            let mut text = String::new();
+            i += 1;
+            while i < chars.len() {
+                let ch = chars[i].1;
+                if ch == c {
+                    if chars.get(i + 1).map(|x| x.1) == Some(c) {
+                        text.push(c);
+                        i += 2;
+                        continue;
+                    }
+                    i += 1;
+                    break;
+                }
+                if ch == '\\' && c == '\'' && i + 1 < chars.len() {
+                    text.push(chars[i + 1].1);
+                    i += 2;
+                    continue;
+                }
+                text.push(ch);
+                i += 1;
+            }
+            let kind = if c == '\'' { Kind::Str } else { Kind::Ident };
+            tokens.push(Token {
+                kind,
+                text,
+                start: offset(start),
+                end: offset(i),
+            });
+        } else if c.is_alphanumeric() || c == '_' {
+            while i < chars.len()
+                && (chars[i].1.is_alphanumeric() || chars[i].1 == '_' || chars[i].1 == '$')
+            {
+                i += 1;
+            }
+            let kind = if c.is_ascii_digit() {
+                // Keep a decimal part with its number.
+                if chars.get(i).map(|x| x.1) == Some('.') {
+                    i += 1;
+                    while i < chars.len() && chars[i].1.is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                Kind::Num
+            } else {
+                Kind::Word
+            };
+            tokens.push(Token {
+                kind,
+                text: sql[offset(start)..offset(i)].to_string(),
+                start: offset(start),
+                end: offset(i),
+            });
+        } else {
+            i += if c == ':' && next == Some(':') { 2 } else { 1 };
+            tokens.push(Token {
+                kind: Kind::Punct,
+                text: sql[offset(start)..offset(i)].to_string(),
+                start: offset(start),
+                end: offset(i),
+            });
+        }
+    }
+    tokens
+}
+
+/// A statement's tokens, read through a cursor.
+struct Parser<'a> {
+    sql: &'a str,
+    toks: &'a [Token],
+}
+
+impl<'a> Parser<'a> {
+    fn is_kw(&self, i: usize, kw: &str) -> bool {
+        self.toks
+            .get(i)
+            .is_some_and(|t| t.kind == Kind::Word && t.text.eq_ignore_ascii_case(kw))
+    }
+
+    fn is_punct(&self, i: usize, p: &str) -> bool {
+        self.toks
+            .get(i)
+            .is_some_and(|t| t.kind == Kind::Punct && t.text == p)
+    }
+
+    /// Advance past `kws` if they come next, in order.
+    fn eat_kws(&self, i: &mut usize, kws: &[&str]) -> bool {
+        if kws.iter().enumerate().all(|(n, kw)| self.is_kw(*i + n, kw)) {
+            *i += kws.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&self, i: &mut usize) -> Option<String> {
+        let t = self.toks.get(*i)?;
+        match t.kind {
+            Kind::Word | Kind::Ident => {
+                *i += 1;
+                Some(t.text.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// The value of a `NAME [=] value` table option, with `i` past the name.
+    fn option_value(&self, i: &mut usize) -> Option<String> {
+        if self.is_punct(*i, "=") {
+            *i += 1;
+        }
+        self.ident(i)
+    }
+
+    /// A possibly schema-qualified name; only the last part is kept.
+    fn qualified_name(&self, i: &mut usize) -> Option<String> {
+        let mut name = self.ident(i)?;
+        while self.is_punct(*i, ".") {
+            *i += 1;
+            name = self.ident(i)?;
+        }
+        Some(name)
+    }
+
+    /// Index of the `)` closing the `(` at `open`.
+    fn closing_paren(&self, open: usize) -> Option<usize> {
+        let mut depth = 0;
+        for (i, t) in self.toks.iter().enumerate().skip(open) {
+            if t.kind == Kind::Punct && t.text == "(" {
+                depth += 1;
+            } else if t.kind == Kind::Punct && t.text == ")" {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+        }
+        None
+    }
+
+    /// Source text of tokens `from..to`.
+    fn raw(&self, from: usize, to: usize) -> &'a str {
+        if from >= to {
+            return "";
+        }
+        &self.sql[self.toks[from].start..self.toks[to - 1].end]
+    }
+
+    /// Token ranges between top-level commas in `from..to`.
+    fn split_commas(&self, from: usize, to: usize) -> Vec<(usize, usize)> {
+        let mut parts = Vec::new();
+        let mut depth = 0;
+        let mut start = from;
+        for i in from..to {
+            match self.toks[i].text.as_str() {
+                "(" if self.toks[i].kind == Kind::Punct => depth += 1,
+                ")" if self.toks[i].kind == Kind::Punct => depth -= 1,
+                "," if self.toks[i].kind == Kind::Punct && depth == 0 => {
+                    parts.push((start, i));
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if start < to {
+            parts.push((start, to));
+        }
+        parts
+    }
+
+    /// A parenthesized list of plain column names, allowing MySQL prefix lengths
+    /// and `ASC`. `None` when a key part is an expression or sorted otherwise.
+    fn column_list(&self, i: &mut usize) -> Option<Vec<String>> {
+        if !self.is_punct(*i, "(") {
+            return None;
+        }
+        let close = self.closing_paren(*i)?;
+        let mut columns = Vec::new();
+        for (from, to) in self.split_commas(*i + 1, close) {
+            let mut j = from;
+            let name = self.ident(&mut j)?;
+            if self.is_punct(j, "(")
+                && self.toks.get(j + 1).is_some_and(|t| t.kind == Kind::Num)
+                && self.is_punct(j + 2, ")")
+            {
+                j += 3;
+            }
+            self.eat_kws(&mut j, &["ASC"]);
+            if j != to {
+                return None;
+            }
+            columns.push(name);
+        }
+        *i = close + 1;
+        Some(columns)
+    }
+
+    /// `ON DELETE` / `ON UPDATE` actions from `i` up to `to`.
+    fn referential_actions(&self, mut i: usize, to: usize) -> (String, String) {
+        let mut on_delete = "NO ACTION".to_string();
+        let mut on_update = "NO ACTION".to_string();
+        while i < to {
+            let target = if self.eat_kws(&mut i, &["ON", "DELETE"]) {
+                &mut on_delete
+            } else if self.eat_kws(&mut i, &["ON", "UPDATE"]) {
+                &mut on_update
+            } else {
+                i += 1;
+                continue;
+            };
+            let action = ["CASCADE", "RESTRICT"]
+                .iter()
+                .find(|kw| self.is_kw(i, kw))
+                .map(|kw| (kw.to_string(), 1))
+                .or_else(|| {
+                    [("SET", "NULL"), ("SET", "DEFAULT"), ("NO", "ACTION")]
+                        .iter()
+                        .find(|(a, b)| self.is_kw(i, a) && self.is_kw(i + 1, b))
+                        .map(|(a, b)| (format!("{} {}", a, b), 2))
+                });
+            if let Some((action, len)) = action {
+                *target = action;
+                i += len;
+            }
+        }
+        (on_delete, on_update)
+    }
+
+    /// Postgres `MATCH`, `DEFERRABLE` and `INITIALLY DEFERRED` clauses from `i`
+    /// up to `to`, as `(deferrable, initially_deferred, match_type)`.
+    fn constraint_timing(&self, mut i: usize, to: usize) -> (bool, bool, Option<String>) {
+        let mut deferrable = false;
+        let mut initially_deferred = false;
+        let mut match_type = None;
+        while i < to {
+            if self.eat_kws(&mut i, &["NOT", "DEFERRABLE"]) {
+                deferrable = false;
+            } else if self.eat_kws(&mut i, &["DEFERRABLE"]) {
+                deferrable = true;
+            } else if self.eat_kws(&mut i, &["INITIALLY", "DEFERRED"]) {
+                initially_deferred = true;
+            } else if self.eat_kws(&mut i, &["MATCH", "FULL"]) {
+                match_type = Some("FULL".to_string());
+            } else if self.eat_kws(&mut i, &["MATCH", "PARTIAL"]) {
+                match_type = Some("PARTIAL".to_string());
+            } else {
+                i += 1;
+            }
+        }
+        (deferrable, initially_deferred, match_type)
+    }
+}
+
+/// Words that end a column's type or DEFAULT expression.
+const COLUMN_ATTRIBUTES: &[&str] = &[
+    "NOT",
+    "NULL",
+    "DEFAULT",
+    "AUTO_INCREMENT",
+    "PRIMARY",
+    "UNIQUE",
+    "COMMENT",
+    "REFERENCES",
+    "GENERATED",
+    "ON",
+    "COLLATE",
+    "CONSTRAINT",
+    "CHECK",
+    "INVISIBLE",
+    "VISIBLE",
+    "SRID",
+    "STORAGE",
+    "COLUMN_FORMAT",
+];
+
+/// Additionally end a type, but may appear inside a default (`'a'::character varying`).
+const TYPE_ATTRIBUTES: &[&str] = &["AS", "CHARACTER", "CHARSET", "KEY"];
+
+struct ParsedSchema {
+    dialect: DbType,
+    tables: Vec<TableSchema>,
+    /// Postgres enum types, by name.
+    enums: HashMap<String, Vec<String>>,
+}
+
+impl ParsedSchema {
+    fn is_postgres(&self) -> bool {
+        self.dialect == DbType::PostgreSQL
+    }
+
+    fn table_mut(&mut self, name: &str) -> Option<&mut TableSchema> {
+        self.tables.iter_mut().find(|t| t.name == name)
+    }
+
+    fn statement(&mut self, p: &Parser) -> Result<()> {
+        let mut i = 0;
+        if p.eat_kws(&mut i, &["CREATE"]) {
+            p.eat_kws(&mut i, &["OR", "REPLACE"]);
+            for modifier in ["TEMPORARY", "TEMP", "UNLOGGED", "GLOBAL", "LOCAL"] {
+                p.eat_kws(&mut i, &[modifier]);
+            }
+            if p.eat_kws(&mut i, &["TABLE"]) {
+                return self.create_table(p, i);
+            }
+            let unique = p.eat_kws(&mut i, &["UNIQUE"]);
+            if p.eat_kws(&mut i, &["INDEX"]) {
+                self.create_index(p, i, unique);
+            } else if p.eat_kws(&mut i, &["TYPE"]) {
+                self.create_type(p, i);
+            } else if p.eat_kws(&mut i, &["POLICY"]) {
+                self.create_policy(p, i);
+            }
+        } else if p.eat_kws(&mut i, &["ALTER", "TABLE"]) {
+            self.alter_table(p, i);
+        }
+        Ok(())
+    }
+
+    fn create_table(&mut self, p: &Parser, mut i: usize) -> Result<()> {
+        p.eat_kws(&mut i, &["IF", "NOT", "EXISTS"]);
+        let Some(name) = p.qualified_name(&mut i) else {
+            return Ok(());
+        };
+        if p.eat_kws(&mut i, &["PARTITION", "OF"]) {
+            if let Some(parent) = p.qualified_name(&mut i) {
+                self.attach_partition(&parent, name, p.raw(i, p.toks.len()));
+            }
+            return Ok(());
+        }
+        // CREATE TABLE ... AS SELECT / LIKE carry no column list.
+        if !p.is_punct(i, "(") {
+            return Ok(());
+        }
+        let Some(close) = p.closing_paren(i) else {
+            bail!("Unterminated column list in CREATE TABLE {}", name);
+        };
+
+        let mut table = TableSchema {
+            name,
+            columns: vec![],
+            primary_key: None,
+            indexes: vec![],
+            foreign_keys: vec![],
+            unique_constraints: vec![],
+            tablespace: None,
+            exclusion_constraints: vec![],
+            check_constraints: vec![],
+            row_security: false,
+            policies: vec![],
+            partitioning: None,
+            options: TableOptions::default(),
+            inherits: vec![],
+        };
+        for (from, to) in p.split_commas(i + 1, close) {
+            self.element(p, &mut table, from, to);
+        }
+        let mut j = close + 1;
+        while j < p.toks.len() {
+            if p.eat_kws(&mut j, &["TABLESPACE"]) {
+                table.tablespace = p.ident(&mut j);
+            } else if p.eat_kws(&mut j, &["ENGINE"]) {
+                table.options.engine = p.option_value(&mut j);
+            } else if p.eat_kws(&mut j, &["DEFAULT", "CHARSET"])
+                || p.eat_kws(&mut j, &["DEFAULT", "CHARACTER", "SET"])
+                || p.eat_kws(&mut j, &["CHARSET"])
+                || p.eat_kws(&mut j, &["CHARACTER", "SET"])
+            {
+                table.options.charset = p.option_value(&mut j);
+            } else if p.eat_kws(&mut j, &["ROW_FORMAT"]) {
+                table.options.row_format = p.option_value(&mut j).map(|f| f.to_uppercase());
+            } else if p.eat_kws(&mut j, &["INHERITS"]) && p.is_punct(j, "(") {
+                let end = p.closing_paren(j).unwrap_or(p.toks.len());
+                for (from, _) in p.split_commas(j + 1, end) {
+                    let mut k = from;
+                    table.inherits.extend(p.qualified_name(&mut k));
+                }
+                j = end + 1;
+            } else if p.eat_kws(&mut j, &["PARTITION", "BY"]) {
+                let (spec, next) = Self::partition_by(p, j);
+                table.partitioning = spec;
+                j = next;
+            } else {
+                j += 1;
+            }
+        }
+
+        self.tables.retain(|t| t.name != table.name);
+        self.tables.push(table);
+        Ok(())
+    }
+
+    /// `method (key) [PARTITIONS n] [(PARTITION name bound, ...)]` after
+    /// `PARTITION BY`, and the index of the token following it.
+    fn partition_by(p: &Parser, mut i: usize) -> (Option<PartitionSpec>, usize) {
+        let mut method = Vec::new();
+        while i < p.toks.len() && !p.is_punct(i, "(") {
+            method.push(p.toks[i].text.to_ascii_uppercase());
+            i += 1;
+        }
+        let Some(close) = p.closing_paren(i) else {
+            return (None, p.toks.len());
+        };
+        let mut spec = PartitionSpec {
+            method: method.join(" "),
+            expression: p.raw(i + 1, close).to_string(),
+            partitions: vec![],
+        };
+        i = close + 1;
+
+        // MySQL names unlisted HASH/KEY partitions p0, p1, ...
+        if p.eat_kws(&mut i, &["PARTITIONS"]) {
+            let count = p.toks.get(i).and_then(|t| t.text.parse().ok()).unwrap_or(0);
+            spec.partitions = (0..count)
+                .map(|n| Partition {
+                    name: format!("p{}", n),
+                    bound: String::new(),
+                })
+                .collect();
+            i += 1;
+        }
+        if p.is_punct(i, "(") && p.is_kw(i + 1, "PARTITION") {
+            let Some(close) = p.closing_paren(i) else {
+                return (Some(spec), p.toks.len());
+            };
+            spec.partitions = p
+                .split_commas(i + 1, close)
+                .into_iter()
+                .filter_map(|(mut from, to)| {
+                    p.eat_kws(&mut from, &["PARTITION"]);
+                    let name = p.ident(&mut from)?;
+                    // Per-partition storage options aren't compared.
+                    let end = (from..to)
+                        .find(|&k| {
+                            ["ENGINE", "STORAGE", "COMMENT"]
+                                .iter()
+                                .any(|kw| p.is_kw(k, kw))
+                        })
+                        .unwrap_or(to);
+                    Some(Partition {
+                        name,
+                        bound: p.raw(from, end).to_string(),
+                    })
+                })
+                .collect();
+            i = close + 1;
+        }
+        (Some(spec), i)
+    }
+
+    /// Record `partition` under `parent`'s partitioning; Postgres partitions
+    /// are not reported as tables of their own.
+    fn attach_partition(&mut self, parent: &str, partition: String, bound: &str) {
+        self.tables.retain(|t| t.name != partition);
+        if let Some(spec) = self.table_mut(parent).and_then(|t| t.partitioning.as_mut()) {
+            spec.partitions.retain(|p| p.name != partition);
+            spec.partitions.push(Partition {
+                name: partition,
+                bound: bound.to_string(),
+            });
+        }
+    }
+
+    /// One entry of a CREATE TABLE column list, or what follows `ALTER TABLE ... ADD`.
+    fn element(&self, p: &Parser, table: &mut TableSchema, from: usize, to: usize) {
+        let mut i = from;
+        let mut constraint = None;
+        if p.eat_kws(&mut i, &["CONSTRAINT"])
+            && !["PRIMARY", "UNIQUE", "FOREIGN", "CHECK", "EXCLUDE"]
+                .iter()
+                .any(|kw| p.is_kw(i, kw))
+        {
+            constraint = p.ident(&mut i);
+        }
+
+        if p.eat_kws(&mut i, &["PRIMARY", "KEY"]) {
+            if let Some(columns) = p.column_list(&mut i) {
+                self.set_primary_key(table, constraint, columns);
+            }
+        } else if p.eat_kws(&mut i, &["UNIQUE"]) {
+            let _ = p.eat_kws(&mut i, &["KEY"]) || p.eat_kws(&mut i, &["INDEX"]);
+            let index_name = if p.is_punct(i, "(") {
+                None
+            } else {
+                p.ident(&mut i)
+            };
+            if let Some(columns) = p.column_list(&mut i) {
+                let name = constraint
+                    .or(index_name)
+                    .unwrap_or_else(|| self.unique_name(&table.name, &columns));
+                table
+                    .unique_constraints
+                    .push(UniqueConstraint { name, columns });
+            }
+        } else if p.eat_kws(&mut i, &["FOREIGN", "KEY"]) {
+            let index_name = if p.is_punct(i, "(") {
+                None
+            } else {
+                p.ident(&mut i)
+            };
+            let Some(columns) = p.column_list(&mut i) else {
+                return;
+            };
+            if !p.eat_kws(&mut i, &["REFERENCES"]) {
+                return;
+            }
+            let name = constraint
+                .or(index_name)
+                .unwrap_or_else(|| self.foreign_key_name(table, &columns));
+            self.push_foreign_key(p, table, name, columns, i, to);
+        } else if p.is_kw(i, "KEY")
+            || p.is_kw(i, "INDEX")
+            || p.is_kw(i, "FULLTEXT")
+            || p.is_kw(i, "SPATIAL")
+        {
+            let index_type = if p.eat_kws(&mut i, &["FULLTEXT"]) {
+                "FULLTEXT"
+            } else if p.eat_kws(&mut i, &["SPATIAL"]) {
+                "SPATIAL"
+            } else {
+                "BTREE"
+            };
+            let _ = p.eat_kws(&mut i, &["KEY"]) || p.eat_kws(&mut i, &["INDEX"]);
+            let index_name = if p.is_punct(i, "(") {
+                None
+            } else {
+                p.ident(&mut i)
+            };
+            let mut index_type = index_type.to_string();
+            if p.eat_kws(&mut i, &["USING"]) {
+                index_type = p.ident(&mut i).unwrap_or(index_type).to_ascii_uppercase();
+            }
+            if let Some(columns) = p.column_list(&mut i) {
+                if p.eat_kws(&mut i, &["USING"]) {
+                    index_type = p.ident(&mut i).unwrap_or(index_type).to_ascii_uppercase();
+                }
+                let visible = !(i..to).any(|j| p.is_kw(j, "INVISIBLE"));
+                let comment = (i..to)
+                    .find(|j| p.is_kw(*j, "COMMENT"))
+                    .and_then(|j| p.toks.get(j + 1))
+                    .map(|t| t.text.clone());
+                table.indexes.push(Index {
+                    name: index_name.unwrap_or_else(|| columns[0].clone()),
+                    columns,
+                    unique: false,
+                    index_type,
+                    tablespace: None,
+                    visible,
+                    expression: None,
+                    storage_params: vec![],
+                    comment,
+                });
+            }
+        } else if p.eat_kws(&mut i, &["CHECK"]) {
+            let Some(close) = p.is_punct(i, "(").then(|| p.closing_paren(i)).flatten() else {
+                return;
+            };
+            let mut after = close + 1;
+            let enforced = !p.eat_kws(&mut after, &["NOT", "ENFORCED"]);
+            // MySQL's own naming for unnamed checks.
+            let name = constraint.unwrap_or_else(|| {
+                format!("{}_chk_{}", table.name, table.check_constraints.len() + 1)
+            });
+            table.check_constraints.push(CheckConstraint {
+                name,
+                expression: p.raw(i + 1, close).to_string(),
+                enforced,
+            });
+        } else if p.eat_kws(&mut i, &["NOT", "NULL"]) {
+            // Postgres 18 names NOT NULL constraints (`CONSTRAINT users_email_not_null
+            // NOT NULL email`); they are the column's nullability, not a constraint.
+            let Some(column) = p.ident(&mut i) else {
+                return;
+            };
+            if let Some(col) = table.columns.iter_mut().find(|c| c.name == column) {
+                col.nullable = false;
+            }
+        } else if p.is_kw(i, "EXCLUDE") || p.is_kw(i, "LIKE") {
+            // Not modelled.
+        } else {
+            p.eat_kws(&mut i, &["COLUMN"]);
+            self.column(p, table, i, to);
+        }
+    }
+
+    fn column(&self, p: &Parser, table: &mut TableSchema, mut i: usize, to: usize) {
+        let Some(name) = p.ident(&mut i) else {
+            return;
+        };
+        let type_start = i;
+        let mut depth = 0;
+        while i < to {
+            let t = &p.toks[i];
+            if t.kind == Kind::Punct && t.text == "(" {
+                depth += 1;
+            } else if t.kind == Kind::Punct && t.text == ")" {
+                depth -= 1;
+            } else if depth == 0
+                && i > type_start
+                && t.kind == Kind::Word
+                && COLUMN_ATTRIBUTES
+                    .iter()
+                    .chain(TYPE_ATTRIBUTES)
+                    .any(|kw| t.text.eq_ignore_ascii_case(kw))
+            {
+                break;
+            }
+            i += 1;
+        }
+        let (data_type, serial) = self.normalize_type(p.raw(type_start, i));
+        // A serial column is NOT NULL with a default from its own sequence.
+        let sequence = serial.then(|| format!("{}_{}_seq", table.name, name));
+
+        let mut col = Column {
+            name,
+            data_type,
+            nullable: !serial,
+            default_value: None,
+            auto_increment: serial,
+            comment: None,
+            ordinal_position: table.columns.len() as u32 + 1,
+            visible: true,
+            on_update: None,
+            sequence,
+            generated: None,
+            srid: None,
+            octet_length: None,
+        };
+        let mut constraint = None;
+
+        while i < to {
+            if p.eat_kws(&mut i, &["NOT", "NULL"]) {
+                col.nullable = false;
+            } else if p.eat_kws(&mut i, &["NULL"]) {
+                col.nullable = true;
+            } else if p.eat_kws(&mut i, &["DEFAULT"]) {
+                let start = i;
+                i = Self::expression_end(p, i, to);
+                self.set_default(&mut col, p, start, i);
+            } else if p.eat_kws(&mut i, &["AUTO_INCREMENT"]) {
+                col.auto_increment = true;
+            } else if p.eat_kws(&mut i, &["PRIMARY", "KEY"]) {
+                col.nullable = false;
+                self.set_primary_key(table, constraint.take(), vec![col.name.clone()]);
+            } else if p.eat_kws(&mut i, &["UNIQUE"]) {
+                p.eat_kws(&mut i, &["KEY"]);
+                let columns = vec![col.name.clone()];
+                let name = constraint
+                    .take()
+                    .unwrap_or_else(|| self.unique_name(&table.name, &columns));
+                table
+                    .unique_constraints
+                    .push(UniqueConstraint { name, columns });
+            } else if p.eat_kws(&mut i, &["COMMENT"]) {
+                col.comment = p.toks.get(i).map(|t| t.text.clone());
+                i += 1;
+            } else if p.eat_kws(&mut i, &["REFERENCES"]) {
+                let columns = vec![col.name.clone()];
+                let name = constraint
+                    .take()
+                    .unwrap_or_else(|| self.foreign_key_name(table, &columns));
+                self.push_foreign_key(p, table, name, columns, i, to);
+                break;
+            } else if p.eat_kws(&mut i, &["GENERATED", "ALWAYS", "AS", "IDENTITY"])
+                || p.eat_kws(&mut i, &["GENERATED", "BY", "DEFAULT", "AS", "IDENTITY"])
+            {
+                col.auto_increment = true;
+                col.nullable = false;
+                if p.is_punct(i, "(") {
+                    i = p.closing_paren(i).map_or(to, |c| c + 1);
+                }
+            } else if p.eat_kws(&mut i, &["GENERATED", "ALWAYS", "AS"])
+                || p.eat_kws(&mut i, &["AS"])
+            {
+                let Some(close) = p.closing_paren(i).filter(|_| p.is_punct(i, "(")) else {
+                    break;
+                };
+                let expression = p.raw(i + 1, close).to_string();
+                i = close + 1;
+                let storage =
+                    if p.eat_kws(&mut i, &["STORED"]) || p.eat_kws(&mut i, &["PERSISTENT"]) {
+                        GeneratedStorage::Stored
+                    } else if p.eat_kws(&mut i, &["VIRTUAL"]) || !self.is_postgres() {
+                        GeneratedStorage::Virtual
+                    } else {
+                        GeneratedStorage::Stored
+                    };
+                col.generated = Some(GeneratedColumn {
+                    expression,
+                    storage,
+                });
+            } else if p.eat_kws(&mut i, &["ON", "UPDATE"]) {
+                let start = i;
+                i += 1;
+                if p.is_punct(i, "(") {
+                    i = p.closing_paren(i).map_or(to, |c| c + 1);
+                }
+                col.on_update = Some(p.raw(start, i.min(to)).to_string());
+            } else if p.eat_kws(&mut i, &["CHARACTER", "SET"])
+                || p.eat_kws(&mut i, &["CHARSET"])
+                || p.eat_kws(&mut i, &["COLLATE"])
+                || p.eat_kws(&mut i, &["STORAGE"])
+                || p.eat_kws(&mut i, &["COLUMN_FORMAT"])
+            {
+                i += 1;
+            } else if p.eat_kws(&mut i, &["CONSTRAINT"]) {
+                constraint = p.ident(&mut i);
+            } else if p.eat_kws(&mut i, &["CHECK"]) {
+                if p.is_punct(i, "(") {
+                    i = p.closing_paren(i).map_or(to, |c| c + 1);
+                }
+            } else if p.eat_kws(&mut i, &["INVISIBLE"]) {
+                col.visible = false;
+            } else if p.eat_kws(&mut i, &["SRID"]) {
+                col.srid = p.toks.get(i).and_then(|t| t.text.parse().ok());
+                i += 1;
+            } else {
+                i += 1;
+            }
+        }
+
+        table.columns.retain(|c| c.name != col.name);
+        table.columns.push(col);
+    }
+
+    /// End of a DEFAULT expression starting at `i`: its first token, then anything
+    /// up to the next column attribute at the top level.
+    fn expression_end(p: &Parser, mut i: usize, to: usize) -> usize {
+        let mut depth = 0;
+        let mut first = true;
+        while i < to {
+            let t = &p.toks[i];
+            if t.kind == Kind::Punct && t.text == "(" {
+                depth += 1;
+            } else if t.kind == Kind::Punct && t.text == ")" {
+                depth -= 1;
+            } else if depth == 0
+                && !first
+                && t.kind == Kind::Word
+                && COLUMN_ATTRIBUTES
+                    .iter()
+                    .any(|kw| t.text.eq_ignore_ascii_case(kw))
+            {
+                break;
+            }
+            first = false;
+            i += 1;
+        }
+        i
+    }
+
+    /// Store a default as the reader reports it: MySQL gives string literals
+    /// unquoted and expressions in the parentheses `DEFAULT` needs; a Postgres `nextval()`
+    /// default marks a serial column instead.
+    fn set_default(&self, col: &mut Column, p: &Parser, from: usize, to: usize) {
+        let raw = p.raw(from, to);
+        if raw.eq_ignore_ascii_case("NULL") {
+            col.default_value = None;
+        } else if self.is_postgres() {
+            if raw.starts_with("nextval(") {
+                col.auto_increment = true;
+                col.sequence = sequence_from_default(raw);
+                col.default_value = None;
+            } else {
+                col.default_value = Some(raw.to_string());
+            }
+        } else if to == from + 1 && p.toks[from].kind == Kind::Str {
+            col.default_value = Some(p.toks[from].text.clone());
+        } else if p.is_punct(from, "(") && p.closing_paren(from) == Some(to - 1) {
+            col.default_value = Some(expression_default(p.raw(from + 1, to - 1)));
+        } else {
+            col.default_value = Some(raw.to_string());
+        }
+    }
+
+    fn set_primary_key(&self, table: &mut TableSchema, name: Option<String>, columns: Vec<String>) {
+        let name = name.unwrap_or_else(|| {
+            if self.is_postgres() {
+                format!("{}_pkey", table.name)
+            } else {
+                "PRIMARY".to_string()
+            }
+        });
+        for col in table
+            .columns
+            .iter_mut()
+            .filter(|c| columns.contains(&c.name))
+        {
+            col.nullable = false;
+        }
+        table.primary_key = Some(PrimaryKey {
+            name: Some(name),
+            columns,
+        });
+    }
+
+    fn unique_name(&self, table: &str, columns: &[String]) -> String {
+        if self.is_postgres() {
+            format!("{}_{}_key", table, columns.join("_"))
+        } else {
+            columns[0].clone()
+        }
+    }
+
+    fn foreign_key_name(&self, table: &TableSchema, columns: &[String]) -> String {
+        if self.is_postgres() {
+            format!("{}_{}_fkey", table.name, columns.join("_"))
+        } else {
+            format!("{}_ibfk_{}", table.name, table.foreign_keys.len() + 1)
+        }
+    }
+
+    /// `REFERENCES table [(columns)] [ON DELETE ...] [ON UPDATE ...]` from `i`.
+    fn push_foreign_key(
+        &self,
+        p: &Parser,
+        table: &mut TableSchema,
+        name: String,
+        columns: Vec<String>,
+        mut i: usize,
+        to: usize,
+    ) {
+        let Some(ref_table) = p.qualified_name(&mut i) else {
+            return;
+        };
+        let ref_columns = p.column_list(&mut i).unwrap_or_else(|| columns.clone());
+        let (on_delete, on_update) = p.referential_actions(i, to);
+        let (deferrable, initially_deferred, match_type) = if self.is_postgres() {
+            p.constraint_timing(i, to)
+        } else {
+            (false, false, None)
+        };
+        table.foreign_keys.push(ForeignKey {
+            name,
+            columns,
+            ref_table,
+            ref_columns,
+            on_delete,
+            on_update,
+            deferrable,
+            initially_deferred,
+            match_type,
+        });
+    }
+
+    /// The column type as the dialect's reader spells it, and whether it is a
+    /// Postgres serial type (an integer with a sequence default).
+    fn normalize_type(&self, raw: &str) -> (String, bool) {
+        let lowered = lowercase_outside_quotes(raw.trim());
+        if !self.is_postgres() {
+            return (lowered, false);
+        }
+        let (base, array) = match lowered.strip_suffix("[]") {
+            Some(base) => (base.trim_end(), "[]"),
+            None => (lowered.as_str(), ""),
+        };
+        let compact: String = base.split_whitespace().collect::<Vec<_>>().join(" ");
+        let (head, modifier) = match compact.find('(') {
+            Some(pos) => (compact[..pos].trim(), &compact[pos..]),
+            None => (compact.as_str(), ""),
+        };
+        // Postgres drops the precision of time types in information_schema.data_type.
+        let zone = |rest: &str| {
+            if rest.contains("with time zone") && !rest.contains("without") {
+                "with time zone"
+            } else {
+                "without time zone"
+            }
+        };
+        let (name, serial) = match head {
+            "serial" | "serial4" => ("integer".to_string(), true),
+            "bigserial" | "serial8" => ("bigint".to_string(), true),
+            "smallserial" | "serial2" => ("smallint".to_string(), true),
+            "int" | "int4" | "integer" => ("integer".to_string(), false),
+            "int8" | "bigint" => ("bigint".to_string(), false),
+            "int2" | "smallint" => ("smallint".to_string(), false),
+            "bool" | "boolean" => ("boolean".to_string(), false),
+            "float8" | "double precision" => ("double precision".to_string(), false),
+            "float4" | "real" => ("real".to_string(), false),
+            "timestamptz" => ("timestamp with time zone".to_string(), false),
+            "timetz" => ("time with time zone".to_string(), false),
+            "character varying" | "varchar" if modifier.is_empty() => {
+                ("character varying".to_string(), false)
+            }
+            "character varying" | "varchar" => (format!("varchar{}", modifier), false),
+            "character" | "char" | "bpchar" if modifier.is_empty() => {
+                ("char(1)".to_string(), false)
+            }
+            "character" | "char" | "bpchar" => (format!("char{}", modifier), false),
+            "numeric" | "decimal" if !modifier.contains(',') && !modifier.is_empty() => (
+                format!("numeric{},0)", modifier.trim_end_matches(')')),
+                false,
+            ),
+            "numeric" | "decimal" => (format!("numeric{}", modifier), false),
+            _ if head.starts_with("timestamp") => (format!("timestamp {}", zone(&compact)), false),
+            _ if head.starts_with("time") => (format!("time {}", zone(&compact)), false),
+            _ => match self.enums.get(head.rsplit('.').next().unwrap_or(head)) {
+                Some(values) if array.is_empty() => {
+                    let values: Vec<String> = values.iter().map(|v| format!("'{}'", v)).collect();
+                    (format!("enum({})", values.join(",")), false)
+                }
+                _ => (compact.clone(), false),
+            },
+        };
+        (format!("{}{}", name, array), serial)
+    }
+
+    fn create_index(&mut self, p: &Parser, mut i: usize, unique: bool) {
+        p.eat_kws(&mut i, &["CONCURRENTLY"]);
+        p.eat_kws(&mut i, &["IF", "NOT", "EXISTS"]);
+        let Some(name) = p.ident(&mut i) else {
+            return;
+        };
+        let mut index_type = if self.is_postgres() { "btree" } else { "BTREE" }.to_string();
+        if p.eat_kws(&mut i, &["USING"]) {
+            index_type = p.ident(&mut i).unwrap_or(index_type);
+        }
+        if !p.eat_kws(&mut i, &["ON"]) {
+            return;
+        }
+        p.eat_kws(&mut i, &["ONLY"]);
+        let Some(table_name) = p.qualified_name(&mut i) else {
+            return;
+        };
+        if p.eat_kws(&mut i, &["USING"]) {
+            index_type = p.ident(&mut i).unwrap_or(index_type);
+        }
+        let columns = p.column_list(&mut i);
+        let mut storage_params = Vec::new();
+        if p.is_kw(i, "WITH") && p.is_punct(i + 1, "(") {
+            let Some(close) = p.closing_paren(i + 1) else {
+                return;
+            };
+            // pg_dump quotes the values: WITH (fillfactor='70').
+            storage_params = p
+                .split_commas(i + 2, close)
+                .into_iter()
+                .map(|(from, to)| p.raw(from, to).replace([' ', '\''], ""))
+                .collect();
+            i = close + 1;
+        }
+        let trailing = (i..p.toks.len()).any(|j| {
+            ["INCLUDE", "WHERE", "WITH", "NULLS"]
+                .iter()
+                .any(|kw| p.is_kw(j, kw))
+        });
+        let comment = (i..p.toks.len())
+            .find(|j| p.is_kw(*j, "COMMENT"))
+            .and_then(|j| p.toks.get(j + 1))
+            .map(|t| t.text.clone());
+        let is_postgres = self.is_postgres();
+        let definition = p
+            .raw(0, p.toks.len())
+            .replacen(" ON public.", " ON ", 1)
+            .replacen(" ON ONLY public.", " ON ONLY ", 1);
+        let Some(table) = self.table_mut(&table_name) else {
+            return;
+        };
+        let (columns, expression) = match columns {
+            Some(columns) if !trailing => (columns, None),
+            // Only Postgres keeps indexes a column list can't describe.
+            _ if is_postgres => (vec![], Some(definition)),
+            _ => return,
+        };
+        if !is_postgres {
+            index_type = index_type.to_ascii_uppercase();
+        }
+        table.indexes.retain(|idx| idx.name != name);
+        table.indexes.push(Index {
+            name,
+            columns,
+            unique,
+            index_type,
+            tablespace: None,
+            visible: true,
+            expression,
+            storage_params,
+            comment,
+        });
+    }
+
+    fn create_type(&mut self, p: &Parser, mut i: usize) {
+        let Some(name) = p.qualified_name(&mut i) else {
+            return;
+        };
+        if !p.eat_kws(&mut i, &["AS", "ENUM"]) || !p.is_punct(i, "(") {
+            return;
+        }
+        let close = p.closing_paren(i).unwrap_or(p.toks.len());
+        let values = p.toks[i + 1..close]
+            .iter()
+            .filter(|t| t.kind == Kind::Str)
+            .map(|t| t.text.clone())
+            .collect();
+        self.enums.insert(name, values);
+    }
+
+    /// `CREATE POLICY name ON table [AS ...] [FOR ...] [TO ...] [USING (...)] [WITH CHECK (...)]`.
+    fn create_policy(&mut self, p: &Parser, mut i: usize) {
+        let Some(name) = p.ident(&mut i) else {
+            return;
+        };
+        if !p.eat_kws(&mut i, &["ON"]) {
+            return;
+        }
+        let Some(table_name) = p.qualified_name(&mut i) else {
+            return;
+        };
+        let mut policy = Policy {
+            name,
+            table: table_name.clone(),
+            command: "ALL".to_string(),
+            permissive: true,
+            roles: vec!["public".to_string()],
+            using_expr: None,
+            check_expr: None,
+        };
+        // The text inside a parenthesized expression at `i`.
+        let inner = |i: &mut usize| {
+            let close = p.closing_paren(*i).filter(|_| p.is_punct(*i, "("))?;
+            let expr = p.raw(*i + 1, close).to_string();
+            *i = close + 1;
+            Some(expr)
+        };
+        while i < p.toks.len() {
+            if p.eat_kws(&mut i, &["AS"]) {
+                policy.permissive = !p.is_kw(i, "RESTRICTIVE");
+                i += 1;
+            } else if p.eat_kws(&mut i, &["FOR"]) {
+                policy.command = p.ident(&mut i).unwrap_or_default().to_ascii_uppercase();
+            } else if p.eat_kws(&mut i, &["TO"]) {
+                policy.roles.clear();
+                while let Some(t) = p.toks.get(i) {
+                    // Unquoted role names fold to lowercase, PUBLIC included.
+                    policy.roles.push(match t.kind {
+                        Kind::Word => t.text.to_lowercase(),
+                        _ => t.text.clone(),
+                    });
+                    i += 1;
+                    if !p.is_punct(i, ",") {
+                        break;
+                    }
+                    i += 1;
+                }
+            } else if p.eat_kws(&mut i, &["USING"]) {
+                policy.using_expr = inner(&mut i);
+            } else if p.eat_kws(&mut i, &["WITH", "CHECK"]) {
+                policy.check_expr = inner(&mut i);
+            } else {
+                i += 1;
+            }
+        }
+        if let Some(table) = self.table_mut(&table_name) {
+            table
+                .policies
+                .retain(|existing| existing.name != policy.name);
+            table.policies.push(policy);
+        }
+    }
+
+    fn alter_table(&mut self, p: &Parser, mut i: usize) {
+        p.eat_kws(&mut i, &["IF", "EXISTS"]);
+        p.eat_kws(&mut i, &["ONLY"]);
+        let Some(name) = p.qualified_name(&mut i) else {
+            return;
+        };
+        let Some(mut table) = self
+            .tables
+            .iter()
+            .position(|t| t.name == name)
+            .map(|pos| self.tables.remove(pos))
+        else {
+            return;
+        };
+
+        let mut attached = Vec::new();
+        for (from, to) in p.split_commas(i, p.toks.len()) {
+            let mut j = from;
+            if p.eat_kws(&mut j, &["ADD"]) {
+                self.element(p, &mut table, j, to);
+            } else if p.eat_kws(&mut j, &["ALTER"]) {
+                p.eat_kws(&mut j, &["COLUMN"]);
+                let Some(column) = p.ident(&mut j) else {
+                    continue;
+                };
+                let Some(pos) = table.columns.iter().position(|c| c.name == column) else {
+                    continue;
+                };
+                if p.eat_kws(&mut j, &["SET", "DEFAULT"]) {
+                    let end = Self::expression_end(p, j, to);
+                    self.set_default(&mut table.columns[pos], p, j, end);
+                } else if p.eat_kws(&mut j, &["ADD", "GENERATED"]) {
+                    table.columns[pos].auto_increment = true;
+                }
+            } else if p.eat_kws(&mut j, &["ATTACH", "PARTITION"]) {
+                if let Some(partition) = p.qualified_name(&mut j) {
+                    attached.push((partition, p.raw(j, to).to_string()));
+                }
+            } else if p.eat_kws(&mut j, &["ENABLE", "ROW", "LEVEL", "SECURITY"]) {
+                table.row_security = true;
+            } else if p.eat_kws(&mut j, &["DISABLE", "ROW", "LEVEL", "SECURITY"]) {
+                table.row_security = false;
+            }
+        }
+        self.tables.push(table);
+        for (partition, bound) in attached {
+            self.attach_partition(&name, partition, &bound);
+        }
+    }
+}
+
+fn lowercase_outside_quotes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut quote = None;
+    for c in text.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None => {
+                out.extend(c.to_lowercase());
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mysql_show_create_table() {
+        let sql = "CREATE TABLE `orders` (
+  `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+  `user_id` int NOT NULL,
+  `status` enum('New','Paid') NOT NULL DEFAULT 'New' COMMENT 'order state',
+  `note` varchar(255) CHARACTER SET utf8mb4 COLLATE utf8mb4_bin DEFAULT NULL,
+  `token` char(36) NOT NULL DEFAULT (uuid()),
+  `updated_at` timestamp(3) NULL DEFAULT CURRENT_TIMESTAMP(3) ON UPDATE CURRENT_TIMESTAMP(3),
+  `total` decimal(10,2) GENERATED ALWAYS AS ((`price` * `qty`)) STORED,
+  `secret` int DEFAULT NULL /*!80023 INVISIBLE */,
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `uq_note` (`note`(100)),
+  KEY `idx_user` (`user_id`),
+  CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;";
+
+        let tables = parse_schema_sql(sql, &DbType::MySQL).unwrap();
+        assert_eq!(tables.len(), 1);
+        let orders = &tables[0];
+        let columns: Vec<(&str, &str, bool)> = orders
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_str(), c.nullable))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("id", "bigint unsigned", false),
+                ("user_id", "int", false),
+                ("status", "enum('New','Paid')", false),
+                ("note", "varchar(255)", true),
+                ("token", "char(36)", false),
+                ("updated_at", "timestamp(3)", true),
+                ("total", "decimal(10,2)", true),
+                ("secret", "int", true),
+            ]
+        );
+        assert!(orders.columns[0].auto_increment);
+        assert_eq!(orders.columns[2].default_value.as_deref(), Some("New"));
+        assert_eq!(orders.columns[2].comment.as_deref(), Some("order state"));
+        assert_eq!(orders.columns[3].default_value, None);
+        assert_eq!(orders.columns[4].default_value.as_deref(), Some("(uuid())"));
+        assert_eq!(
+            orders.columns[5].on_update.as_deref(),
+            Some("CURRENT_TIMESTAMP(3)")
+        );
+        assert_eq!(
+            orders.columns[6].generated,
+            Some(GeneratedColumn {
+                expression: "(`price` * `qty`)".to_string(),
+                storage: GeneratedStorage::Stored,
+            })
+        );
+        assert!(!orders.columns[7].visible);
+
+        assert_eq!(
+            orders.primary_key,
+            Some(PrimaryKey {
+                name: Some("PRIMARY".to_string()),
+                columns: vec!["id".to_string()],
+            })
+        );
+        assert_eq!(orders.unique_constraints[0].name, "uq_note");
+        assert_eq!(orders.unique_constraints[0].columns, vec!["note"]);
+        assert_eq!(orders.indexes[0].name, "idx_user");
+        assert_eq!(orders.indexes[0].index_type, "BTREE");
+        assert_eq!(
+            orders.options,
+            TableOptions {
+                engine: Some("InnoDB".to_string()),
+                charset: Some("utf8mb4".to_string()),
+                row_format: None,
+            }
+        );
+        assert_eq!(
+            orders.foreign_keys,
+            vec![ForeignKey {
+                name: "fk_user".to_string(),
+                columns: vec!["user_id".to_string()],
+                ref_table: "users".to_string(),
+                ref_columns: vec!["id".to_string()],
+                on_delete: "CASCADE".to_string(),
+                on_update: "NO ACTION".to_string(),
+                deferrable: false,
+                initially_deferred: false,
+                match_type: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_pg_dump_schema() {
+        let sql = r#"
+-- Dumped from database version 16.2
+SET statement_timeout = 0;
+CREATE TYPE public.mood AS ENUM ('happy', 'sad');
+
+CREATE TABLE public.users (
+    id integer NOT NULL,
+    email character varying(255) NOT NULL,
+    balance numeric(12,2) DEFAULT 0 NOT NULL,
+    created_at timestamp(6) with time zone DEFAULT now(),
+    tags text[],
+    feeling public.mood,
+    name_upper text GENERATED ALWAYS AS (upper(email)) STORED
+);
+
+CREATE SEQUENCE public.users_id_seq AS integer START WITH 1 INCREMENT BY 1 NO MINVALUE NO MAXVALUE CACHE 1;
+ALTER SEQUENCE public.users_id_seq OWNED BY public.users.id;
+ALTER TABLE ONLY public.users ALTER COLUMN id SET DEFAULT nextval('public.users_id_seq'::regclass);
+ALTER TABLE ONLY public.users
+    ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.users
+    ADD CONSTRAINT users_email_key UNIQUE (email);
+CREATE INDEX idx_users_created ON public.users USING btree (created_at);
+CREATE INDEX idx_users_lower_email ON public.users USING btree (lower((email)::text));
+ALTER TABLE public.users ENABLE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON public.users AS RESTRICTIVE FOR SELECT TO app_user, "Admin" USING ((email = CURRENT_USER));
+
+CREATE TABLE public.orders (
+    id bigserial PRIMARY KEY,
+    user_id integer REFERENCES public.users(id) ON DELETE SET NULL
+);
+"#;
+
+        let tables = parse_schema_sql(sql, &DbType::PostgreSQL).unwrap();
+        let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["orders", "users"]);
+
+        let users = &tables[1];
+        let columns: Vec<(&str, &str)> = users
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_str()))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("id", "integer"),
+                ("email", "varchar(255)"),
+                ("balance", "numeric(12,2)"),
+                ("created_at", "timestamp with time zone"),
+                ("tags", "text[]"),
+                ("feeling", "enum('happy','sad')"),
+                ("name_upper", "text"),
+            ]
+        );
+        assert!(users.columns[0].auto_increment);
+        assert_eq!(users.columns[0].sequence.as_deref(), Some("users_id_seq"));
+        assert_eq!(users.columns[0].default_value, None);
+        assert_eq!(users.columns[2].default_value.as_deref(), Some("0"));
+        assert_eq!(users.columns[3].default_value.as_deref(), Some("now()"));
+        assert_eq!(
+            users.columns[6].generated.as_ref().map(|g| g.storage),
+            Some(GeneratedStorage::Stored)
+        );
+        assert_eq!(
+            users.primary_key.as_ref().and_then(|pk| pk.name.as_deref()),
+            Some("users_pkey")
+        );
+        assert_eq!(users.unique_constraints[0].name, "users_email_key");
+        assert_eq!(users.indexes.len(), 2);
+        assert_eq!(users.indexes[0].columns, vec!["created_at"]);
+        assert_eq!(users.indexes[0].index_type, "btree");
+        assert_eq!(
+            users.indexes[1].expression.as_deref(),
+            Some("CREATE INDEX idx_users_lower_email ON users USING btree (lower((email)::text))")
+        );
+
+        assert!(users.row_security);
+        assert_eq!(
+            users.policies,
+            vec![Policy {
+                name: "tenant_isolation".to_string(),
+                table: "users".to_string(),
+                command: "SELECT".to_string(),
+                permissive: false,
+                roles: vec!["app_user".to_string(), "Admin".to_string()],
+                using_expr: Some("(email = CURRENT_USER)".to_string()),
+                check_expr: None,
+            }]
+        );
+
+        let orders = &tables[0];
+        assert!(!orders.row_security);
+        assert_eq!(orders.columns[0].data_type, "bigint");
+        assert!(orders.columns[0].auto_increment);
+        assert_eq!(orders.columns[0].sequence.as_deref(), Some("orders_id_seq"));
+        assert!(!orders.columns[0].nullable);
+        assert_eq!(orders.foreign_keys[0].name, "orders_user_id_fkey");
+        assert_eq!(orders.foreign_keys[0].ref_table, "users");
+        assert_eq!(orders.foreign_keys[0].on_delete, "SET NULL");
+    }
+
+    #[test]
+    fn parses_partitioned_tables() {
+        let mysql = "CREATE TABLE `events` (\n  `id` int NOT NULL,\n  `created` date NOT NULL\n\
+            ) ENGINE=InnoDB\n/*!50100 PARTITION BY RANGE (year(`created`))\n\
+            (PARTITION p2023 VALUES LESS THAN (2024) ENGINE = InnoDB,\n \
+            PARTITION pmax VALUES LESS THAN MAXVALUE ENGINE = InnoDB) */;\n\
+            CREATE TABLE `hits` (`id` int NOT NULL) PARTITION BY HASH (`id`) PARTITIONS 2;";
+        let tables = parse_schema_sql(mysql, &DbType::MySQL).unwrap();
+        let events = tables[0].partitioning.as_ref().unwrap();
+        assert_eq!(events.method, "RANGE");
+        assert_eq!(events.expression, "year(`created`)");
+        assert_eq!(
+            events.partitions,
+            vec![
+                Partition {
+                    name: "p2023".to_string(),
+                    bound: "VALUES LESS THAN (2024)".to_string(),
+                },
+                Partition {
+                    name: "pmax".to_string(),
+                    bound: "VALUES LESS THAN MAXVALUE".to_string(),
+                },
+            ]
+        );
+        let hits = tables[1].partitioning.as_ref().unwrap();
+        assert_eq!(hits.method, "HASH");
+        let names: Vec<&str> = hits.partitions.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["p0", "p1"]);
+
+        // pg_dump creates partitions as plain tables and attaches them afterwards.
+        let pg = "CREATE TABLE public.events (id integer NOT NULL, created date NOT NULL)\n\
+            PARTITION BY RANGE (created);\n\
+            CREATE TABLE public.events_2024 (id integer NOT NULL, created date NOT NULL);\n\
+            CREATE TABLE public.events_2023 PARTITION OF public.events\n\
+            FOR VALUES FROM ('2023-01-01') TO ('2024-01-01');\n\
+            ALTER TABLE ONLY public.events ATTACH PARTITION public.events_2024 \
+            FOR VALUES FROM ('2024-01-01') TO ('2025-01-01');";
+        let tables = parse_schema_sql(pg, &DbType::PostgreSQL).unwrap();
+        assert_eq!(tables.len(), 1);
+        let spec = tables[0].partitioning.as_ref().unwrap();
+        assert_eq!(spec.method, "RANGE");
+        assert_eq!(spec.expression, "created");
+        assert_eq!(spec.partitions[0].name, "events_2023");
+        assert_eq!(
+            spec.partitions[1].bound,
+            "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')"
+        );
+    }
+
+    #[test]
+    fn named_not_null_constraint_is_one_nullability_diff() {
+        let pg18 = "CREATE TABLE public.users (
+                id integer CONSTRAINT users_id_not_null NOT NULL,
+                email varchar(255),
+                name text,
+                CONSTRAINT users_email_not_null NOT NULL email
+            );
+            ALTER TABLE public.users ADD CONSTRAINT users_name_not_null NOT NULL name;";
+        let older = "CREATE TABLE public.users (
+                id integer NOT NULL,
+                email varchar(255),
+                name text NOT NULL
+            );";
+        let source = parse_schema_sql(pg18, &DbType::PostgreSQL).unwrap();
+        let target = parse_schema_sql(older, &DbType::PostgreSQL).unwrap();
+        assert_eq!(source[0].columns.len(), 3);
+        assert!(source[0].check_constraints.is_empty());
+
+        let diffs = crate::diff::compare_snapshots(&source, &target, DbType::PostgreSQL);
+        assert_eq!(diffs.len(), 1, "{:?}", diffs);
+        assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+        assert_eq!(diffs[0].object_name.as_deref(), Some("email"));
+        assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Nullable]);
+    }
+
+    #[test]
+    fn unterminated_create_table_is_an_error() {
+        let err = parse_schema_sql("CREATE TABLE t (id int", &DbType::MySQL).unwrap_err();
+        assert!(err.to_string().contains("CREATE TABLE t"));
+    }
+}
@@ -0,0 +1,488 @@
+use log::{error, info, warn};
+
+use crate::db::{SqlExecutor, runs_outside_transaction, terminated_statements};
+use crate::models::{DiffItem, RollbackReport, StatementResult};
+
+/// Run `statements` in order and record how each one went. With `stop_on_error`
+/// the run ends at the first failure and later statements are left out of the
+/// result; otherwise every statement is attempted.
+///
+/// Nothing is wrapped in a transaction, so statements that succeeded stay applied.
+pub async fn execute_with_report(
+    executor: &dyn SqlExecutor,
+    statements: &[String],
+    stop_on_error: bool,
+) -> Vec<StatementResult> {
+    let mut results = Vec::with_capacity(statements.len());
+    for (index, sql) in statements.iter().enumerate() {
+        info!("Executing statement {}/{}", index + 1, statements.len());
+        let outcome = executor.execute_sql(sql).await;
+        let failed = outcome.is_err();
+        if let Err(e) = &outcome {
+            error!("Failed to execute SQL: {}\nError: {}", sql, e);
+        }
+        results.push(StatementResult {
+            index,
+            sql: sql.clone(),
+            ok: !failed,
+            error: outcome.err().map(|e| e.to_string()),
+        });
+        if failed && stop_on_error {
+            break;
+        }
+    }
+    results
+}
+
+/// Run `statements` in order, each stretch of transactional statements in its own
+/// transaction and the ones `runs_outside_transaction` flags on their own between
+/// them. The run stops at the first failure; stretches already committed, and
+/// statements already run outside a transaction, stay applied.
+pub async fn execute_in_transactions(
+    executor: &dyn SqlExecutor,
+    statements: &[String],
+) -> anyhow::Result<()> {
+    let mut batch: Vec<String> = Vec::new();
+    for sql in statements {
+        if !runs_outside_transaction(sql) {
+            batch.push(sql.clone());
+            continue;
+        }
+        if !batch.is_empty() {
+            executor.execute_in_transaction(&batch).await?;
+            batch.clear();
+        }
+        executor.execute_sql(sql).await?;
+    }
+    if !batch.is_empty() {
+        executor.execute_in_transaction(&batch).await?;
+    }
+    Ok(())
+}
+
+/// Apply a single diff item and report how it went, as one result covering all
+/// of its statements. Without a transaction, the item stops at its first
+/// failing statement and the ones before it stay applied. With one, statements
+/// that can't run in a transaction run outside it; see `execute_in_transactions`.
+pub async fn apply_item(
+    executor: &dyn SqlExecutor,
+    item: &DiffItem,
+    use_transaction: bool,
+) -> StatementResult {
+    apply_item_counted(executor, item, use_transaction).await.0
+}
+
+/// `apply_item`, also counting the item's statements that succeeded: without a
+/// transaction, those before the failing one; with one, all of them or none.
+async fn apply_item_counted(
+    executor: &dyn SqlExecutor,
+    item: &DiffItem,
+    use_transaction: bool,
+) -> (StatementResult, usize) {
+    let statements = item.sql_statements();
+    info!(
+        "Applying diff item {} ({} statement(s)){}",
+        item.id,
+        statements.len(),
+        if use_transaction {
+            " in a transaction"
+        } else {
+            ""
+        }
+    );
+    let mut ran = statements.len();
+    let outcome = if !use_transaction {
+        let results = execute_with_report(executor, &statements, true).await;
+        ran = results.iter().filter(|r| r.ok).count();
+        match results.into_iter().find(|r| !r.ok) {
+            Some(failed) => Err(failed.error.unwrap_or_default()),
+            None => Ok(()),
+        }
+    } else {
+        execute_in_transactions(executor, &statements)
+            .await
+            .map_err(|e| {
+                error!("Failed to apply diff item {}: {}", item.id, e);
+                e.to_string()
+            })
+    };
+    if use_transaction && outcome.is_err() {
+        ran = 0;
+    }
+    let result = StatementResult {
+        index: 0,
+        sql: statements.join("\n"),
+        ok: outcome.is_ok(),
+        error: outcome.err(),
+    };
+    (result, ran)
+}
+
+/// Apply `items` in order so that a failure leaves the target as it was.
+///
+/// With `transactional_ddl` (Postgres) the whole batch runs in one transaction,
+/// rolled back on failure; the report then has a single result for the batch.
+/// A batch with statements that can't run in a transaction, such as
+/// `CREATE INDEX CONCURRENTLY`, falls back to the undo path below.
+///
+/// Otherwise (MySQL, which commits each DDL statement as it runs) items are
+/// applied one by one through `apply_item`, and on the first failure what already
+/// ran is undone, latest first: the failing item's statements that succeeded,
+/// then the items applied before it. `undo` gives the compensating SQL for an
+/// item of which the first `ran` statements were applied. Undo is best effort:
+/// items without one, or whose undo fails, are listed in `left_applied` and the
+/// rollback isn't clean.
+pub async fn apply_with_rollback(
+    executor: &dyn SqlExecutor,
+    items: &[DiffItem],
+    transactional_ddl: bool,
+    undo: impl Fn(&DiffItem, usize) -> Option<String>,
+) -> RollbackReport {
+    let mut report = RollbackReport {
+        clean_rollback: true,
+        ..Default::default()
+    };
+    let statements: Vec<String> = items.iter().flat_map(DiffItem::sql_statements).collect();
+    if transactional_ddl && !statements.iter().any(|s| runs_outside_transaction(s)) {
+        info!("Applying {} diff item(s) in one transaction", items.len());
+        match executor.execute_in_transaction(&statements).await {
+            Ok(()) => {
+                report.results = items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| StatementResult {
+                        index,
+                        sql: item.sql_statements().join("\n"),
+                        ok: true,
+                        error: None,
+                    })
+                    .collect();
+            }
+            Err(e) => {
+                error!("Failed to apply diff items, rolled back: {}", e);
+                report.results.push(StatementResult {
+                    index: 0,
+                    sql: statements.join("\n"),
+                    ok: false,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+        return report;
+    }
+    if transactional_ddl {
+        warn!("Statements that can't run in a transaction; undoing by hand on failure");
+    }
+
+    let mut applied: Vec<(&DiffItem, usize)> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let (result, ran) = apply_item_counted(executor, item, false).await;
+        if ran > 0 {
+            applied.push((item, ran));
+        }
+        let failed = !result.ok;
+        report.results.push(StatementResult { index, ..result });
+        if !failed {
+            continue;
+        }
+        for (done, ran) in applied.iter().rev() {
+            undo_item(executor, done, undo(done, *ran), &mut report).await;
+        }
+        report.clean_rollback = report.left_applied.is_empty();
+        break;
+    }
+    report
+}
+
+/// Run one item's compensating SQL, recording each statement in `report.undo`
+/// and the item in `report.left_applied` when it can't be undone.
+async fn undo_item(
+    executor: &dyn SqlExecutor,
+    item: &DiffItem,
+    sql: Option<String>,
+    report: &mut RollbackReport,
+) {
+    let Some(sql) = sql else {
+        warn!("No undo for diff item {}; leaving it applied", item.id);
+        report.left_applied.push(item.id.clone());
+        return;
+    };
+    info!("Undoing diff item {}", item.id);
+    let statements = terminated_statements(&sql);
+    let offset = report.undo.len();
+    let results = execute_with_report(executor, &statements, true).await;
+    if results.iter().any(|r| !r.ok) {
+        report.left_applied.push(item.id.clone());
+    }
+    report
+        .undo
+        .extend(results.into_iter().map(|r| StatementResult {
+            index: offset + r.index,
+            ..r
+        }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MySqlSqlGenerator;
+    use crate::diff::undo_sql;
+    use crate::models::DiffType;
+    use anyhow::{Result, anyhow};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Records what it ran and fails any statement containing `FAIL`.
+    #[derive(Default)]
+    struct MockExecutor {
+        ran: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl SqlExecutor for MockExecutor {
+        async fn execute_sql(&self, sql: &str) -> Result<()> {
+            self.ran.lock().unwrap().push(sql.to_string());
+            if sql.contains("FAIL") {
+                return Err(anyhow!("syntax error near FAIL"));
+            }
+            Ok(())
+        }
+
+        async fn execute_in_transaction(&self, statements: &[String]) -> Result<()> {
+            self.ran.lock().unwrap().push("BEGIN;".to_string());
+            for sql in statements {
+                self.execute_sql(sql).await?;
+            }
+            self.ran.lock().unwrap().push("COMMIT;".to_string());
+            Ok(())
+        }
+    }
+
+    fn statements() -> Vec<String> {
+        vec![
+            "CREATE TABLE a (id INT);".to_string(),
+            "FAIL;".to_string(),
+            "CREATE TABLE b (id INT);".to_string(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn stop_on_error_ends_at_failing_statement() {
+        let executor = MockExecutor::default();
+        let results = execute_with_report(&executor, &statements(), true).await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].ok);
+        assert!(!results[1].ok);
+        assert_eq!(results[1].index, 1);
+        assert_eq!(results[1].error.as_deref(), Some("syntax error near FAIL"));
+        assert_eq!(executor.ran.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn continue_runs_every_statement() {
+        let executor = MockExecutor::default();
+        let results = execute_with_report(&executor, &statements(), false).await;
+
+        let outcomes: Vec<_> = results.iter().map(|r| (r.index, r.ok)).collect();
+        assert_eq!(outcomes, vec![(0, true), (1, false), (2, true)]);
+        assert_eq!(results[2].error, None);
+        assert_eq!(executor.ran.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn concurrent_index_statements_run_between_transactions() {
+        let executor = MockExecutor::default();
+        let statements = vec![
+            "DROP INDEX CONCURRENTLY \"idx_a\";".to_string(),
+            "ALTER TABLE \"a\" ADD COLUMN \"b\" int;".to_string(),
+            "ALTER TABLE \"a\" ADD COLUMN \"c\" int;".to_string(),
+            "CREATE INDEX CONCURRENTLY \"idx_a\" ON \"a\" (\"b\");".to_string(),
+        ];
+        execute_in_transactions(&executor, &statements)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *executor.ran.lock().unwrap(),
+            vec![
+                statements[0].clone(),
+                "BEGIN;".to_string(),
+                statements[1].clone(),
+                statements[2].clone(),
+                "COMMIT;".to_string(),
+                statements[3].clone(),
+            ]
+        );
+    }
+
+    fn column_added() -> DiffItem {
+        DiffItem {
+            object_name: Some("nick".to_string()),
+            source_def: Some("varchar(20)".to_string()),
+            sql: "ALTER TABLE `users` ADD COLUMN `nick` varchar(20) NULL;".to_string(),
+            ..DiffItem::new("7", DiffType::ColumnAdded, "users")
+        }
+    }
+
+    #[tokio::test]
+    async fn single_column_added_is_applied_with_or_without_transaction() {
+        let executor = MockExecutor::default();
+        let result = apply_item(&executor, &column_added(), false).await;
+        assert!(result.ok, "{:?}", result.error);
+        assert_eq!(
+            result.sql,
+            "ALTER TABLE `users` ADD COLUMN `nick` varchar(20) NULL;"
+        );
+        assert_eq!(executor.ran.lock().unwrap().len(), 1);
+
+        let executor = MockExecutor::default();
+        let mut failing = column_added();
+        failing.sql.push_str("\nFAIL;");
+        let result = apply_item(&executor, &failing, true).await;
+        assert!(!result.ok);
+        assert_eq!(result.error.as_deref(), Some("syntax error near FAIL"));
+        // Rolled back: the transaction never reached its commit.
+        assert_eq!(executor.ran.lock().unwrap()[0], "BEGIN;");
+        assert!(
+            !executor
+                .ran
+                .lock()
+                .unwrap()
+                .contains(&"COMMIT;".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn transactional_apply_runs_concurrent_index_outside_the_transaction() {
+        let executor = MockExecutor::default();
+        let item = DiffItem {
+            sql: "ALTER TABLE \"users\" ADD COLUMN \"nick\" varchar(20);\n\
+                  CREATE INDEX CONCURRENTLY \"idx_nick\" ON \"users\" (\"nick\");"
+                .to_string(),
+            ..DiffItem::new("8", DiffType::ColumnAdded, "users")
+        };
+        let result = apply_item(&executor, &item, true).await;
+
+        assert!(result.ok, "{:?}", result.error);
+        assert_eq!(
+            *executor.ran.lock().unwrap(),
+            vec![
+                "BEGIN;",
+                "ALTER TABLE \"users\" ADD COLUMN \"nick\" varchar(20);",
+                "COMMIT;",
+                "CREATE INDEX CONCURRENTLY \"idx_nick\" ON \"users\" (\"nick\");",
+            ]
+        );
+    }
+
+    fn item(id: &str, diff_type: DiffType, object: &str, sql: &str) -> DiffItem {
+        DiffItem {
+            object_name: Some(object.to_string()),
+            sql: sql.to_string(),
+            ..DiffItem::new(id, diff_type, "users")
+        }
+    }
+
+    #[tokio::test]
+    async fn mysql_failure_undoes_applied_statements_with_compensating_ones() {
+        let executor = MockExecutor::default();
+        let items = vec![
+            item(
+                "1",
+                DiffType::ColumnAdded,
+                "nick",
+                "ALTER TABLE `users` ADD COLUMN `nick` varchar(20) NULL;",
+            ),
+            item(
+                "2",
+                DiffType::IndexAdded,
+                "idx_nick",
+                "CREATE INDEX `idx_nick` ON `users` (`nick`);",
+            ),
+            // Its column is added before the statement after it fails.
+            item(
+                "3",
+                DiffType::ColumnAdded,
+                "age",
+                "ALTER TABLE `users` ADD COLUMN `age` int;\nFAIL;",
+            ),
+        ];
+        let report = apply_with_rollback(&executor, &items, false, |item, ran| {
+            undo_sql(item, ran, &[], &MySqlSqlGenerator)
+        })
+        .await;
+
+        let outcomes: Vec<_> = report.results.iter().map(|r| (r.index, r.ok)).collect();
+        assert_eq!(outcomes, vec![(0, true), (1, true), (2, false)]);
+        let undone: Vec<_> = report.undo.iter().map(|r| r.sql.as_str()).collect();
+        assert_eq!(
+            undone,
+            vec![
+                "ALTER TABLE `users` DROP COLUMN `age`;",
+                "DROP INDEX `idx_nick` ON `users`;",
+                "ALTER TABLE `users` DROP COLUMN `nick`;",
+            ]
+        );
+        assert!(report.left_applied.is_empty());
+        assert!(report.clean_rollback);
+    }
+
+    #[tokio::test]
+    async fn items_without_undo_are_left_applied() {
+        let executor = MockExecutor::default();
+        let items = vec![
+            item("1", DiffType::RoutineModified, "f", "DROP FUNCTION f;"),
+            item("2", DiffType::ColumnAdded, "age", "FAIL;"),
+        ];
+        let report = apply_with_rollback(&executor, &items, false, |item, ran| {
+            undo_sql(item, ran, &[], &MySqlSqlGenerator)
+        })
+        .await;
+
+        assert!(report.undo.is_empty());
+        assert_eq!(report.left_applied, vec!["1"]);
+        assert!(!report.clean_rollback);
+
+        let report = apply_with_rollback(&executor, &items[..1], false, |_, _| None).await;
+        assert!(report.undo.is_empty());
+        assert!(report.clean_rollback);
+    }
+
+    #[tokio::test]
+    async fn postgres_batch_runs_in_one_transaction_rolled_back_on_failure() {
+        let executor = MockExecutor::default();
+        let items = vec![
+            item(
+                "1",
+                DiffType::ColumnAdded,
+                "nick",
+                "ALTER TABLE \"users\" ADD COLUMN \"nick\" varchar(20);",
+            ),
+            item("2", DiffType::ColumnAdded, "age", "FAIL;"),
+        ];
+        let report = apply_with_rollback(&executor, &items, true, |_, _| {
+            panic!("a rolled-back transaction needs no undo")
+        })
+        .await;
+
+        assert_eq!(
+            *executor.ran.lock().unwrap(),
+            vec![
+                "BEGIN;",
+                "ALTER TABLE \"users\" ADD COLUMN \"nick\" varchar(20);",
+                "FAIL;",
+            ]
+        );
+        assert_eq!(report.results.len(), 1);
+        assert!(!report.results[0].ok);
+        assert!(report.undo.is_empty());
+        assert!(report.clean_rollback);
+
+        let executor = MockExecutor::default();
+        let report = apply_with_rollback(&executor, &items[..1], true, |_, _| None).await;
+        let outcomes: Vec<_> = report.results.iter().map(|r| (r.index, r.ok)).collect();
+        assert_eq!(outcomes, vec![(0, true)]);
+        assert_eq!(executor.ran.lock().unwrap().last().unwrap(), "COMMIT;");
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+
+use crate::db::ddl::parse_schema_sql;
+use crate::db::{SchemaReader, SqlExecutor, SqlGenerator, sql_generator_for};
+use crate::models::*;
+
+/// A schema dump read in place of a live database. The file is parsed once on
+/// load; it can be compared against but never changed.
+pub struct SchemaFile {
+    path: String,
+    db_type: DbType,
+    tables: Vec<TableSchema>,
+}
+
+impl SchemaFile {
+    pub fn load(path: &str, db_type: &DbType) -> Result<Self> {
+        let sql = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read schema file {}", path))?;
+        let tables = parse_schema_sql(&sql, db_type)
+            .with_context(|| format!("Failed to parse schema file {}", path))?;
+        if tables.is_empty() {
+            bail!("No CREATE TABLE statements found in {}", path);
+        }
+        Ok(Self {
+            path: path.to_string(),
+            db_type: db_type.clone(),
+            tables,
+        })
+    }
+
+    /// Generator for the file's dialect.
+    pub fn sql_generator(&self) -> &'static dyn SqlGenerator {
+        sql_generator_for(&self.db_type)
+    }
+}
+
+#[async_trait]
+impl SchemaReader for SchemaFile {
+    async fn test_connection(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn server_version(&self) -> Result<String> {
+        Ok(format!("schema file {}", self.path))
+    }
+
+    async fn get_tables(&self) -> Result<Vec<TableSchema>> {
+        Ok(self.tables.clone())
+    }
+
+    async fn get_tables_named(&self, names: &[String]) -> Result<Vec<TableSchema>> {
+        Ok(self
+            .tables
+            .iter()
+            .filter(|t| names.contains(&t.name))
+            .cloned()
+            .collect())
+    }
+
+    async fn list_tables(&self) -> Result<Vec<String>> {
+        Ok(self.tables.iter().map(|t| t.name.clone()).collect())
+    }
+
+    async fn list_databases(&self, _: Option<&str>, _: Option<u32>) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    async fn get_database_info(&self) -> Result<DatabaseInfo> {
+        Ok(DatabaseInfo::default())
+    }
+
+    async fn estimated_row_count(&self, _table: &str) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    async fn get_routines(&self) -> Result<Vec<Routine>> {
+        Ok(vec![])
+    }
+
+    async fn get_grants(&self) -> Result<Vec<Grant>> {
+        Ok(vec![])
+    }
+
+    fn take_read_warnings(&self) -> Vec<String> {
+        vec![]
+    }
+
+    async fn list_schema_objects(&self) -> Result<SchemaObjects> {
+        Ok(SchemaObjects::default())
+    }
+
+    async fn native_create_table(&self, table: &str) -> Result<String> {
+        let Some(schema) = self.tables.iter().find(|t| t.name == table) else {
+            bail!("Table {} not found in {}", table, self.path);
+        };
+        Ok(self.sql_generator().generate_create_table(schema))
+    }
+}
+
+#[async_trait]
+impl SqlExecutor for SchemaFile {
+    async fn execute_sql(&self, _sql: &str) -> Result<()> {
+        bail!("Cannot apply changes to a schema file connection")
+    }
+
+    async fn execute_in_transaction(&self, _statements: &[String]) -> Result<()> {
+        bail!("Cannot apply changes to a schema file connection")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn loads_and_compares_tables_from_dump() {
+        let path = std::env::temp_dir().join(format!("schema-{}.sql", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "CREATE TABLE `users` (`id` int NOT NULL, PRIMARY KEY (`id`));\n\
+             CREATE TABLE `orders` (`id` int NOT NULL);",
+        )
+        .unwrap();
+
+        let file = SchemaFile::load(path.to_str().unwrap(), &DbType::MySQL).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(file.list_tables().await.unwrap(), vec!["orders", "users"]);
+        let named = file.get_tables_named(&["users".to_string()]).await.unwrap();
+        assert_eq!(named.len(), 1);
+        assert!(named[0].primary_key.is_some());
+        assert!(file.execute_sql("DROP TABLE users").await.is_err());
+
+        let target = file
+            .get_tables_named(&["orders".to_string()])
+            .await
+            .unwrap();
+        let diff = crate::diff::compare_snapshots(
+            &file.get_tables().await.unwrap(),
+            &target,
+            DbType::MySQL,
+        );
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].diff_type, DiffType::TableAdded);
+        assert_eq!(diff[0].table_name, "users");
+    }
+
+    #[test]
+    fn dump_without_tables_is_rejected() {
+        let path = std::env::temp_dir().join(format!("schema-{}.sql", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "SET NAMES utf8mb4;").unwrap();
+
+        let err = SchemaFile::load(path.to_str().unwrap(), &DbType::MySQL)
+            .err()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.to_string().contains("No CREATE TABLE"));
+    }
+}
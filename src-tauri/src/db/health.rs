@@ -0,0 +1,56 @@
+use log::warn;
+use std::time::Instant;
+
+use crate::db::SchemaReader;
+use crate::models::PingResult;
+
+/// Time a trivial query on `reader` and fetch the server version. A failed query
+/// gives `ok: false` rather than an error; the version is best-effort.
+pub async fn ping(reader: &dyn SchemaReader) -> PingResult {
+    let started = Instant::now();
+    let outcome = reader.test_connection().await;
+    let latency_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+    if let Err(e) = outcome {
+        warn!("Ping failed: {}", e);
+        return PingResult {
+            ok: false,
+            latency_ms,
+            server_version: None,
+        };
+    }
+
+    let server_version = match reader.server_version().await {
+        Ok(version) => Some(version),
+        Err(e) => {
+            warn!("Failed to read server version: {}", e);
+            None
+        }
+    };
+    PingResult {
+        ok: true,
+        latency_ms,
+        server_version,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::PostgresDriver;
+    use sqlx::postgres::PgPoolOptions;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn unreachable_server_pings_not_ok() {
+        // Nothing listens on port 1, so the connection is refused straight away.
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_secs(5))
+            .connect_lazy("postgres://app@127.0.0.1:1/app")
+            .unwrap();
+        let result = ping(&PostgresDriver::from_pool(pool)).await;
+
+        assert!(!result.ok);
+        assert_eq!(result.server_version, None);
+    }
+}
@@ -1,18 +1,40 @@
+pub mod cache;
+pub mod ddl;
+pub mod execute;
+pub mod file;
+pub mod health;
 pub mod mysql;
 pub mod postgres;
+pub mod registry;
+mod snapshot;
+pub mod statements;
+pub mod tolerant;
 pub mod traits;
 
 use crate::models::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+pub use cache::ConnectionCache;
+pub use execute::{apply_item, apply_with_rollback, execute_in_transactions, execute_with_report};
+pub use file::SchemaFile;
+pub use health::ping;
 pub use mysql::MySqlDriver;
 pub use mysql::MySqlSqlGenerator;
 pub use postgres::PostgresDriver;
 pub use postgres::PostgresSqlGenerator;
-pub use traits::{SchemaReader, SqlGenerator};
+pub use registry::GeneratorRegistry;
+pub use statements::{runs_outside_transaction, split_statements, terminated_statements};
+pub use tolerant::{TableRead, read_tables_tolerant};
+pub use traits::{SchemaReader, SqlExecutor, SqlGenerator};
 
 /// Raw row types for batch metadata queries.
 /// Each driver queries all tables at once and returns these intermediate types.
+pub struct TableRow {
+    pub table_name: String,
+    pub tablespace: Option<String>,
+    pub options: TableOptions,
+}
+
 pub struct ColumnRow {
     pub table_name: String,
     pub name: String,
@@ -22,6 +44,12 @@ pub struct ColumnRow {
     pub auto_increment: bool,
     pub comment: Option<String>,
     pub ordinal_position: u32,
+    pub visible: bool,
+    pub on_update: Option<String>,
+    pub sequence: Option<String>,
+    pub generated: Option<GeneratedColumn>,
+    pub srid: Option<u32>,
+    pub octet_length: Option<u64>,
 }
 
 pub struct PkRow {
@@ -36,6 +64,15 @@ pub struct IndexRow {
     pub column_name: String,
     pub is_unique: bool,
     pub index_type: String,
+    pub tablespace: Option<String>,
+    pub visible: bool,
+    /// Position of `column_name` within the index key; only the relative order matters.
+    pub seq_in_index: u32,
+    /// Set on the single row reported for a definition-only index; see `Index::expression`.
+    pub expression: Option<String>,
+    /// Storage parameters of the index; see `Index::storage_params`.
+    pub storage_params: Vec<String>,
+    pub comment: Option<String>,
 }
 
 pub struct FkRow {
@@ -46,6 +83,9 @@ pub struct FkRow {
     pub ref_column: String,
     pub on_delete: String,
     pub on_update: String,
+    pub deferrable: bool,
+    pub initially_deferred: bool,
+    pub match_type: Option<String>,
 }
 
 pub struct UcRow {
@@ -54,19 +94,36 @@ pub struct UcRow {
     pub column_name: String,
 }
 
-/// (is_unique, index_type, columns)
-type IndexEntry = (bool, String, Vec<String>);
-/// (ref_table, columns, ref_columns, on_delete, on_update)
-type FkEntry = (String, Vec<String>, Vec<String>, String, String);
+pub struct ExclusionRow {
+    pub table_name: String,
+    pub constraint_name: String,
+    pub definition: String,
+}
+
+/// (is_unique, index_type, tablespace, visible, expression, storage_params, comment, columns)
+type IndexEntry = (
+    bool,
+    String,
+    Option<String>,
+    bool,
+    Option<String>,
+    Vec<String>,
+    Option<String>,
+    Vec<String>,
+);
 
 /// Assemble raw metadata rows into Vec<TableSchema>, grouped by table name.
+///
+/// Output is deterministic: tables and their indexes, foreign keys and constraints
+/// are sorted by name, while columns within each keep the order the rows arrived in.
 pub fn assemble_schemas(
-    table_names: Vec<String>,
+    mut table_rows: Vec<TableRow>,
     column_rows: Vec<ColumnRow>,
     pk_rows: Vec<PkRow>,
-    index_rows: Vec<IndexRow>,
+    mut index_rows: Vec<IndexRow>,
     fk_rows: Vec<FkRow>,
     uc_rows: Vec<UcRow>,
+    exclusion_rows: Vec<ExclusionRow>,
 ) -> Vec<TableSchema> {
     // Group columns by table
     let mut columns_map: HashMap<String, Vec<Column>> = HashMap::new();
@@ -79,6 +136,12 @@ pub fn assemble_schemas(
             auto_increment: r.auto_increment,
             comment: r.comment,
             ordinal_position: r.ordinal_position,
+            visible: r.visible,
+            on_update: r.on_update,
+            sequence: r.sequence,
+            generated: r.generated,
+            srid: r.srid,
+            octet_length: r.octet_length,
         });
     }
 
@@ -91,34 +154,50 @@ pub fn assemble_schemas(
         entry.1.push(r.column_name);
     }
 
-    // Group indexes by table -> index_name
-    let mut index_map: HashMap<String, HashMap<String, IndexEntry>> = HashMap::new();
+    // Group indexes by table -> index_name; columns are pushed in key order
+    // regardless of the order the rows were returned in.
+    index_rows.sort_by_key(|r| r.seq_in_index);
+    let mut index_map: HashMap<String, BTreeMap<String, IndexEntry>> = HashMap::new();
     for r in index_rows {
         let table_entry = index_map.entry(r.table_name).or_default();
-        let idx_entry =
-            table_entry
-                .entry(r.index_name)
-                .or_insert((r.is_unique, r.index_type, Vec::new()));
-        idx_entry.2.push(r.column_name);
+        let idx_entry = table_entry.entry(r.index_name).or_insert((
+            r.is_unique,
+            r.index_type,
+            r.tablespace,
+            r.visible,
+            r.expression.clone(),
+            r.storage_params,
+            r.comment,
+            Vec::new(),
+        ));
+        if r.expression.is_none() {
+            idx_entry.7.push(r.column_name);
+        }
     }
 
     // Group FKs by table -> constraint_name
-    let mut fk_map: HashMap<String, HashMap<String, FkEntry>> = HashMap::new();
+    let mut fk_map: HashMap<String, BTreeMap<String, ForeignKey>> = HashMap::new();
     for r in fk_rows {
         let table_entry = fk_map.entry(r.table_name).or_default();
-        let fk_entry = table_entry.entry(r.constraint_name).or_insert((
-            r.ref_table,
-            Vec::new(),
-            Vec::new(),
-            r.on_delete,
-            r.on_update,
-        ));
-        fk_entry.1.push(r.column_name);
-        fk_entry.2.push(r.ref_column);
+        let fk = table_entry
+            .entry(r.constraint_name.clone())
+            .or_insert_with(|| ForeignKey {
+                name: r.constraint_name,
+                columns: Vec::new(),
+                ref_table: r.ref_table,
+                ref_columns: Vec::new(),
+                on_delete: r.on_delete,
+                on_update: r.on_update,
+                deferrable: r.deferrable,
+                initially_deferred: r.initially_deferred,
+                match_type: r.match_type,
+            });
+        fk.columns.push(r.column_name);
+        fk.ref_columns.push(r.ref_column);
     }
 
     // Group UCs by table -> constraint_name
-    let mut uc_map: HashMap<String, HashMap<String, Vec<String>>> = HashMap::new();
+    let mut uc_map: HashMap<String, BTreeMap<String, Vec<String>>> = HashMap::new();
     for r in uc_rows {
         let table_entry = uc_map.entry(r.table_name).or_default();
         table_entry
@@ -127,10 +206,23 @@ pub fn assemble_schemas(
             .push(r.column_name);
     }
 
+    let mut exclusion_map: HashMap<String, Vec<ExclusionConstraint>> = HashMap::new();
+    for r in exclusion_rows {
+        exclusion_map
+            .entry(r.table_name)
+            .or_default()
+            .push(ExclusionConstraint {
+                name: r.constraint_name,
+                definition: r.definition,
+            });
+    }
+
     // Assemble
-    table_names
+    table_rows.sort_by(|a, b| a.table_name.cmp(&b.table_name));
+    table_rows
         .into_iter()
-        .map(|name| {
+        .map(|table| {
+            let name = table.table_name;
             let columns = columns_map.remove(&name).unwrap_or_default();
 
             let primary_key = pk_map.remove(&name).map(|(pk_name, columns)| PrimaryKey {
@@ -142,28 +234,37 @@ pub fn assemble_schemas(
                 .remove(&name)
                 .unwrap_or_default()
                 .into_iter()
-                .map(|(idx_name, (unique, idx_type, cols))| Index {
-                    name: idx_name,
-                    columns: cols,
-                    unique,
-                    index_type: idx_type,
-                })
+                .map(
+                    |(
+                        idx_name,
+                        (
+                            unique,
+                            idx_type,
+                            tablespace,
+                            visible,
+                            expression,
+                            storage_params,
+                            comment,
+                            cols,
+                        ),
+                    )| Index {
+                        name: idx_name,
+                        columns: cols,
+                        unique,
+                        index_type: idx_type,
+                        tablespace,
+                        visible,
+                        expression,
+                        storage_params,
+                        comment,
+                    },
+                )
                 .collect();
 
             let foreign_keys = fk_map
                 .remove(&name)
                 .unwrap_or_default()
-                .into_iter()
-                .map(
-                    |(fk_name, (ref_table, cols, ref_cols, on_delete, on_update))| ForeignKey {
-                        name: fk_name,
-                        columns: cols,
-                        ref_table,
-                        ref_columns: ref_cols,
-                        on_delete,
-                        on_update,
-                    },
-                )
+                .into_values()
                 .collect();
 
             let unique_constraints = uc_map
@@ -176,6 +277,9 @@ pub fn assemble_schemas(
                 })
                 .collect();
 
+            let mut exclusion_constraints = exclusion_map.remove(&name).unwrap_or_default();
+            exclusion_constraints.sort_by(|a, b| a.name.cmp(&b.name));
+
             TableSchema {
                 name,
                 columns,
@@ -183,11 +287,27 @@ pub fn assemble_schemas(
                 indexes,
                 foreign_keys,
                 unique_constraints,
+                tablespace: table.tablespace,
+                exclusion_constraints,
+                check_constraints: vec![],
+                row_security: false,
+                policies: vec![],
+                partitioning: None,
+                options: table.options,
+                inherits: vec![],
             }
         })
         .collect()
 }
 
+/// Human-readable table scope for log lines: "all tables" or the listed names.
+pub(crate) fn describe_tables(tables: Option<&[String]>) -> String {
+    match tables {
+        Some(names) => names.join(", "),
+        None => "all tables".to_string(),
+    }
+}
+
 /// Macro to delegate SqlGenerator trait from a Driver to its inner SqlGenerator.
 macro_rules! impl_sql_generator_delegation {
     ($driver:ty, $generator:expr) => {
@@ -204,21 +324,50 @@ macro_rules! impl_sql_generator_delegation {
             fn generate_add_column(&self, table: &str, column: &Column) -> String {
                 $generator.generate_add_column(table, column)
             }
+            fn generate_add_column_online(&self, table: &str, column: &Column) -> String {
+                $generator.generate_add_column_online(table, column)
+            }
             fn generate_drop_column(&self, table: &str, column_name: &str) -> String {
                 $generator.generate_drop_column(table, column_name)
             }
             fn generate_modify_column(&self, table: &str, column: &Column) -> String {
                 $generator.generate_modify_column(table, column)
             }
+            fn generate_alter_column(
+                &self,
+                table: &str,
+                source: &Column,
+                target: &Column,
+            ) -> String {
+                $generator.generate_alter_column(table, source, target)
+            }
+            fn generate_rename_column(
+                &self,
+                table: &str,
+                source: &Column,
+                target: &Column,
+            ) -> String {
+                $generator.generate_rename_column(table, source, target)
+            }
             fn generate_add_index(&self, table: &str, index: &Index) -> String {
                 $generator.generate_add_index(table, index)
             }
+            fn generate_add_index_online(&self, table: &str, index: &Index) -> String {
+                $generator.generate_add_index_online(table, index)
+            }
             fn generate_drop_index(&self, table: &str, index_name: &str) -> String {
                 $generator.generate_drop_index(table, index_name)
             }
             fn generate_add_foreign_key(&self, table: &str, fk: &ForeignKey) -> String {
                 $generator.generate_add_foreign_key(table, fk)
             }
+            fn generate_add_foreign_key_deferred_validation(
+                &self,
+                table: &str,
+                fk: &ForeignKey,
+            ) -> String {
+                $generator.generate_add_foreign_key_deferred_validation(table, fk)
+            }
             fn generate_drop_foreign_key(&self, table: &str, fk_name: &str) -> String {
                 $generator.generate_drop_foreign_key(table, fk_name)
             }
@@ -228,12 +377,205 @@ macro_rules! impl_sql_generator_delegation {
             fn generate_drop_unique(&self, table: &str, uc_name: &str) -> String {
                 $generator.generate_drop_unique(table, uc_name)
             }
+            fn generate_alter_primary_key(
+                &self,
+                table: &str,
+                current: Option<&PrimaryKey>,
+                wanted: Option<&PrimaryKey>,
+            ) -> String {
+                $generator.generate_alter_primary_key(table, current, wanted)
+            }
+            fn generate_add_exclusion(&self, table: &str, ec: &ExclusionConstraint) -> String {
+                $generator.generate_add_exclusion(table, ec)
+            }
+            fn generate_drop_exclusion(&self, table: &str, ec_name: &str) -> String {
+                $generator.generate_drop_exclusion(table, ec_name)
+            }
+            fn generate_add_check(&self, table: &str, check: &CheckConstraint) -> String {
+                $generator.generate_add_check(table, check)
+            }
+            fn generate_drop_check(&self, table: &str, check_name: &str) -> String {
+                $generator.generate_drop_check(table, check_name)
+            }
+            fn generate_create_policy(&self, table: &str, policy: &Policy) -> String {
+                $generator.generate_create_policy(table, policy)
+            }
+            fn generate_alter_policy(&self, table: &str, policy: &Policy) -> String {
+                $generator.generate_alter_policy(table, policy)
+            }
+            fn generate_drop_policy(&self, table: &str, policy_name: &str) -> String {
+                $generator.generate_drop_policy(table, policy_name)
+            }
+            fn generate_set_row_security(&self, table: &str, enabled: bool) -> String {
+                $generator.generate_set_row_security(table, enabled)
+            }
+            fn generate_create_routine(&self, routine: &Routine) -> String {
+                $generator.generate_create_routine(routine)
+            }
+            fn generate_replace_routine(&self, routine: &Routine) -> String {
+                $generator.generate_replace_routine(routine)
+            }
+            fn generate_drop_routine(&self, routine: &Routine) -> String {
+                $generator.generate_drop_routine(routine)
+            }
+            fn generate_rebuild_table(&self, source: &TableSchema, target: &TableSchema) -> String {
+                $generator.generate_rebuild_table(source, target)
+            }
+            fn generate_move_column(
+                &self,
+                table: &str,
+                column: &Column,
+                after: Option<&str>,
+            ) -> Option<String> {
+                $generator.generate_move_column(table, column, after)
+            }
+            fn generate_alter_partitioning(
+                &self,
+                table: &str,
+                partitioning: Option<&PartitionSpec>,
+            ) -> Option<String> {
+                $generator.generate_alter_partitioning(table, partitioning)
+            }
+            fn generate_set_tablespace(&self, table: &str, tablespace: Option<&str>) -> String {
+                $generator.generate_set_tablespace(table, tablespace)
+            }
+            fn generate_set_table_options(&self, table: &str, options: &TableOptions) -> String {
+                $generator.generate_set_table_options(table, options)
+            }
+            fn generate_set_inherits(
+                &self,
+                table: &str,
+                add: &[String],
+                remove: &[String],
+            ) -> String {
+                $generator.generate_set_inherits(table, add, remove)
+            }
+            fn generate_grant(&self, grant: &Grant) -> String {
+                $generator.generate_grant(grant)
+            }
+            fn generate_revoke(&self, grant: &Grant) -> String {
+                $generator.generate_revoke(grant)
+            }
         }
     };
 }
 
 pub(crate) use impl_sql_generator_delegation;
 
+/// Split a table rebuild into the temporary table to create and the columns whose
+/// data can be copied over. The temporary table carries only columns and the primary
+/// key; indexes and constraints are added after the rename so their names don't
+/// collide with the ones still owned by the old table. Generated columns are
+/// recomputed rather than copied.
+pub(crate) fn rebuild_parts(
+    source: &TableSchema,
+    target: &TableSchema,
+) -> (TableSchema, Vec<String>) {
+    let temp = TableSchema {
+        name: format!("{}__rebuild", source.name),
+        columns: source.columns.clone(),
+        primary_key: source.primary_key.clone(),
+        indexes: vec![],
+        foreign_keys: vec![],
+        unique_constraints: vec![],
+        tablespace: source.tablespace.clone(),
+        exclusion_constraints: vec![],
+        check_constraints: vec![],
+        row_security: false,
+        policies: vec![],
+        partitioning: source.partitioning.clone(),
+        options: source.options.clone(),
+        inherits: source.inherits.clone(),
+    };
+    let copy_columns = source
+        .columns
+        .iter()
+        .filter(|c| c.generated.is_none())
+        .filter(|c| target.columns.iter().any(|t| t.name == c.name))
+        .map(|c| c.name.clone())
+        .collect();
+    (temp, copy_columns)
+}
+
+/// Build `SET name = value` statements for connection session parameters. Names must
+/// be plain (optionally dotted) identifiers; values are passed through as written, so
+/// string values need their own quotes (`'STRICT_TRANS_TABLES'`).
+pub fn session_set_statements(params: &[(String, String)]) -> anyhow::Result<Vec<String>> {
+    params
+        .iter()
+        .map(|(name, value)| {
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+            if !valid_name {
+                anyhow::bail!("Invalid session parameter name: {:?}", name);
+            }
+            if value.trim().is_empty() || value.contains(';') {
+                anyhow::bail!("Invalid value for session parameter {}: {:?}", name, value);
+            }
+            Ok(format!("SET {} = {}", name, value.trim()))
+        })
+        .collect()
+}
+
+/// How this app names itself to database servers, for telling its sessions
+/// apart in server monitoring.
+pub const APPLICATION_NAME: &str = "database-structure-sync";
+
+/// The stateless generator for a dialect, for work that needs no connection.
+pub fn sql_generator_for(db_type: &DbType) -> &'static dyn SqlGenerator {
+    match db_type {
+        DbType::MySQL | DbType::MariaDB => &MySqlSqlGenerator,
+        DbType::PostgreSQL => &PostgresSqlGenerator {
+            concurrent_indexes: false,
+        },
+    }
+}
+
+/// Longest statement timeout every dialect accepts: Postgres caps
+/// `statement_timeout` at `i32::MAX` milliseconds.
+pub const MAX_STATEMENT_TIMEOUT_SECS: u32 = 2_147_483;
+
+/// Session parameters that cap how long one statement may run, so a stuck DDL
+/// statement aborts instead of blocking for hours. MySQL's `max_execution_time`
+/// only covers SELECTs; `lock_wait_timeout` bounds the metadata lock DDL waits on.
+pub fn statement_timeout_params(db_type: &DbType, secs: u32) -> Vec<(String, String)> {
+    let millis = u64::from(secs) * 1000;
+    match db_type {
+        DbType::PostgreSQL => vec![("statement_timeout".to_string(), millis.to_string())],
+        DbType::MySQL => vec![
+            ("max_execution_time".to_string(), millis.to_string()),
+            ("lock_wait_timeout".to_string(), secs.to_string()),
+        ],
+        DbType::MariaDB => vec![
+            ("max_statement_time".to_string(), secs.to_string()),
+            ("lock_wait_timeout".to_string(), secs.to_string()),
+        ],
+    }
+}
+
+/// Report a statement cancelled by the session's statement timeout as
+/// `AppError::Database("statement timed out")`; other errors pass through.
+pub(crate) fn statement_timeout_error(err: sqlx::Error) -> anyhow::Error {
+    let timed_out = match &err {
+        sqlx::Error::Database(db) => {
+            // Postgres query_canceled; MySQL ER_QUERY_TIMEOUT and
+            // ER_LOCK_WAIT_TIMEOUT; MariaDB ER_STATEMENT_TIMEOUT.
+            db.code().as_deref() == Some("57014")
+                || db
+                    .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+                    .is_some_and(|e| matches!(e.number(), 3024 | 1205 | 1969))
+        }
+        _ => false,
+    };
+    if timed_out {
+        crate::error::AppError::Database("statement timed out".to_string()).into()
+    } else {
+        err.into()
+    }
+}
+
 /// Validate a foreign key action string. Returns the action if valid, or "NO ACTION" as fallback.
 pub fn validate_fk_action(action: &str) -> &str {
     match action.to_uppercase().as_str() {
@@ -6,32 +6,129 @@ use super::reader::MySqlDriver;
 
 pub struct MySqlSqlGenerator;
 
+/// Versioned comments so the DDL still runs on servers without visibility support
+/// (invisible columns arrived in 8.0.23, invisible indexes in 8.0.0).
+const INVISIBLE_COLUMN: &str = " /*!80023 INVISIBLE */";
+const INVISIBLE_INDEX: &str = " /*!80000 INVISIBLE */";
+/// Check constraints are only enforced from 8.0.16; earlier servers parse and
+/// ignore them, so the clause is versioned like the ones above.
+const NOT_ENFORCED: &str = " /*!80016 NOT ENFORCED */";
+
+/// Online DDL request for ALTER TABLE. MySQL rejects the statement rather than
+/// silently taking a lock when the operation can't honour it, so it is only
+/// added where InnoDB supports it: nullable columns that need no backfill and
+/// BTREE/HASH secondary indexes. Primary keys, FULLTEXT and SPATIAL indexes,
+/// NOT NULL, AUTO_INCREMENT and stored generated columns keep the default.
+const ONLINE_DDL: &str = "ALGORITHM=INPLACE, LOCK=NONE";
+
+/// Column SRIDs (8.0.3+), in the versioned comment form SHOW CREATE TABLE uses.
+fn srid_clause(column: &Column) -> String {
+    column
+        .srid
+        .map(|srid| format!(" /*!80003 SRID {} */", srid))
+        .unwrap_or_default()
+}
+
+/// ` COMMENT '...'` for an index that has one.
+fn index_comment(index: &Index) -> String {
+    index
+        .comment
+        .as_ref()
+        .map(|comment| format!(" COMMENT '{}'", comment.replace('\'', "''")))
+        .unwrap_or_default()
+}
+
+/// ` ENGINE=... DEFAULT CHARSET=... ROW_FORMAT=...` for the options that are set.
+fn table_option_clauses(options: &TableOptions) -> String {
+    let mut sql = String::new();
+    if let Some(engine) = &options.engine {
+        sql.push_str(&format!(" ENGINE={}", engine));
+    }
+    if let Some(charset) = &options.charset {
+        sql.push_str(&format!(" DEFAULT CHARSET={}", charset));
+    }
+    if let Some(row_format) = &options.row_format {
+        sql.push_str(&format!(" ROW_FORMAT={}", row_format));
+    }
+    sql
+}
+
+impl MySqlSqlGenerator {
+    /// `CONSTRAINT name CHECK (expression)`, marked when it isn't enforced.
+    fn check_clause(&self, check: &CheckConstraint) -> String {
+        format!(
+            "CONSTRAINT {} CHECK ({}){}",
+            self.quote_identifier(&check.name),
+            check.expression,
+            if check.enforced { "" } else { NOT_ENFORCED }
+        )
+    }
+
+    /// `PARTITION BY method (expression)` followed by the partition list, if any.
+    fn partition_clause(&self, spec: &PartitionSpec) -> String {
+        let mut clause = format!("PARTITION BY {} ({})", spec.method, spec.expression);
+        let partitions: Vec<String> = spec
+            .partitions
+            .iter()
+            .map(|p| {
+                let name = format!("PARTITION {}", self.quote_identifier(&p.name));
+                if p.bound.is_empty() {
+                    name
+                } else {
+                    format!("{} {}", name, p.bound)
+                }
+            })
+            .collect();
+        if !partitions.is_empty() {
+            clause.push_str(&format!("\n({})", partitions.join(",\n ")));
+        }
+        clause
+    }
+}
+
 impl SqlGenerator for MySqlSqlGenerator {
     fn quote_identifier(&self, name: &str) -> String {
         format!("`{}`", name.replace('`', "``"))
     }
 
     fn generate_create_table(&self, table: &TableSchema) -> String {
+        if table.columns.is_empty() {
+            // MySQL needs at least one column; a comment at least runs cleanly.
+            return format!(
+                "-- Table {} has no columns; MySQL cannot create it",
+                self.quote_identifier(&table.name)
+            );
+        }
         let mut sql = format!("CREATE TABLE {} (\n", self.quote_identifier(&table.name));
 
         let mut parts: Vec<String> = Vec::new();
 
         for col in &table.columns {
             let mut col_def = format!("  {} {}", self.quote_identifier(&col.name), col.data_type);
+            if let Some(generated) = &col.generated {
+                col_def.push_str(&format!(" {}", generated.clause()));
+            }
             if !col.nullable {
                 col_def.push_str(" NOT NULL");
             } else {
                 col_def.push_str(" NULL");
             }
-            if let Some(default) = &col.default_value {
+            col_def.push_str(&srid_clause(col));
+            if let Some(default) = col.effective_default() {
                 col_def.push_str(&format!(" DEFAULT {}", default));
             }
             if col.auto_increment {
                 col_def.push_str(" AUTO_INCREMENT");
             }
+            if let Some(on_update) = &col.on_update {
+                col_def.push_str(&format!(" ON UPDATE {}", on_update));
+            }
             if let Some(comment) = &col.comment {
                 col_def.push_str(&format!(" COMMENT '{}'", comment.replace('\'', "''")));
             }
+            if !col.visible {
+                col_def.push_str(INVISIBLE_COLUMN);
+            }
             parts.push(col_def);
         }
 
@@ -52,10 +149,12 @@ impl SqlGenerator for MySqlSqlGenerator {
                 .collect();
             let idx_type = if idx.unique { "UNIQUE INDEX" } else { "INDEX" };
             parts.push(format!(
-                "  {} {} ({})",
+                "  {} {} ({}){}{}",
                 idx_type,
                 self.quote_identifier(&idx.name),
-                cols.join(", ")
+                cols.join(", "),
+                index_comment(idx),
+                if idx.visible { "" } else { INVISIBLE_INDEX }
             ));
         }
 
@@ -94,8 +193,21 @@ impl SqlGenerator for MySqlSqlGenerator {
             ));
         }
 
+        for check in &table.check_constraints {
+            parts.push(format!("  {}", self.check_clause(check)));
+        }
+
         sql.push_str(&parts.join(",\n"));
-        sql.push_str("\n);");
+        sql.push_str("\n)");
+        sql.push_str(&table_option_clauses(&table.options));
+        if let Some(ts) = &table.tablespace {
+            sql.push_str(&format!(" TABLESPACE {}", self.quote_identifier(ts)));
+        }
+        if let Some(spec) = &table.partitioning {
+            sql.push('\n');
+            sql.push_str(&self.partition_clause(spec));
+        }
+        sql.push(';');
         sql
     }
 
@@ -110,24 +222,43 @@ impl SqlGenerator for MySqlSqlGenerator {
             self.quote_identifier(&column.name),
             column.data_type
         );
+        if let Some(generated) = &column.generated {
+            sql.push_str(&format!(" {}", generated.clause()));
+        }
         if !column.nullable {
             sql.push_str(" NOT NULL");
         } else {
             sql.push_str(" NULL");
         }
-        if let Some(default) = &column.default_value {
+        sql.push_str(&srid_clause(column));
+        if let Some(default) = column.effective_default() {
             sql.push_str(&format!(" DEFAULT {}", default));
         }
         if column.auto_increment {
             sql.push_str(" AUTO_INCREMENT");
         }
+        if let Some(on_update) = &column.on_update {
+            sql.push_str(&format!(" ON UPDATE {}", on_update));
+        }
         if let Some(comment) = &column.comment {
             sql.push_str(&format!(" COMMENT '{}'", comment.replace('\'', "''")));
         }
+        if !column.visible {
+            sql.push_str(INVISIBLE_COLUMN);
+        }
         sql.push(';');
         sql
     }
 
+    fn generate_add_column_online(&self, table: &str, column: &Column) -> String {
+        let add = self.generate_add_column(table, column);
+        let stored = matches!(&column.generated, Some(g) if g.storage == GeneratedStorage::Stored);
+        if !column.nullable || column.auto_increment || stored {
+            return add;
+        }
+        format!("{}, {};", add.trim_end_matches(';'), ONLINE_DDL)
+    }
+
     fn generate_drop_column(&self, table: &str, column_name: &str) -> String {
         format!(
             "ALTER TABLE {} DROP COLUMN {};",
@@ -143,26 +274,71 @@ impl SqlGenerator for MySqlSqlGenerator {
             self.quote_identifier(&column.name),
             column.data_type
         );
+        if let Some(generated) = &column.generated {
+            sql.push_str(&format!(" {}", generated.clause()));
+        }
         if !column.nullable {
             sql.push_str(" NOT NULL");
         } else {
             sql.push_str(" NULL");
         }
-        if let Some(default) = &column.default_value {
+        sql.push_str(&srid_clause(column));
+        if let Some(default) = column.effective_default() {
             sql.push_str(&format!(" DEFAULT {}", default));
-        } else if column.nullable {
+        } else if column.nullable && column.generated.is_none() {
             sql.push_str(" DEFAULT NULL");
         }
         if column.auto_increment {
             sql.push_str(" AUTO_INCREMENT");
         }
+        if let Some(on_update) = &column.on_update {
+            sql.push_str(&format!(" ON UPDATE {}", on_update));
+        }
         if let Some(comment) = &column.comment {
             sql.push_str(&format!(" COMMENT '{}'", comment.replace('\'', "''")));
         }
+        if !column.visible {
+            sql.push_str(INVISIBLE_COLUMN);
+        }
         sql.push(';');
         sql
     }
 
+    fn generate_alter_column(&self, table: &str, source: &Column, target: &Column) -> String {
+        // MySQL can't MODIFY a column into or out of VIRTUAL generation; the
+        // column has to be dropped and added back.
+        let is_virtual =
+            |c: &Column| matches!(&c.generated, Some(g) if g.storage == GeneratedStorage::Virtual);
+        if is_virtual(source) != is_virtual(target) {
+            return format!(
+                "{}\n{}",
+                self.generate_drop_column(table, &source.name),
+                self.generate_add_column(table, source)
+            );
+        }
+        // MODIFY COLUMN restates the whole definition, so there is nothing to narrow
+        // down; an attribute left out, such as AUTO_INCREMENT, is removed. Keys are
+        // table-level and survive the MODIFY.
+        self.generate_modify_column(table, source)
+    }
+
+    fn generate_rename_column(&self, table: &str, source: &Column, target: &Column) -> String {
+        // CHANGE COLUMN renames and restates the definition in one statement.
+        let modify = self.generate_modify_column(table, source);
+        let prefix = format!(
+            "ALTER TABLE {} MODIFY COLUMN {}",
+            self.quote_identifier(table),
+            self.quote_identifier(&source.name)
+        );
+        format!(
+            "ALTER TABLE {} CHANGE COLUMN {} {}{}",
+            self.quote_identifier(table),
+            self.quote_identifier(&target.name),
+            self.quote_identifier(&source.name),
+            &modify[prefix.len()..]
+        )
+    }
+
     fn generate_add_index(&self, table: &str, index: &Index) -> String {
         let cols: Vec<String> = index
             .columns
@@ -175,11 +351,27 @@ impl SqlGenerator for MySqlSqlGenerator {
             "INDEX"
         };
         format!(
-            "CREATE {} {} ON {} ({});",
+            "CREATE {} {} ON {} ({}){}{};",
             idx_type,
             self.quote_identifier(&index.name),
             self.quote_identifier(table),
-            cols.join(", ")
+            cols.join(", "),
+            index_comment(index),
+            if index.visible { "" } else { INVISIBLE_INDEX }
+        )
+    }
+
+    fn generate_add_index_online(&self, table: &str, index: &Index) -> String {
+        let add = self.generate_add_index(table, index);
+        let index_type = index.index_type.to_ascii_uppercase();
+        if index_type == "FULLTEXT" || index_type == "SPATIAL" {
+            return add;
+        }
+        // CREATE INDEX takes the options space-separated, unlike ALTER TABLE.
+        format!(
+            "{} {};",
+            add.trim_end_matches(';'),
+            ONLINE_DDL.replace(',', "")
         )
     }
 
@@ -214,6 +406,11 @@ impl SqlGenerator for MySqlSqlGenerator {
         )
     }
 
+    fn generate_add_foreign_key_deferred_validation(&self, table: &str, fk: &ForeignKey) -> String {
+        // MySQL has no NOT VALID; the constraint is always checked when added.
+        self.generate_add_foreign_key(table, fk)
+    }
+
     fn generate_drop_foreign_key(&self, table: &str, fk_name: &str) -> String {
         format!(
             "ALTER TABLE {} DROP FOREIGN KEY {};",
@@ -243,6 +440,223 @@ impl SqlGenerator for MySqlSqlGenerator {
             self.quote_identifier(uc_name)
         )
     }
+
+    fn generate_alter_primary_key(
+        &self,
+        table: &str,
+        current: Option<&PrimaryKey>,
+        wanted: Option<&PrimaryKey>,
+    ) -> String {
+        // One statement, so an AUTO_INCREMENT column is never left without a key.
+        let mut changes = Vec::new();
+        if current.is_some() {
+            changes.push("DROP PRIMARY KEY".to_string());
+        }
+        if let Some(pk) = wanted {
+            let cols: Vec<String> = pk
+                .columns
+                .iter()
+                .map(|c| self.quote_identifier(c))
+                .collect();
+            changes.push(format!("ADD PRIMARY KEY ({})", cols.join(", ")));
+        }
+        format!(
+            "ALTER TABLE {} {};",
+            self.quote_identifier(table),
+            changes.join(", ")
+        )
+    }
+
+    fn generate_add_exclusion(&self, _table: &str, ec: &ExclusionConstraint) -> String {
+        // Only Postgres reads these, and they are left out of cross-engine diffs.
+        format!(
+            "-- MySQL does not support exclusion constraints; skipped {}",
+            ec.name
+        )
+    }
+
+    fn generate_drop_exclusion(&self, _table: &str, ec_name: &str) -> String {
+        format!(
+            "-- MySQL does not support exclusion constraints; skipped {}",
+            ec_name
+        )
+    }
+
+    fn generate_add_check(&self, table: &str, check: &CheckConstraint) -> String {
+        format!(
+            "ALTER TABLE {} ADD {};",
+            self.quote_identifier(table),
+            self.check_clause(check)
+        )
+    }
+
+    fn generate_drop_check(&self, table: &str, check_name: &str) -> String {
+        format!(
+            "ALTER TABLE {} DROP CHECK {};",
+            self.quote_identifier(table),
+            self.quote_identifier(check_name)
+        )
+    }
+
+    fn generate_create_policy(&self, _table: &str, policy: &Policy) -> String {
+        // Like exclusion constraints, policies only come from Postgres.
+        format!(
+            "-- MySQL does not support row-level security; skipped policy {}",
+            policy.name
+        )
+    }
+
+    fn generate_alter_policy(&self, table: &str, policy: &Policy) -> String {
+        self.generate_create_policy(table, policy)
+    }
+
+    fn generate_drop_policy(&self, _table: &str, policy_name: &str) -> String {
+        format!(
+            "-- MySQL does not support row-level security; skipped policy {}",
+            policy_name
+        )
+    }
+
+    fn generate_set_row_security(&self, table: &str, _enabled: bool) -> String {
+        format!(
+            "-- MySQL does not support row-level security; skipped {}",
+            table
+        )
+    }
+
+    fn generate_create_routine(&self, routine: &Routine) -> String {
+        format!("{};", routine.definition.trim_end().trim_end_matches(';'))
+    }
+
+    fn generate_replace_routine(&self, routine: &Routine) -> String {
+        // MySQL has no CREATE OR REPLACE for routines.
+        format!(
+            "{}\n{}",
+            self.generate_drop_routine(routine),
+            self.generate_create_routine(routine)
+        )
+    }
+
+    fn generate_drop_routine(&self, routine: &Routine) -> String {
+        format!(
+            "DROP {} IF EXISTS {};",
+            routine.kind.keyword(),
+            self.quote_identifier(&routine.name)
+        )
+    }
+
+    fn generate_rebuild_table(&self, source: &TableSchema, target: &TableSchema) -> String {
+        let (temp, copy_columns) = crate::db::rebuild_parts(source, target);
+        let tbl = self.quote_identifier(&source.name);
+        let tmp = self.quote_identifier(&temp.name);
+        let mut stmts = vec![self.generate_create_table(&temp)];
+
+        if !copy_columns.is_empty() {
+            let cols: Vec<String> = copy_columns
+                .iter()
+                .map(|c| self.quote_identifier(c))
+                .collect();
+            stmts.push(format!(
+                "INSERT INTO {} ({}) SELECT {} FROM {};",
+                tmp,
+                cols.join(", "),
+                cols.join(", "),
+                tbl
+            ));
+        }
+
+        stmts.push(self.generate_drop_table(&source.name));
+        stmts.push(format!("RENAME TABLE {} TO {};", tmp, tbl));
+
+        for uc in &source.unique_constraints {
+            stmts.push(self.generate_add_unique(&source.name, uc));
+        }
+        for idx in &source.indexes {
+            stmts.push(self.generate_add_index(&source.name, idx));
+        }
+        for fk in &source.foreign_keys {
+            stmts.push(self.generate_add_foreign_key(&source.name, fk));
+        }
+        for check in &source.check_constraints {
+            stmts.push(self.generate_add_check(&source.name, check));
+        }
+
+        stmts.join("\n")
+    }
+
+    fn generate_move_column(
+        &self,
+        table: &str,
+        column: &Column,
+        after: Option<&str>,
+    ) -> Option<String> {
+        let modify = self.generate_modify_column(table, column);
+        let position = match after {
+            Some(name) => format!("AFTER {}", self.quote_identifier(name)),
+            None => "FIRST".to_string(),
+        };
+        Some(format!("{} {};", modify.trim_end_matches(';'), position))
+    }
+
+    fn generate_alter_partitioning(
+        &self,
+        table: &str,
+        partitioning: Option<&PartitionSpec>,
+    ) -> Option<String> {
+        let change = match partitioning {
+            Some(spec) => self.partition_clause(spec),
+            None => "REMOVE PARTITIONING".to_string(),
+        };
+        Some(format!(
+            "ALTER TABLE {} {};",
+            self.quote_identifier(table),
+            change
+        ))
+    }
+
+    fn generate_set_tablespace(&self, table: &str, tablespace: Option<&str>) -> String {
+        // InnoDB has no named default; file-per-table is what CREATE TABLE uses
+        // when no tablespace is given.
+        format!(
+            "ALTER TABLE {} TABLESPACE {};",
+            self.quote_identifier(table),
+            self.quote_identifier(tablespace.unwrap_or("innodb_file_per_table"))
+        )
+    }
+
+    fn generate_set_table_options(&self, table: &str, options: &TableOptions) -> String {
+        format!(
+            "ALTER TABLE {}{};",
+            self.quote_identifier(table),
+            table_option_clauses(options)
+        )
+    }
+
+    fn generate_set_inherits(&self, table: &str, _add: &[String], _remove: &[String]) -> String {
+        format!(
+            "-- MySQL does not support table inheritance; skipped {}",
+            table
+        )
+    }
+
+    fn generate_grant(&self, grant: &Grant) -> String {
+        // The grantee is already quoted: 'user'@'host'.
+        format!(
+            "GRANT {} ON {} TO {};",
+            grant.privilege,
+            self.quote_identifier(&grant.object),
+            grant.grantee
+        )
+    }
+
+    fn generate_revoke(&self, grant: &Grant) -> String {
+        format!(
+            "REVOKE {} ON {} FROM {};",
+            grant.privilege,
+            self.quote_identifier(&grant.object),
+            grant.grantee
+        )
+    }
 }
 
 crate::db::impl_sql_generator_delegation!(MySqlDriver, MySqlSqlGenerator);
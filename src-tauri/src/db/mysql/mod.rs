@@ -1,5 +1,8 @@
 mod generator;
+mod queries;
 mod reader;
 
 pub use generator::MySqlSqlGenerator;
+pub use queries::SchemaQueries;
 pub use reader::MySqlDriver;
+pub(crate) use reader::expression_default;
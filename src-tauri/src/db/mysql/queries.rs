@@ -0,0 +1,156 @@
+/// The metadata queries the MySQL driver reads a schema with.
+///
+/// The defaults read `information_schema`. Servers that restrict or slow it
+/// down (some proxies and managed offerings) can swap in their own SQL via
+/// `MySqlDriver::with_queries`. A replacement must return the same columns in
+/// the same order as the default it replaces, and should keep the
+/// `{table_filter}` placeholder where the default has it: it expands to
+/// `AND <table column> IN (?, ...)` when only some tables are read, with the
+/// table column named as in the default query.
+#[derive(Debug, Clone)]
+pub struct SchemaQueries {
+    /// Table, general tablespace, engine, default charset, row format.
+    /// Filtered on `t.table_name`.
+    pub tables: String,
+    /// Table, column, column type, nullable, default, extra, comment, position,
+    /// generation expression. Filtered on `table_name`.
+    pub columns: String,
+    /// Table, constraint, column. Filtered on `table_name`.
+    pub primary_keys: String,
+    /// Table, index, non-unique, column, index type, position in index.
+    /// Filtered on `s.table_name`.
+    pub indexes: String,
+    /// Table, constraint, column, referenced table and column, delete rule,
+    /// update rule. Filtered on `kcu.table_name`.
+    pub foreign_keys: String,
+    /// Table, constraint, column. Filtered on `tc.table_name`.
+    pub unique_constraints: String,
+    /// Table, method, expression, partition, description.
+    /// Filtered on `table_name`.
+    pub partitioning: String,
+}
+
+/// Which of the `SchemaQueries` to render.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum MetadataQuery {
+    Tables,
+    Columns,
+    PrimaryKeys,
+    Indexes,
+    ForeignKeys,
+    UniqueConstraints,
+    Partitioning,
+}
+
+impl SchemaQueries {
+    /// The SQL for `query` with `{table_filter}` replaced by `filter`.
+    pub(super) fn render(&self, query: MetadataQuery, filter: impl Fn(&str) -> String) -> String {
+        let (sql, column) = match query {
+            MetadataQuery::Tables => (&self.tables, "t.table_name"),
+            MetadataQuery::Columns => (&self.columns, "table_name"),
+            MetadataQuery::PrimaryKeys => (&self.primary_keys, "table_name"),
+            MetadataQuery::Indexes => (&self.indexes, "s.table_name"),
+            MetadataQuery::ForeignKeys => (&self.foreign_keys, "kcu.table_name"),
+            MetadataQuery::UniqueConstraints => (&self.unique_constraints, "tc.table_name"),
+            MetadataQuery::Partitioning => (&self.partitioning, "table_name"),
+        };
+        sql.replace("{table_filter}", &filter(column))
+    }
+}
+
+impl Default for SchemaQueries {
+    fn default() -> Self {
+        Self {
+            // Only general tablespaces are reported; InnoDB's implicit ones
+            // (innodb_system, innodb_file_per_table.N) count as the default.
+            tables: r#"
+            SELECT CAST(t.table_name AS BINARY), CAST(MAX(p.tablespace_name) AS CHAR),
+                CAST(t.engine AS CHAR), CAST(c.character_set_name AS CHAR),
+                CAST(UPPER(t.row_format) AS CHAR)
+            FROM information_schema.tables t
+            LEFT JOIN information_schema.partitions p
+                ON p.table_schema = t.table_schema AND p.table_name = t.table_name
+                AND p.tablespace_name NOT LIKE 'innodb\\_%'
+            LEFT JOIN information_schema.collation_character_set_applicability c
+                ON c.collation_name = t.table_collation
+            WHERE t.table_schema = DATABASE() AND t.table_type = 'BASE TABLE'{table_filter}
+            GROUP BY t.table_name, t.engine, c.character_set_name, t.row_format
+            "#
+            .to_string(),
+            // column_type (not data_type) carries the full enum(...)/set(...) value list;
+            // it is LONGTEXT, so the CAST to CHAR does not truncate it.
+            columns: r#"
+            SELECT
+                CAST(table_name AS BINARY),
+                CAST(column_name AS BINARY),
+                CAST(column_type AS CHAR),
+                CAST(is_nullable AS CHAR),
+                CAST(column_default AS CHAR),
+                CAST(extra AS CHAR),
+                CAST(column_comment AS BINARY),
+                ordinal_position,
+                CAST(generation_expression AS CHAR)
+            FROM information_schema.columns
+            WHERE table_schema = DATABASE(){table_filter}
+            ORDER BY table_name, ordinal_position
+            "#
+            .to_string(),
+            primary_keys: r#"
+            SELECT CAST(table_name AS CHAR), CAST(constraint_name AS CHAR), CAST(column_name AS CHAR)
+            FROM information_schema.key_column_usage
+            WHERE table_schema = DATABASE() AND constraint_name = 'PRIMARY'{table_filter}
+            ORDER BY table_name, ordinal_position
+            "#
+            .to_string(),
+            indexes: r#"
+            SELECT CAST(s.table_name AS CHAR), CAST(s.index_name AS CHAR), s.non_unique, CAST(s.column_name AS CHAR), CAST(s.index_type AS CHAR), CAST(s.seq_in_index AS UNSIGNED)
+            FROM information_schema.statistics s
+            WHERE s.table_schema = DATABASE() AND s.index_name != 'PRIMARY'{table_filter}
+                AND NOT EXISTS (
+                    SELECT 1 FROM information_schema.table_constraints tc
+                    WHERE tc.table_schema = s.table_schema
+                        AND tc.table_name = s.table_name
+                        AND tc.constraint_name = s.index_name
+                        AND tc.constraint_type = 'UNIQUE'
+                )
+            ORDER BY s.table_name, s.index_name, s.seq_in_index
+            "#
+            .to_string(),
+            foreign_keys: r#"
+            SELECT
+                CAST(kcu.table_name AS CHAR),
+                CAST(kcu.constraint_name AS CHAR),
+                CAST(kcu.column_name AS CHAR),
+                CAST(kcu.referenced_table_name AS CHAR),
+                CAST(kcu.referenced_column_name AS CHAR),
+                CAST(rc.delete_rule AS CHAR),
+                CAST(rc.update_rule AS CHAR)
+            FROM information_schema.key_column_usage kcu
+            JOIN information_schema.referential_constraints rc
+                ON kcu.constraint_name = rc.constraint_name AND kcu.table_schema = rc.constraint_schema
+            WHERE kcu.table_schema = DATABASE() AND kcu.referenced_table_name IS NOT NULL{table_filter}
+            ORDER BY kcu.table_name, kcu.constraint_name, kcu.ordinal_position
+            "#
+            .to_string(),
+            unique_constraints: r#"
+            SELECT CAST(tc.table_name AS CHAR), CAST(tc.constraint_name AS CHAR), CAST(kcu.column_name AS CHAR)
+            FROM information_schema.table_constraints tc
+            JOIN information_schema.key_column_usage kcu
+                ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema AND tc.table_name = kcu.table_name
+            WHERE tc.table_schema = DATABASE() AND tc.constraint_type = 'UNIQUE'{table_filter}
+            ORDER BY tc.table_name, tc.constraint_name, kcu.ordinal_position
+            "#
+            .to_string(),
+            partitioning: r#"
+            SELECT CAST(table_name AS CHAR), CAST(partition_method AS CHAR),
+                   CAST(partition_expression AS CHAR), CAST(partition_name AS CHAR),
+                   CAST(partition_description AS CHAR)
+            FROM information_schema.partitions
+            WHERE table_schema = DATABASE() AND partition_name IS NOT NULL
+                AND COALESCE(subpartition_ordinal_position, 1) = 1{table_filter}
+            ORDER BY table_name, partition_ordinal_position
+            "#
+            .to_string(),
+        }
+    }
+}
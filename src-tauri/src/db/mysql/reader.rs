@@ -1,24 +1,178 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::{MySql, Pool, mysql::MySqlPoolOptions};
+use log::{debug, warn};
+use sqlx::mysql::{MySqlArguments, MySqlConnection, MySqlPoolOptions};
+use sqlx::query::QueryAs;
+use sqlx::{MySql, Pool, Row};
 
-use crate::db::traits::SchemaReader;
+use super::queries::{MetadataQuery, SchemaQueries};
+use crate::db::describe_tables;
+use crate::db::snapshot::read_in_snapshot;
+use crate::db::split_statements;
+use crate::db::traits::{SchemaReader, SqlExecutor, SqlGenerator};
 use crate::models::*;
+use std::collections::{HashMap, HashSet};
 
-/// Raw column row fetched from information_schema.
+/// Raw column row fetched from information_schema. Table name, name and
+/// comment come back as bytes, since they aren't always valid UTF-8 (see
+/// `lossy_text`).
 type ColumnQueryRow = (
-    String,
-    String,
+    Vec<u8>,
+    Vec<u8>,
     String,
     String,
     Option<String>,
     String,
-    Option<String>,
+    Option<Vec<u8>>,
     u32,
+    Option<String>,
 );
 
+/// Raw table row: table (as bytes, like column names), general tablespace,
+/// engine, default charset, row format.
+type TableQueryRow = (
+    Vec<u8>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+/// Schemas the server keeps for itself, never offered as sync targets.
+const SYSTEM_SCHEMAS: [&str; 4] = ["information_schema", "performance_schema", "mysql", "sys"];
+
+/// Raw partition row: table, method, expression, partition, description.
+type PartitionQueryRow = (String, String, Option<String>, String, Option<String>);
+
+/// The `ON UPDATE` expression from `information_schema.columns.extra`, e.g.
+/// `DEFAULT_GENERATED on update CURRENT_TIMESTAMP(3)` -> `CURRENT_TIMESTAMP(3)`.
+fn on_update_from_extra(extra: &str) -> Option<String> {
+    let start = extra.to_ascii_lowercase().find("on update ")? + "on update ".len();
+    extra[start..].split_whitespace().next().map(str::to_string)
+}
+
+/// An expression default as `DEFAULT` takes it: wrapped in parentheses, except
+/// `CURRENT_TIMESTAMP[(fsp)]`, which MySQL writes bare and accepts before 8.0.13.
+pub(crate) fn expression_default(expression: &str) -> String {
+    if expression
+        .to_ascii_uppercase()
+        .starts_with("CURRENT_TIMESTAMP")
+    {
+        expression.to_string()
+    } else {
+        format!("({})", expression)
+    }
+}
+
+/// Metadata bytes as a string. Names and comments written through a latin1
+/// connection can hold bytes that aren't valid UTF-8; those are replaced with
+/// U+FFFD and a warning is recorded rather than failing the whole read.
+fn lossy_text(bytes: Vec<u8>, what: impl FnOnce() -> String, warnings: &mut Vec<String>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => {
+            let text = String::from_utf8_lossy(e.as_bytes()).into_owned();
+            let warning = format!("{} is not valid UTF-8, read as '{}'", what(), text);
+            // A table's name is decoded once per column.
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+            text
+        }
+    }
+}
+
+/// One `information_schema.columns` row as a `ColumnRow`.
+fn column_from_row(
+    row: ColumnQueryRow,
+    srids: &HashMap<(String, String), u32>,
+    octet_lengths: &HashMap<(String, String), u64>,
+    warnings: &mut Vec<String>,
+) -> crate::db::ColumnRow {
+    let (table_name, name, data_type, nullable, default, extra, comment, pos, generation) = row;
+    let table_name = lossy_text(table_name, || "A table name".to_string(), warnings);
+    let name = lossy_text(
+        name,
+        || format!("Name of a column in {}", table_name),
+        warnings,
+    );
+    let comment = comment
+        .map(|c| {
+            lossy_text(
+                c,
+                || format!("Comment on {}.{}", table_name, name),
+                warnings,
+            )
+        })
+        .filter(|c| !c.is_empty());
+    let key = (table_name.clone(), name.clone());
+    let srid = srids.get(&key).copied();
+    let octet_length = octet_lengths.get(&key).copied();
+    crate::db::ColumnRow {
+        table_name,
+        name,
+        data_type,
+        nullable: nullable == "YES",
+        default_value: default_from_column(default, &extra),
+        auto_increment: extra.contains("auto_increment"),
+        comment,
+        ordinal_position: pos,
+        visible: !extra.contains("INVISIBLE"),
+        on_update: on_update_from_extra(&extra),
+        sequence: None,
+        generated: generated_from_extra(&extra, generation),
+        srid,
+        octet_length,
+    }
+}
+
+/// `column_default` as DDL spells it. MySQL 8 marks expression defaults with
+/// `DEFAULT_GENERATED` in `extra` and reports them without their parentheses.
+fn default_from_column(default: Option<String>, extra: &str) -> Option<String> {
+    let default = default?;
+    if extra.contains("DEFAULT_GENERATED") {
+        Some(expression_default(&default))
+    } else {
+        Some(default)
+    }
+}
+
+/// The `VALUES ...` clause of a partition from its `partition_description`;
+/// HASH and KEY partitions have none.
+fn partition_bound(method: &str, description: Option<&str>) -> String {
+    match description {
+        // Plain RANGE takes a bare MAXVALUE; RANGE COLUMNS lists it like any value.
+        Some("MAXVALUE") if method == "RANGE" => "VALUES LESS THAN MAXVALUE".to_string(),
+        Some(values) if method.starts_with("RANGE") => format!("VALUES LESS THAN ({})", values),
+        Some(values) if method.starts_with("LIST") => format!("VALUES IN ({})", values),
+        _ => String::new(),
+    }
+}
+
+/// Generated-column details from `extra` (`VIRTUAL GENERATED` / `STORED GENERATED`;
+/// MariaDB says `PERSISTENT` for stored) and `generation_expression`, which is
+/// empty for ordinary columns.
+fn generated_from_extra(extra: &str, expression: Option<String>) -> Option<GeneratedColumn> {
+    let expression = expression.filter(|e| !e.is_empty())?;
+    let extra = extra.to_ascii_uppercase();
+    let storage = if extra.contains("STORED") || extra.contains("PERSISTENT") {
+        GeneratedStorage::Stored
+    } else if extra.contains("VIRTUAL") {
+        GeneratedStorage::Virtual
+    } else {
+        return None;
+    };
+    Some(GeneratedColumn {
+        expression,
+        storage,
+    })
+}
+
 pub struct MySqlDriver {
     pool: Pool<MySql>,
+    queries: SchemaQueries,
+    /// Warnings from schema reads, until `take_read_warnings` collects them.
+    read_warnings: std::sync::Mutex<Vec<String>>,
 }
 
 impl MySqlDriver {
@@ -29,7 +183,7 @@ impl MySqlDriver {
         password: &str,
         database: &str,
     ) -> Result<Self> {
-        Self::new_with_ssl(host, port, user, password, database, None).await
+        Self::new_with_ssl(host, port, user, password, database, None, &[]).await
     }
 
     pub async fn new_with_ssl(
@@ -39,6 +193,7 @@ impl MySqlDriver {
         password: &str,
         database: &str,
         ssl_config: Option<&SslConfig>,
+        session_params: &[(String, String)],
     ) -> Result<Self> {
         let mut opts = sqlx::mysql::MySqlConnectOptions::new()
             .host(host)
@@ -66,17 +221,74 @@ impl MySqlDriver {
             }
         }
 
+        let statements = crate::db::session_set_statements(session_params)?;
         let pool = MySqlPoolOptions::new()
             .max_connections(5)
+            .after_connect(move |conn, _meta| {
+                let statements = statements.clone();
+                Box::pin(async move {
+                    for stmt in &statements {
+                        sqlx::query(stmt).execute(&mut *conn).await?;
+                    }
+                    Ok(())
+                })
+            })
             .connect_with(opts)
             .await?;
 
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
+    }
+
+    /// Wrap a pool owned by the caller, e.g. when embedding the diff engine in a
+    /// backend that already holds one. The session settings and SSL options of
+    /// `new_with_ssl` are up to whoever built the pool.
+    pub fn from_pool(pool: Pool<MySql>) -> Self {
+        Self {
+            pool,
+            queries: SchemaQueries::default(),
+            read_warnings: Default::default(),
+        }
+    }
+
+    /// Read schemas with `queries` instead of the default `information_schema` SQL.
+    pub fn with_queries(mut self, queries: SchemaQueries) -> Self {
+        self.queries = queries;
+        self
     }
 
     pub fn pool(&self) -> &Pool<MySql> {
         &self.pool
     }
+
+    /// A driver on one dedicated connection to the same server, with the same
+    /// options and session settings, for reading several databases in turn with
+    /// `use_database` instead of opening a pool per database.
+    pub async fn single_connection(&self) -> Result<Self> {
+        let pool = self
+            .pool
+            .options()
+            .clone()
+            .min_connections(0)
+            .max_connections(1)
+            .connect_with((*self.pool.connect_options()).clone())
+            .await?;
+        Ok(Self {
+            pool,
+            queries: self.queries.clone(),
+            read_warnings: Default::default(),
+        })
+    }
+
+    /// Switch to `database` with `USE`, so later reads see its schema. Only
+    /// reliable on a `single_connection` driver, where every read goes through
+    /// the connection that was switched; a reconnect lands in `database` too.
+    pub async fn use_database(&self, database: &str) -> Result<()> {
+        let sql = format!("USE {}", self.quote_identifier(database));
+        sqlx::query(&sql).execute(&self.pool).await?;
+        let opts = (*self.pool.connect_options()).clone().database(database);
+        self.pool.set_connect_options(opts);
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -86,94 +298,521 @@ impl SchemaReader for MySqlDriver {
         Ok(())
     }
 
-    async fn list_databases(&self) -> Result<Vec<String>> {
+    async fn list_databases(
+        &self,
+        name_filter: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Vec<String>> {
+        let sql = Self::list_databases_sql(name_filter.is_some(), limit.is_some());
+        let mut query = sqlx::query_as::<_, (String,)>(&sql);
+        if let Some(pattern) = name_filter {
+            query = query.bind(pattern);
+        }
+        if let Some(limit) = limit {
+            query = query.bind(limit);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+        let listed: Vec<String> = rows.into_iter().map(|(name,)| name).collect();
+        if limit.is_some_and(|limit| listed.len() >= limit as usize) {
+            return Ok(listed);
+        }
+
+        // information_schema.schemata can be filtered more tightly than
+        // SHOW DATABASES for users limited to one database; list both.
+        let shown: Vec<(String,)> = match sqlx::query_as(&Self::show_databases_sql(name_filter))
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                debug!("SHOW DATABASES not available: {}", e);
+                return Ok(listed);
+            }
+        };
+        Ok(Self::merge_database_names(
+            listed,
+            shown.into_iter().map(|(name,)| name),
+            limit,
+        ))
+    }
+
+    async fn get_database_info(&self) -> Result<DatabaseInfo> {
+        let row: Option<(String, String)> = sqlx::query_as(
+            "SELECT CAST(default_character_set_name AS CHAR), CAST(default_collation_name AS CHAR) FROM information_schema.schemata WHERE schema_name = DATABASE()"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row
+            .map(|(charset, collation)| Self::database_info_from_schemata(charset, collation))
+            .unwrap_or_default())
+    }
+
+    async fn estimated_row_count(&self, table: &str) -> Result<Option<u64>> {
+        let row: Option<(Option<u64>,)> = sqlx::query_as(
+            "SELECT CAST(table_rows AS UNSIGNED) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = ?",
+        )
+        .bind(table)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.and_then(|(rows,)| rows))
+    }
+
+    async fn server_version(&self) -> Result<String> {
+        let (version,): (String,) = sqlx::query_as("SELECT CAST(VERSION() AS CHAR)")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(version)
+    }
+
+    async fn get_tables(&self) -> Result<Vec<TableSchema>> {
+        self.read_tables(None).await
+    }
+
+    async fn get_tables_named(&self, names: &[String]) -> Result<Vec<TableSchema>> {
+        if names.is_empty() {
+            return Ok(vec![]);
+        }
+        self.read_tables(Some(names)).await
+    }
+
+    async fn list_tables(&self) -> Result<Vec<String>> {
         let rows: Vec<(String,)> = sqlx::query_as(
-            "SELECT CAST(schema_name AS CHAR) FROM information_schema.schemata WHERE schema_name NOT IN ('information_schema', 'performance_schema', 'mysql', 'sys') ORDER BY schema_name"
+            "SELECT CAST(table_name AS CHAR) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE' ORDER BY table_name",
         )
         .fetch_all(&self.pool)
         .await?;
         Ok(rows.into_iter().map(|(name,)| name).collect())
     }
 
-    async fn get_tables(&self) -> Result<Vec<TableSchema>> {
-        let table_names: Vec<(String,)> = sqlx::query_as(
-            "SELECT CAST(table_name AS CHAR) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE'"
+    async fn get_routines(&self) -> Result<Vec<Routine>> {
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT CAST(routine_name AS CHAR), CAST(routine_type AS CHAR), CAST(routine_body AS CHAR) FROM information_schema.routines WHERE routine_schema = DATABASE() ORDER BY routine_name",
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let table_names: Vec<String> = table_names.into_iter().map(|(n,)| n).collect();
-        let columns = self.fetch_all_columns().await?;
-        let pks = self.fetch_all_primary_keys().await?;
-        let indexes = self.fetch_all_indexes().await?;
-        let fks = self.fetch_all_foreign_keys().await?;
-        let ucs = self.fetch_all_unique_constraints().await?;
-        Ok(crate::db::assemble_schemas(
-            table_names,
-            columns,
-            pks,
-            indexes,
-            fks,
-            ucs,
-        ))
+        let mut routines = Vec::with_capacity(rows.len());
+        for (name, routine_type, language) in rows {
+            let kind = if routine_type.eq_ignore_ascii_case("PROCEDURE") {
+                RoutineKind::Procedure
+            } else {
+                RoutineKind::Function
+            };
+            let sql = format!(
+                "SHOW CREATE {} `{}`",
+                kind.keyword(),
+                name.replace('`', "``")
+            );
+            let row = sqlx::query(&sql).fetch_one(&self.pool).await?;
+            // The body is NULL when the user lacks the privileges to see it.
+            let Some(definition) = row.try_get::<Option<String>, _>(2)? else {
+                warn!(
+                    "Skipping {} {}: definition is not visible",
+                    kind.keyword(),
+                    name
+                );
+                continue;
+            };
+            routines.push(Routine {
+                name,
+                definition: Self::strip_definer(&definition, &kind),
+                kind,
+                language,
+            });
+        }
+        Ok(routines)
     }
-}
 
-impl MySqlDriver {
-    async fn fetch_all_columns(&self) -> Result<Vec<crate::db::ColumnRow>> {
-        let rows: Vec<ColumnQueryRow> = sqlx::query_as(
+    async fn get_grants(&self) -> Result<Vec<Grant>> {
+        // Only table-level grants; database- and column-level ones live in
+        // schema_privileges and column_privileges.
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
             r#"
-            SELECT
-                CAST(table_name AS CHAR),
-                CAST(column_name AS CHAR),
-                CAST(column_type AS CHAR),
-                CAST(is_nullable AS CHAR),
-                CAST(column_default AS CHAR),
-                CAST(extra AS CHAR),
-                CAST(column_comment AS CHAR),
-                ordinal_position
-            FROM information_schema.columns
+            SELECT CAST(grantee AS CHAR), CAST(privilege_type AS CHAR), CAST(table_name AS CHAR)
+            FROM information_schema.table_privileges
             WHERE table_schema = DATABASE()
-            ORDER BY table_name, ordinal_position
+            ORDER BY 1, 2, 3
             "#,
         )
         .fetch_all(&self.pool)
         .await?;
 
         Ok(rows
+            .into_iter()
+            .map(|(grantee, privilege, object)| Grant {
+                grantee,
+                privilege,
+                object,
+            })
+            .collect())
+    }
+
+    fn take_read_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut *self.read_warnings.lock().unwrap())
+    }
+
+    async fn list_schema_objects(&self) -> Result<SchemaObjects> {
+        let names = |sql: &'static str| async move {
+            let rows: Vec<(String,)> = sqlx::query_as(sql).fetch_all(&self.pool).await?;
+            Ok::<_, anyhow::Error>(rows.into_iter().map(|(name,)| name).collect())
+        };
+        Ok(SchemaObjects {
+            views: names(
+                "SELECT CAST(table_name AS CHAR) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_type = 'VIEW' ORDER BY table_name",
+            )
+            .await?,
+            // MariaDB lists its sequences here; MySQL has none.
+            sequences: names(
+                "SELECT CAST(table_name AS CHAR) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_type = 'SEQUENCE' ORDER BY table_name",
+            )
+            .await?,
+            enum_types: vec![],
+        })
+    }
+
+    async fn native_create_table(&self, table: &str) -> Result<String> {
+        let sql = format!("SHOW CREATE TABLE `{}`", table.replace('`', "``"));
+        let row = sqlx::query(&sql).fetch_one(&self.pool).await?;
+        Ok(row.try_get(1)?)
+    }
+}
+
+#[async_trait]
+impl SqlExecutor for MySqlDriver {
+    async fn execute_sql(&self, sql: &str) -> Result<()> {
+        for stmt in split_statements(sql) {
+            let full = format!("{};", stmt);
+            debug!("Executing: {}", full);
+            // Sent as plain text: MySQL refuses CREATE PROCEDURE/FUNCTION as a
+            // prepared statement.
+            sqlx::raw_sql(&full)
+                .execute(&self.pool)
+                .await
+                .map_err(crate::db::statement_timeout_error)?;
+        }
+        Ok(())
+    }
+
+    async fn execute_in_transaction(&self, statements: &[String]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let split: Vec<String> = statements
+            .iter()
+            .flat_map(|sql| split_statements(sql))
+            .map(|stmt| format!("{};", stmt))
+            .collect();
+        for full in &split {
+            debug!("Executing in transaction: {}", full);
+            // Through the connection rather than `RawSql::execute`, whose
+            // generic executor bound trips the `Send` check on a borrowed transaction.
+            sqlx::Executor::execute(&mut *tx, sqlx::raw_sql(full))
+                .await
+                .map_err(crate::db::statement_timeout_error)?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+impl MySqlDriver {
+    /// Read every table in the database, or only those named in `tables`, all
+    /// within one repeatable-read snapshot so DDL running meanwhile can't leave
+    /// the tables and their columns or indexes out of step.
+    async fn read_tables(&self, tables: Option<&[String]>) -> Result<Vec<TableSchema>> {
+        debug!("Reading MySQL metadata for {}", describe_tables(tables));
+        let mut conn = self.pool.acquire().await?;
+        // Applies to the next transaction only, whatever the session default is.
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *conn)
+            .await?;
+        read_in_snapshot::<MySql, _>(
+            &mut conn,
+            "START TRANSACTION WITH CONSISTENT SNAPSHOT, READ ONLY",
+            async |conn| self.read_tables_in(conn, tables).await,
+        )
+        .await
+    }
+
+    async fn read_tables_in(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<TableSchema>> {
+        let sql = self.metadata_sql(MetadataQuery::Tables, tables);
+        let table_rows: Vec<TableQueryRow> = Self::bind_tables(sqlx::query_as(&sql), tables)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        let mut warnings = Vec::new();
+        let table_rows = table_rows
             .into_iter()
             .map(
-                |(table_name, name, data_type, nullable, default, extra, comment, pos)| {
-                    crate::db::ColumnRow {
+                |(table_name, tablespace, engine, charset, row_format)| crate::db::TableRow {
+                    table_name: lossy_text(
                         table_name,
-                        name,
-                        data_type,
-                        nullable: nullable == "YES",
-                        default_value: default,
-                        auto_increment: extra.contains("auto_increment"),
-                        comment: if comment.as_ref().map(|c| c.is_empty()).unwrap_or(true) {
-                            None
-                        } else {
-                            comment
-                        },
-                        ordinal_position: pos,
-                    }
+                        || "A table name".to_string(),
+                        &mut warnings,
+                    ),
+                    tablespace,
+                    options: TableOptions {
+                        engine,
+                        charset,
+                        row_format,
+                    },
                 },
             )
+            .collect();
+        let columns = self.fetch_all_columns(conn, tables, &mut warnings).await?;
+        let pks = self.fetch_all_primary_keys(conn, tables).await?;
+        let indexes = self.fetch_all_indexes(conn, tables).await?;
+        let fks = self.fetch_all_foreign_keys(conn, tables).await?;
+        let ucs = self.fetch_all_unique_constraints(conn, tables).await?;
+        let mut partitioning = self.fetch_all_partitioning(conn, tables).await?;
+        let mut checks = self.fetch_all_check_constraints(conn, tables).await;
+        // MySQL has no exclusion constraints.
+        let mut schemas =
+            crate::db::assemble_schemas(table_rows, columns, pks, indexes, fks, ucs, vec![]);
+        for table in &mut schemas {
+            table.check_constraints = checks.remove(&table.name).unwrap_or_default();
+            table.partitioning = partitioning.remove(&table.name);
+        }
+        for warning in &warnings {
+            warn!("{}", warning);
+        }
+        self.read_warnings.lock().unwrap().extend(warnings);
+        Ok(schemas)
+    }
+
+    /// ` AND {column} IN (?, ...)` restricting a metadata query to `tables`,
+    /// or nothing when reading every table. Bind the names with `bind_tables`.
+    fn table_filter(column: &str, tables: Option<&[String]>) -> String {
+        match tables {
+            Some(names) => format!(" AND {} IN ({})", column, vec!["?"; names.len()].join(", ")),
+            None => String::new(),
+        }
+    }
+
+    /// One of the metadata queries, restricted to `tables` when given.
+    fn metadata_sql(&self, query: MetadataQuery, tables: Option<&[String]>) -> String {
+        self.queries
+            .render(query, |column| Self::table_filter(column, tables))
+    }
+
+    fn bind_tables<'q, O>(
+        mut query: QueryAs<'q, MySql, O, MySqlArguments>,
+        tables: Option<&'q [String]>,
+    ) -> QueryAs<'q, MySql, O, MySqlArguments> {
+        for name in tables.unwrap_or_default() {
+            query = query.bind(name);
+        }
+        query
+    }
+
+    /// Drop the `DEFINER=...` clause from a `SHOW CREATE` statement so routines
+    /// owned by different accounts compare equal.
+    fn strip_definer(definition: &str, kind: &RoutineKind) -> String {
+        let keyword = format!(" {} ", kind.keyword());
+        match definition.find(&keyword) {
+            Some(pos) => format!("CREATE{}", &definition[pos..]),
+            None => definition.to_string(),
+        }
+    }
+
+    fn list_databases_sql(with_filter: bool, with_limit: bool) -> String {
+        let system = SYSTEM_SCHEMAS.map(|s| format!("'{}'", s)).join(", ");
+        let mut sql = format!(
+            "SELECT CAST(schema_name AS CHAR) FROM information_schema.schemata WHERE schema_name NOT IN ({})",
+            system
+        );
+        if with_filter {
+            sql.push_str(" AND schema_name LIKE ?");
+        }
+        sql.push_str(" ORDER BY schema_name");
+        if with_limit {
+            sql.push_str(" LIMIT ?");
+        }
+        sql
+    }
+
+    /// `SHOW DATABASES`, narrowed by `name_filter`. SHOW takes no placeholders,
+    /// so the pattern is inlined as a quoted literal.
+    fn show_databases_sql(name_filter: Option<&str>) -> String {
+        match name_filter {
+            Some(pattern) => format!(
+                "SHOW DATABASES LIKE '{}'",
+                pattern.replace('\\', "\\\\").replace('\'', "''")
+            ),
+            None => "SHOW DATABASES".to_string(),
+        }
+    }
+
+    /// Database names from both listings, sorted and without duplicates or
+    /// system schemas, capped at `limit`.
+    fn merge_database_names(
+        listed: Vec<String>,
+        shown: impl IntoIterator<Item = String>,
+        limit: Option<u32>,
+    ) -> Vec<String> {
+        let mut names: Vec<String> = listed
+            .into_iter()
+            .chain(shown)
+            .filter(|name| !SYSTEM_SCHEMAS.contains(&name.as_str()))
+            .collect();
+        names.sort();
+        names.dedup();
+        if let Some(limit) = limit {
+            names.truncate(limit as usize);
+        }
+        names
+    }
+
+    /// MySQL has no separate encoding setting; the character set is the encoding.
+    fn database_info_from_schemata(charset: String, collation: String) -> DatabaseInfo {
+        DatabaseInfo {
+            encoding: Some(charset.clone()),
+            charset: Some(charset),
+            collation: Some(collation),
+        }
+    }
+
+    async fn fetch_all_columns(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<crate::db::ColumnRow>> {
+        let sql = self.metadata_sql(MetadataQuery::Columns, tables);
+        let rows: Vec<ColumnQueryRow> = Self::bind_tables(sqlx::query_as(&sql), tables)
+            .fetch_all(&mut *conn)
+            .await?;
+        let srids = self.fetch_column_srids(conn, tables).await;
+        let octet_lengths = self.fetch_column_octet_lengths(conn, tables).await;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| column_from_row(row, &srids, &octet_lengths, warnings))
             .collect())
     }
 
-    async fn fetch_all_primary_keys(&self) -> Result<Vec<crate::db::PkRow>> {
-        let rows: Vec<(String, String, String)> = sqlx::query_as(
+    /// SRIDs of geometry columns restricted to one spatial reference system,
+    /// keyed by (table, column). `srs_id` only exists from MySQL 8.0.3; on older
+    /// servers and MariaDB the query fails and no column has an SRID.
+    async fn fetch_column_srids(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> HashMap<(String, String), u32> {
+        let sql = format!(
             r#"
-            SELECT CAST(table_name AS CHAR), CAST(constraint_name AS CHAR), CAST(column_name AS CHAR)
-            FROM information_schema.key_column_usage
-            WHERE table_schema = DATABASE() AND constraint_name = 'PRIMARY'
-            ORDER BY table_name, ordinal_position
+            SELECT CAST(table_name AS CHAR), CAST(column_name AS CHAR), CAST(srs_id AS UNSIGNED)
+            FROM information_schema.columns
+            WHERE table_schema = DATABASE() AND srs_id IS NOT NULL{}
             "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+            Self::table_filter("table_name", tables)
+        );
+        let rows: Result<Vec<(String, String, u64)>, _> =
+            Self::bind_tables(sqlx::query_as(&sql), tables)
+                .fetch_all(&mut *conn)
+                .await;
+        match rows {
+            Ok(rows) => rows
+                .into_iter()
+                .map(|(table, column, srid)| ((table, column), srid as u32))
+                .collect(),
+            Err(e) => {
+                debug!("Column SRIDs not available: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Byte lengths of character columns, keyed by (table, column). Read apart
+    /// from the column query so replacement `SchemaQueries` keep working; if
+    /// the server refuses, no column has one.
+    async fn fetch_column_octet_lengths(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> HashMap<(String, String), u64> {
+        let sql = format!(
+            r#"
+            SELECT CAST(table_name AS CHAR), CAST(column_name AS CHAR),
+                CAST(character_octet_length AS UNSIGNED)
+            FROM information_schema.columns
+            WHERE table_schema = DATABASE() AND character_octet_length IS NOT NULL{}
+            "#,
+            Self::table_filter("table_name", tables)
+        );
+        let rows: Result<Vec<(String, String, u64)>, _> =
+            Self::bind_tables(sqlx::query_as(&sql), tables)
+                .fetch_all(&mut *conn)
+                .await;
+        match rows {
+            Ok(rows) => rows
+                .into_iter()
+                .map(|(table, column, length)| ((table, column), length))
+                .collect(),
+            Err(e) => {
+                debug!("Column octet lengths not available: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Check constraints by table, each table's sorted by name. The
+    /// `check_constraints` view and the `enforced` column arrived in MySQL
+    /// 8.0.16; on older servers and MariaDB the query fails and no table has
+    /// any checks.
+    async fn fetch_all_check_constraints(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> HashMap<String, Vec<CheckConstraint>> {
+        let sql = format!(
+            r#"
+            SELECT CAST(tc.table_name AS CHAR), CAST(cc.constraint_name AS CHAR),
+                CAST(cc.check_clause AS CHAR), CAST(tc.enforced AS CHAR)
+            FROM information_schema.check_constraints cc
+            JOIN information_schema.table_constraints tc
+                ON tc.constraint_schema = cc.constraint_schema
+                AND tc.constraint_name = cc.constraint_name
+                AND tc.constraint_type = 'CHECK'
+            WHERE cc.constraint_schema = DATABASE(){}
+            ORDER BY tc.table_name, cc.constraint_name
+            "#,
+            Self::table_filter("tc.table_name", tables)
+        );
+        let rows: Result<Vec<(String, String, String, String)>, _> =
+            Self::bind_tables(sqlx::query_as(&sql), tables)
+                .fetch_all(&mut *conn)
+                .await;
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(e) => {
+                debug!("Check constraints not available: {}", e);
+                return HashMap::new();
+            }
+        };
+
+        let mut checks: HashMap<String, Vec<CheckConstraint>> = HashMap::new();
+        for (table, name, expression, enforced) in rows {
+            checks.entry(table).or_default().push(CheckConstraint {
+                name,
+                expression,
+                enforced: !enforced.eq_ignore_ascii_case("NO"),
+            });
+        }
+        checks
+    }
+
+    async fn fetch_all_primary_keys(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::PkRow>> {
+        let sql = self.metadata_sql(MetadataQuery::PrimaryKeys, tables);
+        let rows: Vec<(String, String, String)> = Self::bind_tables(sqlx::query_as(&sql), tables)
+            .fetch_all(&mut *conn)
+            .await?;
 
         Ok(rows
             .into_iter()
@@ -187,61 +826,96 @@ impl MySqlDriver {
             .collect())
     }
 
-    async fn fetch_all_indexes(&self) -> Result<Vec<crate::db::IndexRow>> {
-        let rows: Vec<(String, String, i32, String, String)> = sqlx::query_as(
-            r#"
-            SELECT CAST(s.table_name AS CHAR), CAST(s.index_name AS CHAR), s.non_unique, CAST(s.column_name AS CHAR), CAST(s.index_type AS CHAR)
-            FROM information_schema.statistics s
-            WHERE s.table_schema = DATABASE() AND s.index_name != 'PRIMARY'
-                AND NOT EXISTS (
-                    SELECT 1 FROM information_schema.table_constraints tc
-                    WHERE tc.table_schema = s.table_schema
-                        AND tc.table_name = s.table_name
-                        AND tc.constraint_name = s.index_name
-                        AND tc.constraint_type = 'UNIQUE'
-                )
-            ORDER BY s.table_name, s.index_name, s.seq_in_index
-            "#
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn fetch_all_indexes(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::IndexRow>> {
+        let sql = self.metadata_sql(MetadataQuery::Indexes, tables);
+        let rows: Vec<(String, String, i32, String, String, u64)> =
+            Self::bind_tables(sqlx::query_as(&sql), tables)
+                .fetch_all(&mut *conn)
+                .await?;
+        let invisible = self.fetch_invisible_indexes(conn).await;
+        let comments = self.fetch_index_comments(conn).await;
 
         Ok(rows
             .into_iter()
             .map(
-                |(table_name, index_name, non_unique, column_name, index_type)| {
+                |(table_name, index_name, non_unique, column_name, index_type, seq)| {
+                    let key = (table_name.clone(), index_name.clone());
+                    let visible = !invisible.contains(&key);
+                    let comment = comments.get(&key).cloned();
                     crate::db::IndexRow {
                         table_name,
                         index_name,
                         column_name,
                         is_unique: non_unique == 0,
                         index_type,
+                        tablespace: None,
+                        visible,
+                        seq_in_index: seq as u32,
+                        expression: None,
+                        storage_params: vec![],
+                        comment,
                     }
                 },
             )
             .collect())
     }
 
-    async fn fetch_all_foreign_keys(&self) -> Result<Vec<crate::db::FkRow>> {
-        let rows: Vec<(String, String, String, String, String, String, String)> = sqlx::query_as(
+    /// `(table, index)` pairs marked invisible. `is_visible` only exists on MySQL 8,
+    /// so on older servers (and MariaDB) the query fails and every index is visible.
+    async fn fetch_invisible_indexes(
+        &self,
+        conn: &mut MySqlConnection,
+    ) -> HashSet<(String, String)> {
+        sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT DISTINCT CAST(table_name AS CHAR), CAST(index_name AS CHAR)
+            FROM information_schema.statistics
+            WHERE table_schema = DATABASE() AND is_visible = 'NO'
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map(|rows| rows.into_iter().collect())
+        .unwrap_or_default()
+    }
+
+    /// Non-empty index comments by `(table, index)`. Read apart from the index
+    /// query so a customized one needn't select them; on failure no index has one.
+    async fn fetch_index_comments(
+        &self,
+        conn: &mut MySqlConnection,
+    ) -> HashMap<(String, String), String> {
+        sqlx::query_as::<_, (String, String, String)>(
             r#"
-            SELECT
-                CAST(kcu.table_name AS CHAR),
-                CAST(kcu.constraint_name AS CHAR),
-                CAST(kcu.column_name AS CHAR),
-                CAST(kcu.referenced_table_name AS CHAR),
-                CAST(kcu.referenced_column_name AS CHAR),
-                CAST(rc.delete_rule AS CHAR),
-                CAST(rc.update_rule AS CHAR)
-            FROM information_schema.key_column_usage kcu
-            JOIN information_schema.referential_constraints rc
-                ON kcu.constraint_name = rc.constraint_name AND kcu.table_schema = rc.constraint_schema
-            WHERE kcu.table_schema = DATABASE() AND kcu.referenced_table_name IS NOT NULL
-            ORDER BY kcu.table_name, kcu.constraint_name, kcu.ordinal_position
-            "#
+            SELECT DISTINCT CAST(table_name AS CHAR), CAST(index_name AS CHAR), CAST(index_comment AS CHAR)
+            FROM information_schema.statistics
+            WHERE table_schema = DATABASE() AND index_comment <> ''
+            "#,
         )
-        .fetch_all(&self.pool)
-        .await?;
+        .fetch_all(&mut *conn)
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(table, index, comment)| ((table, index), comment))
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    async fn fetch_all_foreign_keys(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::FkRow>> {
+        let sql = self.metadata_sql(MetadataQuery::ForeignKeys, tables);
+        let rows: Vec<(String, String, String, String, String, String, String)> =
+            Self::bind_tables(sqlx::query_as(&sql), tables)
+                .fetch_all(&mut *conn)
+                .await?;
 
         Ok(rows
             .into_iter()
@@ -263,25 +937,52 @@ impl MySqlDriver {
                         ref_column,
                         on_delete,
                         on_update,
+                        deferrable: false,
+                        initially_deferred: false,
+                        match_type: None,
                     }
                 },
             )
             .collect())
     }
 
-    async fn fetch_all_unique_constraints(&self) -> Result<Vec<crate::db::UcRow>> {
-        let rows: Vec<(String, String, String)> = sqlx::query_as(
-            r#"
-            SELECT CAST(tc.table_name AS CHAR), CAST(tc.constraint_name AS CHAR), CAST(kcu.column_name AS CHAR)
-            FROM information_schema.table_constraints tc
-            JOIN information_schema.key_column_usage kcu
-                ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema AND tc.table_name = kcu.table_name
-            WHERE tc.table_schema = DATABASE() AND tc.constraint_type = 'UNIQUE'
-            ORDER BY tc.table_name, tc.constraint_name, kcu.ordinal_position
-            "#
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    /// Partitioning of each partitioned table. Subpartitions are not modelled;
+    /// only the first subpartition row of each partition is read.
+    async fn fetch_all_partitioning(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> Result<HashMap<String, PartitionSpec>> {
+        let sql = self.metadata_sql(MetadataQuery::Partitioning, tables);
+        let rows: Vec<PartitionQueryRow> = Self::bind_tables(sqlx::query_as(&sql), tables)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        let mut specs: HashMap<String, PartitionSpec> = HashMap::new();
+        for (table, method, expression, name, description) in rows {
+            let bound = partition_bound(&method, description.as_deref());
+            specs
+                .entry(table)
+                .or_insert_with(|| PartitionSpec {
+                    method,
+                    expression: expression.unwrap_or_default(),
+                    partitions: vec![],
+                })
+                .partitions
+                .push(Partition { name, bound });
+        }
+        Ok(specs)
+    }
+
+    async fn fetch_all_unique_constraints(
+        &self,
+        conn: &mut MySqlConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::UcRow>> {
+        let sql = self.metadata_sql(MetadataQuery::UniqueConstraints, tables);
+        let rows: Vec<(String, String, String)> = Self::bind_tables(sqlx::query_as(&sql), tables)
+            .fetch_all(&mut *conn)
+            .await?;
 
         Ok(rows
             .into_iter()
@@ -295,3 +996,221 @@ impl MySqlDriver {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_column_storage_from_extra() {
+        let expr = || Some("(`price` * `qty`)".to_string());
+        assert_eq!(
+            generated_from_extra("VIRTUAL GENERATED", expr()).map(|g| g.storage),
+            Some(GeneratedStorage::Virtual)
+        );
+        assert_eq!(
+            generated_from_extra("STORED GENERATED", expr()).map(|g| g.storage),
+            Some(GeneratedStorage::Stored)
+        );
+        assert_eq!(generated_from_extra("", Some(String::new())), None);
+    }
+
+    #[tokio::test]
+    async fn custom_column_query_is_used() {
+        let pool = MySqlPoolOptions::new()
+            .connect_lazy("mysql://app@localhost/app")
+            .unwrap();
+        let driver = MySqlDriver::from_pool(pool).with_queries(SchemaQueries {
+            columns: "SELECT * FROM app_meta.columns WHERE 1 = 1{table_filter}".to_string(),
+            ..Default::default()
+        });
+
+        let names = ["users".to_string(), "orders".to_string()];
+        assert_eq!(
+            driver.metadata_sql(MetadataQuery::Columns, Some(&names)),
+            "SELECT * FROM app_meta.columns WHERE 1 = 1 AND table_name IN (?, ?)"
+        );
+        assert_eq!(
+            driver.metadata_sql(MetadataQuery::Columns, None),
+            "SELECT * FROM app_meta.columns WHERE 1 = 1"
+        );
+        assert!(
+            driver
+                .metadata_sql(MetadataQuery::Indexes, None)
+                .contains("information_schema.statistics")
+        );
+    }
+
+    #[tokio::test]
+    async fn from_pool_shares_the_callers_pool() {
+        let pool = MySqlPoolOptions::new()
+            .connect_lazy("mysql://app@localhost/app")
+            .unwrap();
+        let driver = MySqlDriver::from_pool(pool.clone());
+
+        pool.close().await;
+        assert!(driver.pool().is_closed());
+    }
+
+    #[test]
+    fn invalid_utf8_comment_is_replaced_with_a_warning() {
+        // "café" written through a latin1 connection: 0xE9 on its own.
+        let row: ColumnQueryRow = (
+            b"menu".to_vec(),
+            b"name".to_vec(),
+            "varchar(50)".to_string(),
+            "YES".to_string(),
+            None,
+            String::new(),
+            Some(b"caf\xe9".to_vec()),
+            1,
+            None,
+        );
+        let mut warnings = Vec::new();
+        let column = column_from_row(row, &HashMap::new(), &HashMap::new(), &mut warnings);
+
+        assert_eq!(column.name, "name");
+        assert_eq!(column.comment.as_deref(), Some("caf\u{FFFD}"));
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].starts_with("Comment on menu.name"),
+            "{}",
+            warnings[0]
+        );
+    }
+
+    #[test]
+    fn utf8mb4_varchar_reads_its_octet_length() {
+        let row: ColumnQueryRow = (
+            b"users".to_vec(),
+            b"email".to_vec(),
+            "varchar(255)".to_string(),
+            "YES".to_string(),
+            None,
+            String::new(),
+            None,
+            2,
+            None,
+        );
+        // utf8mb4 takes up to four bytes per character.
+        let octet_lengths = HashMap::from([(("users".to_string(), "email".to_string()), 1020)]);
+        let column = column_from_row(row, &HashMap::new(), &octet_lengths, &mut Vec::new());
+        assert_eq!(column.data_type, "varchar(255)");
+        assert_eq!(column.octet_length, Some(1020));
+    }
+
+    #[test]
+    fn strip_definer_removes_owner_clause() {
+        let definition =
+            "CREATE DEFINER=`root`@`%` PROCEDURE `p`(IN x INT)\nBEGIN\n  SELECT x;\nEND";
+        assert_eq!(
+            MySqlDriver::strip_definer(definition, &RoutineKind::Procedure),
+            "CREATE PROCEDURE `p`(IN x INT)\nBEGIN\n  SELECT x;\nEND"
+        );
+    }
+
+    #[test]
+    fn list_databases_sql_unfiltered_by_default() {
+        let sql = MySqlDriver::list_databases_sql(false, false);
+        assert!(!sql.contains("LIKE"));
+        assert!(!sql.contains("LIMIT"));
+        assert!(sql.ends_with("ORDER BY schema_name"));
+    }
+
+    #[test]
+    fn database_lists_are_merged_without_duplicates() {
+        let listed = vec!["app".to_string(), "reports".to_string()];
+        let shown = ["information_schema", "reports", "analytics", "app"].map(String::from);
+
+        assert_eq!(
+            MySqlDriver::merge_database_names(listed.clone(), shown.clone(), None),
+            vec!["analytics", "app", "reports"]
+        );
+        assert_eq!(
+            MySqlDriver::merge_database_names(listed, shown, Some(2)),
+            vec!["analytics", "app"]
+        );
+        assert_eq!(
+            MySqlDriver::show_databases_sql(Some("o'brien%")),
+            "SHOW DATABASES LIKE 'o''brien%'"
+        );
+    }
+
+    #[test]
+    fn list_databases_sql_pushes_filter_and_limit_into_query() {
+        let sql = MySqlDriver::list_databases_sql(true, true);
+        assert!(sql.contains("AND schema_name LIKE ? ORDER BY schema_name LIMIT ?"));
+    }
+
+    #[test]
+    fn expression_defaults_are_parenthesized() {
+        assert_eq!(
+            default_from_column(Some("uuid()".to_string()), "DEFAULT_GENERATED").as_deref(),
+            Some("(uuid())")
+        );
+        assert_eq!(
+            default_from_column(
+                Some("CURRENT_TIMESTAMP(3)".to_string()),
+                "DEFAULT_GENERATED on update CURRENT_TIMESTAMP(3)"
+            )
+            .as_deref(),
+            Some("CURRENT_TIMESTAMP(3)")
+        );
+        assert_eq!(
+            default_from_column(Some("uuid()".to_string()), "").as_deref(),
+            Some("uuid()")
+        );
+    }
+
+    #[test]
+    fn partition_bound_from_description() {
+        assert_eq!(
+            partition_bound("RANGE", Some("2024")),
+            "VALUES LESS THAN (2024)"
+        );
+        assert_eq!(
+            partition_bound("RANGE", Some("MAXVALUE")),
+            "VALUES LESS THAN MAXVALUE"
+        );
+        assert_eq!(
+            partition_bound("RANGE COLUMNS", Some("'2024-01-01',MAXVALUE")),
+            "VALUES LESS THAN ('2024-01-01',MAXVALUE)"
+        );
+        assert_eq!(partition_bound("LIST", Some("1,2")), "VALUES IN (1,2)");
+        assert_eq!(partition_bound("HASH", None), "");
+    }
+
+    #[test]
+    fn on_update_read_from_extra() {
+        assert_eq!(
+            on_update_from_extra("DEFAULT_GENERATED on update CURRENT_TIMESTAMP(3)").as_deref(),
+            Some("CURRENT_TIMESTAMP(3)")
+        );
+        assert_eq!(
+            on_update_from_extra("on update current_timestamp()").as_deref(),
+            Some("current_timestamp()")
+        );
+        assert_eq!(on_update_from_extra("auto_increment"), None);
+    }
+
+    #[test]
+    fn table_filter_adds_one_placeholder_per_name() {
+        let names = vec!["users".to_string(), "orders".to_string()];
+        assert_eq!(
+            MySqlDriver::table_filter("t.table_name", Some(&names)),
+            " AND t.table_name IN (?, ?)"
+        );
+        assert_eq!(MySqlDriver::table_filter("t.table_name", None), "");
+    }
+
+    #[test]
+    fn database_info_from_schemata_uses_charset_as_encoding() {
+        let info = MySqlDriver::database_info_from_schemata(
+            "utf8mb4".to_string(),
+            "utf8mb4_0900_ai_ci".to_string(),
+        );
+        assert_eq!(info.charset.as_deref(), Some("utf8mb4"));
+        assert_eq!(info.collation.as_deref(), Some("utf8mb4_0900_ai_ci"));
+        assert_eq!(info.encoding.as_deref(), Some("utf8mb4"));
+    }
+}
@@ -4,7 +4,13 @@ use crate::models::*;
 
 use super::reader::PostgresDriver;
 
-pub struct PostgresSqlGenerator;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostgresSqlGenerator {
+    /// Build and drop indexes on existing tables `CONCURRENTLY`, without
+    /// blocking writes. Such statements can't run inside a transaction; see
+    /// `runs_outside_transaction` and `execute_in_transactions`.
+    pub concurrent_indexes: bool,
+}
 
 /// Choose the correct SERIAL variant based on the column's data type.
 /// Falls back to SERIAL if the type doesn't clearly map to a size.
@@ -19,6 +25,66 @@ fn serial_type_for(data_type: &str) -> &'static str {
     }
 }
 
+/// Postgres only supports stored generated columns, so a virtual one is stored.
+fn stored_generation(generated: &GeneratedColumn) -> String {
+    GeneratedColumn {
+        storage: GeneratedStorage::Stored,
+        ..generated.clone()
+    }
+    .clause()
+}
+
+/// The sequence an auto-increment column draws from: the one read from the
+/// database, or the name SERIAL would create.
+fn sequence_name(table: &str, column: &Column) -> String {
+    column
+        .sequence
+        .clone()
+        .unwrap_or_else(|| format!("{}_{}_seq", table, column.name))
+}
+
+/// ` MATCH FULL` / ` MATCH PARTIAL`; empty for the default `MATCH SIMPLE`.
+fn fk_match_clause(fk: &ForeignKey) -> String {
+    match fk.match_type.as_deref().map(str::to_uppercase).as_deref() {
+        Some(m @ ("FULL" | "PARTIAL")) => format!(" MATCH {}", m),
+        _ => String::new(),
+    }
+}
+
+/// ` DEFERRABLE` with its initial mode; empty for a non-deferrable key.
+fn fk_timing_clause(fk: &ForeignKey) -> &'static str {
+    match (fk.deferrable, fk.initially_deferred) {
+        (false, _) => "",
+        (true, false) => " DEFERRABLE",
+        (true, true) => " DEFERRABLE INITIALLY DEFERRED",
+    }
+}
+
+/// A role as GRANT and policies name it; the `public` pseudo-role is a keyword.
+fn role_name(sql_gen: &PostgresSqlGenerator, role: &str) -> String {
+    if role.eq_ignore_ascii_case("public") {
+        "PUBLIC".to_string()
+    } else {
+        sql_gen.quote_identifier(role)
+    }
+}
+
+/// The `TO`, `USING` and `WITH CHECK` clauses shared by CREATE and ALTER POLICY.
+fn policy_clauses(sql_gen: &PostgresSqlGenerator, policy: &Policy) -> String {
+    let roles: Vec<String> = policy.roles.iter().map(|r| role_name(sql_gen, r)).collect();
+    let mut sql = String::new();
+    if !roles.is_empty() {
+        sql.push_str(&format!(" TO {}", roles.join(", ")));
+    }
+    if let Some(using) = &policy.using_expr {
+        sql.push_str(&format!(" USING ({})", using));
+    }
+    if let Some(check) = &policy.check_expr {
+        sql.push_str(&format!(" WITH CHECK ({})", check));
+    }
+    sql
+}
+
 impl SqlGenerator for PostgresSqlGenerator {
     fn quote_identifier(&self, name: &str) -> String {
         format!("\"{}\"", name.replace('"', "\"\""))
@@ -35,10 +101,13 @@ impl SqlGenerator for PostgresSqlGenerator {
                 col.data_type.clone()
             };
             let mut col_def = format!("  {} {}", self.quote_identifier(&col.name), data_type);
+            if let Some(generated) = &col.generated {
+                col_def.push_str(&format!(" {}", stored_generation(generated)));
+            }
             if !col.nullable && !col.auto_increment {
                 col_def.push_str(" NOT NULL");
             }
-            if let Some(default) = &col.default_value {
+            if let Some(default) = col.effective_default() {
                 col_def.push_str(&format!(" DEFAULT {}", default));
             }
             parts.push(col_def);
@@ -66,6 +135,22 @@ impl SqlGenerator for PostgresSqlGenerator {
             ));
         }
 
+        for ec in &table.exclusion_constraints {
+            parts.push(format!(
+                "  CONSTRAINT {} {}",
+                self.quote_identifier(&ec.name),
+                ec.definition
+            ));
+        }
+
+        for check in &table.check_constraints {
+            parts.push(format!(
+                "  CONSTRAINT {} CHECK ({})",
+                self.quote_identifier(&check.name),
+                check.expression
+            ));
+        }
+
         for fk in &table.foreign_keys {
             let cols: Vec<String> = fk
                 .columns
@@ -78,35 +163,70 @@ impl SqlGenerator for PostgresSqlGenerator {
                 .map(|c| self.quote_identifier(c))
                 .collect();
             parts.push(format!(
-                "  CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}) ON DELETE {} ON UPDATE {}",
+                "  CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}){} ON DELETE {} ON UPDATE {}{}",
                 self.quote_identifier(&fk.name),
                 cols.join(", "),
                 self.quote_identifier(&fk.ref_table),
                 ref_cols.join(", "),
+                fk_match_clause(fk),
                 validate_fk_action(&fk.on_delete),
-                validate_fk_action(&fk.on_update)
+                validate_fk_action(&fk.on_update),
+                fk_timing_clause(fk)
             ));
         }
 
-        sql.push_str(&parts.join(",\n"));
-        sql.push_str("\n);");
-
-        for idx in &table.indexes {
-            let cols: Vec<String> = idx
-                .columns
+        if parts.is_empty() {
+            // Postgres allows a table without columns, but not an empty line in its parentheses.
+            sql.pop();
+            sql.push(')');
+        } else {
+            sql.push_str(&parts.join(",\n"));
+            sql.push_str("\n)");
+        }
+        if !table.inherits.is_empty() {
+            let parents: Vec<String> = table
+                .inherits
                 .iter()
-                .map(|c| self.quote_identifier(c))
+                .map(|p| self.quote_identifier(p))
                 .collect();
-            let idx_type = if idx.unique { "UNIQUE INDEX" } else { "INDEX" };
+            sql.push_str(&format!(" INHERITS ({})", parents.join(", ")));
+        }
+        if let Some(spec) = &table.partitioning {
+            sql.push_str(&format!(
+                " PARTITION BY {} ({})",
+                spec.method, spec.expression
+            ));
+        }
+        if let Some(ts) = &table.tablespace {
+            sql.push_str(&format!(" TABLESPACE {}", self.quote_identifier(ts)));
+        }
+        sql.push(';');
+
+        // Each partition is a table of its own, attached by its bound.
+        for partition in table.partitioning.iter().flat_map(|s| &s.partitions) {
             sql.push_str(&format!(
-                "\nCREATE {} {} ON {} ({});",
-                idx_type,
-                self.quote_identifier(&idx.name),
+                "\nCREATE TABLE {} PARTITION OF {} {};",
+                self.quote_identifier(&partition.name),
                 self.quote_identifier(&table.name),
-                cols.join(", ")
+                partition.bound
             ));
         }
 
+        // Nothing writes to a table that was just created; no need to build concurrently.
+        let plain = PostgresSqlGenerator::default();
+        for idx in &table.indexes {
+            sql.push('\n');
+            sql.push_str(&plain.generate_add_index(&table.name, idx));
+        }
+        if table.row_security {
+            sql.push('\n');
+            sql.push_str(&self.generate_set_row_security(&table.name, true));
+        }
+        for policy in &table.policies {
+            sql.push('\n');
+            sql.push_str(&self.generate_create_policy(&table.name, policy));
+        }
+
         sql
     }
 
@@ -126,16 +246,24 @@ impl SqlGenerator for PostgresSqlGenerator {
             self.quote_identifier(&column.name),
             data_type
         );
+        if let Some(generated) = &column.generated {
+            sql.push_str(&format!(" {}", stored_generation(generated)));
+        }
         if !column.nullable && !column.auto_increment {
             sql.push_str(" NOT NULL");
         }
-        if let Some(default) = &column.default_value {
+        if let Some(default) = column.effective_default() {
             sql.push_str(&format!(" DEFAULT {}", default));
         }
         sql.push(';');
         sql
     }
 
+    fn generate_add_column_online(&self, table: &str, column: &Column) -> String {
+        // Postgres has no per-statement lock hints.
+        self.generate_add_column(table, column)
+    }
+
     fn generate_drop_column(&self, table: &str, column_name: &str) -> String {
         format!(
             "ALTER TABLE {} DROP COLUMN {};",
@@ -157,7 +285,7 @@ impl SqlGenerator for PostgresSqlGenerator {
 
         // Auto-increment: create sequence and wire it up
         if column.auto_increment {
-            let seq_name = format!("{}_{}_seq", table, column.name);
+            let seq_name = sequence_name(table, column);
             let quoted_seq = self.quote_identifier(&seq_name);
             stmts.push(format!("CREATE SEQUENCE IF NOT EXISTS {};", quoted_seq));
             stmts.push(format!(
@@ -185,7 +313,7 @@ impl SqlGenerator for PostgresSqlGenerator {
 
         // DEFAULT (only if not auto_increment, which is handled above)
         if !column.auto_increment {
-            if let Some(default) = &column.default_value {
+            if let Some(default) = column.effective_default() {
                 stmts.push(format!(
                     "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {};",
                     tbl, col, default
@@ -201,28 +329,174 @@ impl SqlGenerator for PostgresSqlGenerator {
         stmts.join("\n")
     }
 
+    fn generate_alter_column(&self, table: &str, source: &Column, target: &Column) -> String {
+        let tbl = self.quote_identifier(table);
+        let col = self.quote_identifier(&source.name);
+        let mut stmts = Vec::new();
+        // `visible` and `srid` are MySQL-only and always at their defaults here.
+
+        // A generation expression can't be set on an existing column, so a column
+        // that becomes generated, or changes how, is dropped and added back; its
+        // values are computed again. One that stops being generated keeps them.
+        if source.generated != target.generated {
+            if source.generated.is_some() {
+                return format!(
+                    "{}\n{}",
+                    self.generate_drop_column(table, &source.name),
+                    self.generate_add_column(table, source)
+                );
+            }
+            stmts.push(format!(
+                "ALTER TABLE {} ALTER COLUMN {} DROP EXPRESSION;",
+                tbl, col
+            ));
+        }
+
+        if source.data_type != target.data_type {
+            stmts.push(format!(
+                "ALTER TABLE {} ALTER COLUMN {} TYPE {};",
+                tbl, col, source.data_type
+            ));
+        }
+
+        // Also re-point the default when the column draws from a different sequence.
+        if source.auto_increment && (!target.auto_increment || source.sequence != target.sequence) {
+            let seq_name = sequence_name(table, source);
+            let quoted_seq = self.quote_identifier(&seq_name);
+            stmts.push(format!("CREATE SEQUENCE IF NOT EXISTS {};", quoted_seq));
+            stmts.push(format!(
+                "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT nextval('{}');",
+                tbl, col, seq_name
+            ));
+            stmts.push(format!(
+                "ALTER SEQUENCE {} OWNED BY {}.{};",
+                quoted_seq, tbl, col
+            ));
+        }
+
+        if source.nullable != target.nullable {
+            let action = if source.nullable { "DROP" } else { "SET" };
+            stmts.push(format!(
+                "ALTER TABLE {} ALTER COLUMN {} {} NOT NULL;",
+                tbl, col, action
+            ));
+        }
+
+        // Dropping auto-increment also drops its nextval() default
+        let default_changed =
+            source.effective_default() != target.effective_default() || target.auto_increment;
+        if !source.auto_increment && default_changed {
+            match source.effective_default() {
+                Some(default) => stmts.push(format!(
+                    "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {};",
+                    tbl, col, default
+                )),
+                None => stmts.push(format!(
+                    "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT;",
+                    tbl, col
+                )),
+            }
+        }
+
+        if source.comment != target.comment {
+            let comment = source
+                .comment
+                .as_ref()
+                .map(|c| format!("'{}'", c.replace('\'', "''")))
+                .unwrap_or_else(|| "NULL".to_string());
+            stmts.push(format!("COMMENT ON COLUMN {}.{} IS {};", tbl, col, comment));
+        }
+
+        stmts.join("\n")
+    }
+
+    fn generate_rename_column(&self, table: &str, source: &Column, target: &Column) -> String {
+        let rename = format!(
+            "ALTER TABLE {} RENAME COLUMN {} TO {};",
+            self.quote_identifier(table),
+            self.quote_identifier(&target.name),
+            self.quote_identifier(&source.name)
+        );
+        let renamed = Column {
+            name: source.name.clone(),
+            ..target.clone()
+        };
+        let alter = self.generate_alter_column(table, source, &renamed);
+        if alter.is_empty() {
+            rename
+        } else {
+            format!("{}\n{}", rename, alter)
+        }
+    }
+
     fn generate_add_index(&self, table: &str, index: &Index) -> String {
+        let tablespace = index
+            .tablespace
+            .as_ref()
+            .map(|ts| format!(" TABLESPACE {}", self.quote_identifier(ts)))
+            .unwrap_or_default();
+        if let Some(definition) = &index.expression {
+            let mut definition = if self.concurrent_indexes {
+                definition.replacen(" INDEX ", " INDEX CONCURRENTLY ", 1)
+            } else {
+                definition.clone()
+            };
+            // TABLESPACE goes after any WITH (...) but before a partial index's
+            // WHERE, and may already be part of the definition.
+            if !definition.contains(" TABLESPACE ") {
+                let at = definition.find(" WHERE ").unwrap_or(definition.len());
+                definition.insert_str(at, &tablespace);
+            }
+            return format!("{};", definition);
+        }
         let cols: Vec<String> = index
             .columns
             .iter()
             .map(|c| self.quote_identifier(c))
             .collect();
-        let idx_type = if index.unique {
-            "UNIQUE INDEX"
+        let idx_type = match (index.unique, self.concurrent_indexes) {
+            (true, true) => "UNIQUE INDEX CONCURRENTLY",
+            (true, false) => "UNIQUE INDEX",
+            (false, true) => "INDEX CONCURRENTLY",
+            (false, false) => "INDEX",
+        };
+        // btree is the default; FULLTEXT and SPATIAL only come from MySQL.
+        let method = match index.index_type.to_ascii_lowercase().as_str() {
+            "" | "btree" | "fulltext" | "spatial" => String::new(),
+            method => format!(" USING {}", method),
+        };
+        let storage = if index.storage_params.is_empty() {
+            String::new()
         } else {
-            "INDEX"
+            format!(" WITH ({})", index.storage_params.join(", "))
         };
         format!(
-            "CREATE {} {} ON {} ({});",
+            "CREATE {} {} ON {}{} ({}){}{};",
             idx_type,
             self.quote_identifier(&index.name),
             self.quote_identifier(table),
-            cols.join(", ")
+            method,
+            cols.join(", "),
+            storage,
+            tablespace
         )
     }
 
+    fn generate_add_index_online(&self, table: &str, index: &Index) -> String {
+        self.generate_add_index(table, index)
+    }
+
     fn generate_drop_index(&self, _table: &str, index_name: &str) -> String {
-        format!("DROP INDEX {};", self.quote_identifier(index_name))
+        let concurrently = if self.concurrent_indexes {
+            "CONCURRENTLY "
+        } else {
+            ""
+        };
+        format!(
+            "DROP INDEX {}{};",
+            concurrently,
+            self.quote_identifier(index_name)
+        )
     }
 
     fn generate_add_foreign_key(&self, table: &str, fk: &ForeignKey) -> String {
@@ -237,14 +511,28 @@ impl SqlGenerator for PostgresSqlGenerator {
             .map(|c| self.quote_identifier(c))
             .collect();
         format!(
-            "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}) ON DELETE {} ON UPDATE {};",
+            "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}){} ON DELETE {} ON UPDATE {}{};",
             self.quote_identifier(table),
             self.quote_identifier(&fk.name),
             cols.join(", "),
             self.quote_identifier(&fk.ref_table),
             ref_cols.join(", "),
+            fk_match_clause(fk),
             validate_fk_action(&fk.on_delete),
-            validate_fk_action(&fk.on_update)
+            validate_fk_action(&fk.on_update),
+            fk_timing_clause(fk)
+        )
+    }
+
+    fn generate_add_foreign_key_deferred_validation(&self, table: &str, fk: &ForeignKey) -> String {
+        // NOT VALID only takes a brief lock; VALIDATE scans the rows under a
+        // SHARE UPDATE EXCLUSIVE lock that doesn't block reads or writes.
+        let add = self.generate_add_foreign_key(table, fk);
+        format!(
+            "{} NOT VALID;\nALTER TABLE {} VALIDATE CONSTRAINT {};",
+            add.trim_end_matches(';'),
+            self.quote_identifier(table),
+            self.quote_identifier(&fk.name)
         )
     }
 
@@ -277,6 +565,320 @@ impl SqlGenerator for PostgresSqlGenerator {
             self.quote_identifier(uc_name)
         )
     }
+
+    fn generate_alter_primary_key(
+        &self,
+        table: &str,
+        current: Option<&PrimaryKey>,
+        wanted: Option<&PrimaryKey>,
+    ) -> String {
+        let tbl = self.quote_identifier(table);
+        let name = |pk: &PrimaryKey| {
+            self.quote_identifier(&pk.name.clone().unwrap_or_else(|| format!("{}_pkey", table)))
+        };
+        let mut stmts = Vec::new();
+        // Dropping one of the key's columns may already have taken the key with it.
+        if let Some(pk) = current {
+            stmts.push(format!(
+                "ALTER TABLE {} DROP CONSTRAINT IF EXISTS {};",
+                tbl,
+                name(pk)
+            ));
+        }
+        if let Some(pk) = wanted {
+            let cols: Vec<String> = pk
+                .columns
+                .iter()
+                .map(|c| self.quote_identifier(c))
+                .collect();
+            stmts.push(format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} PRIMARY KEY ({});",
+                tbl,
+                name(pk),
+                cols.join(", ")
+            ));
+        }
+        stmts.join("\n")
+    }
+
+    fn generate_add_exclusion(&self, table: &str, ec: &ExclusionConstraint) -> String {
+        format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} {};",
+            self.quote_identifier(table),
+            self.quote_identifier(&ec.name),
+            ec.definition
+        )
+    }
+
+    fn generate_drop_exclusion(&self, table: &str, ec_name: &str) -> String {
+        format!(
+            "ALTER TABLE {} DROP CONSTRAINT {};",
+            self.quote_identifier(table),
+            self.quote_identifier(ec_name)
+        )
+    }
+
+    fn generate_add_check(&self, table: &str, check: &CheckConstraint) -> String {
+        // Postgres checks are always enforced, so `enforced` is not written out.
+        format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} CHECK ({});",
+            self.quote_identifier(table),
+            self.quote_identifier(&check.name),
+            check.expression
+        )
+    }
+
+    fn generate_drop_check(&self, table: &str, check_name: &str) -> String {
+        format!(
+            "ALTER TABLE {} DROP CONSTRAINT {};",
+            self.quote_identifier(table),
+            self.quote_identifier(check_name)
+        )
+    }
+
+    fn generate_create_policy(&self, table: &str, policy: &Policy) -> String {
+        let mut sql = format!(
+            "CREATE POLICY {} ON {}",
+            self.quote_identifier(&policy.name),
+            self.quote_identifier(table)
+        );
+        if !policy.permissive {
+            sql.push_str(" AS RESTRICTIVE");
+        }
+        sql.push_str(&format!(" FOR {}", policy.command));
+        sql.push_str(&policy_clauses(self, policy));
+        sql.push(';');
+        sql
+    }
+
+    fn generate_alter_policy(&self, table: &str, policy: &Policy) -> String {
+        format!(
+            "ALTER POLICY {} ON {}{};",
+            self.quote_identifier(&policy.name),
+            self.quote_identifier(table),
+            policy_clauses(self, policy)
+        )
+    }
+
+    fn generate_drop_policy(&self, table: &str, policy_name: &str) -> String {
+        format!(
+            "DROP POLICY {} ON {};",
+            self.quote_identifier(policy_name),
+            self.quote_identifier(table)
+        )
+    }
+
+    fn generate_set_row_security(&self, table: &str, enabled: bool) -> String {
+        format!(
+            "ALTER TABLE {} {} ROW LEVEL SECURITY;",
+            self.quote_identifier(table),
+            if enabled { "ENABLE" } else { "DISABLE" }
+        )
+    }
+
+    fn generate_create_routine(&self, routine: &Routine) -> String {
+        // pg_get_functiondef already reads CREATE OR REPLACE.
+        format!("{};", routine.definition.trim_end().trim_end_matches(';'))
+    }
+
+    fn generate_replace_routine(&self, routine: &Routine) -> String {
+        self.generate_create_routine(routine)
+    }
+
+    fn generate_drop_routine(&self, routine: &Routine) -> String {
+        // The name carries the argument list, which DROP needs to pick the overload.
+        let (name, args) = routine
+            .name
+            .split_once('(')
+            .map(|(name, args)| (name, format!("({}", args)))
+            .unwrap_or((routine.name.as_str(), String::new()));
+        format!(
+            "DROP {} IF EXISTS {}{};",
+            routine.kind.keyword(),
+            self.quote_identifier(name),
+            args
+        )
+    }
+
+    fn generate_rebuild_table(&self, source: &TableSchema, target: &TableSchema) -> String {
+        // PostgreSQL can't reposition columns, so the only way to match the source
+        // order is to copy the data into a freshly created table.
+        let (mut temp, copy_columns) = crate::db::rebuild_parts(source, target);
+        // Partitions are tables too; they take temporary names until the old
+        // table, and with it any partitions of the same name, is gone.
+        let partition_names: Vec<String> = temp
+            .partitioning
+            .iter_mut()
+            .flat_map(|s| &mut s.partitions)
+            .map(|p| {
+                let temp_name = format!("{}__rebuild", p.name);
+                std::mem::replace(&mut p.name, temp_name)
+            })
+            .collect();
+        let tbl = self.quote_identifier(&source.name);
+        let tmp = self.quote_identifier(&temp.name);
+        let mut stmts = vec![self.generate_create_table(&temp)];
+
+        if !copy_columns.is_empty() {
+            let cols: Vec<String> = copy_columns
+                .iter()
+                .map(|c| self.quote_identifier(c))
+                .collect();
+            // ONLY keeps an inheritance parent from taking in its children's rows;
+            // a partitioned table keeps all of its rows in its partitions.
+            let only = if target.partitioning.is_none() {
+                "ONLY "
+            } else {
+                ""
+            };
+            stmts.push(format!(
+                "INSERT INTO {} ({}) SELECT {} FROM {}{};",
+                tmp,
+                cols.join(", "),
+                cols.join(", "),
+                only,
+                tbl
+            ));
+        }
+
+        // SERIAL columns get a brand-new sequence; move it past the copied values.
+        for col in temp.columns.iter().filter(|c| c.auto_increment) {
+            stmts.push(format!(
+                "SELECT setval(pg_get_serial_sequence('{}', '{}'), COALESCE(MAX({}), 0) + 1, false) FROM {};",
+                tmp.replace('\'', "''"),
+                col.name.replace('\'', "''"),
+                self.quote_identifier(&col.name),
+                tmp
+            ));
+        }
+
+        stmts.push(self.generate_drop_table(&source.name));
+        stmts.push(format!("ALTER TABLE {} RENAME TO {};", tmp, tbl));
+        // The new sequences are named after the temporary table; give them back
+        // the names the old ones had, or the next comparison reports them.
+        for col in temp.columns.iter().filter(|c| c.auto_increment) {
+            stmts.push(format!(
+                "ALTER SEQUENCE {} RENAME TO {};",
+                self.quote_identifier(&format!("{}_{}_seq", temp.name, col.name)),
+                self.quote_identifier(&sequence_name(&source.name, col))
+            ));
+        }
+        for name in &partition_names {
+            stmts.push(format!(
+                "ALTER TABLE {} RENAME TO {};",
+                self.quote_identifier(&format!("{}__rebuild", name)),
+                self.quote_identifier(name)
+            ));
+        }
+
+        // The key was created as `<temp>_pkey`; an implicit name has to become
+        // `<table>_pkey` again just like an explicit one.
+        if let Some(pk) = &source.primary_key {
+            let pk_name = pk
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{}_pkey", source.name));
+            stmts.push(format!(
+                "ALTER TABLE {} RENAME CONSTRAINT {} TO {};",
+                tbl,
+                self.quote_identifier(&format!("{}_pkey", temp.name)),
+                self.quote_identifier(&pk_name)
+            ));
+        }
+        for uc in &source.unique_constraints {
+            stmts.push(self.generate_add_unique(&source.name, uc));
+        }
+        for ec in &source.exclusion_constraints {
+            stmts.push(self.generate_add_exclusion(&source.name, ec));
+        }
+        for check in &source.check_constraints {
+            stmts.push(self.generate_add_check(&source.name, check));
+        }
+        let plain = PostgresSqlGenerator::default();
+        for idx in &source.indexes {
+            stmts.push(plain.generate_add_index(&source.name, idx));
+        }
+        for fk in &source.foreign_keys {
+            stmts.push(self.generate_add_foreign_key(&source.name, fk));
+        }
+        if source.row_security {
+            stmts.push(self.generate_set_row_security(&source.name, true));
+        }
+        for policy in &source.policies {
+            stmts.push(self.generate_create_policy(&source.name, policy));
+        }
+
+        stmts.join("\n")
+    }
+
+    fn generate_move_column(
+        &self,
+        _table: &str,
+        _column: &Column,
+        _after: Option<&str>,
+    ) -> Option<String> {
+        None
+    }
+
+    fn generate_alter_partitioning(
+        &self,
+        _table: &str,
+        _partitioning: Option<&PartitionSpec>,
+    ) -> Option<String> {
+        // A table can't become, or stop being, partitioned; only rebuilding it can.
+        None
+    }
+
+    fn generate_set_tablespace(&self, table: &str, tablespace: Option<&str>) -> String {
+        format!(
+            "ALTER TABLE {} SET TABLESPACE {};",
+            self.quote_identifier(table),
+            self.quote_identifier(tablespace.unwrap_or("pg_default"))
+        )
+    }
+
+    fn generate_set_table_options(&self, table: &str, _options: &TableOptions) -> String {
+        format!(
+            "-- PostgreSQL has no engine, charset or row format options; skipped {}",
+            table
+        )
+    }
+
+    fn generate_set_inherits(&self, table: &str, add: &[String], remove: &[String]) -> String {
+        let table = self.quote_identifier(table);
+        remove
+            .iter()
+            .map(|parent| (parent, "NO INHERIT"))
+            .chain(add.iter().map(|parent| (parent, "INHERIT")))
+            .map(|(parent, action)| {
+                format!(
+                    "ALTER TABLE {} {} {};",
+                    table,
+                    action,
+                    self.quote_identifier(parent)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn generate_grant(&self, grant: &Grant) -> String {
+        format!(
+            "GRANT {} ON {} TO {};",
+            grant.privilege,
+            self.quote_identifier(&grant.object),
+            role_name(self, &grant.grantee)
+        )
+    }
+
+    fn generate_revoke(&self, grant: &Grant) -> String {
+        format!(
+            "REVOKE {} ON {} FROM {};",
+            grant.privilege,
+            self.quote_identifier(&grant.object),
+            role_name(self, &grant.grantee)
+        )
+    }
 }
 
-crate::db::impl_sql_generator_delegation!(PostgresDriver, PostgresSqlGenerator);
+crate::db::impl_sql_generator_delegation!(PostgresDriver, PostgresSqlGenerator::default());
@@ -3,3 +3,4 @@ mod reader;
 
 pub use generator::PostgresSqlGenerator;
 pub use reader::PostgresDriver;
+pub(crate) use reader::sequence_from_default;
@@ -1,10 +1,150 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::{PgPool, postgres::PgPoolOptions};
+use log::debug;
+use sqlx::{PgConnection, PgPool, Postgres, postgres::PgPoolOptions};
+use std::collections::{HashMap, HashSet};
 
-use crate::db::traits::SchemaReader;
+use crate::db::describe_tables;
+use crate::db::snapshot::read_in_snapshot;
+use crate::db::split_statements;
+use crate::db::traits::{SchemaReader, SqlExecutor};
 use crate::models::*;
 
+/// Raw column row fetched from information_schema: table, column, resolved type,
+/// udt name, nullable, default, position, generation expression.
+type ColumnQueryRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    i32,
+    Option<String>,
+);
+/// Raw index row fetched from pg_index: table, index, unique, column, access
+/// method, tablespace, key position.
+type IndexQueryRow = (
+    String,
+    String,
+    bool,
+    String,
+    String,
+    Option<String>,
+    Vec<String>,
+    i32,
+);
+/// Raw definition-only index row: table, index, unique, access method, tablespace,
+/// `pg_get_indexdef` output.
+type DefinitionIndexQueryRow = (String, String, bool, String, Option<String>, String);
+/// Raw foreign key row: table, constraint, column, referenced table and column,
+/// delete and update rules, deferrable, initially deferred, match option.
+type ForeignKeyQueryRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    bool,
+    bool,
+    String,
+);
+/// Raw policy row: table, policy, command, permissive, roles, USING and WITH CHECK
+/// expressions.
+type PolicyQueryRow = (
+    String,
+    String,
+    String,
+    bool,
+    Vec<String>,
+    Option<String>,
+    Option<String>,
+);
+
+/// An array type as `format_type` renders it, with the element spelled the way
+/// scalar columns are read: `character varying(50)[]` -> `varchar(50)[]`.
+fn array_type(formatted: &str) -> String {
+    if let Some(rest) = formatted.strip_prefix("character varying") {
+        format!("varchar{}", rest)
+    } else if let Some(rest) = formatted.strip_prefix("character(") {
+        format!("char({}", rest)
+    } else {
+        formatted.to_string()
+    }
+}
+
+/// Drop the `public.` qualifier `pg_get_indexdef` puts on the table, so definitions
+/// read from different databases compare equal and replay against the target's schema.
+fn normalize_index_definition(definition: &str) -> String {
+    definition
+        .replacen(" ON public.", " ON ", 1)
+        .replacen(" ON ONLY public.", " ON ONLY ", 1)
+}
+
+/// Same for `pg_get_functiondef`, which qualifies the routine name.
+fn normalize_routine_definition(definition: &str) -> String {
+    definition
+        .replacen(" FUNCTION public.", " FUNCTION ", 1)
+        .replacen(" PROCEDURE public.", " PROCEDURE ", 1)
+}
+
+/// Lay out catalog-rendered pieces as a `CREATE TABLE` followed by the table's
+/// standalone indexes. Columns are `(name, type, not null, default)`.
+fn format_native_create_table(
+    table: &str,
+    columns: &[(String, String, bool, Option<String>)],
+    constraints: &[(String, String)],
+    indexes: &[String],
+) -> String {
+    let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+    let mut lines: Vec<String> = columns
+        .iter()
+        .map(|(name, data_type, not_null, default)| {
+            let mut line = format!("    {} {}", quote(name), data_type);
+            if let Some(default) = default {
+                line.push_str(&format!(" DEFAULT {}", default));
+            }
+            if *not_null {
+                line.push_str(" NOT NULL");
+            }
+            line
+        })
+        .collect();
+    lines.extend(
+        constraints
+            .iter()
+            .map(|(name, def)| format!("    CONSTRAINT {} {}", quote(name), def)),
+    );
+
+    let mut ddl = format!("CREATE TABLE {} (\n{}\n);", quote(table), lines.join(",\n"));
+    for index in indexes {
+        ddl.push_str(&format!("\n{};", index));
+    }
+    ddl
+}
+
+/// Sequence named by a `nextval('users_id_seq'::regclass)` default, without the
+/// `public.` qualifier or identifier quotes.
+/// Split `pg_get_partkeydef` output such as `RANGE (created_at)` into the
+/// method and the key inside the parentheses.
+fn split_partition_key(key: &str) -> (String, String) {
+    match key.split_once(" (") {
+        Some((method, rest)) => (
+            method.to_string(),
+            rest.strip_suffix(')').unwrap_or(rest).to_string(),
+        ),
+        None => (key.to_string(), String::new()),
+    }
+}
+
+pub(crate) fn sequence_from_default(default: &str) -> Option<String> {
+    let name = default.strip_prefix("nextval('")?.split('\'').next()?;
+    let name = name.strip_prefix("public.").unwrap_or(name);
+    Some(name.trim_matches('"').replace("\"\"", "\""))
+}
+
 pub struct PostgresDriver {
     pool: PgPool,
 }
@@ -17,7 +157,7 @@ impl PostgresDriver {
         password: &str,
         database: &str,
     ) -> Result<Self> {
-        Self::new_with_ssl(host, port, user, password, database, None).await
+        Self::new_with_ssl(host, port, user, password, database, None, &[]).await
     }
 
     pub async fn new_with_ssl(
@@ -27,13 +167,45 @@ impl PostgresDriver {
         password: &str,
         database: &str,
         ssl_config: Option<&SslConfig>,
+        session_params: &[(String, String)],
     ) -> Result<Self> {
+        let opts = Self::connect_options(host, port, user, password, database, ssl_config);
+        let statements = crate::db::session_set_statements(session_params)?;
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .after_connect(move |conn, _meta| {
+                let statements = statements.clone();
+                Box::pin(async move {
+                    for stmt in &statements {
+                        sqlx::query(stmt).execute(&mut *conn).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .connect_with(opts)
+            .await?;
+
+        Ok(Self { pool })
+    }
+
+    /// Connect options naming this app as the `application_name`, so its
+    /// sessions are recognisable in `pg_stat_activity`. A connection's session
+    /// parameters can still set a different one.
+    fn connect_options(
+        host: &str,
+        port: u16,
+        user: &str,
+        password: &str,
+        database: &str,
+        ssl_config: Option<&SslConfig>,
+    ) -> sqlx::postgres::PgConnectOptions {
         let mut opts = sqlx::postgres::PgConnectOptions::new()
             .host(host)
             .port(port)
             .username(user)
             .password(password)
-            .database(database);
+            .database(database)
+            .application_name(crate::db::APPLICATION_NAME);
 
         if let Some(ssl) = ssl_config {
             if ssl.enabled {
@@ -47,18 +219,35 @@ impl PostgresDriver {
                 }
             }
         }
+        opts
+    }
 
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect_with(opts)
-            .await?;
-
-        Ok(Self { pool })
+    /// Wrap a pool owned by the caller, e.g. when embedding the diff engine in a
+    /// backend that already holds one. The session settings and SSL options of
+    /// `new_with_ssl` are up to whoever built the pool.
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { pool }
     }
 
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
+
+    /// A single-connection driver for `database` on the same server, with the
+    /// same credentials, SSL and session settings. Postgres can't switch the
+    /// database of an open connection, so each database needs its own.
+    pub async fn connect_to_database(&self, database: &str) -> Result<Self> {
+        let opts = (*self.pool.connect_options()).clone().database(database);
+        let pool = self
+            .pool
+            .options()
+            .clone()
+            .min_connections(0)
+            .max_connections(1)
+            .connect_with(opts)
+            .await?;
+        Ok(Self { pool })
+    }
 }
 
 #[async_trait]
@@ -68,45 +257,381 @@ impl SchemaReader for PostgresDriver {
         Ok(())
     }
 
-    async fn list_databases(&self) -> Result<Vec<String>> {
+    async fn list_databases(
+        &self,
+        name_filter: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Vec<String>> {
+        let sql = Self::list_databases_sql(name_filter.is_some(), limit.is_some());
+        let mut query = sqlx::query_as::<_, (String,)>(&sql);
+        if let Some(pattern) = name_filter {
+            query = query.bind(pattern);
+        }
+        if let Some(limit) = limit {
+            query = query.bind(i64::from(limit));
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|(name,)| name).collect())
+    }
+
+    async fn get_database_info(&self) -> Result<DatabaseInfo> {
+        let row: Option<(String, String, String)> = sqlx::query_as(
+            "SELECT datctype::text, datcollate::text, pg_encoding_to_char(encoding)::text FROM pg_database WHERE datname = current_database()"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row
+            .map(|(ctype, collate, encoding)| DatabaseInfo {
+                charset: Some(ctype),
+                collation: Some(collate),
+                encoding: Some(encoding),
+            })
+            .unwrap_or_default())
+    }
+
+    async fn estimated_row_count(&self, table: &str) -> Result<Option<u64>> {
+        // reltuples is -1 until the table is first vacuumed or analyzed.
+        let row: Option<(i64,)> = sqlx::query_as(
+            "SELECT reltuples::bigint FROM pg_class WHERE relnamespace = 'public'::regnamespace AND relname = $1 AND relkind IN ('r', 'p')",
+        )
+        .bind(table)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.and_then(|(rows,)| u64::try_from(rows).ok()))
+    }
+
+    async fn server_version(&self) -> Result<String> {
+        let (version,): (String,) = sqlx::query_as("SELECT current_setting('server_version')")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(version)
+    }
+
+    async fn get_tables(&self) -> Result<Vec<TableSchema>> {
+        self.read_tables(None).await
+    }
+
+    async fn get_tables_named(&self, names: &[String]) -> Result<Vec<TableSchema>> {
+        if names.is_empty() {
+            return Ok(vec![]);
+        }
+        self.read_tables(Some(names)).await
+    }
+
+    async fn list_tables(&self) -> Result<Vec<String>> {
         let rows: Vec<(String,)> = sqlx::query_as(
-            "SELECT datname FROM pg_database WHERE datistemplate = false AND datname NOT IN ('postgres') ORDER BY datname"
+            r#"
+            SELECT table_name::text FROM information_schema.tables t
+            WHERE table_schema = 'public' AND table_type = 'BASE TABLE'
+                AND NOT EXISTS (
+                    SELECT 1 FROM pg_class c
+                    WHERE c.relnamespace = 'public'::regnamespace
+                        AND c.relname = t.table_name AND c.relispartition
+                )
+            ORDER BY table_name
+            "#,
         )
         .fetch_all(&self.pool)
         .await?;
         Ok(rows.into_iter().map(|(name,)| name).collect())
     }
 
-    async fn get_tables(&self) -> Result<Vec<TableSchema>> {
-        let table_names: Vec<(String,)> = sqlx::query_as(
-            "SELECT table_name FROM information_schema.tables WHERE table_schema = 'public' AND table_type = 'BASE TABLE'"
+    async fn get_routines(&self) -> Result<Vec<Routine>> {
+        // Functions installed by extensions belong to the extension, not the schema.
+        let rows: Vec<(String, String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT p.proname || '(' || pg_get_function_identity_arguments(p.oid) || ')',
+                   p.prokind::text, l.lanname::text, pg_get_functiondef(p.oid)
+            FROM pg_proc p
+            JOIN pg_language l ON l.oid = p.prolang
+            WHERE p.pronamespace = 'public'::regnamespace
+              AND p.prokind IN ('f', 'p')
+              AND NOT EXISTS (
+                  SELECT 1 FROM pg_depend d
+                  WHERE d.classid = 'pg_proc'::regclass AND d.objid = p.oid AND d.deptype = 'e'
+              )
+            ORDER BY 1
+            "#,
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let table_names: Vec<String> = table_names.into_iter().map(|(n,)| n).collect();
-        let columns = self.fetch_all_columns().await?;
-        let pks = self.fetch_all_primary_keys().await?;
-        let indexes = self.fetch_all_indexes().await?;
-        let fks = self.fetch_all_foreign_keys().await?;
-        let ucs = self.fetch_all_unique_constraints().await?;
-        Ok(crate::db::assemble_schemas(
-            table_names,
-            columns,
-            pks,
-            indexes,
-            fks,
-            ucs,
+        Ok(rows
+            .into_iter()
+            .map(|(name, prokind, language, definition)| Routine {
+                name,
+                kind: if prokind == "p" {
+                    RoutineKind::Procedure
+                } else {
+                    RoutineKind::Function
+                },
+                definition: normalize_routine_definition(&definition),
+                language,
+            })
+            .collect())
+    }
+
+    async fn get_grants(&self) -> Result<Vec<Grant>> {
+        // An owner's own privileges are implicit, not something to reconcile.
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT g.grantee::text, g.privilege_type::text, g.table_name::text
+            FROM information_schema.role_table_grants g
+            JOIN pg_class c
+                ON c.relname = g.table_name AND c.relnamespace = 'public'::regnamespace
+            WHERE g.table_schema = 'public'
+              AND g.grantee <> pg_get_userbyid(c.relowner)
+            ORDER BY 1, 2, 3
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(grantee, privilege, object)| Grant {
+                grantee,
+                privilege,
+                object,
+            })
+            .collect())
+    }
+
+    fn take_read_warnings(&self) -> Vec<String> {
+        vec![]
+    }
+
+    async fn list_schema_objects(&self) -> Result<SchemaObjects> {
+        let names = |sql: &'static str| async move {
+            let rows: Vec<(String,)> = sqlx::query_as(sql).fetch_all(&self.pool).await?;
+            Ok::<_, anyhow::Error>(rows.into_iter().map(|(name,)| name).collect())
+        };
+        Ok(SchemaObjects {
+            views: names(
+                "SELECT table_name::text FROM information_schema.views WHERE table_schema = 'public' ORDER BY table_name",
+            )
+            .await?,
+            // Serial and identity sequences depend on their column ('a' / 'i').
+            sequences: names(
+                r#"
+                SELECT c.relname::text FROM pg_class c
+                WHERE c.relnamespace = 'public'::regnamespace AND c.relkind = 'S'
+                    AND NOT EXISTS (
+                        SELECT 1 FROM pg_depend d
+                        WHERE d.objid = c.oid AND d.classid = 'pg_class'::regclass
+                            AND d.deptype IN ('a', 'i')
+                    )
+                ORDER BY c.relname
+                "#,
+            )
+            .await?,
+            // Types installed by extensions belong to the extension.
+            enum_types: names(
+                r#"
+                SELECT t.typname::text FROM pg_type t
+                WHERE t.typnamespace = 'public'::regnamespace AND t.typtype = 'e'
+                    AND NOT EXISTS (
+                        SELECT 1 FROM pg_depend d
+                        WHERE d.objid = t.oid AND d.deptype = 'e'
+                    )
+                ORDER BY t.typname
+                "#,
+            )
+            .await?,
+        })
+    }
+
+    async fn native_create_table(&self, table: &str) -> Result<String> {
+        // Postgres has no SHOW CREATE TABLE; rebuild it from the catalog's own
+        // renderings (format_type, pg_get_expr, pg_get_constraintdef,
+        // pg_get_indexdef), as pg_dump does.
+        let columns: Vec<(String, String, bool, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT a.attname::text, format_type(a.atttypid, a.atttypmod), a.attnotnull,
+                   pg_get_expr(d.adbin, d.adrelid)
+            FROM pg_attribute a
+            LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+            WHERE a.attrelid = ('public.' || quote_ident($1))::regclass
+              AND a.attnum > 0 AND NOT a.attisdropped
+            ORDER BY a.attnum
+            "#,
+        )
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await?;
+        let constraints: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT conname::text, pg_get_constraintdef(oid)
+            FROM pg_constraint
+            WHERE conrelid = ('public.' || quote_ident($1))::regclass
+            ORDER BY CASE contype WHEN 'p' THEN 0 WHEN 'u' THEN 1 WHEN 'c' THEN 2 ELSE 3 END, conname
+            "#,
+        )
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await?;
+        // Indexes backing a constraint are already covered above.
+        let indexes: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT pg_get_indexdef(i.indexrelid)
+            FROM pg_index i
+            WHERE i.indrelid = ('public.' || quote_ident($1))::regclass
+              AND NOT EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conindid = i.indexrelid)
+            ORDER BY 1
+            "#,
+        )
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(format_native_create_table(
+            table,
+            &columns,
+            &constraints,
+            &indexes
+                .iter()
+                .map(|(def,)| normalize_index_definition(def))
+                .collect::<Vec<_>>(),
         ))
     }
 }
 
+#[async_trait]
+impl SqlExecutor for PostgresDriver {
+    async fn execute_sql(&self, sql: &str) -> Result<()> {
+        for stmt in split_statements(sql) {
+            let full = format!("{};", stmt);
+            debug!("Executing: {}", full);
+            // Sent as plain text so scripts behave the same as on MySQL.
+            sqlx::raw_sql(&full)
+                .execute(&self.pool)
+                .await
+                .map_err(crate::db::statement_timeout_error)?;
+        }
+        Ok(())
+    }
+
+    async fn execute_in_transaction(&self, statements: &[String]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let split: Vec<String> = statements
+            .iter()
+            .flat_map(|sql| split_statements(sql))
+            .map(|stmt| format!("{};", stmt))
+            .collect();
+        for full in &split {
+            debug!("Executing in transaction: {}", full);
+            // Through the connection rather than `RawSql::execute`, whose
+            // generic executor bound trips the `Send` check on a borrowed transaction.
+            sqlx::Executor::execute(&mut *tx, sqlx::raw_sql(full))
+                .await
+                .map_err(crate::db::statement_timeout_error)?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
 impl PostgresDriver {
-    async fn fetch_all_columns(&self) -> Result<Vec<crate::db::ColumnRow>> {
-        // Use udt_name for USER-DEFINED (enum) and ARRAY types to get the real type name.
-        // For arrays, udt_name starts with '_' (e.g., '_int4' for integer[]).
-        // For enums, data_type = 'USER-DEFINED' and udt_name = the enum type name.
-        let rows: Vec<(String, String, String, String, String, Option<String>, i32)> =
+    /// Read every table in the schema, or only those named in `tables`, all within
+    /// one repeatable-read snapshot so DDL running meanwhile can't leave the tables
+    /// and their columns or indexes out of step.
+    async fn read_tables(&self, tables: Option<&[String]>) -> Result<Vec<TableSchema>> {
+        debug!("Reading Postgres metadata for {}", describe_tables(tables));
+        let mut conn = self.pool.acquire().await?;
+        read_in_snapshot::<Postgres, _>(
+            &mut conn,
+            "START TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY",
+            async |conn| self.read_tables_in(conn, tables).await,
+        )
+        .await
+    }
+
+    /// The reads behind `read_tables`; each query takes the names as `$1`
+    /// (NULL for all tables).
+    async fn read_tables_in(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<TableSchema>> {
+        // pg_tables.tablespace is NULL when the table uses the database default.
+        // Partitions are read as part of their parent's partitioning.
+        let table_rows: Vec<(String, Option<String>, bool)> = sqlx::query_as(
+            r#"
+            SELECT t.table_name::text, pt.tablespace::text, COALESCE(pt.rowsecurity, false)
+            FROM information_schema.tables t
+            LEFT JOIN pg_tables pt ON pt.schemaname = t.table_schema AND pt.tablename = t.table_name
+            WHERE t.table_schema = 'public' AND t.table_type = 'BASE TABLE'
+                AND ($1::text[] IS NULL OR t.table_name::text = ANY($1))
+                AND NOT EXISTS (
+                    SELECT 1 FROM pg_class c
+                    WHERE c.relnamespace = 'public'::regnamespace
+                        AND c.relname = t.table_name AND c.relispartition
+                )
+            "#,
+        )
+        .bind(tables)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let row_security: HashSet<String> = table_rows
+            .iter()
+            .filter(|(_, _, enabled)| *enabled)
+            .map(|(name, _, _)| name.clone())
+            .collect();
+        let table_rows = table_rows
+            .into_iter()
+            .map(|(table_name, tablespace, _)| crate::db::TableRow {
+                table_name,
+                tablespace,
+                options: TableOptions::default(),
+            })
+            .collect();
+        let columns = self.fetch_all_columns(conn, tables).await?;
+        let pks = self.fetch_all_primary_keys(conn, tables).await?;
+        let indexes = self.fetch_all_indexes(conn, tables).await?;
+        let fks = self.fetch_all_foreign_keys(conn, tables).await?;
+        let ucs = self.fetch_all_unique_constraints(conn, tables).await?;
+        let exclusions = self.fetch_all_exclusion_constraints(conn, tables).await?;
+        let mut policies = self.fetch_all_policies(conn, tables).await?;
+        let mut partitioning = self.fetch_all_partitioning(conn, tables).await?;
+        let mut checks = self.fetch_all_check_constraints(conn, tables).await?;
+        let mut inherits = self.fetch_all_inherits(conn, tables).await?;
+        let mut schemas =
+            crate::db::assemble_schemas(table_rows, columns, pks, indexes, fks, ucs, exclusions);
+        for table in &mut schemas {
+            table.row_security = row_security.contains(&table.name);
+            table.policies = policies.remove(&table.name).unwrap_or_default();
+            table.partitioning = partitioning.remove(&table.name);
+            table.check_constraints = checks.remove(&table.name).unwrap_or_default();
+            table.inherits = inherits.remove(&table.name).unwrap_or_default();
+        }
+        Ok(schemas)
+    }
+
+    fn list_databases_sql(with_filter: bool, with_limit: bool) -> String {
+        let mut sql = "SELECT datname FROM pg_database WHERE datistemplate = false AND datname NOT IN ('postgres')".to_string();
+        let mut param = 0;
+        if with_filter {
+            param += 1;
+            sql.push_str(&format!(" AND datname LIKE ${}", param));
+        }
+        sql.push_str(" ORDER BY datname");
+        if with_limit {
+            param += 1;
+            sql.push_str(&format!(" LIMIT ${}", param));
+        }
+        sql
+    }
+
+    async fn fetch_all_columns(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::ColumnRow>> {
+        // information_schema reports arrays as just 'ARRAY'; format_type renders the
+        // element type with its modifiers (e.g. 'character varying(50)[]'). For
+        // enums, data_type = 'USER-DEFINED' and udt_name = the enum type name.
+        let rows: Vec<ColumnQueryRow> =
             sqlx::query_as(
                 r#"
             SELECT
@@ -116,43 +641,49 @@ impl PostgresDriver {
                     WHEN data_type = 'character varying' THEN 'varchar(' || character_maximum_length || ')'
                     WHEN data_type = 'character' THEN 'char(' || character_maximum_length || ')'
                     WHEN data_type = 'numeric' THEN 'numeric(' || numeric_precision || ',' || numeric_scale || ')'
-                    WHEN data_type = 'ARRAY' THEN udt_name
+                    WHEN data_type = 'ARRAY' THEN (
+                        SELECT format_type(a.atttypid, a.atttypmod)
+                        FROM pg_attribute a
+                        WHERE a.attrelid = format('%I.%I', table_schema, table_name)::regclass
+                            AND a.attname = column_name
+                    )
                     WHEN data_type = 'USER-DEFINED' THEN udt_name
                     ELSE data_type
                 END as data_type,
                 udt_name,
                 is_nullable,
                 column_default,
-                ordinal_position
+                ordinal_position,
+                CASE WHEN is_generated = 'ALWAYS' THEN generation_expression::text END
             FROM information_schema.columns
             WHERE table_schema = 'public'
+                AND ($1::text[] IS NULL OR table_name::text = ANY($1))
             ORDER BY table_name, ordinal_position
             "#,
             )
-            .fetch_all(&self.pool)
+            .bind(tables)
+            .fetch_all(&mut *conn)
             .await?;
 
         // Fetch enum values for all user-defined enum types in public schema
-        let enum_values = self.fetch_enum_values().await?;
+        let enum_values = self.fetch_enum_values(conn).await?;
 
         Ok(rows
             .into_iter()
             .map(
-                |(table_name, name, data_type, udt_name, nullable, default, pos)| {
+                |(table_name, name, data_type, udt_name, nullable, default, pos, generation)| {
+                    let sequence = default.as_deref().and_then(sequence_from_default);
                     let auto_increment = default
                         .as_ref()
                         .map(|d| d.starts_with("nextval("))
                         .unwrap_or(false);
 
                     // Resolve the final data_type:
-                    // - Arrays: udt_name starts with '_', convert to element_type[]
+                    // - Arrays: udt_name starts with '_', spell the element type as scalars are
                     // - Enums: if we have enum values, format as enum('a','b','c')
                     // - Otherwise: use the CASE result as-is
                     let resolved_type = if udt_name.starts_with('_') {
-                        // Array type: strip leading '_' and map to base type + '[]'
-                        let element_udt = &udt_name[1..];
-                        let element_type = Self::udt_to_sql_type(element_udt);
-                        format!("{}[]", element_type)
+                        array_type(&data_type)
                     } else if let Some(values) = enum_values.get(&udt_name) {
                         // User-defined enum: format as enum('val1','val2',...)
                         let vals = values
@@ -174,6 +705,16 @@ impl PostgresDriver {
                         auto_increment,
                         comment: None,
                         ordinal_position: pos as u32,
+                        visible: true,
+                        on_update: None,
+                        sequence,
+                        // Postgres only has stored generated columns.
+                        generated: generation.map(|expression| GeneratedColumn {
+                            expression,
+                            storage: GeneratedStorage::Stored,
+                        }),
+                        srid: None,
+                        octet_length: None,
                     }
                 },
             )
@@ -181,7 +722,10 @@ impl PostgresDriver {
     }
 
     /// Fetch all enum type values from pg_enum for types in the public schema.
-    async fn fetch_enum_values(&self) -> Result<std::collections::HashMap<String, Vec<String>>> {
+    async fn fetch_enum_values(
+        &self,
+        conn: &mut PgConnection,
+    ) -> Result<std::collections::HashMap<String, Vec<String>>> {
         let rows: Vec<(String, String)> = sqlx::query_as(
             r#"
             SELECT t.typname, e.enumlabel
@@ -192,7 +736,7 @@ impl PostgresDriver {
             ORDER BY t.typname, e.enumsortorder
             "#,
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         let mut map: std::collections::HashMap<String, Vec<String>> =
@@ -203,32 +747,11 @@ impl PostgresDriver {
         Ok(map)
     }
 
-    /// Map PostgreSQL udt_name (internal name) to SQL type string.
-    fn udt_to_sql_type(udt_name: &str) -> &str {
-        match udt_name {
-            "int2" => "smallint",
-            "int4" => "integer",
-            "int8" => "bigint",
-            "float4" => "real",
-            "float8" => "double precision",
-            "bool" => "boolean",
-            "varchar" => "character varying",
-            "bpchar" => "character",
-            "text" => "text",
-            "bytea" => "bytea",
-            "numeric" => "numeric",
-            "date" => "date",
-            "time" | "timetz" => "time",
-            "timestamp" | "timestamptz" => "timestamp",
-            "json" => "json",
-            "jsonb" => "jsonb",
-            "uuid" => "uuid",
-            "inet" => "inet",
-            other => other,
-        }
-    }
-
-    async fn fetch_all_primary_keys(&self) -> Result<Vec<crate::db::PkRow>> {
+    async fn fetch_all_primary_keys(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::PkRow>> {
         let rows: Vec<(String, String, String)> = sqlx::query_as(
             r#"
             SELECT tc.table_name, tc.constraint_name, kcu.column_name
@@ -236,10 +759,12 @@ impl PostgresDriver {
             JOIN information_schema.key_column_usage kcu
                 ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema
             WHERE tc.table_schema = 'public' AND tc.constraint_type = 'PRIMARY KEY'
+                AND ($1::text[] IS NULL OR tc.table_name::text = ANY($1))
             ORDER BY tc.table_name, kcu.ordinal_position
             "#,
         )
-        .fetch_all(&self.pool)
+        .bind(tables)
+        .fetch_all(&mut *conn)
         .await?;
 
         Ok(rows
@@ -254,50 +779,149 @@ impl PostgresDriver {
             .collect())
     }
 
-    async fn fetch_all_indexes(&self) -> Result<Vec<crate::db::IndexRow>> {
-        let rows: Vec<(String, String, bool, String, String)> = sqlx::query_as(
+    /// Secondary indexes. Indexes backing a unique or exclusion constraint share
+    /// its name and are read as the constraint only; standalone
+    /// `CREATE UNIQUE INDEX` indexes are kept.
+    async fn fetch_all_indexes(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::IndexRow>> {
+        let rows: Vec<IndexQueryRow> = sqlx::query_as(
             r#"
             SELECT
                 t.relname as table_name,
                 i.relname as index_name,
                 ix.indisunique as is_unique,
                 a.attname as column_name,
-                am.amname as index_type
+                am.amname as index_type,
+                ts.spcname as tablespace,
+                COALESCE(i.reloptions, '{}') as storage_params,
+                array_position(ix.indkey, a.attnum) as seq_in_index
             FROM pg_index ix
             JOIN pg_class t ON t.oid = ix.indrelid
             JOIN pg_class i ON i.oid = ix.indexrelid
             JOIN pg_am am ON i.relam = am.oid
             JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = ANY(ix.indkey)
+            LEFT JOIN pg_tablespace ts ON ts.oid = i.reltablespace
             WHERE t.relnamespace = 'public'::regnamespace
+                AND ($1::text[] IS NULL OR t.relname::text = ANY($1))
                 AND NOT ix.indisprimary
                 AND NOT EXISTS (
                     SELECT 1 FROM pg_constraint c
-                    WHERE c.conindid = ix.indexrelid AND c.contype = 'u'
+                    WHERE c.conindid = ix.indexrelid AND c.contype IN ('u', 'x')
+                )
+                AND ix.indexprs IS NULL AND ix.indpred IS NULL
+                AND NOT (0 <> ANY(ix.indoption::int2[]))
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM unnest(ix.indkey::int2[], ix.indcollation::oid[]) AS k(attnum, collation)
+                    JOIN pg_attribute ka ON ka.attrelid = ix.indrelid AND ka.attnum = k.attnum
+                    WHERE k.collation <> ka.attcollation
                 )
-            ORDER BY t.relname, i.relname, array_position(ix.indkey, a.attnum)
+            ORDER BY t.relname, i.relname, seq_in_index
             "#,
         )
-        .fetch_all(&self.pool)
+        .bind(tables)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        // Indexes a column list can't describe are kept as their full definition:
+        // expression keys, partial indexes, DESC / NULLS FIRST / NULLS LAST ordering
+        // (non-zero indoption) and a COLLATE other than the column's own.
+        let definition_rows: Vec<DefinitionIndexQueryRow> = sqlx::query_as(
+            r#"
+            SELECT
+                t.relname as table_name,
+                i.relname as index_name,
+                ix.indisunique as is_unique,
+                am.amname as index_type,
+                ts.spcname as tablespace,
+                pg_get_indexdef(ix.indexrelid) as definition
+            FROM pg_index ix
+            JOIN pg_class t ON t.oid = ix.indrelid
+            JOIN pg_class i ON i.oid = ix.indexrelid
+            JOIN pg_am am ON i.relam = am.oid
+            LEFT JOIN pg_tablespace ts ON ts.oid = i.reltablespace
+            WHERE t.relnamespace = 'public'::regnamespace
+                AND ($1::text[] IS NULL OR t.relname::text = ANY($1))
+                AND NOT ix.indisprimary
+                AND NOT EXISTS (
+                    SELECT 1 FROM pg_constraint c
+                    WHERE c.conindid = ix.indexrelid AND c.contype IN ('u', 'x')
+                )
+                AND (ix.indexprs IS NOT NULL OR ix.indpred IS NOT NULL
+                    OR 0 <> ANY(ix.indoption::int2[])
+                    OR EXISTS (
+                        SELECT 1
+                        FROM unnest(ix.indkey::int2[], ix.indcollation::oid[]) AS k(attnum, collation)
+                        JOIN pg_attribute ka ON ka.attrelid = ix.indrelid AND ka.attnum = k.attnum
+                        WHERE k.collation <> ka.attcollation
+                    ))
+            ORDER BY t.relname, i.relname
+            "#,
+        )
+        .bind(tables)
+        .fetch_all(&mut *conn)
         .await?;
 
+        let definition_indexes = definition_rows.into_iter().map(
+            |(table_name, index_name, is_unique, index_type, tablespace, definition)| {
+                crate::db::IndexRow {
+                    table_name,
+                    index_name,
+                    column_name: String::new(),
+                    is_unique,
+                    index_type,
+                    tablespace,
+                    visible: true,
+                    seq_in_index: 0,
+                    expression: Some(normalize_index_definition(&definition)),
+                    // Already part of the definition's WITH clause.
+                    storage_params: vec![],
+                    comment: None,
+                }
+            },
+        );
+
         Ok(rows
             .into_iter()
             .map(
-                |(table_name, index_name, is_unique, column_name, index_type)| {
+                |(
+                    table_name,
+                    index_name,
+                    is_unique,
+                    column_name,
+                    index_type,
+                    tablespace,
+                    storage_params,
+                    seq,
+                )| {
                     crate::db::IndexRow {
                         table_name,
                         index_name,
                         column_name,
                         is_unique,
                         index_type,
+                        tablespace,
+                        visible: true,
+                        seq_in_index: seq as u32,
+                        expression: None,
+                        storage_params,
+                        comment: None,
                     }
                 },
             )
+            .chain(definition_indexes)
             .collect())
     }
 
-    async fn fetch_all_foreign_keys(&self) -> Result<Vec<crate::db::FkRow>> {
-        let rows: Vec<(String, String, String, String, String, String, String)> = sqlx::query_as(
+    async fn fetch_all_foreign_keys(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::FkRow>> {
+        let rows: Vec<ForeignKeyQueryRow> = sqlx::query_as(
             r#"
             SELECT
                 tc.table_name,
@@ -306,16 +930,21 @@ impl PostgresDriver {
                 ccu.table_name AS ref_table,
                 ccu.column_name AS ref_column,
                 rc.delete_rule,
-                rc.update_rule
+                rc.update_rule,
+                tc.is_deferrable = 'YES',
+                tc.initially_deferred = 'YES',
+                rc.match_option
             FROM information_schema.table_constraints tc
             JOIN information_schema.key_column_usage kcu ON tc.constraint_name = kcu.constraint_name
             JOIN information_schema.constraint_column_usage ccu ON tc.constraint_name = ccu.constraint_name
             JOIN information_schema.referential_constraints rc ON tc.constraint_name = rc.constraint_name
             WHERE tc.table_schema = 'public' AND tc.constraint_type = 'FOREIGN KEY'
+                AND ($1::text[] IS NULL OR tc.table_name::text = ANY($1))
             ORDER BY tc.table_name, tc.constraint_name, kcu.ordinal_position
             "#
         )
-        .fetch_all(&self.pool)
+        .bind(tables)
+        .fetch_all(&mut *conn)
         .await?;
 
         Ok(rows
@@ -329,6 +958,9 @@ impl PostgresDriver {
                     ref_column,
                     on_delete,
                     on_update,
+                    deferrable,
+                    initially_deferred,
+                    match_option,
                 )| {
                     crate::db::FkRow {
                         table_name,
@@ -338,23 +970,33 @@ impl PostgresDriver {
                         ref_column,
                         on_delete,
                         on_update,
+                        deferrable,
+                        initially_deferred,
+                        // information_schema reports the default SIMPLE as NONE.
+                        match_type: Some(match_option).filter(|m| m != "NONE"),
                     }
                 },
             )
             .collect())
     }
 
-    async fn fetch_all_unique_constraints(&self) -> Result<Vec<crate::db::UcRow>> {
+    async fn fetch_all_unique_constraints(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::UcRow>> {
         let rows: Vec<(String, String, String)> = sqlx::query_as(
             r#"
             SELECT tc.table_name, tc.constraint_name, kcu.column_name
             FROM information_schema.table_constraints tc
             JOIN information_schema.key_column_usage kcu ON tc.constraint_name = kcu.constraint_name
             WHERE tc.table_schema = 'public' AND tc.constraint_type = 'UNIQUE'
+                AND ($1::text[] IS NULL OR tc.table_name::text = ANY($1))
             ORDER BY tc.table_name, tc.constraint_name, kcu.ordinal_position
             "#,
         )
-        .fetch_all(&self.pool)
+        .bind(tables)
+        .fetch_all(&mut *conn)
         .await?;
 
         Ok(rows
@@ -368,4 +1010,302 @@ impl PostgresDriver {
             )
             .collect())
     }
+
+    /// Parent tables by child table, in `INHERITS` order. Partitions are also in
+    /// `pg_inherits` but belong to their parent's partitioning.
+    async fn fetch_all_inherits(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<HashMap<String, Vec<String>>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT c.relname::text, p.relname::text
+            FROM pg_inherits i
+            JOIN pg_class c ON c.oid = i.inhrelid
+            JOIN pg_class p ON p.oid = i.inhparent
+            WHERE c.relnamespace = 'public'::regnamespace AND NOT c.relispartition
+                AND ($1::text[] IS NULL OR c.relname::text = ANY($1))
+            ORDER BY c.relname, i.inhseqno
+            "#,
+        )
+        .bind(tables)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut inherits: HashMap<String, Vec<String>> = HashMap::new();
+        for (table, parent) in rows {
+            inherits.entry(table).or_default().push(parent);
+        }
+        Ok(inherits)
+    }
+
+    /// Row-level security policies by table, each table's sorted by name.
+    async fn fetch_all_policies(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<HashMap<String, Vec<Policy>>> {
+        let rows: Vec<PolicyQueryRow> = sqlx::query_as(
+            r#"
+            SELECT tablename::text, policyname::text, cmd, permissive = 'PERMISSIVE',
+                roles::text[], qual, with_check
+            FROM pg_policies
+            WHERE schemaname = 'public'
+                AND ($1::text[] IS NULL OR tablename::text = ANY($1))
+            ORDER BY tablename, policyname
+            "#,
+        )
+        .bind(tables)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut policies: HashMap<String, Vec<Policy>> = HashMap::new();
+        for (table, name, command, permissive, roles, using_expr, check_expr) in rows {
+            policies.entry(table.clone()).or_default().push(Policy {
+                name,
+                table,
+                command,
+                permissive,
+                roles,
+                using_expr,
+                check_expr,
+            });
+        }
+        Ok(policies)
+    }
+
+    /// Partitioning of each partitioned table, with its partitions sorted by name.
+    async fn fetch_all_partitioning(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<HashMap<String, PartitionSpec>> {
+        let rows: Vec<(String, String, Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT c.relname::text, pg_get_partkeydef(c.oid), p.relname::text,
+                pg_get_expr(p.relpartbound, p.oid)
+            FROM pg_partitioned_table pt
+            JOIN pg_class c ON c.oid = pt.partrelid
+            LEFT JOIN pg_inherits i ON i.inhparent = c.oid
+            LEFT JOIN pg_class p ON p.oid = i.inhrelid
+            WHERE c.relnamespace = 'public'::regnamespace
+                AND ($1::text[] IS NULL OR c.relname::text = ANY($1))
+            ORDER BY c.relname, p.relname
+            "#,
+        )
+        .bind(tables)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut specs: HashMap<String, PartitionSpec> = HashMap::new();
+        for (table, key, partition, bound) in rows {
+            let spec = specs.entry(table).or_insert_with(|| {
+                let (method, expression) = split_partition_key(&key);
+                PartitionSpec {
+                    method,
+                    expression,
+                    partitions: vec![],
+                }
+            });
+            if let Some(name) = partition {
+                spec.partitions.push(Partition {
+                    name,
+                    bound: bound.unwrap_or_default(),
+                });
+            }
+        }
+        Ok(specs)
+    }
+
+    /// Check constraints by table, each table's sorted by name. Postgres
+    /// always enforces them.
+    async fn fetch_all_check_constraints(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<HashMap<String, Vec<CheckConstraint>>> {
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT rel.relname::text, con.conname::text, pg_get_expr(con.conbin, con.conrelid)
+            FROM pg_constraint con
+            JOIN pg_class rel ON rel.oid = con.conrelid
+            JOIN pg_namespace ns ON ns.oid = rel.relnamespace
+            WHERE ns.nspname = 'public' AND con.contype = 'c'
+                AND ($1::text[] IS NULL OR rel.relname::text = ANY($1))
+            ORDER BY rel.relname, con.conname
+            "#,
+        )
+        .bind(tables)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut checks: HashMap<String, Vec<CheckConstraint>> = HashMap::new();
+        for (table, name, expression) in rows {
+            checks.entry(table).or_default().push(CheckConstraint {
+                name,
+                expression,
+                enforced: true,
+            });
+        }
+        Ok(checks)
+    }
+
+    async fn fetch_all_exclusion_constraints(
+        &self,
+        conn: &mut PgConnection,
+        tables: Option<&[String]>,
+    ) -> Result<Vec<crate::db::ExclusionRow>> {
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT rel.relname::text, con.conname::text, pg_get_constraintdef(con.oid)
+            FROM pg_constraint con
+            JOIN pg_class rel ON rel.oid = con.conrelid
+            JOIN pg_namespace ns ON ns.oid = rel.relnamespace
+            WHERE ns.nspname = 'public' AND con.contype = 'x'
+                AND ($1::text[] IS NULL OR rel.relname::text = ANY($1))
+            ORDER BY rel.relname, con.conname
+            "#,
+        )
+        .bind(tables)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(table_name, constraint_name, definition)| crate::db::ExclusionRow {
+                    table_name,
+                    constraint_name,
+                    definition,
+                },
+            )
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connect_options_name_the_application() {
+        let opts =
+            PostgresDriver::connect_options("localhost", 5432, "app", "secret", "shop", None);
+        assert_eq!(opts.get_application_name(), Some("database-structure-sync"));
+    }
+
+    #[tokio::test]
+    async fn from_pool_shares_the_callers_pool() {
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://app@localhost/app")
+            .unwrap();
+        let driver = PostgresDriver::from_pool(pool.clone());
+
+        pool.close().await;
+        assert!(driver.pool().is_closed());
+    }
+
+    #[test]
+    fn array_types_keep_element_modifiers() {
+        assert_eq!(array_type("integer[]"), "integer[]");
+        assert_eq!(array_type("character varying(50)[]"), "varchar(50)[]");
+        assert_eq!(array_type("character(2)[]"), "char(2)[]");
+        assert_eq!(
+            array_type("timestamp with time zone[]"),
+            "timestamp with time zone[]"
+        );
+    }
+
+    #[test]
+    fn index_definition_drops_public_schema() {
+        assert_eq!(
+            normalize_index_definition(
+                "CREATE INDEX idx_lower_email ON public.users USING btree (lower((email)::text))"
+            ),
+            "CREATE INDEX idx_lower_email ON users USING btree (lower((email)::text))"
+        );
+    }
+
+    #[test]
+    fn native_create_table_layout() {
+        let ddl = format_native_create_table(
+            "users",
+            &[
+                (
+                    "id".to_string(),
+                    "integer".to_string(),
+                    true,
+                    Some("nextval('users_id_seq'::regclass)".to_string()),
+                ),
+                (
+                    "email".to_string(),
+                    "character varying(255)".to_string(),
+                    false,
+                    None,
+                ),
+            ],
+            &[("users_pkey".to_string(), "PRIMARY KEY (id)".to_string())],
+            &["CREATE INDEX idx_email ON users USING btree (email)".to_string()],
+        );
+        assert_eq!(
+            ddl,
+            "CREATE TABLE \"users\" (\n    \"id\" integer DEFAULT nextval('users_id_seq'::regclass) NOT NULL,\n    \"email\" character varying(255),\n    CONSTRAINT \"users_pkey\" PRIMARY KEY (id)\n);\nCREATE INDEX idx_email ON users USING btree (email);"
+        );
+    }
+
+    #[test]
+    fn sequence_from_nextval_default() {
+        assert_eq!(
+            sequence_from_default("nextval('users_id_seq'::regclass)").as_deref(),
+            Some("users_id_seq")
+        );
+        assert_eq!(
+            sequence_from_default("nextval('public.\"Order_Seq\"'::regclass)").as_deref(),
+            Some("Order_Seq")
+        );
+        assert_eq!(sequence_from_default("now()"), None);
+    }
+
+    #[test]
+    fn partition_key_split_into_method_and_key() {
+        assert_eq!(
+            split_partition_key("RANGE (created_at)"),
+            ("RANGE".to_string(), "created_at".to_string())
+        );
+        assert_eq!(
+            split_partition_key("LIST (lower((region)::text))"),
+            ("LIST".to_string(), "lower((region)::text)".to_string())
+        );
+    }
+
+    #[test]
+    fn routine_definition_drops_public_schema() {
+        assert_eq!(
+            normalize_routine_definition(
+                "CREATE OR REPLACE FUNCTION public.add(a integer, b integer)\n RETURNS integer"
+            ),
+            "CREATE OR REPLACE FUNCTION add(a integer, b integer)\n RETURNS integer"
+        );
+    }
+
+    #[test]
+    fn list_databases_sql_unfiltered_by_default() {
+        let sql = PostgresDriver::list_databases_sql(false, false);
+        assert!(!sql.contains("LIKE"));
+        assert!(!sql.contains("LIMIT"));
+        assert!(sql.ends_with("ORDER BY datname"));
+    }
+
+    #[test]
+    fn list_databases_sql_pushes_filter_and_limit_into_query() {
+        let sql = PostgresDriver::list_databases_sql(true, true);
+        assert!(sql.contains("AND datname LIKE $1 ORDER BY datname LIMIT $2"));
+    }
+
+    #[test]
+    fn list_databases_sql_limit_only_uses_first_placeholder() {
+        let sql = PostgresDriver::list_databases_sql(false, true);
+        assert!(sql.ends_with("ORDER BY datname LIMIT $1"));
+    }
 }
@@ -0,0 +1,96 @@
+use crate::db::{MySqlSqlGenerator, PostgresSqlGenerator, SqlGenerator};
+use crate::models::DbType;
+
+/// Builds a fresh generator for one dialect.
+pub type GeneratorFactory = Box<dyn Fn() -> Box<dyn SqlGenerator> + Send + Sync>;
+
+/// The SQL generator to use for each `DbType`, so a new dialect registers its
+/// generator once instead of being matched on wherever SQL is generated.
+pub struct GeneratorRegistry {
+    factories: Vec<(DbType, GeneratorFactory)>,
+}
+
+impl GeneratorRegistry {
+    /// A registry with no dialects; `Default` has the built-in ones.
+    pub fn empty() -> Self {
+        GeneratorRegistry {
+            factories: Vec::new(),
+        }
+    }
+
+    /// Use `factory` for `db_type`, replacing any generator registered before.
+    pub fn register<F>(&mut self, db_type: DbType, factory: F)
+    where
+        F: Fn() -> Box<dyn SqlGenerator> + Send + Sync + 'static,
+    {
+        self.factories
+            .retain(|(registered, _)| *registered != db_type);
+        self.factories.push((db_type, Box::new(factory)));
+    }
+
+    /// A generator for `db_type`, or `None` when no dialect registered it.
+    pub fn generator(&self, db_type: &DbType) -> Option<Box<dyn SqlGenerator>> {
+        self.factories
+            .iter()
+            .find(|(registered, _)| registered == db_type)
+            .map(|(_, factory)| factory())
+    }
+}
+
+impl Default for GeneratorRegistry {
+    /// MySQL and MariaDB share the MySQL generator.
+    fn default() -> Self {
+        let mut registry = GeneratorRegistry::empty();
+        registry.register(DbType::MySQL, || Box::new(MySqlSqlGenerator));
+        registry.register(DbType::MariaDB, || Box::new(MySqlSqlGenerator));
+        registry.register(DbType::PostgreSQL, || {
+            Box::new(PostgresSqlGenerator::default())
+        });
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Postgres output with every index built concurrently.
+    fn concurrent_postgres() -> Box<dyn SqlGenerator> {
+        Box::new(PostgresSqlGenerator {
+            concurrent_indexes: true,
+        })
+    }
+
+    #[test]
+    fn registered_generator_is_returned_for_its_db_type() {
+        let mut registry = GeneratorRegistry::default();
+        assert_eq!(
+            registry
+                .generator(&DbType::PostgreSQL)
+                .unwrap()
+                .generate_drop_index("users", "idx_email"),
+            "DROP INDEX \"idx_email\";"
+        );
+
+        registry.register(DbType::PostgreSQL, concurrent_postgres);
+        assert_eq!(
+            registry
+                .generator(&DbType::PostgreSQL)
+                .unwrap()
+                .generate_drop_index("users", "idx_email"),
+            "DROP INDEX CONCURRENTLY \"idx_email\";"
+        );
+        assert_eq!(
+            registry
+                .generator(&DbType::MariaDB)
+                .unwrap()
+                .quote_identifier("users"),
+            "`users`"
+        );
+        assert!(
+            GeneratorRegistry::empty()
+                .generator(&DbType::MySQL)
+                .is_none()
+        );
+    }
+}
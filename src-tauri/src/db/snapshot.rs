@@ -0,0 +1,53 @@
+use anyhow::Result;
+use sqlx::{Connection, Database};
+
+/// Run `read` inside one transaction opened with `begin`, committed once every
+/// query has run, so a schema read sees a single snapshot instead of whatever
+/// DDL lands between its queries. A failed read rolls back.
+pub(crate) async fn read_in_snapshot<DB, T>(
+    conn: &mut DB::Connection,
+    begin: &'static str,
+    read: impl AsyncFnOnce(&mut DB::Connection) -> Result<T>,
+) -> Result<T>
+where
+    DB: Database,
+{
+    let mut tx = conn.begin_with(begin).await?;
+    let result = read(&mut tx).await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqliteConnection;
+
+    #[tokio::test]
+    async fn reads_run_inside_the_transaction_and_it_is_committed() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE seen (step TEXT)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        read_in_snapshot::<sqlx::Sqlite, _>(&mut conn, "BEGIN", async |conn| {
+            // A second BEGIN fails while the snapshot transaction is open.
+            assert!(sqlx::query("BEGIN").execute(&mut *conn).await.is_err());
+            sqlx::query("INSERT INTO seen VALUES ('first'), ('last')")
+                .execute(&mut *conn)
+                .await?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        // Committed: the connection is out of the transaction and kept the rows.
+        sqlx::query("BEGIN").execute(&mut conn).await.unwrap();
+        let (rows,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM seen")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(rows, 2);
+    }
+}
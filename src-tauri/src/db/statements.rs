@@ -0,0 +1,244 @@
+/// Split a SQL script into statements on top-level `;`, without the terminator.
+///
+/// Semicolons inside quoted strings and identifiers, comments, Postgres
+/// dollar-quoted bodies and MySQL `BEGIN ... END` blocks don't end a statement,
+/// so stored routine definitions come through in one piece.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut depth: u32 = 0;
+    let mut pending = Pending::None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let skip_to = if c == '-' && next == Some('-') {
+            Some(find_from(&chars, i + 2, &['\n']).map_or(chars.len(), |p| p + 1))
+        } else if c == '/' && next == Some('*') {
+            Some(find_from(&chars, i + 2, &['*', '/']).map_or(chars.len(), |p| p + 2))
+        } else if c == '\'' || c == '"' || c == '`' {
+            Some(quoted_end(&chars, i))
+        } else if c == '$' {
+            dollar_quoted_end(&chars, i)
+        } else {
+            None
+        };
+        if let Some(end) = skip_to {
+            current.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+
+        if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let upper = word.to_ascii_uppercase();
+            if !pending.resolve(Some(&upper), &mut depth) {
+                match upper.as_str() {
+                    "BEGIN" => pending = Pending::Begin,
+                    "END" => pending = Pending::End,
+                    "CASE" => depth += 1,
+                    _ => {}
+                }
+            }
+            current.push_str(&word);
+            continue;
+        }
+
+        if !c.is_whitespace() {
+            pending.resolve(None, &mut depth);
+        }
+        if c == ';' && depth == 0 {
+            push_statement(&mut statements, &current);
+            current.clear();
+        } else {
+            current.push(c);
+        }
+        i += 1;
+    }
+
+    push_statement(&mut statements, &current);
+    statements
+}
+
+/// `split_statements`, with each statement given back its `;` unless it
+/// ends in a `--` comment, where the terminator would be commented out.
+pub fn terminated_statements(sql: &str) -> Vec<String> {
+    split_statements(sql)
+        .into_iter()
+        .map(|statement| {
+            let ends_in_comment = statement
+                .lines()
+                .last()
+                .is_some_and(|line| line.trim_start().starts_with("--"));
+            if ends_in_comment {
+                statement
+            } else {
+                format!("{};", statement)
+            }
+        })
+        .collect()
+}
+
+/// Whether `statement` can't run inside a transaction block, as Postgres refuses
+/// `CREATE INDEX CONCURRENTLY` and `DROP INDEX CONCURRENTLY` there.
+/// `execute_in_transactions` runs these outside the transactions it opens.
+pub fn runs_outside_transaction(statement: &str) -> bool {
+    let words: Vec<String> = statement
+        .split_whitespace()
+        .take(4)
+        .map(|w| w.to_ascii_uppercase())
+        .collect();
+    matches!(
+        words.iter().map(String::as_str).collect::<Vec<_>>()[..],
+        ["CREATE", "INDEX", "CONCURRENTLY", ..]
+            | ["CREATE", "UNIQUE", "INDEX", "CONCURRENTLY", ..]
+            | ["DROP", "INDEX", "CONCURRENTLY", ..]
+    )
+}
+
+/// A `BEGIN` or `END` whose meaning depends on the word after it.
+#[derive(Clone, Copy, PartialEq)]
+enum Pending {
+    None,
+    Begin,
+    End,
+}
+
+impl Pending {
+    /// Settle a pending keyword given the next word (`None` for punctuation).
+    /// Returns true when that word was consumed as part of the keyword.
+    fn resolve(&mut self, next_word: Option<&str>, depth: &mut u32) -> bool {
+        let pending = std::mem::replace(self, Pending::None);
+        match pending {
+            Pending::None => false,
+            // `BEGIN;` / `BEGIN WORK` / `BEGIN TRANSACTION` start a transaction, not a block.
+            Pending::Begin => {
+                let transaction =
+                    matches!(next_word, None | Some("WORK" | "TRANSACTION" | "ISOLATION"));
+                if !transaction {
+                    *depth += 1;
+                }
+                false
+            }
+            // `END IF` / `END LOOP` / ... close blocks that never counted as depth,
+            // `END CASE` closes a CASE statement.
+            Pending::End => match next_word {
+                Some("IF" | "LOOP" | "WHILE" | "REPEAT") => true,
+                Some("CASE") => {
+                    *depth = depth.saturating_sub(1);
+                    true
+                }
+                _ => {
+                    *depth = depth.saturating_sub(1);
+                    false
+                }
+            },
+        }
+    }
+}
+
+fn push_statement(statements: &mut Vec<String>, statement: &str) {
+    let statement = statement.trim();
+    if !statement.is_empty() {
+        statements.push(statement.to_string());
+    }
+}
+
+/// Index of the first occurrence of `needle` at or after `from`.
+fn find_from(chars: &[char], from: usize, needle: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&p| chars[p..].starts_with(needle))
+}
+
+/// End (exclusive) of the quoted string or identifier starting at `start`;
+/// a doubled quote character is an escaped one.
+fn quoted_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// End (exclusive) of a `$tag$ ... $tag$` string starting at `start`, or `None`
+/// if the `$` doesn't open one (e.g. a `$1` parameter).
+fn dollar_quoted_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+        i += 1;
+    }
+    if chars.get(i) != Some(&'$') || chars.get(start + 1).is_some_and(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let tag = &chars[start..=i];
+    Some(find_from(chars, i + 1, tag).map_or(chars.len(), |p| p + tag.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_plain_statements() {
+        assert_eq!(
+            split_statements("ALTER TABLE a ADD x INT;\n\nDROP TABLE b;  "),
+            vec!["ALTER TABLE a ADD x INT", "DROP TABLE b"]
+        );
+    }
+
+    #[test]
+    fn ignores_semicolons_in_strings_and_comments() {
+        assert_eq!(
+            split_statements(
+                "ALTER TABLE a MODIFY c INT COMMENT 'a;b''s'; -- x;y\nDROP /* ; */ TABLE `t;1`;"
+            ),
+            vec![
+                "ALTER TABLE a MODIFY c INT COMMENT 'a;b''s'",
+                "-- x;y\nDROP /* ; */ TABLE `t;1`",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_dollar_quoted_body_whole() {
+        let function = "CREATE OR REPLACE FUNCTION f(a integer) RETURNS integer LANGUAGE plpgsql AS $function$\nBEGIN\n  RETURN a + $1;\nEND;\n$function$";
+        assert_eq!(
+            split_statements(&format!("{};\nDROP FUNCTION g();", function)),
+            vec![function, "DROP FUNCTION g()"]
+        );
+    }
+
+    #[test]
+    fn keeps_mysql_compound_statement_whole() {
+        let procedure = "CREATE PROCEDURE p(IN x INT)\nBEGIN\n  IF x > 0 THEN\n    SELECT CASE x WHEN 1 THEN 'one' ELSE 'many' END;\n  END IF;\n  loop_a: LOOP\n    LEAVE loop_a;\n  END LOOP;\nEND";
+        assert_eq!(
+            split_statements(&format!(
+                "DROP PROCEDURE IF EXISTS p;\n{};\nSELECT 1;",
+                procedure
+            )),
+            vec!["DROP PROCEDURE IF EXISTS p", procedure, "SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn transaction_begin_is_not_a_block() {
+        assert_eq!(
+            split_statements("BEGIN; UPDATE t SET a = 1; COMMIT;"),
+            vec!["BEGIN", "UPDATE t SET a = 1", "COMMIT"]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::Result;
+use log::warn;
+
+use crate::db::SchemaReader;
+use crate::models::TableSchema;
+
+/// Tables that could be read, plus the names of those that couldn't and a note
+/// on why for each.
+#[derive(Debug, Default)]
+pub struct TableRead {
+    pub tables: Vec<TableSchema>,
+    pub skipped: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Read every table (or the named ones), skipping tables the connecting user
+/// can't read instead of failing the whole read.
+///
+/// The bulk read is tried first. If it fails, tables are read one at a time and
+/// each failure becomes a warning. When no table can be read at all the original
+/// error is returned, since the problem is then the connection, not a table.
+pub async fn read_tables_tolerant(
+    reader: &dyn SchemaReader,
+    names: Option<&[String]>,
+) -> Result<TableRead> {
+    let bulk = match names {
+        Some(names) => reader.get_tables_named(names).await,
+        None => reader.get_tables().await,
+    };
+    let bulk_error = match bulk {
+        Ok(tables) => {
+            return Ok(TableRead {
+                tables,
+                warnings: reader.take_read_warnings(),
+                ..Default::default()
+            });
+        }
+        Err(e) => e,
+    };
+    warn!(
+        "Bulk schema read failed, reading tables one by one: {}",
+        bulk_error
+    );
+
+    let names = match names {
+        Some(names) => names.to_vec(),
+        None => match reader.list_tables().await {
+            Ok(names) => names,
+            Err(_) => return Err(bulk_error),
+        },
+    };
+
+    let mut read = TableRead::default();
+    for name in &names {
+        match reader.get_tables_named(std::slice::from_ref(name)).await {
+            Ok(tables) => read.tables.extend(tables),
+            Err(e) => {
+                warn!("Skipping table {}: {}", name, e);
+                read.skipped.push(name.clone());
+                read.warnings.push(format!("Skipped table {}: {}", name, e));
+            }
+        }
+    }
+
+    if read.tables.is_empty() && !read.warnings.is_empty() {
+        return Err(bulk_error);
+    }
+    read.warnings.extend(reader.take_read_warnings());
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::*;
+    use anyhow::anyhow;
+    use async_trait::async_trait;
+
+    /// A reader whose user can't see `secret`: any read touching it fails.
+    struct RestrictedReader {
+        names: Vec<String>,
+        denied: Vec<String>,
+        decode_warnings: std::sync::Mutex<Vec<String>>,
+    }
+
+    fn table(name: &str) -> TableSchema {
+        TableSchema {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[async_trait]
+    impl SchemaReader for RestrictedReader {
+        async fn test_connection(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn server_version(&self) -> Result<String> {
+            Ok("8.0.36".to_string())
+        }
+        async fn get_tables(&self) -> Result<Vec<TableSchema>> {
+            self.get_tables_named(&self.names).await
+        }
+        async fn get_tables_named(&self, names: &[String]) -> Result<Vec<TableSchema>> {
+            if let Some(name) = names.iter().find(|n| self.denied.contains(n)) {
+                return Err(anyhow!("permission denied for table {}", name));
+            }
+            Ok(names.iter().map(|n| table(n)).collect())
+        }
+        async fn list_tables(&self) -> Result<Vec<String>> {
+            Ok(self.names.clone())
+        }
+        async fn list_databases(&self, _: Option<&str>, _: Option<u32>) -> Result<Vec<String>> {
+            Ok(vec![])
+        }
+        async fn get_database_info(&self) -> Result<DatabaseInfo> {
+            Ok(DatabaseInfo::default())
+        }
+        async fn estimated_row_count(&self, _table: &str) -> Result<Option<u64>> {
+            Ok(None)
+        }
+        async fn get_routines(&self) -> Result<Vec<Routine>> {
+            Ok(vec![])
+        }
+        async fn get_grants(&self) -> Result<Vec<Grant>> {
+            Ok(vec![])
+        }
+        fn take_read_warnings(&self) -> Vec<String> {
+            std::mem::take(&mut self.decode_warnings.lock().unwrap())
+        }
+        async fn list_schema_objects(&self) -> Result<SchemaObjects> {
+            Ok(SchemaObjects::default())
+        }
+        async fn native_create_table(&self, table: &str) -> Result<String> {
+            Ok(format!("CREATE TABLE {} ()", table))
+        }
+    }
+
+    fn reader(denied: &[&str]) -> RestrictedReader {
+        RestrictedReader {
+            names: vec![
+                "orders".to_string(),
+                "secret".to_string(),
+                "users".to_string(),
+            ],
+            denied: denied.iter().map(|n| n.to_string()).collect(),
+            decode_warnings: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn unreadable_table_is_skipped_with_warning() {
+        let read = read_tables_tolerant(&reader(&["secret"]), None)
+            .await
+            .unwrap();
+
+        let names: Vec<_> = read.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["orders", "users"]);
+        assert_eq!(read.skipped, vec!["secret"]);
+        assert_eq!(
+            read.warnings,
+            vec!["Skipped table secret: permission denied for table secret"]
+        );
+    }
+
+    #[tokio::test]
+    async fn readable_schema_has_no_warnings() {
+        let read = read_tables_tolerant(&reader(&[]), None).await.unwrap();
+        assert_eq!(read.tables.len(), 3);
+        assert!(read.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn decoding_warnings_are_surfaced() {
+        let r = reader(&[]);
+        r.decode_warnings
+            .lock()
+            .unwrap()
+            .push("A table name is not valid UTF-8, read as 't\u{fffd}'".to_string());
+
+        let read = read_tables_tolerant(&r, None).await.unwrap();
+        assert_eq!(read.tables.len(), 3);
+        assert_eq!(
+            read.warnings,
+            vec!["A table name is not valid UTF-8, read as 't\u{fffd}'"]
+        );
+        assert!(r.take_read_warnings().is_empty());
+    }
+
+    #[tokio::test]
+    async fn nothing_readable_returns_the_error() {
+        let err = read_tables_tolerant(&reader(&["orders", "secret", "users"]), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("permission denied"));
+    }
+}
@@ -1,13 +1,54 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
-use crate::models::{Column, Index, TableSchema};
+use crate::models::{
+    Column, DatabaseInfo, Grant, Index, PartitionSpec, Policy, PrimaryKey, Routine, SchemaObjects,
+    TableOptions, TableSchema,
+};
 
 #[async_trait]
 pub trait SchemaReader: Send + Sync {
     async fn test_connection(&self) -> Result<()>;
+    /// Version string the server reports, e.g. `8.0.36` or `16.2`.
+    async fn server_version(&self) -> Result<String>;
     async fn get_tables(&self) -> Result<Vec<TableSchema>>;
-    async fn list_databases(&self) -> Result<Vec<String>>;
+    /// Read only the named tables; names that don't exist are skipped.
+    async fn get_tables_named(&self, names: &[String]) -> Result<Vec<TableSchema>>;
+    /// Names of the base tables, sorted, without reading their structure.
+    async fn list_tables(&self) -> Result<Vec<String>>;
+    /// List databases, optionally narrowed by a SQL `LIKE` pattern and capped at `limit`.
+    async fn list_databases(
+        &self,
+        name_filter: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Vec<String>>;
+    /// Default charset/collation of the database this driver is connected to.
+    async fn get_database_info(&self) -> Result<DatabaseInfo>;
+    /// The server's row estimate from table statistics (no table scan);
+    /// `None` when the table is missing or has never been analyzed.
+    async fn estimated_row_count(&self, table: &str) -> Result<Option<u64>>;
+    /// Stored procedures and functions, sorted by name.
+    async fn get_routines(&self) -> Result<Vec<Routine>>;
+    /// Table-level privileges granted to users and roles, sorted.
+    async fn get_grants(&self) -> Result<Vec<Grant>>;
+    /// Problems earlier schema reads worked around instead of failing on, e.g.
+    /// names or comments that weren't valid UTF-8. Taking them clears them.
+    fn take_read_warnings(&self) -> Vec<String>;
+    /// Names of the views, standalone sequences and enum types.
+    async fn list_schema_objects(&self) -> Result<SchemaObjects>;
+    /// `CREATE TABLE` for `table` as the engine describes it, independent of
+    /// our own generator, for checking generated DDL against.
+    async fn native_create_table(&self, table: &str) -> Result<String>;
+}
+
+#[async_trait]
+pub trait SqlExecutor: Send + Sync {
+    /// Run a script, statement by statement, stopping at the first error.
+    async fn execute_sql(&self, sql: &str) -> Result<()>;
+    /// Run `statements` in one transaction, rolled back if any of them fails.
+    /// MySQL commits DDL implicitly, so there a failure only undoes the data
+    /// changes since the last DDL statement.
+    async fn execute_in_transaction(&self, statements: &[String]) -> Result<()>;
 }
 
 pub trait SqlGenerator: Send + Sync {
@@ -15,12 +56,84 @@ pub trait SqlGenerator: Send + Sync {
     fn generate_create_table(&self, table: &TableSchema) -> String;
     fn generate_drop_table(&self, table_name: &str) -> String;
     fn generate_add_column(&self, table: &str, column: &Column) -> String;
+    /// Add a column without blocking writes where the dialect and column allow it;
+    /// otherwise a plain add.
+    fn generate_add_column_online(&self, table: &str, column: &Column) -> String;
     fn generate_drop_column(&self, table: &str, column_name: &str) -> String;
     fn generate_modify_column(&self, table: &str, column: &Column) -> String;
+    /// Turn the `target` column into `source`, emitting only what is needed where the
+    /// dialect alters column attributes separately.
+    fn generate_alter_column(&self, table: &str, source: &Column, target: &Column) -> String;
+    /// Rename the `target` column to `source`'s name and turn it into `source`,
+    /// keeping its data.
+    fn generate_rename_column(&self, table: &str, source: &Column, target: &Column) -> String;
     fn generate_add_index(&self, table: &str, index: &Index) -> String;
+    /// Build an index without blocking writes where the dialect and index type
+    /// allow it; otherwise a plain add.
+    fn generate_add_index_online(&self, table: &str, index: &Index) -> String;
     fn generate_drop_index(&self, table: &str, index_name: &str) -> String;
     fn generate_add_foreign_key(&self, table: &str, fk: &crate::models::ForeignKey) -> String;
+    /// Add a foreign key without checking existing rows, then validate it separately.
+    /// Dialects without that split fall back to a plain add.
+    fn generate_add_foreign_key_deferred_validation(
+        &self,
+        table: &str,
+        fk: &crate::models::ForeignKey,
+    ) -> String;
     fn generate_drop_foreign_key(&self, table: &str, fk_name: &str) -> String;
     fn generate_add_unique(&self, table: &str, uc: &crate::models::UniqueConstraint) -> String;
     fn generate_drop_unique(&self, table: &str, uc_name: &str) -> String;
+    /// Replace the `current` primary key with `wanted`; either may be absent.
+    fn generate_alter_primary_key(
+        &self,
+        table: &str,
+        current: Option<&PrimaryKey>,
+        wanted: Option<&PrimaryKey>,
+    ) -> String;
+    fn generate_add_exclusion(
+        &self,
+        table: &str,
+        ec: &crate::models::ExclusionConstraint,
+    ) -> String;
+    fn generate_drop_exclusion(&self, table: &str, ec_name: &str) -> String;
+    fn generate_add_check(&self, table: &str, check: &crate::models::CheckConstraint) -> String;
+    fn generate_drop_check(&self, table: &str, check_name: &str) -> String;
+    fn generate_create_policy(&self, table: &str, policy: &Policy) -> String;
+    /// Change a policy's roles and expressions in place; its command and
+    /// permissiveness can only change by re-creating it.
+    fn generate_alter_policy(&self, table: &str, policy: &Policy) -> String;
+    fn generate_drop_policy(&self, table: &str, policy_name: &str) -> String;
+    /// Turn row-level security on or off for a table.
+    fn generate_set_row_security(&self, table: &str, enabled: bool) -> String;
+    fn generate_create_routine(&self, routine: &Routine) -> String;
+    /// Replace an existing routine of the same name with `routine`.
+    fn generate_replace_routine(&self, routine: &Routine) -> String;
+    fn generate_drop_routine(&self, routine: &Routine) -> String;
+    /// Recreate `target` with the shape of `source`, copying shared columns' data,
+    /// so the resulting column order matches the source exactly.
+    fn generate_rebuild_table(&self, source: &TableSchema, target: &TableSchema) -> String;
+    /// Move `column` to just after the column named `after`, or to the front when
+    /// `None`, in place. `None` when the dialect can only reorder columns by
+    /// rebuilding the table.
+    fn generate_move_column(
+        &self,
+        table: &str,
+        column: &Column,
+        after: Option<&str>,
+    ) -> Option<String>;
+    /// Repartition `table` as `partitioning` in place, or remove its partitioning
+    /// when `None`. `None` when the dialect can only repartition by rebuilding.
+    fn generate_alter_partitioning(
+        &self,
+        table: &str,
+        partitioning: Option<&PartitionSpec>,
+    ) -> Option<String>;
+    /// Move a table to `tablespace`, or back to the default one when `None`.
+    fn generate_set_tablespace(&self, table: &str, tablespace: Option<&str>) -> String;
+    /// Change the storage options that are set in `options`, leaving the rest alone.
+    fn generate_set_table_options(&self, table: &str, options: &TableOptions) -> String;
+    /// Detach `table` from the `remove` parents, then attach it to the `add` ones.
+    fn generate_set_inherits(&self, table: &str, add: &[String], remove: &[String]) -> String;
+    fn generate_grant(&self, grant: &Grant) -> String;
+    fn generate_revoke(&self, grant: &Grant) -> String;
 }
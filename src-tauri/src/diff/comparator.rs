@@ -1,8 +1,11 @@
 use log::debug;
 
-use crate::db::SqlGenerator;
+use crate::db::{SqlGenerator, sql_generator_for};
+use crate::diff::identifiers::fold_to_target_case;
+use crate::diff::renames::column_renames;
 use crate::models::*;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 trait NamedItem {
     fn name(&self) -> &str;
@@ -23,11 +26,26 @@ impl NamedItem for ForeignKey {
         &self.name
     }
 }
+impl NamedItem for Policy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
 impl NamedItem for UniqueConstraint {
     fn name(&self) -> &str {
         &self.name
     }
 }
+impl NamedItem for ExclusionConstraint {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+impl NamedItem for CheckConstraint {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
 
 struct DiffConfig<'a, T> {
     table_name: &'a str,
@@ -65,6 +83,9 @@ fn compare_named_items<T: NamedItem + PartialEq>(
                 sql: (config.generate_add)(sql_gen, config.table_name, item),
                 selected: true,
                 warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             });
         } else if let Some(target_item) = target_map.get(item.name()) {
             if item != *target_item {
@@ -83,6 +104,9 @@ fn compare_named_items<T: NamedItem + PartialEq>(
                     ),
                     selected: true,
                     warnings: vec![],
+                    changes: vec![],
+                    priority: DiffPriority::Structural,
+                    severity: Severity::default(),
                 });
             }
         }
@@ -102,11 +126,209 @@ fn compare_named_items<T: NamedItem + PartialEq>(
                 sql: (config.generate_drop)(sql_gen, config.table_name, item.name()),
                 selected: true,
                 warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             });
         }
     }
 }
 
+/// Order-insensitive equality of two named item lists, matching how
+/// `compare_named_items` pairs items up by name.
+fn same_named_items<T: NamedItem + PartialEq>(source: &[T], target: &[T]) -> bool {
+    source.len() == target.len()
+        && source
+            .iter()
+            .all(|s| target.iter().any(|t| t.name() == s.name() && t == s))
+}
+
+/// Whether two tables have the same structure. Columns are matched by name, so
+/// the same columns declared in a different order count as equal.
+fn tables_equal(s: &TableSchema, t: &TableSchema) -> bool {
+    t.name == s.name
+        && t.tablespace == s.tablespace
+        && same_named_items(&s.columns, &t.columns)
+        && primary_key_columns(t) == primary_key_columns(s)
+        && same_named_items(&s.indexes, &t.indexes)
+        && same_named_items(&s.foreign_keys, &t.foreign_keys)
+        && same_named_items(&s.unique_constraints, &t.unique_constraints)
+        && same_named_items(&s.exclusion_constraints, &t.exclusion_constraints)
+        && same_named_items(&s.check_constraints, &t.check_constraints)
+        && t.row_security == s.row_security
+        && same_named_items(&s.policies, &t.policies)
+        && t.partitioning == s.partitioning
+        && t.inherits.len() == s.inherits.len()
+        && s.inherits.iter().all(|p| t.inherits.contains(p))
+        && changed_table_options(&s.options, &t.options).is_none()
+}
+
+/// The columns of a table's primary key. Its name isn't compared: MySQL always
+/// calls it PRIMARY, and Postgres names it after the table unless told otherwise.
+fn primary_key_columns(table: &TableSchema) -> Option<&[String]> {
+    table.primary_key.as_ref().map(|pk| pk.columns.as_slice())
+}
+
+/// Whether `compare_schemas` would report no differences, without generating any
+/// SQL. Stops at the first difference found.
+pub fn schemas_in_sync(source: &[TableSchema], target: &[TableSchema]) -> bool {
+    source.len() == target.len()
+        && source
+            .iter()
+            .all(|s| target.iter().any(|t| tables_equal(s, t)))
+}
+
+/// A charset name as MySQL 8 reports it: the legacy `utf8` alias is `utf8mb3`.
+/// `utf8mb4` stays distinct, since moving to it is a real change (4-byte
+/// characters such as emoji).
+fn canonical_charset(charset: &str) -> String {
+    let lowered = charset.to_ascii_lowercase();
+    match lowered.as_str() {
+        "utf8" => "utf8mb3".to_string(),
+        _ => lowered,
+    }
+}
+
+/// A column type in one spelling: lower case, single spaces, and without an
+/// integer display width, which MySQL 8 ignores (`int(11)` is `int`).
+fn canonical_type(data_type: &str) -> String {
+    let lowered = data_type
+        .to_ascii_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    for int in [
+        "tinyint",
+        "smallint",
+        "mediumint",
+        "bigint",
+        "integer",
+        "int",
+    ] {
+        let width_and_rest = lowered
+            .strip_prefix(int)
+            .and_then(|r| r.strip_prefix('('))
+            .and_then(|r| r.split_once(')'))
+            .filter(|(width, _)| !width.is_empty() && width.chars().all(|c| c.is_ascii_digit()));
+        if let Some((_, rest)) = width_and_rest {
+            return format!("{}{}", int, rest);
+        }
+    }
+    lowered
+}
+
+/// The table options that differ, as `(source, target)` with the others unset.
+/// An option is only compared when both sides have it.
+fn changed_table_options(
+    source: &TableOptions,
+    target: &TableOptions,
+) -> Option<(TableOptions, TableOptions)> {
+    let pick = |s: &Option<String>, t: &Option<String>, same: fn(&str, &str) -> bool| match (s, t) {
+        (Some(s), Some(t)) if !same(s, t) => (Some(s.clone()), Some(t.clone())),
+        _ => (None, None),
+    };
+    let (source_engine, target_engine) = pick(&source.engine, &target.engine, |a, b| {
+        a.eq_ignore_ascii_case(b)
+    });
+    let (source_charset, target_charset) = pick(&source.charset, &target.charset, |a, b| {
+        canonical_charset(a) == canonical_charset(b)
+    });
+    let (source_row_format, target_row_format) =
+        pick(&source.row_format, &target.row_format, |a, b| {
+            a.eq_ignore_ascii_case(b)
+        });
+    let changed = TableOptions {
+        engine: source_engine,
+        charset: source_charset,
+        row_format: source_row_format,
+    };
+    if changed.is_empty() {
+        return None;
+    }
+    Some((
+        changed,
+        TableOptions {
+            engine: target_engine,
+            charset: target_charset,
+            row_format: target_row_format,
+        },
+    ))
+}
+
+fn table_options_detail(options: &TableOptions) -> String {
+    let mut parts = Vec::new();
+    if let Some(engine) = &options.engine {
+        parts.push(format!("ENGINE={}", engine));
+    }
+    if let Some(charset) = &options.charset {
+        parts.push(format!("DEFAULT CHARSET={}", charset));
+    }
+    if let Some(row_format) = &options.row_format {
+        parts.push(format!("ROW_FORMAT={}", row_format));
+    }
+    parts.join(" ")
+}
+
+/// Warn when both databases default to different charsets, which usually shows up
+/// as a long tail of per-column charset/collation diffs.
+pub fn database_charset_warning(source: &DatabaseInfo, target: &DatabaseInfo) -> Option<String> {
+    match (&source.charset, &target.charset) {
+        (Some(s), Some(t)) if canonical_charset(s) != canonical_charset(t) => Some(format!(
+            "Source database charset '{}' differs from target '{}'; column-level differences may follow from this",
+            s, t
+        )),
+        _ => None,
+    }
+}
+
+/// Names that occur more than once, each listed once, in first-seen order.
+fn repeated_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut seen = HashSet::new();
+    let mut repeated = Vec::new();
+    for name in names {
+        if !seen.insert(name) && !repeated.contains(&name) {
+            repeated.push(name);
+        }
+    }
+    repeated
+}
+
+/// Warn about tables, or columns, indexes and foreign keys within one table,
+/// that share a name. Comparison keys everything by name, so only one of each
+/// would be compared; this points at an introspection bug or a hand-edited
+/// snapshot. `side` names the schema, e.g. "Source".
+pub fn duplicate_name_warnings(side: &str, tables: &[TableSchema]) -> Vec<String> {
+    let mut warnings: Vec<String> = repeated_names(tables.iter().map(|t| t.name.as_str()))
+        .into_iter()
+        .map(|name| format!("{} schema has more than one table named {}", side, name))
+        .collect();
+    for table in tables {
+        let objects = [
+            (
+                "column",
+                repeated_names(table.columns.iter().map(|c| c.name.as_str())),
+            ),
+            (
+                "index",
+                repeated_names(table.indexes.iter().map(|i| i.name.as_str())),
+            ),
+            (
+                "foreign key",
+                repeated_names(table.foreign_keys.iter().map(|f| f.name.as_str())),
+            ),
+        ];
+        for (kind, names) in objects {
+            warnings.extend(names.into_iter().map(|name| {
+                format!(
+                    "{} table {} has more than one {} named {}",
+                    side, table.name, kind, name
+                )
+            }));
+        }
+    }
+    warnings
+}
+
 fn column_detail(col: &Column) -> String {
     let mut parts = vec![col.data_type.clone()];
     if col.nullable {
@@ -120,17 +342,229 @@ fn column_detail(col: &Column) -> String {
     if col.auto_increment {
         parts.push("AUTO_INCREMENT".to_string());
     }
+    if let Some(sequence) = &col.sequence {
+        parts.push(format!("SEQUENCE {}", sequence));
+    }
+    if let Some(generated) = &col.generated {
+        parts.push(generated.clause());
+    }
+    if let Some(srid) = col.srid {
+        parts.push(format!("SRID {}", srid));
+    }
+    if let Some(on_update) = &col.on_update {
+        parts.push(format!("ON UPDATE {}", on_update));
+    }
     if let Some(comment) = &col.comment {
         parts.push(format!("COMMENT '{}'", comment));
     }
+    if !col.visible {
+        parts.push("INVISIBLE".to_string());
+    }
     parts.join(" ")
 }
 
+fn index_detail(idx: &Index) -> String {
+    if let Some(definition) = &idx.expression {
+        return definition.clone();
+    }
+    let mut detail = idx.columns.join(", ");
+    if !idx.visible {
+        detail.push_str(" INVISIBLE");
+    }
+    if let Some(comment) = &idx.comment {
+        detail.push_str(&format!(" COMMENT '{}'", comment));
+    }
+    detail
+}
+
+/// List which attributes of a column differ between source and target.
+pub(crate) fn column_changes(source: &Column, target: &Column) -> Vec<ColumnChangeKind> {
+    let mut changes = Vec::new();
+    if source.data_type != target.data_type {
+        changes.push(
+            if canonical_type(&source.data_type) == canonical_type(&target.data_type) {
+                ColumnChangeKind::DataTypeSpelling
+            } else {
+                ColumnChangeKind::DataType
+            },
+        );
+    }
+    if source.nullable != target.nullable {
+        changes.push(ColumnChangeKind::Nullable);
+    }
+    if source.effective_default() != target.effective_default() {
+        changes.push(ColumnChangeKind::Default);
+    }
+    if source.comment != target.comment {
+        changes.push(ColumnChangeKind::Comment);
+    }
+    if source.auto_increment != target.auto_increment {
+        changes.push(ColumnChangeKind::AutoIncrement);
+    }
+    if source.visible != target.visible {
+        changes.push(ColumnChangeKind::Visibility);
+    }
+    if source.on_update != target.on_update {
+        changes.push(ColumnChangeKind::OnUpdate);
+    }
+    if source.sequence != target.sequence {
+        changes.push(ColumnChangeKind::Sequence);
+    }
+    if source.generated != target.generated {
+        changes.push(ColumnChangeKind::Generated);
+    }
+    if source.srid != target.srid {
+        changes.push(ColumnChangeKind::Srid);
+    }
+    changes
+}
+
+/// A note for a column declared the same on both sides that still holds a
+/// different number of bytes: the character sets differ, which the generated
+/// SQL leaves alone.
+fn octet_length_warning(source: &Column, target: &Column) -> Option<TypeWarning> {
+    let (Some(source_bytes), Some(target_bytes)) = (source.octet_length, target.octet_length)
+    else {
+        return None;
+    };
+    if source.data_type != target.data_type || source_bytes == target_bytes {
+        return None;
+    }
+    Some(TypeWarning {
+        column_name: source.name.clone(),
+        source_type: source.data_type.clone(),
+        target_type: target.data_type.clone(),
+        message: format!(
+            "Holds up to {} bytes on the source but {} on the target; the character sets differ",
+            source_bytes, target_bytes
+        ),
+        severity: WarningSeverity::Degraded,
+    })
+}
+
+/// Leave out an added table that has no columns: MySQL can't create one, and
+/// it usually means the schema it came from was read or saved incompletely.
+pub(crate) fn skip_if_columnless(item: &mut DiffItem, table: &TableSchema) {
+    if !table.columns.is_empty() {
+        return;
+    }
+    item.sql.clear();
+    item.selected = false;
+    item.warnings.push(TypeWarning {
+        column_name: String::new(),
+        source_type: String::new(),
+        target_type: String::new(),
+        message: format!("Table {} has no columns and was not created", table.name),
+        severity: WarningSeverity::Skipped,
+    });
+}
+
+/// `col` with the AUTO_INCREMENT flag and sequence, and the comment, of `target`
+/// when the options ignore them, so neither the comparison nor the generated
+/// ALTER touches them.
+pub(crate) fn align_ignored_attributes<'a>(
+    col: &'a Column,
+    target: &Column,
+    options: &CompareOptions,
+) -> Cow<'a, Column> {
+    let mut col = Cow::Borrowed(col);
+    let auto_increment_differs =
+        col.auto_increment != target.auto_increment || col.sequence != target.sequence;
+    if options.ignore_auto_increment && auto_increment_differs {
+        let col = col.to_mut();
+        col.auto_increment = target.auto_increment;
+        col.sequence = target.sequence.clone();
+    }
+    if options.ignore_comments && col.comment != target.comment {
+        col.to_mut().comment = target.comment.clone();
+    }
+    col
+}
+
+/// The value non-strict MySQL gives existing rows for a NOT NULL column of this
+/// type added without a default.
+fn implicit_default(data_type: &str) -> Option<&'static str> {
+    let lower = data_type.to_lowercase();
+    let base = lower.split('(').next().unwrap_or(&lower).trim();
+    let base = base.strip_suffix(" unsigned").unwrap_or(base);
+    match base {
+        "char" | "varchar" | "character" | "character varying" | "bpchar" | "text" | "tinytext"
+        | "mediumtext" | "longtext" => Some("''"),
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "decimal"
+        | "numeric" | "float" | "double" | "double precision" | "real" => Some("0"),
+        _ => None,
+    }
+}
+
+/// `col` as it should be added: a NOT NULL column without a default gets its
+/// implicit default when the options ask for it, or a warning that the add fails
+/// on a table with rows.
+pub(crate) fn with_backfill_default<'a>(
+    col: &'a Column,
+    options: &CompareOptions,
+) -> (Cow<'a, Column>, Option<TypeWarning>) {
+    if !col.needs_backfill() {
+        return (Cow::Borrowed(col), None);
+    }
+    let warning = |message: String| TypeWarning {
+        column_name: col.name.clone(),
+        source_type: col.data_type.clone(),
+        target_type: col.data_type.clone(),
+        message,
+        severity: WarningSeverity::Degraded,
+    };
+    match implicit_default(&col.data_type).filter(|_| options.fill_implicit_defaults) {
+        Some(default) => (
+            Cow::Owned(Column {
+                default_value: Some(default.to_string()),
+                ..col.clone()
+            }),
+            Some(warning(format!(
+                "Added with DEFAULT {} to fill existing rows; the source column has no default",
+                default
+            ))),
+        ),
+        None => (
+            Cow::Borrowed(col),
+            Some(warning(
+                "NOT NULL without a default; adding it fails if the table has rows".to_string(),
+            )),
+        ),
+    }
+}
+
 pub fn compare_schemas(
     source: &[TableSchema],
     target: &[TableSchema],
     sql_gen: &dyn SqlGenerator,
 ) -> Vec<DiffItem> {
+    compare_schemas_with_options(source, target, sql_gen, &CompareOptions::default())
+}
+
+/// Diff two schema snapshots without a connection, using the stateless
+/// generator for `db_type`. MariaDB shares the MySQL generator.
+pub fn compare_snapshots(
+    source: &[TableSchema],
+    target: &[TableSchema],
+    db_type: DbType,
+) -> Vec<DiffItem> {
+    compare_schemas(source, target, sql_generator_for(&db_type))
+}
+
+pub fn compare_schemas_with_options(
+    source: &[TableSchema],
+    target: &[TableSchema],
+    sql_gen: &dyn SqlGenerator,
+    options: &CompareOptions,
+) -> Vec<DiffItem> {
+    let options = &options.resolved();
+    let folded;
+    let source = if options.fold_identifier_case {
+        folded = fold_to_target_case(source, target);
+        folded.as_slice()
+    } else {
+        source
+    };
     let mut diffs = Vec::new();
     let mut id_counter = 0;
 
@@ -143,7 +577,7 @@ pub fn compare_schemas(
     for table in source {
         if !target_map.contains_key(table.name.as_str()) {
             id_counter += 1;
-            diffs.push(DiffItem {
+            let mut item = DiffItem {
                 id: id_counter.to_string(),
                 diff_type: DiffType::TableAdded,
                 table_name: table.name.clone(),
@@ -153,7 +587,12 @@ pub fn compare_schemas(
                 sql: sql_gen.generate_create_table(table),
                 selected: true,
                 warnings: vec![],
-            });
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
+            };
+            skip_if_columnless(&mut item, table);
+            diffs.push(item);
         }
     }
 
@@ -171,6 +610,9 @@ pub fn compare_schemas(
                 sql: sql_gen.generate_drop_table(&table.name),
                 selected: true,
                 warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             });
         }
     }
@@ -178,26 +620,327 @@ pub fn compare_schemas(
     // Compare existing tables
     for source_table in source {
         if let Some(target_table) = target_map.get(source_table.name.as_str()) {
+            let inbound = inbound_foreign_keys(source, target, &source_table.name);
             compare_tables(
                 source_table,
                 target_table,
+                &inbound,
                 sql_gen,
+                options,
                 &mut diffs,
                 &mut id_counter,
             );
         }
     }
 
+    finish_items(&mut diffs);
+    log_diffs(&diffs);
     diffs
 }
 
+/// Set each item's `severity` from its final `sql`.
+pub(crate) fn finish_items(diffs: &mut [DiffItem]) {
+    for item in diffs {
+        item.severity = Severity::for_item(item);
+    }
+}
+
+/// Trace every produced diff at debug level, for reproducing comparison bugs.
+pub(crate) fn log_diffs(diffs: &[DiffItem]) {
+    for d in diffs {
+        debug!(
+            "Diff #{} {:?} on {}{}",
+            d.id,
+            d.diff_type,
+            d.table_name,
+            d.object_name
+                .as_ref()
+                .map(|o| format!(".{}", o))
+                .unwrap_or_default()
+        );
+    }
+}
+
+/// True when applying column adds/drops (which append at the end) would leave the
+/// target's column order different from the source's.
+fn column_order_diverges(source: &TableSchema, target: &TableSchema) -> bool {
+    let in_target = |name: &str| target.columns.iter().any(|c| c.name == name);
+    let in_source = |name: &str| source.columns.iter().any(|c| c.name == name);
+
+    let resulting = target
+        .columns
+        .iter()
+        .filter(|c| in_source(&c.name))
+        .chain(source.columns.iter().filter(|c| !in_target(&c.name)))
+        .map(|c| c.name.as_str());
+
+    !resulting.eq(source.columns.iter().map(|c| c.name.as_str()))
+}
+
+/// Statements that move columns in place until the order left by column adds
+/// and drops matches the source's, or `None` when the dialect can't move
+/// columns without rebuilding the table.
+fn column_moves(
+    source: &TableSchema,
+    target: &TableSchema,
+    sql_gen: &dyn SqlGenerator,
+) -> Option<Vec<String>> {
+    let in_target = |name: &str| target.columns.iter().any(|c| c.name == name);
+    let in_source = |name: &str| source.columns.iter().any(|c| c.name == name);
+    let mut current: Vec<&str> = target
+        .columns
+        .iter()
+        .filter(|c| in_source(&c.name))
+        .chain(source.columns.iter().filter(|c| !in_target(&c.name)))
+        .map(|c| c.name.as_str())
+        .collect();
+
+    let mut moves = Vec::new();
+    for (i, col) in source.columns.iter().enumerate() {
+        if current[i] == col.name {
+            continue;
+        }
+        let after = i.checked_sub(1).map(|p| source.columns[p].name.as_str());
+        moves.push(sql_gen.generate_move_column(&source.name, col, after)?);
+        let from = current.iter().position(|c| *c == col.name)?;
+        let name = current.remove(from);
+        current.insert(i, name);
+    }
+    Some(moves)
+}
+
+/// Foreign keys on other target tables that reference `table` and are still
+/// in the source, as (referencing table, key). A rebuild has to drop them
+/// before dropping the old table and add them back afterwards.
+fn inbound_foreign_keys<'a>(
+    source: &[TableSchema],
+    target: &'a [TableSchema],
+    table: &str,
+) -> Vec<(&'a str, &'a ForeignKey)> {
+    let kept = |referencing: &str, fk: &ForeignKey| {
+        source
+            .iter()
+            .find(|t| t.name == referencing)
+            .is_some_and(|t| t.foreign_keys.iter().any(|s| s.name == fk.name))
+    };
+    target
+        .iter()
+        .filter(|t| t.name != table)
+        .flat_map(|t| t.foreign_keys.iter().map(move |fk| (t.name.as_str(), fk)))
+        .filter(|(referencing, fk)| fk.ref_table == table && kept(referencing, fk))
+        .collect()
+}
+
+/// The rebuild SQL for `source`, with the `inbound` foreign keys dropped
+/// before it and re-created after it.
+fn rebuild_with_inbound_keys(
+    source: &TableSchema,
+    target: &TableSchema,
+    inbound: &[(&str, &ForeignKey)],
+    sql_gen: &dyn SqlGenerator,
+) -> String {
+    let drops = inbound
+        .iter()
+        .map(|(table, fk)| sql_gen.generate_drop_foreign_key(table, &fk.name));
+    let adds = inbound
+        .iter()
+        .map(|(table, fk)| sql_gen.generate_add_foreign_key(table, fk));
+    drops
+        .chain(std::iter::once(
+            sql_gen.generate_rebuild_table(source, target),
+        ))
+        .chain(adds)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn partitioning_item(
+    source: &TableSchema,
+    target: &TableSchema,
+    sql: String,
+    id_counter: &mut u32,
+) -> DiffItem {
+    *id_counter += 1;
+    DiffItem {
+        source_def: source.partitioning.as_ref().map(partition_detail),
+        target_def: target.partitioning.as_ref().map(partition_detail),
+        sql,
+        ..DiffItem::new(
+            &id_counter.to_string(),
+            DiffType::PartitioningModified,
+            &source.name,
+        )
+    }
+}
+
+fn column_names(table: &TableSchema) -> String {
+    table
+        .columns
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn compare_tables(
     source: &TableSchema,
     target: &TableSchema,
+    inbound: &[(&str, &ForeignKey)],
     sql_gen: &dyn SqlGenerator,
+    options: &CompareOptions,
     diffs: &mut Vec<DiffItem>,
     id_counter: &mut u32,
 ) {
+    let reorder = options.rebuild_for_column_order && column_order_diverges(source, target);
+    let moves = reorder.then(|| column_moves(source, target, sql_gen));
+    if let Some(None) = moves {
+        // The dialect can't move columns: copy into a table of the source's shape.
+        *id_counter += 1;
+        diffs.push(DiffItem {
+            id: id_counter.to_string(),
+            diff_type: DiffType::TableRebuilt,
+            table_name: source.name.clone(),
+            object_name: None,
+            source_def: Some(column_names(source)),
+            target_def: Some(column_names(target)),
+            sql: rebuild_with_inbound_keys(source, target, inbound, sql_gen),
+            selected: true,
+            warnings: vec![],
+            changes: vec![],
+            priority: DiffPriority::Structural,
+            severity: Severity::default(),
+        });
+        return;
+    }
+
+    compare_table_contents(source, target, inbound, sql_gen, options, diffs, id_counter);
+
+    // Columns are moved once the adds and drops above have run.
+    if let Some(Some(moves)) = moves {
+        *id_counter += 1;
+        diffs.push(DiffItem {
+            source_def: Some(column_names(source)),
+            target_def: Some(column_names(target)),
+            sql: moves.join("\n"),
+            ..DiffItem::new(
+                &id_counter.to_string(),
+                DiffType::TableRebuilt,
+                &source.name,
+            )
+        });
+    }
+}
+
+fn compare_table_contents(
+    source: &TableSchema,
+    target: &TableSchema,
+    inbound: &[(&str, &ForeignKey)],
+    sql_gen: &dyn SqlGenerator,
+    options: &CompareOptions,
+    diffs: &mut Vec<DiffItem>,
+    id_counter: &mut u32,
+) {
+    // Nothing else can differ between structurally equal tables.
+    if tables_equal(source, target) {
+        return;
+    }
+
+    if source.partitioning != target.partitioning {
+        // The rest of the table is compared as if its partitioning already matched.
+        let aligned = TableSchema {
+            partitioning: source.partitioning.clone(),
+            ..target.clone()
+        };
+        match sql_gen.generate_alter_partitioning(&source.name, source.partitioning.as_ref()) {
+            // Removing partitioning goes first; partitioning by a new key goes last,
+            // once the unique keys that must include it are in place.
+            Some(sql) if source.partitioning.is_none() => {
+                diffs.push(partitioning_item(source, target, sql, id_counter));
+                compare_table_contents(
+                    source, &aligned, inbound, sql_gen, options, diffs, id_counter,
+                );
+            }
+            Some(sql) => {
+                compare_table_contents(
+                    source, &aligned, inbound, sql_gen, options, diffs, id_counter,
+                );
+                diffs.push(partitioning_item(source, target, sql, id_counter));
+            }
+            // The rebuild recreates the table in the source's shape, which covers the
+            // other changes too. They're still listed, unselected, for a user who'd
+            // rather apply them without repartitioning.
+            None => {
+                let sql = rebuild_with_inbound_keys(source, target, inbound, sql_gen);
+                diffs.push(partitioning_item(source, target, sql, id_counter));
+                let covered = diffs.len();
+                compare_table_contents(
+                    source, &aligned, inbound, sql_gen, options, diffs, id_counter,
+                );
+                for item in &mut diffs[covered..] {
+                    item.selected = false;
+                }
+            }
+        }
+        return;
+    }
+
+    if source.tablespace != target.tablespace {
+        *id_counter += 1;
+        diffs.push(DiffItem {
+            id: id_counter.to_string(),
+            diff_type: DiffType::TablespaceModified,
+            table_name: source.name.clone(),
+            object_name: None,
+            source_def: source.tablespace.clone(),
+            target_def: target.tablespace.clone(),
+            sql: sql_gen.generate_set_tablespace(&source.name, source.tablespace.as_deref()),
+            selected: true,
+            warnings: vec![],
+            changes: vec![],
+            priority: DiffPriority::Structural,
+            severity: Severity::default(),
+        });
+    }
+
+    compare_inheritance(source, target, sql_gen, diffs, id_counter);
+
+    if let Some((changed, current)) = changed_table_options(&source.options, &target.options) {
+        *id_counter += 1;
+        diffs.push(DiffItem {
+            id: id_counter.to_string(),
+            diff_type: DiffType::TableOptionsModified,
+            table_name: source.name.clone(),
+            object_name: None,
+            source_def: Some(table_options_detail(&changed)),
+            target_def: Some(table_options_detail(&current)),
+            sql: sql_gen.generate_set_table_options(&source.name, &changed),
+            selected: true,
+            warnings: vec![],
+            changes: vec![],
+            priority: DiffPriority::Structural,
+            severity: Severity::default(),
+        });
+    }
+
+    if source.row_security != target.row_security {
+        let state = |enabled: bool| if enabled { "enabled" } else { "disabled" }.to_string();
+        *id_counter += 1;
+        diffs.push(DiffItem {
+            id: id_counter.to_string(),
+            diff_type: DiffType::RowSecurityModified,
+            table_name: source.name.clone(),
+            object_name: None,
+            source_def: Some(state(source.row_security)),
+            target_def: Some(state(target.row_security)),
+            sql: sql_gen.generate_set_row_security(&source.name, source.row_security),
+            selected: true,
+            warnings: vec![],
+            changes: vec![],
+            priority: DiffPriority::Structural,
+            severity: Severity::default(),
+        });
+    }
+
     let source_cols: HashMap<&str, &Column> = source
         .columns
         .iter()
@@ -209,9 +952,38 @@ fn compare_tables(
         .map(|c| (c.name.as_str(), c))
         .collect();
 
+    let renames = if options.detect_column_renames {
+        column_renames(source, target)
+    } else {
+        vec![]
+    };
+    for (col, old) in &renames {
+        *id_counter += 1;
+        diffs.push(DiffItem {
+            id: id_counter.to_string(),
+            diff_type: DiffType::ColumnRenamed,
+            table_name: source.name.clone(),
+            object_name: Some(col.name.clone()),
+            source_def: Some(format!("{} {}", col.name, column_detail(col))),
+            target_def: Some(format!("{} {}", old.name, column_detail(old))),
+            sql: sql_gen.generate_rename_column(&source.name, col, old),
+            selected: true,
+            warnings: vec![],
+            changes: column_changes(col, old),
+            priority: DiffPriority::Structural,
+            severity: Severity::default(),
+        });
+    }
+    let renamed_from = |name: &str| renames.iter().any(|(_, old)| old.name == name);
+    let renamed_to = |name: &str| renames.iter().any(|(col, _)| col.name == name);
+
     // Compare columns
     for col in &source.columns {
+        if renamed_to(&col.name) {
+            continue;
+        }
         if !target_cols.contains_key(col.name.as_str()) {
+            let (added, warning) = with_backfill_default(col, options);
             *id_counter += 1;
             diffs.push(DiffItem {
                 id: id_counter.to_string(),
@@ -220,16 +992,26 @@ fn compare_tables(
                 object_name: Some(col.name.clone()),
                 source_def: Some(col.data_type.clone()),
                 target_def: None,
-                sql: sql_gen.generate_add_column(&source.name, col),
+                sql: if options.online_ddl {
+                    sql_gen.generate_add_column_online(&source.name, &added)
+                } else {
+                    sql_gen.generate_add_column(&source.name, &added)
+                },
                 selected: true,
-                warnings: vec![],
+                warnings: warning.into_iter().collect(),
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             });
         } else if let Some(target_col) = target_cols.get(col.name.as_str()) {
+            let col = align_ignored_attributes(col, target_col, options);
+            let col = col.as_ref();
             if col != *target_col {
                 debug!(
                     "Column diff detected: {}.{} | source: {:?} | target: {:?}",
                     source.name, col.name, col, target_col
                 );
+                let changes = column_changes(col, target_col);
                 *id_counter += 1;
                 diffs.push(DiffItem {
                     id: id_counter.to_string(),
@@ -238,16 +1020,19 @@ fn compare_tables(
                     object_name: Some(col.name.clone()),
                     source_def: Some(column_detail(col)),
                     target_def: Some(column_detail(target_col)),
-                    sql: sql_gen.generate_modify_column(&source.name, col),
+                    sql: sql_gen.generate_alter_column(&source.name, col, target_col),
                     selected: true,
-                    warnings: vec![],
+                    warnings: octet_length_warning(col, target_col).into_iter().collect(),
+                    priority: DiffPriority::for_column_changes(&changes),
+                    severity: Severity::default(),
+                    changes,
                 });
             }
         }
     }
 
     for col in &target.columns {
-        if !source_cols.contains_key(col.name.as_str()) {
+        if !source_cols.contains_key(col.name.as_str()) && !renamed_from(&col.name) {
             *id_counter += 1;
             diffs.push(DiffItem {
                 id: id_counter.to_string(),
@@ -259,34 +1044,119 @@ fn compare_tables(
                 sql: sql_gen.generate_drop_column(&source.name, &col.name),
                 selected: true,
                 warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             });
         }
     }
 
-    // Compare indexes, foreign keys, unique constraints
-    compare_indexes(source, target, sql_gen, diffs, id_counter);
-    compare_foreign_keys(source, target, sql_gen, diffs, id_counter);
-    compare_unique_constraints(source, target, sql_gen, diffs, id_counter);
+    // The key goes on once the columns it covers exist.
+    compare_primary_key(source, target, sql_gen, diffs, id_counter);
+    // Compare indexes, foreign keys, unique, exclusion and check constraints, and policies
+    compare_indexes(source, target, sql_gen, options, diffs, id_counter);
+    compare_foreign_keys(source, target, sql_gen, options, diffs, id_counter);
+    compare_unique_constraints(source, target, sql_gen, options, diffs, id_counter);
+    compare_exclusion_constraints(source, target, sql_gen, diffs, id_counter);
+    compare_check_constraints(source, target, sql_gen, diffs, id_counter);
+    compare_policies(source, target, sql_gen, diffs, id_counter);
+}
+
+/// Attach the table to the parents it `INHERITS` from in `source` but not in
+/// `target`, and detach it from the ones `source` doesn't have. The order of the
+/// parents isn't compared.
+pub(crate) fn compare_inheritance(
+    source: &TableSchema,
+    target: &TableSchema,
+    sql_gen: &dyn SqlGenerator,
+    diffs: &mut Vec<DiffItem>,
+    id_counter: &mut u32,
+) {
+    let missing = |of: &TableSchema, from: &TableSchema| -> Vec<String> {
+        of.inherits
+            .iter()
+            .filter(|p| !from.inherits.contains(p))
+            .cloned()
+            .collect()
+    };
+    let add = missing(source, target);
+    let remove = missing(target, source);
+    if add.is_empty() && remove.is_empty() {
+        return;
+    }
+    let parents = |t: &TableSchema| (!t.inherits.is_empty()).then(|| t.inherits.join(", "));
+    *id_counter += 1;
+    diffs.push(DiffItem {
+        source_def: parents(source),
+        target_def: parents(target),
+        sql: sql_gen.generate_set_inherits(&source.name, &add, &remove),
+        ..DiffItem::new(
+            &id_counter.to_string(),
+            DiffType::InheritanceModified,
+            &source.name,
+        )
+    });
+}
+
+fn compare_primary_key(
+    source: &TableSchema,
+    target: &TableSchema,
+    sql_gen: &dyn SqlGenerator,
+    diffs: &mut Vec<DiffItem>,
+    id_counter: &mut u32,
+) {
+    if primary_key_columns(source) == primary_key_columns(target) {
+        return;
+    }
+    let detail = |t: &TableSchema| primary_key_columns(t).map(|cols| cols.join(", "));
+    *id_counter += 1;
+    diffs.push(DiffItem {
+        source_def: detail(source),
+        target_def: detail(target),
+        sql: sql_gen.generate_alter_primary_key(
+            &source.name,
+            target.primary_key.as_ref(),
+            source.primary_key.as_ref(),
+        ),
+        ..DiffItem::new(
+            &id_counter.to_string(),
+            DiffType::PrimaryKeyModified,
+            &source.name,
+        )
+    });
 }
 
 pub(crate) fn compare_indexes(
     source: &TableSchema,
     target: &TableSchema,
     sql_gen: &dyn SqlGenerator,
+    options: &CompareOptions,
     diffs: &mut Vec<DiffItem>,
     id_counter: &mut u32,
 ) {
+    let generate_add: fn(&dyn SqlGenerator, &str, &Index) -> String = if options.online_ddl {
+        |sg, t, idx| sg.generate_add_index_online(t, idx)
+    } else {
+        |sg, t, idx| sg.generate_add_index(t, idx)
+    };
+    let aligned;
+    let target_items = if options.name_sensitivity != NameSensitivity::Strict {
+        aligned = align_index_names(&source.indexes, &target.indexes, options.name_sensitivity);
+        &aligned
+    } else {
+        &target.indexes
+    };
     compare_named_items(
         &DiffConfig {
             table_name: &source.name,
             source_items: &source.indexes,
-            target_items: &target.indexes,
+            target_items,
             added_type: DiffType::IndexAdded,
             removed_type: DiffType::IndexRemoved,
             modified_type: DiffType::IndexModified,
-            source_def: |idx| idx.columns.join(", "),
-            target_def: |idx| idx.columns.join(", "),
-            generate_add: |sg, t, idx| sg.generate_add_index(t, idx),
+            source_def: index_detail,
+            target_def: index_detail,
+            generate_add,
             generate_drop: |sg, t, name| sg.generate_drop_index(t, name),
         },
         sql_gen,
@@ -299,20 +1169,38 @@ pub(crate) fn compare_foreign_keys(
     source: &TableSchema,
     target: &TableSchema,
     sql_gen: &dyn SqlGenerator,
+    options: &CompareOptions,
     diffs: &mut Vec<DiffItem>,
     id_counter: &mut u32,
 ) {
+    let generate_add: fn(&dyn SqlGenerator, &str, &ForeignKey) -> String =
+        if options.validate_foreign_keys_separately {
+            |sg, t, fk| sg.generate_add_foreign_key_deferred_validation(t, fk)
+        } else {
+            |sg, t, fk| sg.generate_add_foreign_key(t, fk)
+        };
+    let aligned;
+    let target_items = if options.name_sensitivity != NameSensitivity::Strict {
+        aligned = align_foreign_key_names(
+            &source.foreign_keys,
+            &target.foreign_keys,
+            options.name_sensitivity,
+        );
+        &aligned
+    } else {
+        &target.foreign_keys
+    };
     compare_named_items(
         &DiffConfig {
             table_name: &source.name,
             source_items: &source.foreign_keys,
-            target_items: &target.foreign_keys,
+            target_items,
             added_type: DiffType::ForeignKeyAdded,
             removed_type: DiffType::ForeignKeyRemoved,
             modified_type: DiffType::ForeignKeyModified,
             source_def: |fk| format!("-> {}", fk.ref_table),
             target_def: |fk| format!("-> {}", fk.ref_table),
-            generate_add: |sg, t, fk| sg.generate_add_foreign_key(t, fk),
+            generate_add,
             generate_drop: |sg, t, name| sg.generate_drop_foreign_key(t, name),
         },
         sql_gen,
@@ -321,18 +1209,170 @@ pub(crate) fn compare_foreign_keys(
     );
 }
 
+/// Whether `name` looks like one the engine made up rather than one someone
+/// chose: MySQL's `orders_ibfk_1`, or Postgres's `<table>_<columns>_fkey`,
+/// `_key`, `_pkey`, `_idx`, `_excl` and `_check` suffixes.
+pub(crate) fn is_generated_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let numbered_ibfk = name
+        .rsplit_once("_ibfk_")
+        .is_some_and(|(_, n)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    numbered_ibfk
+        || ["_fkey", "_key", "_pkey", "_idx", "_excl", "_check"]
+            .iter()
+            .any(|suffix| name.len() > suffix.len() && name.ends_with(suffix))
+}
+
+/// Give each target item the name of a source item with the same signature that
+/// has no same-named target item, so generated names don't cause drop/add churn.
+/// Under `IgnoreGenerated` both names must look generated. `rename` builds the
+/// renamed target item from the source and target items.
+fn align_names<T: NamedItem + Clone, K: PartialEq>(
+    source: &[T],
+    target: &[T],
+    sensitivity: NameSensitivity,
+    signature: impl Fn(&T) -> K,
+    rename: impl Fn(&T, &T) -> T,
+) -> Vec<T> {
+    let name_ignored = |s: &T, t: &T| match sensitivity {
+        NameSensitivity::Strict => false,
+        NameSensitivity::IgnoreGenerated => {
+            is_generated_name(s.name()) && is_generated_name(t.name())
+        }
+        NameSensitivity::IgnoreAll => true,
+    };
+    let mut claimed: Vec<&str> = Vec::new();
+    target
+        .iter()
+        .map(|t| {
+            if source.iter().any(|s| s.name() == t.name()) {
+                return t.clone();
+            }
+            let same_signature = source.iter().find(|s| {
+                signature(s) == signature(t)
+                    && name_ignored(s, t)
+                    && !target.iter().any(|o| o.name() == s.name())
+                    && !claimed.contains(&s.name())
+            });
+            match same_signature {
+                Some(s) => {
+                    claimed.push(s.name());
+                    rename(s, t)
+                }
+                None => t.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Give each target unique constraint the name of the source constraint that covers
+/// the same column set.
+fn align_unique_names(
+    source: &[UniqueConstraint],
+    target: &[UniqueConstraint],
+    sensitivity: NameSensitivity,
+) -> Vec<UniqueConstraint> {
+    align_names(
+        source,
+        target,
+        sensitivity,
+        |uc| {
+            let mut cols = uc.columns.clone();
+            cols.sort();
+            cols
+        },
+        |s, _| s.clone(),
+    )
+}
+
+/// Give each target index the name of the source index with the same columns,
+/// uniqueness, access method and definition.
+fn align_index_names(
+    source: &[Index],
+    target: &[Index],
+    sensitivity: NameSensitivity,
+) -> Vec<Index> {
+    // A definition-only index spells its own name; leave it out of the signature.
+    let definition = |idx: &Index| {
+        idx.expression
+            .as_ref()
+            .map(|e| e.replacen(&idx.name, "", 1))
+    };
+    align_names(
+        source,
+        target,
+        sensitivity,
+        |idx| {
+            (
+                idx.columns.clone(),
+                idx.unique,
+                idx.index_type.to_lowercase(),
+                definition(idx),
+            )
+        },
+        |s, t| Index {
+            name: s.name.clone(),
+            expression: s.expression.clone(),
+            ..t.clone()
+        },
+    )
+}
+
+/// Give each target foreign key the name of the source key with the same columns
+/// and referenced columns.
+fn align_foreign_key_names(
+    source: &[ForeignKey],
+    target: &[ForeignKey],
+    sensitivity: NameSensitivity,
+) -> Vec<ForeignKey> {
+    align_names(
+        source,
+        target,
+        sensitivity,
+        |fk| {
+            (
+                fk.columns.clone(),
+                fk.ref_table.clone(),
+                fk.ref_columns.clone(),
+            )
+        },
+        |s, t| ForeignKey {
+            name: s.name.clone(),
+            ..t.clone()
+        },
+    )
+}
+
 pub(crate) fn compare_unique_constraints(
     source: &TableSchema,
     target: &TableSchema,
     sql_gen: &dyn SqlGenerator,
+    options: &CompareOptions,
     diffs: &mut Vec<DiffItem>,
     id_counter: &mut u32,
 ) {
+    let aligned;
+    // Matching by columns ignores every name; otherwise the sensitivity decides.
+    let sensitivity = if options.match_unique_by_columns {
+        NameSensitivity::IgnoreAll
+    } else {
+        options.name_sensitivity
+    };
+    let target_items = if sensitivity != NameSensitivity::Strict {
+        aligned = align_unique_names(
+            &source.unique_constraints,
+            &target.unique_constraints,
+            sensitivity,
+        );
+        &aligned
+    } else {
+        &target.unique_constraints
+    };
     compare_named_items(
         &DiffConfig {
             table_name: &source.name,
             source_items: &source.unique_constraints,
-            target_items: &target.unique_constraints,
+            target_items,
             added_type: DiffType::UniqueConstraintAdded,
             removed_type: DiffType::UniqueConstraintRemoved,
             modified_type: DiffType::UniqueConstraintModified,
@@ -346,3 +1386,149 @@ pub(crate) fn compare_unique_constraints(
         diffs,
     );
 }
+
+fn compare_exclusion_constraints(
+    source: &TableSchema,
+    target: &TableSchema,
+    sql_gen: &dyn SqlGenerator,
+    diffs: &mut Vec<DiffItem>,
+    id_counter: &mut u32,
+) {
+    compare_named_items(
+        &DiffConfig {
+            table_name: &source.name,
+            source_items: &source.exclusion_constraints,
+            target_items: &target.exclusion_constraints,
+            added_type: DiffType::ExclusionConstraintAdded,
+            removed_type: DiffType::ExclusionConstraintRemoved,
+            modified_type: DiffType::ExclusionConstraintModified,
+            source_def: |ec| ec.definition.clone(),
+            target_def: |ec| ec.definition.clone(),
+            generate_add: |sg, t, ec| sg.generate_add_exclusion(t, ec),
+            generate_drop: |sg, t, name| sg.generate_drop_exclusion(t, name),
+        },
+        sql_gen,
+        id_counter,
+        diffs,
+    );
+}
+
+/// A check whose expression or enforcement differs is modified: dropped and
+/// added again.
+fn compare_check_constraints(
+    source: &TableSchema,
+    target: &TableSchema,
+    sql_gen: &dyn SqlGenerator,
+    diffs: &mut Vec<DiffItem>,
+    id_counter: &mut u32,
+) {
+    compare_named_items(
+        &DiffConfig {
+            table_name: &source.name,
+            source_items: &source.check_constraints,
+            target_items: &target.check_constraints,
+            added_type: DiffType::CheckConstraintAdded,
+            removed_type: DiffType::CheckConstraintRemoved,
+            modified_type: DiffType::CheckConstraintModified,
+            source_def: check_detail,
+            target_def: check_detail,
+            generate_add: |sg, t, check| sg.generate_add_check(t, check),
+            generate_drop: |sg, t, name| sg.generate_drop_check(t, name),
+        },
+        sql_gen,
+        id_counter,
+        diffs,
+    );
+}
+
+fn check_detail(check: &CheckConstraint) -> String {
+    if check.enforced {
+        format!("CHECK ({})", check.expression)
+    } else {
+        format!("CHECK ({}) NOT ENFORCED", check.expression)
+    }
+}
+
+/// `RANGE (created_at): p2023 VALUES LESS THAN (2024), ...`
+fn partition_detail(spec: &PartitionSpec) -> String {
+    let partitions: Vec<String> = spec
+        .partitions
+        .iter()
+        .map(|p| format!("{} {}", p.name, p.bound).trim_end().to_string())
+        .collect();
+    format!(
+        "{} ({}): {}",
+        spec.method,
+        spec.expression,
+        partitions.join(", ")
+    )
+}
+
+/// `FOR SELECT TO app USING (...)`, prefixed with `RESTRICTIVE` where it applies.
+fn policy_detail(policy: &Policy) -> String {
+    let mut detail = if policy.permissive {
+        String::new()
+    } else {
+        "RESTRICTIVE ".to_string()
+    };
+    detail.push_str(&format!(
+        "FOR {} TO {}",
+        policy.command,
+        policy.roles.join(", ")
+    ));
+    if let Some(using) = &policy.using_expr {
+        detail.push_str(&format!(" USING ({})", using));
+    }
+    if let Some(check) = &policy.check_expr {
+        detail.push_str(&format!(" WITH CHECK ({})", check));
+    }
+    detail
+}
+
+fn compare_policies(
+    source: &TableSchema,
+    target: &TableSchema,
+    sql_gen: &dyn SqlGenerator,
+    diffs: &mut Vec<DiffItem>,
+    id_counter: &mut u32,
+) {
+    let start = diffs.len();
+    compare_named_items(
+        &DiffConfig {
+            table_name: &source.name,
+            source_items: &source.policies,
+            target_items: &target.policies,
+            added_type: DiffType::PolicyAdded,
+            removed_type: DiffType::PolicyRemoved,
+            modified_type: DiffType::PolicyModified,
+            source_def: policy_detail,
+            target_def: policy_detail,
+            generate_add: |sg, t, p| sg.generate_create_policy(t, p),
+            generate_drop: |sg, t, name| sg.generate_drop_policy(t, name),
+        },
+        sql_gen,
+        id_counter,
+        diffs,
+    );
+
+    // Roles and expressions change in place; a new command or permissiveness
+    // keeps the drop and re-create.
+    for item in &mut diffs[start..] {
+        if item.diff_type != DiffType::PolicyModified {
+            continue;
+        }
+        let name = item.object_name.as_deref();
+        let find = |policies: &[Policy]| {
+            policies
+                .iter()
+                .find(|p| Some(p.name.as_str()) == name)
+                .cloned()
+        };
+        match (find(&source.policies), find(&target.policies)) {
+            (Some(s), Some(t)) if s.command == t.command && s.permissive == t.permissive => {
+                item.sql = sql_gen.generate_alter_policy(&source.name, &s);
+            }
+            _ => {}
+        }
+    }
+}
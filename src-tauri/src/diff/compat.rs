@@ -0,0 +1,135 @@
+use crate::models::DiffItem;
+
+/// Features behind versioned comments are skipped by older servers.
+const SKIPPED: &str = "they will be silently left out";
+
+/// MySQL features the generated SQL may use: the release that added each, the
+/// text that marks it in a statement, and what an older server does with it.
+const MYSQL_FEATURES: &[(&str, [u32; 3], &str, &str)] = &[
+    (
+        "invisible indexes",
+        [8, 0, 0],
+        "/*!80000 INVISIBLE */",
+        SKIPPED,
+    ),
+    ("column SRIDs", [8, 0, 3], "/*!80003 SRID", SKIPPED),
+    (
+        "expression defaults",
+        [8, 0, 13],
+        " DEFAULT (",
+        "the statements will fail",
+    ),
+    (
+        "enforced check constraints",
+        [8, 0, 16],
+        " CHECK (",
+        "the checks will be parsed but not enforced",
+    ),
+    (
+        "invisible columns",
+        [8, 0, 23],
+        "/*!80023 INVISIBLE */",
+        SKIPPED,
+    ),
+];
+
+/// The leading `major.minor.patch` of a server version such as `5.7.44-log`.
+fn version_numbers(version: &str) -> [u32; 3] {
+    let mut numbers = [0; 3];
+    for (slot, part) in numbers.iter_mut().zip(version.split(['.', '-'])) {
+        *slot = part.parse().unwrap_or(0);
+    }
+    numbers
+}
+
+/// Warnings for SQL that uses MySQL features the target server predates, when
+/// the target runs an older MySQL than the source, so a sync doesn't fail (or
+/// quietly lose part of the schema) halfway through. MariaDB numbers its
+/// releases differently, so a MariaDB on either side gets no warnings.
+pub fn version_compatibility_warnings(
+    items: &[DiffItem],
+    source_version: &str,
+    target_version: &str,
+) -> Vec<String> {
+    let is_mariadb = |v: &str| v.to_ascii_lowercase().contains("mariadb");
+    if is_mariadb(source_version) || is_mariadb(target_version) {
+        return vec![];
+    }
+    let target = version_numbers(target_version);
+    if target >= version_numbers(source_version) {
+        return vec![];
+    }
+
+    let mut warnings = Vec::new();
+    for (feature, since, marker, outcome) in MYSQL_FEATURES {
+        if target >= *since {
+            continue;
+        }
+        let mut objects: Vec<String> = Vec::new();
+        for item in items.iter().filter(|i| i.sql.contains(marker)) {
+            let object = match &item.object_name {
+                Some(name) => format!("{}.{}", item.table_name, name),
+                None => item.table_name.clone(),
+            };
+            if !objects.contains(&object) {
+                objects.push(object);
+            }
+        }
+        if objects.is_empty() {
+            continue;
+        }
+        warnings.push(format!(
+            "Target MySQL {} predates {} ({}.{}.{}) used by the source MySQL {}; {}: {}",
+            target_version,
+            feature,
+            since[0],
+            since[1],
+            since[2],
+            source_version,
+            outcome,
+            objects.join(", ")
+        ));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::sql_item;
+    use crate::models::*;
+
+    #[test]
+    fn expression_default_on_an_older_target_is_warned_about() {
+        let items = [
+            DiffItem {
+                object_name: Some("token".to_string()),
+                ..sql_item(
+                    DiffType::ColumnAdded,
+                    "users",
+                    "ALTER TABLE `users` ADD COLUMN `token` varchar(36) NOT NULL DEFAULT (uuid());",
+                )
+            },
+            DiffItem {
+                object_name: Some("age".to_string()),
+                ..sql_item(
+                    DiffType::ColumnAdded,
+                    "users",
+                    "ALTER TABLE `users` ADD COLUMN `age` int NULL DEFAULT 0;",
+                )
+            },
+        ];
+
+        let warnings = version_compatibility_warnings(&items, "8.0.36", "5.7.44-log");
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].contains("expression defaults"),
+            "{}",
+            warnings[0]
+        );
+        assert!(warnings[0].ends_with(": users.token"), "{}", warnings[0]);
+
+        assert!(version_compatibility_warnings(&items, "8.0.36", "8.0.36").is_empty());
+        assert!(version_compatibility_warnings(&items, "8.0.36", "10.4.32-MariaDB").is_empty());
+    }
+}
@@ -0,0 +1,338 @@
+use anyhow::Result;
+
+use crate::db::ddl::parse_schema_sql;
+use crate::db::sql_generator_for;
+use crate::diff::compare_snapshots;
+use crate::models::*;
+
+/// Generate `CREATE TABLE` for every table, parse the DDL back and report what
+/// didn't survive the round trip. An empty result means the generator and the
+/// parser agree on every table; anything else is a bug in one of them.
+pub fn self_consistency_check(
+    tables: &[TableSchema],
+    db_type: DbType,
+) -> Result<Vec<Inconsistency>> {
+    let sql_gen = sql_generator_for(&db_type);
+    let ddl: Vec<String> = tables
+        .iter()
+        .map(|t| sql_gen.generate_create_table(t))
+        .collect();
+    let parsed = parse_schema_sql(&ddl.join("\n"), &db_type)?;
+
+    Ok(compare_snapshots(tables, &parsed, db_type)
+        .into_iter()
+        .map(|item| Inconsistency {
+            table_name: item.table_name,
+            object_name: item.object_name,
+            diff_type: item.diff_type,
+            expected: item.source_def,
+            actual: item.target_def,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::{column, table};
+
+    fn not_null(column: Column) -> Column {
+        Column {
+            nullable: false,
+            ..column
+        }
+    }
+
+    /// A table of `columns` keyed by its `id` column.
+    fn keyed_table(name: &str, columns: Vec<Column>) -> TableSchema {
+        TableSchema {
+            primary_key: Some(PrimaryKey {
+                name: None,
+                columns: vec!["id".to_string()],
+            }),
+            ..table(name, columns)
+        }
+    }
+
+    fn index(name: &str, column: &str, index_type: &str) -> Index {
+        Index {
+            name: name.to_string(),
+            columns: vec![column.to_string()],
+            unique: false,
+            index_type: index_type.to_string(),
+            tablespace: None,
+            visible: true,
+            expression: None,
+            storage_params: vec![],
+            comment: None,
+        }
+    }
+
+    fn foreign_key(name: &str, ref_table: &str) -> ForeignKey {
+        ForeignKey {
+            name: name.to_string(),
+            columns: vec!["user_id".to_string()],
+            ref_table: ref_table.to_string(),
+            ref_columns: vec!["id".to_string()],
+            on_delete: "CASCADE".to_string(),
+            on_update: "NO ACTION".to_string(),
+            deferrable: false,
+            initially_deferred: false,
+            match_type: None,
+        }
+    }
+
+    #[test]
+    fn mysql_tables_round_trip() {
+        let mut id = not_null(column("id", "bigint unsigned"));
+        id.auto_increment = true;
+        let mut status = not_null(column("status", "enum('new','paid')"));
+        status.comment = Some("order state".to_string());
+        let mut attempts = not_null(column("attempts", "tinyint"));
+        attempts.default_value = Some("0".to_string());
+        let mut updated = column("updated_at", "timestamp");
+        updated.on_update = Some("CURRENT_TIMESTAMP".to_string());
+        let mut orders = keyed_table(
+            "orders",
+            vec![
+                id,
+                not_null(column("user_id", "int")),
+                status,
+                attempts,
+                column("note", "varchar(255)"),
+                updated,
+            ],
+        );
+        orders.indexes = vec![index("idx_user", "user_id", "BTREE")];
+        orders.foreign_keys = vec![foreign_key("fk_user", "users")];
+        orders.unique_constraints = vec![UniqueConstraint {
+            name: "uq_note".to_string(),
+            columns: vec!["note".to_string()],
+        }];
+
+        let report = self_consistency_check(&[orders], DbType::MySQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+    }
+
+    #[test]
+    fn postgres_tables_round_trip() {
+        let mut id = not_null(column("id", "integer"));
+        id.auto_increment = true;
+        id.sequence = Some("orders_id_seq".to_string());
+        let mut total = not_null(column("total", "numeric(10,2)"));
+        total.default_value = Some("0".to_string());
+        let mut orders = keyed_table(
+            "orders",
+            vec![
+                id,
+                not_null(column("user_id", "integer")),
+                total,
+                column("email", "varchar(255)"),
+                column("created_at", "timestamp with time zone"),
+                column("tags", "text[]"),
+            ],
+        );
+        orders.indexes = vec![index("idx_orders_user", "user_id", "btree")];
+        orders.foreign_keys = vec![foreign_key("orders_user_id_fkey", "users")];
+
+        let report = self_consistency_check(&[orders], DbType::PostgreSQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+    }
+
+    #[test]
+    fn inheriting_table_round_trips_after_its_parent() {
+        let cities = keyed_table(
+            "cities",
+            vec![not_null(column("id", "integer")), column("name", "text")],
+        );
+        let mut capitals = keyed_table(
+            "capitals",
+            vec![
+                not_null(column("id", "integer")),
+                column("name", "text"),
+                column("state", "text"),
+            ],
+        );
+        capitals.inherits = vec!["cities".to_string()];
+        let tables = [capitals, cities];
+
+        let sql_gen = sql_generator_for(&DbType::PostgreSQL);
+        let ddl = sql_gen.generate_create_table(&tables[0]);
+        assert!(ddl.contains(") INHERITS (\"cities\");"), "{}", ddl);
+        let parsed = parse_schema_sql(&ddl, &DbType::PostgreSQL).unwrap();
+        assert_eq!(parsed[0].inherits, vec!["cities".to_string()]);
+        let report = self_consistency_check(&tables, DbType::PostgreSQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+
+        // The child is listed first but created after its parent.
+        let items = compare_snapshots(&tables, &[], DbType::PostgreSQL);
+        let ordered = crate::diff::order_for_execution(items, &tables, sql_gen);
+        let created: Vec<&str> = ordered.iter().map(|i| i.table_name.as_str()).collect();
+        assert_eq!(created, ["cities", "capitals"]);
+    }
+
+    #[test]
+    fn index_comment_round_trips() {
+        let mut orders = keyed_table(
+            "orders",
+            vec![
+                not_null(column("id", "int")),
+                not_null(column("user_id", "int")),
+            ],
+        );
+        let mut by_user = index("idx_user", "user_id", "BTREE");
+        by_user.comment = Some("customer's orders".to_string());
+        orders.indexes = vec![by_user];
+
+        let sql_gen = sql_generator_for(&DbType::MySQL);
+        let ddl = sql_gen.generate_create_table(&orders);
+        assert!(ddl.contains("COMMENT 'customer''s orders'"), "{}", ddl);
+        let parsed = parse_schema_sql(&ddl, &DbType::MySQL).unwrap();
+        assert_eq!(parsed[0].indexes, orders.indexes);
+        let report = self_consistency_check(std::slice::from_ref(&orders), DbType::MySQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+
+        // A changed comment alone is a modified index.
+        let mut recommented = orders.clone();
+        recommented.indexes[0].comment = Some("by customer".to_string());
+        let items = compare_snapshots(&[recommented], &[orders], DbType::MySQL);
+        let types: Vec<&DiffType> = items.iter().map(|i| &i.diff_type).collect();
+        assert_eq!(types, [&DiffType::IndexModified]);
+    }
+
+    #[test]
+    fn deferrable_foreign_keys_round_trip() {
+        let mut fk = foreign_key("orders_user_id_fkey", "users");
+        fk.deferrable = true;
+        fk.initially_deferred = true;
+        fk.match_type = Some("FULL".to_string());
+        let mut orders = keyed_table(
+            "orders",
+            vec![
+                not_null(column("id", "integer")),
+                not_null(column("user_id", "integer")),
+            ],
+        );
+        orders.foreign_keys = vec![fk];
+
+        let report = self_consistency_check(&[orders], DbType::PostgreSQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+    }
+
+    #[test]
+    fn not_enforced_check_constraint_round_trips() {
+        let mut users = keyed_table(
+            "users",
+            vec![not_null(column("id", "int")), column("age", "int")],
+        );
+        users.check_constraints = vec![CheckConstraint {
+            name: "chk_age".to_string(),
+            expression: "(`age` > 0)".to_string(),
+            enforced: false,
+        }];
+
+        let ddl = sql_generator_for(&DbType::MySQL).generate_create_table(&users);
+        assert!(ddl.contains("NOT ENFORCED"), "{}", ddl);
+        let report = self_consistency_check(std::slice::from_ref(&users), DbType::MySQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+
+        // Turning enforcement on is a change to the constraint.
+        let mut enforced = users.clone();
+        enforced.check_constraints[0].enforced = true;
+        let diffs = compare_snapshots(&[enforced], &[users], DbType::MySQL);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].diff_type, DiffType::CheckConstraintModified);
+    }
+
+    #[test]
+    fn gin_index_storage_parameters_round_trip() {
+        let mut docs = keyed_table(
+            "docs",
+            vec![not_null(column("id", "integer")), column("tags", "text[]")],
+        );
+        let mut tags = index("idx_docs_tags", "tags", "gin");
+        tags.storage_params = vec!["fastupdate=off".to_string()];
+        let mut by_id = index("idx_docs_id", "id", "btree");
+        by_id.storage_params = vec!["fillfactor=70".to_string()];
+        docs.indexes = vec![by_id, tags];
+
+        let ddl = sql_generator_for(&DbType::PostgreSQL).generate_create_table(&docs);
+        assert!(
+            ddl.contains("USING gin (\"tags\") WITH (fastupdate=off)"),
+            "{}",
+            ddl
+        );
+        assert!(ddl.contains("(\"id\") WITH (fillfactor=70)"), "{}", ddl);
+        let report =
+            self_consistency_check(std::slice::from_ref(&docs), DbType::PostgreSQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+
+        let mut denser = docs.clone();
+        denser.indexes[0].storage_params = vec!["fillfactor=90".to_string()];
+        let diffs = compare_snapshots(&[denser], &[docs], DbType::PostgreSQL);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].diff_type, DiffType::IndexModified);
+    }
+
+    #[test]
+    fn range_partitioned_tables_round_trip() {
+        let spec = |bounds: [(&str, &str); 2], expression: &str| PartitionSpec {
+            method: "RANGE".to_string(),
+            expression: expression.to_string(),
+            partitions: bounds
+                .iter()
+                .map(|(name, bound)| Partition {
+                    name: name.to_string(),
+                    bound: bound.to_string(),
+                })
+                .collect(),
+        };
+        let mut events = keyed_table(
+            "events",
+            vec![
+                not_null(column("id", "int")),
+                not_null(column("created", "date")),
+            ],
+        );
+        events.primary_key = None;
+        events.partitioning = Some(spec(
+            [
+                ("p2023", "VALUES LESS THAN (2024)"),
+                ("pmax", "VALUES LESS THAN MAXVALUE"),
+            ],
+            "year(`created`)",
+        ));
+        let report = self_consistency_check(&[events.clone()], DbType::MySQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+
+        events.columns[0].data_type = "integer".to_string();
+        events.partitioning = Some(spec(
+            [
+                (
+                    "events_2023",
+                    "FOR VALUES FROM ('2023-01-01') TO ('2024-01-01')",
+                ),
+                (
+                    "events_2024",
+                    "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')",
+                ),
+            ],
+            "created",
+        ));
+        let report = self_consistency_check(&[events], DbType::PostgreSQL).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+    }
+
+    #[test]
+    fn lost_attribute_is_reported() {
+        // Postgres keeps column comments out of CREATE TABLE.
+        let mut email = column("email", "text");
+        email.comment = Some("login".to_string());
+        let users = keyed_table("users", vec![not_null(column("id", "integer")), email]);
+
+        let report = self_consistency_check(&[users], DbType::PostgreSQL).unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].diff_type, DiffType::ColumnModified);
+        assert_eq!(report[0].object_name.as_deref(), Some("email"));
+    }
+}
@@ -1,9 +1,24 @@
 use std::collections::HashMap;
 
 use crate::db::SqlGenerator;
+use crate::diff::comparator::{
+    align_ignored_attributes, compare_inheritance, skip_if_columnless, with_backfill_default,
+};
+use crate::diff::identifiers::fold_to_target_case;
 use crate::models::*;
 use crate::types::{TypeMapper, TypeMapping};
 
+/// Banner for a comparison between different engines, whose translated types and
+/// defaults are best-effort; `None` when both sides are the same engine.
+pub fn cross_engine_warning(source: &DbType, target: &DbType) -> Option<String> {
+    (source != target).then(|| {
+        format!(
+            "Comparing {} to {}: column types and defaults were translated between engines; review the generated SQL before applying it",
+            source, target
+        )
+    })
+}
+
 /// Compare schemas across different database types using canonical type mapping.
 pub fn compare_schemas_cross(
     source: &[TableSchema],
@@ -12,6 +27,32 @@ pub fn compare_schemas_cross(
     source_mapper: &dyn TypeMapper,
     target_mapper: &dyn TypeMapper,
 ) -> Vec<DiffItem> {
+    compare_schemas_cross_with_options(
+        source,
+        target,
+        sql_gen,
+        source_mapper,
+        target_mapper,
+        &CompareOptions::default(),
+    )
+}
+
+pub fn compare_schemas_cross_with_options(
+    source: &[TableSchema],
+    target: &[TableSchema],
+    sql_gen: &dyn SqlGenerator,
+    source_mapper: &dyn TypeMapper,
+    target_mapper: &dyn TypeMapper,
+    options: &CompareOptions,
+) -> Vec<DiffItem> {
+    let options = &options.resolved();
+    let folded;
+    let source = if options.fold_identifier_case {
+        folded = fold_to_target_case(source, target);
+        folded.as_slice()
+    } else {
+        source
+    };
     let mut diffs = Vec::new();
     let mut id_counter: u32 = 0;
 
@@ -26,7 +67,7 @@ pub fn compare_schemas_cross(
             id_counter += 1;
             let (mapped_table, warnings, prerequisites) =
                 map_table_columns(table, source_mapper, target_mapper);
-            diffs.push(DiffItem {
+            let mut item = DiffItem {
                 id: id_counter.to_string(),
                 diff_type: DiffType::TableAdded,
                 table_name: table.name.clone(),
@@ -43,7 +84,12 @@ pub fn compare_schemas_cross(
                 },
                 selected: true,
                 warnings,
-            });
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
+            };
+            skip_if_columnless(&mut item, table);
+            diffs.push(item);
         }
     }
 
@@ -61,6 +107,9 @@ pub fn compare_schemas_cross(
                 sql: sql_gen.generate_drop_table(&table.name),
                 selected: true,
                 warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             });
         }
     }
@@ -74,12 +123,15 @@ pub fn compare_schemas_cross(
                 sql_gen,
                 source_mapper,
                 target_mapper,
+                options,
                 &mut diffs,
                 &mut id_counter,
             );
         }
     }
 
+    super::comparator::finish_items(&mut diffs);
+    super::comparator::log_diffs(&diffs);
     diffs
 }
 
@@ -137,6 +189,12 @@ fn map_table_columns(
                 auto_increment: col.auto_increment,
                 comment: col.comment.clone(),
                 ordinal_position: col.ordinal_position,
+                visible: col.visible,
+                on_update: col.on_update.clone(),
+                sequence: col.sequence.clone(),
+                generated: col.generated.clone(),
+                srid: col.srid,
+                octet_length: None,
             })
         })
         .collect();
@@ -166,7 +224,9 @@ fn map_table_columns(
         }
     });
 
-    // Filter indexes: remove skipped columns, drop index if no columns remain
+    // Filter indexes: remove skipped columns, drop index if no columns remain.
+    // Definition-only (expression) indexes have no columns, so they are dropped
+    // too: their SQL is specific to the source dialect.
     let mapped_indexes: Vec<Index> = table
         .indexes
         .iter()
@@ -185,6 +245,11 @@ fn map_table_columns(
                     columns: cols,
                     unique: idx.unique,
                     index_type: idx.index_type.clone(),
+                    tablespace: None,
+                    visible: idx.visible,
+                    expression: None,
+                    storage_params: vec![],
+                    comment: None,
                 })
             }
         })
@@ -227,6 +292,16 @@ fn map_table_columns(
         indexes: mapped_indexes,
         foreign_keys: mapped_fks,
         unique_constraints: mapped_ucs,
+        tablespace: None,
+        // Exclusion constraints are Postgres-only and can't carry across engines.
+        exclusion_constraints: vec![],
+        // Check expressions are written in the source dialect's SQL.
+        check_constraints: vec![],
+        row_security: false,
+        policies: vec![],
+        partitioning: None,
+        options: TableOptions::default(),
+        inherits: vec![],
     };
 
     (mapped_table, warnings, prerequisites)
@@ -262,6 +337,12 @@ fn map_column(
         auto_increment: col.auto_increment,
         comment: col.comment.clone(),
         ordinal_position: col.ordinal_position,
+        visible: col.visible,
+        on_update: col.on_update.clone(),
+        sequence: col.sequence.clone(),
+        generated: col.generated.clone(),
+        srid: col.srid,
+        octet_length: None,
     };
 
     (mapped_col, mapping)
@@ -280,21 +361,42 @@ fn columns_equal_cross(
     source_mapper: &dyn TypeMapper,
     target_mapper: &dyn TypeMapper,
 ) -> bool {
+    source.name == target.name
+        && column_changes_cross(source, target, source_mapper, target_mapper).is_empty()
+}
+
+fn column_changes_cross(
+    source: &Column,
+    target: &Column,
+    source_mapper: &dyn TypeMapper,
+    target_mapper: &dyn TypeMapper,
+) -> Vec<ColumnChangeKind> {
     let source_canonical = source_mapper.to_canonical(&source.data_type);
     let target_canonical = target_mapper.to_canonical(&target.data_type);
 
     // Map source default value through the target mapper to normalize dialect
     let mapped_source_default = source
-        .default_value
-        .as_ref()
+        .effective_default()
         .and_then(|d| target_mapper.map_default_value(d, &source_canonical));
 
-    source.name == target.name
-        && source_canonical == target_canonical
-        && source.nullable == target.nullable
-        && source.auto_increment == target.auto_increment
-        && mapped_source_default == target.default_value
-    // Intentionally skip: comment (PG reader doesn't support column comments)
+    let mut changes = Vec::new();
+    if source_canonical != target_canonical {
+        changes.push(ColumnChangeKind::DataType);
+    }
+    if source.nullable != target.nullable {
+        changes.push(ColumnChangeKind::Nullable);
+    }
+    if mapped_source_default.as_deref() != target.effective_default() {
+        changes.push(ColumnChangeKind::Default);
+    }
+    if source.auto_increment != target.auto_increment {
+        changes.push(ColumnChangeKind::AutoIncrement);
+    }
+    // Intentionally skip: comment (PG reader doesn't support column comments),
+    // visibility (PG columns are always visible), ON UPDATE (MySQL-only), the
+    // sequence name (Postgres-only), generation expressions, which are written
+    // in each engine's own SQL, and SRIDs (MySQL-only)
+    changes
 }
 
 fn column_detail_mapped(col: &Column) -> String {
@@ -313,15 +415,20 @@ fn column_detail_mapped(col: &Column) -> String {
     parts.join(" ")
 }
 
+#[allow(clippy::too_many_arguments)]
 fn compare_tables_cross(
     source: &TableSchema,
     target: &TableSchema,
     sql_gen: &dyn SqlGenerator,
     source_mapper: &dyn TypeMapper,
     target_mapper: &dyn TypeMapper,
+    options: &CompareOptions,
     diffs: &mut Vec<DiffItem>,
     id_counter: &mut u32,
 ) {
+    // Only Postgres reads inheritance; the MySQL generator notes it as skipped.
+    compare_inheritance(source, target, sql_gen, diffs, id_counter);
+
     let source_cols: HashMap<&str, &Column> = source
         .columns
         .iter()
@@ -359,6 +466,9 @@ fn compare_tables_cross(
                         message: mapping.warning.unwrap_or_default(),
                         severity: WarningSeverity::Skipped,
                     }],
+                    changes: vec![],
+                    priority: DiffPriority::Structural,
+                    severity: Severity::default(),
                 });
                 continue;
             }
@@ -372,6 +482,8 @@ fn compare_tables_cross(
                     severity: WarningSeverity::Degraded,
                 });
             }
+            let (mapped_col, backfill_warning) = with_backfill_default(&mapped_col, options);
+            warnings.extend(backfill_warning);
             *id_counter += 1;
             diffs.push(DiffItem {
                 id: id_counter.to_string(),
@@ -382,12 +494,21 @@ fn compare_tables_cross(
                 target_def: None,
                 sql: prepend_prerequisite(
                     &mapping,
-                    sql_gen.generate_add_column(&source.name, &mapped_col),
+                    if options.online_ddl {
+                        sql_gen.generate_add_column_online(&source.name, &mapped_col)
+                    } else {
+                        sql_gen.generate_add_column(&source.name, &mapped_col)
+                    },
                 ),
                 selected: true,
                 warnings,
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             });
         } else if let Some(target_col) = target_cols.get(col.name.as_str()) {
+            let col = align_ignored_attributes(col, target_col, options);
+            let col = col.as_ref();
             if !columns_equal_cross(col, target_col, source_mapper, target_mapper) {
                 let (mapped_col, mapping) = map_column(col, source_mapper, target_mapper);
                 if mapping.skipped {
@@ -409,6 +530,9 @@ fn compare_tables_cross(
                             message: mapping.warning.unwrap_or_default(),
                             severity: WarningSeverity::Skipped,
                         }],
+                        changes: vec![],
+                        priority: DiffPriority::Structural,
+                        severity: Severity::default(),
                     });
                     continue;
                 }
@@ -422,6 +546,7 @@ fn compare_tables_cross(
                         severity: WarningSeverity::Degraded,
                     });
                 }
+                let changes = column_changes_cross(col, target_col, source_mapper, target_mapper);
                 *id_counter += 1;
                 diffs.push(DiffItem {
                     id: id_counter.to_string(),
@@ -436,6 +561,9 @@ fn compare_tables_cross(
                     ),
                     selected: true,
                     warnings,
+                    priority: DiffPriority::for_column_changes(&changes),
+                    severity: Severity::default(),
+                    changes,
                 });
             }
         }
@@ -455,6 +583,9 @@ fn compare_tables_cross(
                 sql: sql_gen.generate_drop_column(&source.name, &col.name),
                 selected: true,
                 warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             });
         }
     }
@@ -462,9 +593,13 @@ fn compare_tables_cross(
     // Indexes, FKs, UCs -- delegate to existing helpers, but filter out
     // any that reference skipped columns to avoid generating broken SQL
     if skipped_cols.is_empty() {
-        super::comparator::compare_indexes(source, target, sql_gen, diffs, id_counter);
-        super::comparator::compare_foreign_keys(source, target, sql_gen, diffs, id_counter);
-        super::comparator::compare_unique_constraints(source, target, sql_gen, diffs, id_counter);
+        super::comparator::compare_indexes(source, target, sql_gen, options, diffs, id_counter);
+        super::comparator::compare_foreign_keys(
+            source, target, sql_gen, options, diffs, id_counter,
+        );
+        super::comparator::compare_unique_constraints(
+            source, target, sql_gen, options, diffs, id_counter,
+        );
     } else {
         let filter_indexes = |indexes: &[Index]| -> Vec<Index> {
             indexes
@@ -493,6 +628,14 @@ fn compare_tables_cross(
             indexes: filter_indexes(&source.indexes),
             foreign_keys: filter_fks(&source.foreign_keys),
             unique_constraints: filter_ucs(&source.unique_constraints),
+            tablespace: source.tablespace.clone(),
+            exclusion_constraints: vec![],
+            check_constraints: vec![],
+            row_security: false,
+            policies: vec![],
+            partitioning: None,
+            options: source.options.clone(),
+            inherits: vec![],
         };
         let filtered_target = TableSchema {
             name: target.name.clone(),
@@ -501,12 +644,21 @@ fn compare_tables_cross(
             indexes: filter_indexes(&target.indexes),
             foreign_keys: filter_fks(&target.foreign_keys),
             unique_constraints: filter_ucs(&target.unique_constraints),
+            tablespace: target.tablespace.clone(),
+            exclusion_constraints: vec![],
+            check_constraints: vec![],
+            row_security: false,
+            policies: vec![],
+            partitioning: None,
+            options: target.options.clone(),
+            inherits: vec![],
         };
 
         super::comparator::compare_indexes(
             &filtered_source,
             &filtered_target,
             sql_gen,
+            options,
             diffs,
             id_counter,
         );
@@ -514,6 +666,7 @@ fn compare_tables_cross(
             &filtered_source,
             &filtered_target,
             sql_gen,
+            options,
             diffs,
             id_counter,
         );
@@ -521,6 +674,7 @@ fn compare_tables_cross(
             &filtered_source,
             &filtered_target,
             sql_gen,
+            options,
             diffs,
             id_counter,
         );
@@ -534,6 +688,16 @@ mod tests {
     use crate::db::PostgresSqlGenerator;
     use crate::types::{MySqlTypeMapper, PostgresTypeMapper};
 
+    #[test]
+    fn cross_engine_warning_only_for_different_engines() {
+        let warning = cross_engine_warning(&DbType::MySQL, &DbType::PostgreSQL).unwrap();
+        assert!(warning.starts_with("Comparing MySQL to PostgreSQL"));
+        assert_eq!(
+            cross_engine_warning(&DbType::PostgreSQL, &DbType::PostgreSQL),
+            None
+        );
+    }
+
     fn make_column(name: &str, data_type: &str) -> Column {
         Column {
             name: name.to_string(),
@@ -543,6 +707,12 @@ mod tests {
             auto_increment: false,
             comment: None,
             ordinal_position: 1,
+            visible: true,
+            on_update: None,
+            sequence: None,
+            generated: None,
+            srid: None,
+            octet_length: None,
         }
     }
 
@@ -550,10 +720,7 @@ mod tests {
         TableSchema {
             name: name.to_string(),
             columns,
-            primary_key: None,
-            indexes: vec![],
-            foreign_keys: vec![],
-            unique_constraints: vec![],
+            ..Default::default()
         }
     }
 
@@ -602,6 +769,10 @@ mod tests {
             .filter(|d| d.diff_type == DiffType::ColumnModified)
             .collect();
         assert_eq!(col_mods.len(), 1);
+        assert_eq!(
+            col_mods[0].priority,
+            DiffPriority::for_column_changes(&col_mods[0].changes)
+        );
     }
 
     #[test]
@@ -742,7 +913,7 @@ mod tests {
         let diffs = compare_schemas_cross(
             &source,
             &target,
-            &PostgresSqlGenerator as &dyn SqlGenerator,
+            &PostgresSqlGenerator::default() as &dyn SqlGenerator,
             &MySqlTypeMapper,
             &PostgresTypeMapper,
         );
@@ -766,7 +937,7 @@ mod tests {
         let diffs = compare_schemas_cross(
             &source,
             &target,
-            &PostgresSqlGenerator as &dyn SqlGenerator,
+            &PostgresSqlGenerator::default() as &dyn SqlGenerator,
             &MySqlTypeMapper,
             &PostgresTypeMapper,
         );
@@ -951,7 +1122,7 @@ mod tests {
         let diffs = compare_schemas_cross(
             &source,
             &target,
-            &PostgresSqlGenerator as &dyn SqlGenerator,
+            &PostgresSqlGenerator::default() as &dyn SqlGenerator,
             &MySqlTypeMapper,
             &PostgresTypeMapper,
         );
@@ -979,7 +1150,7 @@ mod tests {
         let diffs = compare_schemas_cross(
             &source,
             &target,
-            &PostgresSqlGenerator as &dyn SqlGenerator,
+            &PostgresSqlGenerator::default() as &dyn SqlGenerator,
             &MySqlTypeMapper,
             &PostgresTypeMapper,
         );
@@ -1082,6 +1253,11 @@ mod tests {
             columns: vec!["meta".to_string()],
             unique: false,
             index_type: "BTREE".to_string(),
+            tablespace: None,
+            visible: true,
+            expression: None,
+            storage_params: vec![],
+            comment: None,
         });
 
         let target = vec![make_table("data", vec![make_column("id", "integer")])];
@@ -1141,4 +1317,43 @@ mod tests {
             "skipped column should not be in PK"
         );
     }
+
+    #[test]
+    fn test_unique_constraint_names_matched_by_columns_cross_db() {
+        let mut source = make_table("users", vec![make_column("email", "varchar(255)")]);
+        source.unique_constraints = vec![UniqueConstraint {
+            name: "email".to_string(),
+            columns: vec!["email".to_string()],
+        }];
+        let mut target = make_table(
+            "users",
+            vec![make_column("email", "character varying(255)")],
+        );
+        target.unique_constraints = vec![UniqueConstraint {
+            name: "users_email_key".to_string(),
+            columns: vec!["email".to_string()],
+        }];
+
+        let options = CompareOptions {
+            match_unique_by_columns: true,
+            ..Default::default()
+        };
+        let diffs = compare_schemas_cross_with_options(
+            &[source],
+            &[target],
+            &PostgresSqlGenerator::default() as &dyn SqlGenerator,
+            &MySqlTypeMapper,
+            &PostgresTypeMapper,
+            &options,
+        );
+
+        assert!(
+            diffs.is_empty(),
+            "same-column unique constraints should not churn, got: {:?}",
+            diffs
+                .iter()
+                .map(|d| (&d.diff_type, &d.object_name))
+                .collect::<Vec<_>>()
+        );
+    }
 }
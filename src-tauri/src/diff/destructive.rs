@@ -0,0 +1,109 @@
+use crate::models::*;
+
+/// A column type split into its lowercased base name and the numbers in its
+/// parenthesised arguments, as in `varchar(255)` or `decimal(10, 2)`. Types
+/// without arguments have no lengths.
+pub(crate) fn type_lengths(def: &str) -> (String, Vec<u64>) {
+    let def = def.trim();
+    let base_end = def
+        .find(|c: char| c == '(' || c.is_whitespace())
+        .unwrap_or(def.len());
+    let base = def[..base_end].to_lowercase();
+    let lengths = def[base_end..]
+        .strip_prefix('(')
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(args, _)| {
+            args.split(',')
+                .map(|arg| arg.trim().parse::<u64>())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+    (base, lengths)
+}
+
+/// Whether changing a column from `from` to `to` shrinks one of its lengths
+/// (`varchar(255)` to `varchar(100)`, or the precision or scale of a decimal).
+/// Only types with the same base name are compared.
+pub(crate) fn is_narrowing(from: &str, to: &str) -> bool {
+    let (from_base, from_lengths) = type_lengths(from);
+    let (to_base, to_lengths) = type_lengths(to);
+    from_base == to_base
+        && from_lengths.len() == to_lengths.len()
+        && from_lengths
+            .iter()
+            .zip(&to_lengths)
+            .any(|(from, to)| to < from)
+}
+
+/// Whether applying `item` drops data or an object on the target.
+fn drops_something(item: &DiffItem) -> bool {
+    match item.diff_type {
+        DiffType::TableRemoved
+        | DiffType::ColumnRemoved
+        | DiffType::IndexRemoved
+        | DiffType::ForeignKeyRemoved
+        | DiffType::UniqueConstraintRemoved => true,
+        // source_def is the column as it will be, target_def as it is now.
+        DiffType::ColumnModified => match (&item.target_def, &item.source_def) {
+            (Some(current), Some(wanted)) => is_narrowing(current, wanted),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Only the items that drop tables, columns, indexes or constraints, or shrink
+/// a column's type, for reviewing the risky part of a sync on its own.
+pub fn filter_destructive(items: Vec<DiffItem>) -> Vec<DiffItem> {
+    items.into_iter().filter(drops_something).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::item;
+
+    #[test]
+    fn object_drops_are_destructive_and_additions_are_not() {
+        let items = vec![
+            item(DiffType::TableRemoved, "users", None),
+            item(DiffType::IndexRemoved, "users", Some("idx_name")),
+            item(DiffType::ForeignKeyRemoved, "users", Some("fk_org")),
+            item(DiffType::UniqueConstraintRemoved, "users", Some("uq_email")),
+            item(DiffType::ColumnAdded, "users", Some("name")),
+            item(DiffType::IndexAdded, "users", Some("idx_email")),
+        ];
+        let kept: Vec<DiffType> = filter_destructive(items)
+            .into_iter()
+            .map(|i| i.diff_type)
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                DiffType::TableRemoved,
+                DiffType::IndexRemoved,
+                DiffType::ForeignKeyRemoved,
+                DiffType::UniqueConstraintRemoved,
+            ]
+        );
+    }
+
+    #[test]
+    fn narrowing_varchar_is_destructive_but_widening_is_not() {
+        let narrowing = DiffItem {
+            source_def: Some("varchar(100) NOT NULL".to_string()),
+            target_def: Some("varchar(255) NOT NULL".to_string()),
+            ..item(DiffType::ColumnModified, "users", Some("name"))
+        };
+        let widening = DiffItem {
+            source_def: Some("VARCHAR(255) NULL".to_string()),
+            target_def: Some("varchar(100) NULL".to_string()),
+            ..item(DiffType::ColumnModified, "users", Some("name"))
+        };
+        let kept = filter_destructive(vec![narrowing, widening]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].source_def.as_deref(), Some("varchar(100) NOT NULL"));
+        assert!(is_narrowing("decimal(10, 2)", "decimal(10, 1)"));
+    }
+}
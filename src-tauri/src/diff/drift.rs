@@ -0,0 +1,65 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::models::TableSchema;
+
+/// A cheap summary of a schema: equal schemas give equal fingerprints whatever
+/// order their tables were read in. Only comparable within one run of the app.
+pub fn schema_fingerprint(tables: &[TableSchema]) -> u64 {
+    let mut serialized: Vec<String> = tables
+        .iter()
+        .map(|t| serde_json::to_string(t).unwrap_or_default())
+        .collect();
+    serialized.sort();
+    let mut hasher = DefaultHasher::new();
+    serialized.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Tracks one source/target pair across checks so the same drift is reported
+/// once, not on every check until someone fixes it.
+#[derive(Debug, Default)]
+pub struct DriftDetector {
+    reported: Option<(u64, u64)>,
+}
+
+impl DriftDetector {
+    /// Whether the pair has drifted in a way not reported yet. Drift that is
+    /// fixed and then reappears is reported again.
+    pub fn observe(&mut self, source: &[TableSchema], target: &[TableSchema]) -> bool {
+        let fingerprints = (schema_fingerprint(source), schema_fingerprint(target));
+        if fingerprints.0 == fingerprints.1 {
+            self.reported = None;
+            return false;
+        }
+        if self.reported == Some(fingerprints) {
+            return false;
+        }
+        self.reported = Some(fingerprints);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::{column, table};
+
+    #[test]
+    fn changed_target_is_reported_once() {
+        let users = table("users", vec![column("id", "int"), column("email", "int")]);
+        let orders = table("orders", vec![column("id", "int")]);
+        let source = vec![users.clone(), orders.clone()];
+        let reordered = vec![orders.clone(), users];
+        let mut detector = DriftDetector::default();
+        assert!(!detector.observe(&source, &reordered));
+
+        let drifted = vec![orders, table("users", vec![column("id", "int")])];
+        assert!(detector.observe(&source, &drifted));
+        assert!(!detector.observe(&source, &drifted));
+
+        // Fixed, then drifted again.
+        assert!(!detector.observe(&source, &reordered));
+        assert!(detector.observe(&source, &drifted));
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::db::SchemaReader;
+use crate::models::*;
+
+/// Rows per second (fastest, slowest) for the work `item` makes the server do
+/// over its whole table, or `None` when it only changes metadata. Deliberately
+/// wide: real rates depend on row width, hardware and concurrent load.
+fn rows_per_second(item: &DiffItem) -> Option<(f64, f64)> {
+    const REWRITE: (f64, f64) = (100_000.0, 10_000.0);
+    const INDEX_BUILD: (f64, f64) = (200_000.0, 20_000.0);
+    const VALIDATION_SCAN: (f64, f64) = (1_000_000.0, 100_000.0);
+
+    match item.diff_type {
+        DiffType::TableRebuilt | DiffType::PartitioningModified => Some(REWRITE),
+        DiffType::ColumnModified | DiffType::ColumnRenamed
+            if item.changes.contains(&ColumnChangeKind::DataType) =>
+        {
+            Some(REWRITE)
+        }
+        DiffType::IndexAdded | DiffType::IndexModified | DiffType::UniqueConstraintAdded => {
+            Some(INDEX_BUILD)
+        }
+        DiffType::ForeignKeyAdded | DiffType::CheckConstraintAdded => Some(VALIDATION_SCAN),
+        _ => None,
+    }
+}
+
+/// Cost the items against known row counts per table; tables missing from
+/// `row_counts`, or without statistics, cost nothing.
+fn estimate_from_row_counts(
+    items: &[DiffItem],
+    row_counts: &HashMap<String, Option<u64>>,
+) -> SyncEstimate {
+    let statements: Vec<StatementEstimate> = items
+        .iter()
+        .filter_map(|item| {
+            let (fastest, slowest) = rows_per_second(item)?;
+            let row_count = row_counts.get(&item.table_name).copied().flatten();
+            let rows = row_count.unwrap_or(0) as f64;
+            Some(StatementEstimate {
+                item_id: item.id.clone(),
+                table_name: item.table_name.clone(),
+                row_count,
+                min_seconds: rows / fastest,
+                max_seconds: rows / slowest,
+            })
+        })
+        .collect();
+
+    SyncEstimate {
+        min_seconds: statements.iter().map(|s| s.min_seconds).sum(),
+        max_seconds: statements.iter().map(|s| s.max_seconds).sum(),
+        statements,
+    }
+}
+
+/// Estimate how long applying `items` to the target behind `reader` takes,
+/// from the approximate size of each table a rewrite, index build or
+/// validation scan touches. Read-only; each table is counted once.
+pub async fn estimate_sync(reader: &dyn SchemaReader, items: &[DiffItem]) -> Result<SyncEstimate> {
+    let mut row_counts: HashMap<String, Option<u64>> = HashMap::new();
+    for item in items.iter().filter(|i| rows_per_second(i).is_some()) {
+        if !row_counts.contains_key(&item.table_name) {
+            let rows = reader.estimated_row_count(&item.table_name).await?;
+            row_counts.insert(item.table_name.clone(), rows);
+        }
+    }
+    Ok(estimate_from_row_counts(items, &row_counts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::{item, numbered};
+
+    #[test]
+    fn index_creation_on_a_large_table_is_costed() {
+        let items = numbered(vec![
+            item(DiffType::IndexAdded, "orders", None),
+            item(DiffType::ColumnAdded, "orders", None),
+            item(DiffType::IndexAdded, "audit", None),
+        ]);
+        let row_counts = HashMap::from([
+            ("orders".to_string(), Some(2_000_000)),
+            ("audit".to_string(), None),
+        ]);
+
+        let estimate = estimate_from_row_counts(&items, &row_counts);
+        assert_eq!(estimate.statements.len(), 2);
+        let orders = &estimate.statements[0];
+        assert_eq!(orders.item_id, "1");
+        assert_eq!(orders.row_count, Some(2_000_000));
+        assert!(orders.min_seconds > 0.0 && orders.min_seconds < orders.max_seconds);
+        // No statistics: listed, but costed at zero.
+        assert_eq!(estimate.statements[1].max_seconds, 0.0);
+        assert_eq!(estimate.min_seconds, orders.min_seconds);
+        assert_eq!(estimate.max_seconds, orders.max_seconds);
+    }
+}
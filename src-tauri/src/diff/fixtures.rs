@@ -0,0 +1,63 @@
+//! Builders shared by the diff tests. Each gives the plainest value of its kind;
+//! a test sets anything else with struct update syntax.
+
+use crate::models::*;
+
+/// An item of `diff_type` on `table`, touching `object` when given.
+pub(crate) fn item(diff_type: DiffType, table: &str, object: Option<&str>) -> DiffItem {
+    DiffItem {
+        object_name: object.map(str::to_string),
+        ..DiffItem::new("1", diff_type, table)
+    }
+}
+
+/// An item of `diff_type` on `table` that runs `sql`.
+pub(crate) fn sql_item(diff_type: DiffType, table: &str, sql: &str) -> DiffItem {
+    DiffItem {
+        sql: sql.to_string(),
+        ..DiffItem::new("1", diff_type, table)
+    }
+}
+
+/// `items` with their ids numbered from 1 in the order given.
+pub(crate) fn numbered(items: Vec<DiffItem>) -> Vec<DiffItem> {
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| DiffItem {
+            id: (i + 1).to_string(),
+            ..item
+        })
+        .collect()
+}
+
+/// A nullable column of `data_type` with nothing else set.
+pub(crate) fn column(name: &str, data_type: &str) -> Column {
+    Column {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable: true,
+        default_value: None,
+        auto_increment: false,
+        comment: None,
+        ordinal_position: 1,
+        visible: true,
+        on_update: None,
+        sequence: None,
+        generated: None,
+        srid: None,
+        octet_length: None,
+    }
+}
+
+/// A table of `columns`, positioned in the order given.
+pub(crate) fn table(name: &str, mut columns: Vec<Column>) -> TableSchema {
+    for (i, col) in columns.iter_mut().enumerate() {
+        col.ordinal_position = i as u32 + 1;
+    }
+    TableSchema {
+        name: name.to_string(),
+        columns,
+        ..Default::default()
+    }
+}
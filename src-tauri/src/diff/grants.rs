@@ -0,0 +1,110 @@
+use crate::db::SqlGenerator;
+use crate::models::*;
+use std::collections::BTreeSet;
+
+fn grant_item(id_counter: &mut u32, diff_type: DiffType, grant: &Grant, sql: String) -> DiffItem {
+    *id_counter += 1;
+    let def = format!("{} TO {}", grant.privilege, grant.grantee);
+    let (source_def, target_def) = match diff_type {
+        DiffType::GrantAdded => (Some(def), None),
+        _ => (None, Some(def)),
+    };
+    DiffItem {
+        id: id_counter.to_string(),
+        diff_type,
+        table_name: grant.object.clone(),
+        object_name: Some(format!("{} {}", grant.privilege, grant.grantee)),
+        source_def,
+        target_def,
+        sql,
+        selected: true,
+        warnings: vec![],
+        changes: vec![],
+        priority: DiffPriority::Structural,
+        severity: Severity::default(),
+    }
+}
+
+/// Compare table-level grants. A grant is the whole (grantee, privilege, table)
+/// triple, so a changed privilege shows up as one revoke and one grant.
+pub fn compare_grants(
+    source: &[Grant],
+    target: &[Grant],
+    sql_gen: &dyn SqlGenerator,
+    id_counter: &mut u32,
+) -> Vec<DiffItem> {
+    let source_set: BTreeSet<&Grant> = source.iter().collect();
+    let target_set: BTreeSet<&Grant> = target.iter().collect();
+    let mut diffs = Vec::new();
+
+    for grant in source_set.difference(&target_set) {
+        diffs.push(grant_item(
+            id_counter,
+            DiffType::GrantAdded,
+            grant,
+            sql_gen.generate_grant(grant),
+        ));
+    }
+    for grant in target_set.difference(&source_set) {
+        diffs.push(grant_item(
+            id_counter,
+            DiffType::GrantRemoved,
+            grant,
+            sql_gen.generate_revoke(grant),
+        ));
+    }
+
+    super::comparator::finish_items(&mut diffs);
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{MySqlSqlGenerator, PostgresSqlGenerator};
+
+    fn grant(grantee: &str, privilege: &str, object: &str) -> Grant {
+        Grant {
+            grantee: grantee.to_string(),
+            privilege: privilege.to_string(),
+            object: object.to_string(),
+        }
+    }
+
+    #[test]
+    fn added_select_grant_is_granted() {
+        let mut id = 0;
+        let diffs = compare_grants(
+            &[
+                grant("'app'@'%'", "INSERT", "orders"),
+                grant("'app'@'%'", "SELECT", "orders"),
+            ],
+            &[grant("'app'@'%'", "INSERT", "orders")],
+            &MySqlSqlGenerator,
+            &mut id,
+        );
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].diff_type, DiffType::GrantAdded);
+        assert_eq!(diffs[0].table_name, "orders");
+        assert_eq!(diffs[0].sql, "GRANT SELECT ON `orders` TO 'app'@'%';");
+    }
+
+    #[test]
+    fn extra_target_grant_is_revoked() {
+        let mut id = 0;
+        let diffs = compare_grants(
+            &[],
+            &[grant("reporting", "SELECT", "orders")],
+            &PostgresSqlGenerator::default(),
+            &mut id,
+        );
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].diff_type, DiffType::GrantRemoved);
+        assert_eq!(
+            diffs[0].sql,
+            "REVOKE SELECT ON \"orders\" FROM \"reporting\";"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::*;
+
+/// Postgres' spelling of an unquoted identifier, if `name` would be folded.
+fn folded(name: &str, target_names: &HashSet<&str>) -> Option<String> {
+    let lower = name.to_lowercase();
+    (lower != name && !target_names.contains(name) && target_names.contains(lower.as_str()))
+        .then_some(lower)
+}
+
+fn rename_all(names: &mut [String], renames: &HashMap<String, String>) {
+    for name in names {
+        if let Some(new) = renames.get(name.as_str()) {
+            *name = new.clone();
+        }
+    }
+}
+
+/// Rename source tables and columns to the target's lowercase spelling where the
+/// two differ only because Postgres folds unquoted identifiers, e.g. a source
+/// `Users` against a target `users`. Names with an exact match, or no folded
+/// match, keep their casing; references in keys, indexes and foreign keys follow
+/// the renames.
+pub fn fold_to_target_case(source: &[TableSchema], target: &[TableSchema]) -> Vec<TableSchema> {
+    let target_tables: HashSet<&str> = target.iter().map(|t| t.name.as_str()).collect();
+    let target_by_name: HashMap<&str, &TableSchema> =
+        target.iter().map(|t| (t.name.as_str(), t)).collect();
+
+    let table_renames: HashMap<String, String> = source
+        .iter()
+        .filter_map(|t| folded(&t.name, &target_tables).map(|new| (t.name.clone(), new)))
+        .collect();
+
+    // Column renames, keyed by the source table's original name.
+    let column_renames: HashMap<&str, HashMap<String, String>> = source
+        .iter()
+        .map(|t| {
+            let target_name = table_renames.get(&t.name).unwrap_or(&t.name);
+            let target_columns: HashSet<&str> = target_by_name
+                .get(target_name.as_str())
+                .map(|tt| tt.columns.iter().map(|c| c.name.as_str()).collect())
+                .unwrap_or_default();
+            let renames = t
+                .columns
+                .iter()
+                .filter_map(|c| folded(&c.name, &target_columns).map(|new| (c.name.clone(), new)))
+                .collect();
+            (t.name.as_str(), renames)
+        })
+        .collect();
+    let no_renames = HashMap::new();
+
+    source
+        .iter()
+        .map(|t| {
+            let mut table = t.clone();
+            let columns = &column_renames[t.name.as_str()];
+            if let Some(new) = table_renames.get(&t.name) {
+                table.name = new.clone();
+            }
+            for col in &mut table.columns {
+                if let Some(new) = columns.get(&col.name) {
+                    col.name = new.clone();
+                }
+            }
+            if let Some(pk) = &mut table.primary_key {
+                rename_all(&mut pk.columns, columns);
+            }
+            for idx in &mut table.indexes {
+                rename_all(&mut idx.columns, columns);
+            }
+            for uc in &mut table.unique_constraints {
+                rename_all(&mut uc.columns, columns);
+            }
+            for fk in &mut table.foreign_keys {
+                rename_all(&mut fk.columns, columns);
+                let referenced = column_renames
+                    .get(fk.ref_table.as_str())
+                    .unwrap_or(&no_renames);
+                rename_all(&mut fk.ref_columns, referenced);
+                if let Some(new) = table_renames.get(&fk.ref_table) {
+                    fk.ref_table = new.clone();
+                }
+            }
+            table
+        })
+        .collect()
+}
@@ -0,0 +1,244 @@
+use anyhow::Result;
+
+use crate::db::SchemaReader;
+use crate::models::*;
+
+/// Whether applying `item` can lose data on the target.
+fn is_destructive(item: &DiffItem) -> bool {
+    match item.diff_type {
+        DiffType::TableRemoved
+        | DiffType::ColumnRemoved
+        | DiffType::TableRebuilt
+        | DiffType::PartitioningModified => true,
+        DiffType::ColumnModified => item.changes.contains(&ColumnChangeKind::DataType),
+        _ => false,
+    }
+}
+
+fn describe_action(item: &DiffItem) -> String {
+    let column = item.object_name.as_deref().unwrap_or_default();
+    match item.diff_type {
+        DiffType::TableRemoved => format!("Drops table {} and all of its data", item.table_name),
+        DiffType::ColumnRemoved => {
+            format!("Drops column {}.{} and its data", item.table_name, column)
+        }
+        DiffType::TableRebuilt => format!(
+            "Recreates table {} and copies its data across",
+            item.table_name
+        ),
+        DiffType::PartitioningModified => format!(
+            "Recreates table {} with the new partitioning and copies its data across",
+            item.table_name
+        ),
+        _ => format!(
+            "Changes the type of {}.{}; existing values may be converted or truncated",
+            item.table_name, column
+        ),
+    }
+}
+
+/// Target objects that depend on the table, or the column, a destructive item touches.
+fn dependents(item: &DiffItem, tables: &[TableSchema]) -> Vec<String> {
+    let column = match item.diff_type {
+        DiffType::ColumnRemoved | DiffType::ColumnModified => item.object_name.as_deref(),
+        _ => None,
+    };
+    let uses = |columns: &[String]| column.is_none_or(|c| columns.iter().any(|x| x == c));
+    let mut found = Vec::new();
+
+    for table in tables {
+        let own_table = table.name == item.table_name;
+        for fk in &table.foreign_keys {
+            let references = fk.ref_table == item.table_name && uses(&fk.ref_columns);
+            // A dropped table takes its own self-references with it.
+            let goes_with_table = own_table && column.is_none();
+            let on_column = own_table && column.is_some() && uses(&fk.columns);
+            if (references && !goes_with_table) || on_column {
+                found.push(format!("foreign key {}.{}", table.name, fk.name));
+            }
+        }
+        if own_table && column.is_some() {
+            for idx in table.indexes.iter().filter(|i| uses(&i.columns)) {
+                found.push(format!("index {}.{}", table.name, idx.name));
+            }
+            for uc in table.unique_constraints.iter().filter(|u| uses(&u.columns)) {
+                found.push(format!("unique constraint {}.{}", table.name, uc.name));
+            }
+        }
+    }
+    found
+}
+
+/// Describe what applying `item` would affect on the target behind `reader`.
+/// Read-only: destructive items get a row estimate and a dependency scan, others
+/// are reported as safe without touching the database.
+pub async fn analyze_impact(reader: &dyn SchemaReader, item: &DiffItem) -> Result<ImpactReport> {
+    if !is_destructive(item) {
+        return Ok(ImpactReport {
+            destructive: false,
+            row_count: None,
+            referenced_by: vec![],
+            summary: "Not destructive; no existing data is removed".to_string(),
+        });
+    }
+
+    let row_count = reader.estimated_row_count(&item.table_name).await?;
+    let referenced_by = dependents(item, &reader.get_tables().await?);
+
+    let mut parts = vec![describe_action(item)];
+    if let Some(rows) = row_count {
+        parts.push(format!("about {} row(s) in the table", rows));
+    }
+    if !referenced_by.is_empty() {
+        parts.push(format!("used by {}", referenced_by.join(", ")));
+    }
+
+    Ok(ImpactReport {
+        destructive: true,
+        row_count,
+        referenced_by,
+        summary: parts.join("; "),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::{item, table};
+    use async_trait::async_trait;
+
+    struct MockTarget {
+        tables: Vec<TableSchema>,
+        rows: Option<u64>,
+    }
+
+    #[async_trait]
+    impl SchemaReader for MockTarget {
+        async fn test_connection(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn server_version(&self) -> Result<String> {
+            Ok("8.0.36".to_string())
+        }
+        async fn get_tables(&self) -> Result<Vec<TableSchema>> {
+            Ok(self.tables.clone())
+        }
+        async fn get_tables_named(&self, names: &[String]) -> Result<Vec<TableSchema>> {
+            Ok(self
+                .tables
+                .iter()
+                .filter(|t| names.contains(&t.name))
+                .cloned()
+                .collect())
+        }
+        async fn list_tables(&self) -> Result<Vec<String>> {
+            Ok(self.tables.iter().map(|t| t.name.clone()).collect())
+        }
+        async fn list_databases(&self, _: Option<&str>, _: Option<u32>) -> Result<Vec<String>> {
+            Ok(vec![])
+        }
+        async fn get_database_info(&self) -> Result<DatabaseInfo> {
+            Ok(DatabaseInfo::default())
+        }
+        async fn estimated_row_count(&self, _table: &str) -> Result<Option<u64>> {
+            Ok(self.rows)
+        }
+        async fn get_routines(&self) -> Result<Vec<Routine>> {
+            Ok(vec![])
+        }
+        async fn get_grants(&self) -> Result<Vec<Grant>> {
+            Ok(vec![])
+        }
+        fn take_read_warnings(&self) -> Vec<String> {
+            vec![]
+        }
+        async fn list_schema_objects(&self) -> Result<SchemaObjects> {
+            Ok(SchemaObjects::default())
+        }
+        async fn native_create_table(&self, table: &str) -> Result<String> {
+            Ok(format!("CREATE TABLE {} ()", table))
+        }
+    }
+
+    fn target() -> MockTarget {
+        let mut users = table("users", vec![]);
+        users.indexes = vec![Index {
+            name: "idx_email".to_string(),
+            columns: vec!["email".to_string()],
+            unique: false,
+            index_type: "BTREE".to_string(),
+            tablespace: None,
+            visible: true,
+            expression: None,
+            storage_params: vec![],
+            comment: None,
+        }];
+        let mut orders = table("orders", vec![]);
+        orders.foreign_keys = vec![ForeignKey {
+            name: "fk_user".to_string(),
+            columns: vec!["user_id".to_string()],
+            ref_table: "users".to_string(),
+            ref_columns: vec!["id".to_string()],
+            on_delete: "CASCADE".to_string(),
+            on_update: "NO ACTION".to_string(),
+            deferrable: false,
+            initially_deferred: false,
+            match_type: None,
+        }];
+        MockTarget {
+            tables: vec![users, orders],
+            rows: Some(1200),
+        }
+    }
+
+    #[tokio::test]
+    async fn column_removed_reports_rows_and_dependents() {
+        let report = analyze_impact(
+            &target(),
+            &item(DiffType::ColumnRemoved, "users", Some("email")),
+        )
+        .await
+        .unwrap();
+
+        assert!(report.destructive);
+        assert_eq!(report.row_count, Some(1200));
+        assert_eq!(report.referenced_by, vec!["index users.idx_email"]);
+        assert_eq!(
+            report.summary,
+            "Drops column users.email and its data; about 1200 row(s) in the table; used by index users.idx_email"
+        );
+
+        let report = analyze_impact(
+            &target(),
+            &item(DiffType::ColumnRemoved, "users", Some("id")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.referenced_by, vec!["foreign key orders.fk_user"]);
+    }
+
+    #[tokio::test]
+    async fn table_removed_lists_incoming_foreign_keys() {
+        let report = analyze_impact(&target(), &item(DiffType::TableRemoved, "users", None))
+            .await
+            .unwrap();
+        assert_eq!(report.referenced_by, vec!["foreign key orders.fk_user"]);
+
+        let report = analyze_impact(&target(), &item(DiffType::TableRemoved, "orders", None))
+            .await
+            .unwrap();
+        assert!(report.referenced_by.is_empty());
+    }
+
+    #[tokio::test]
+    async fn additive_items_are_not_destructive() {
+        let report = analyze_impact(
+            &target(),
+            &item(DiffType::IndexAdded, "users", Some("idx_name")),
+        )
+        .await
+        .unwrap();
+        assert!(!report.destructive);
+        assert_eq!(report.row_count, None);
+    }
+}
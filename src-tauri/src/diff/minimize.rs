@@ -0,0 +1,319 @@
+use log::warn;
+use std::collections::HashSet;
+
+use crate::models::DiffItem;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Create,
+    Drop,
+}
+
+/// The object a statement creates or drops. MySQL index and constraint names
+/// are per table; a Postgres `DROP INDEX` names no table, so `table` may be
+/// missing on one side of a comparison.
+#[derive(Debug, Clone, PartialEq)]
+struct ObjectKey {
+    kind: &'static str,
+    name: String,
+    table: Option<String>,
+}
+
+impl ObjectKey {
+    fn same_object(&self, other: &ObjectKey) -> bool {
+        self.kind == other.kind
+            && self.name == other.name
+            && match (&self.table, &other.table) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+}
+
+/// Words of a statement, keeping quoted identifiers (which may hold spaces)
+/// whole and cutting a trailing column list off a name, as in `t(a)`.
+fn words(statement: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for c in statement.chars() {
+        match quote {
+            Some(q) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            Some(_) => current.push(c),
+            None if c == '`' || c == '"' => {
+                quote = Some(c);
+                current.push(c);
+            }
+            None if c.is_whitespace() || c == '(' || c == ',' => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+                if !c.is_whitespace() {
+                    words.push(c.to_string());
+                }
+            }
+            None => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// An identifier without its quotes.
+pub(crate) fn unquote(word: &str) -> String {
+    word.trim_matches(|c| c == '`' || c == '"').to_string()
+}
+
+/// Whether the words at `at` are `keywords`, compared case-insensitively.
+fn keywords_at(words: &[String], at: usize, keywords: &[&str]) -> bool {
+    keywords.iter().enumerate().all(|(offset, kw)| {
+        words
+            .get(at + offset)
+            .is_some_and(|w| w.eq_ignore_ascii_case(kw))
+    })
+}
+
+/// Skip optional keyword runs such as `IF NOT EXISTS` or `CONCURRENTLY`.
+fn skip_optional(words: &[String], mut at: usize, optional: &[&[&str]]) -> usize {
+    for keywords in optional {
+        if keywords_at(words, at, keywords) {
+            at += keywords.len();
+        }
+    }
+    at
+}
+
+/// The object a statement creates or drops, for the statement shapes the
+/// generators produce; `None` for anything else (column changes, comments, ...).
+fn object_op(statement: &str) -> Option<(Op, ObjectKey)> {
+    let words = words(statement.trim_end_matches(';'));
+    let key = |kind, name: &String, table: Option<&String>| ObjectKey {
+        kind,
+        name: unquote(name),
+        table: table.map(|t| unquote(t)),
+    };
+
+    if keywords_at(&words, 0, &["CREATE", "TABLE"]) {
+        let at = skip_optional(&words, 2, &[&["IF", "NOT", "EXISTS"]]);
+        return Some((Op::Create, key("table", words.get(at)?, None)));
+    }
+    if keywords_at(&words, 0, &["DROP", "TABLE"]) {
+        let at = skip_optional(&words, 2, &[&["IF", "EXISTS"]]);
+        return Some((Op::Drop, key("table", words.get(at)?, None)));
+    }
+    if keywords_at(&words, 0, &["CREATE"]) {
+        let at = skip_optional(&words, 1, &[&["UNIQUE"]]);
+        if !keywords_at(&words, at, &["INDEX"]) {
+            return None;
+        }
+        let at = skip_optional(
+            &words,
+            at + 1,
+            &[&["CONCURRENTLY"], &["IF", "NOT", "EXISTS"]],
+        );
+        let name = words.get(at)?;
+        if !keywords_at(&words, at + 1, &["ON"]) {
+            return None;
+        }
+        let table_at = skip_optional(&words, at + 2, &[&["ONLY"]]);
+        return Some((Op::Create, key("index", name, words.get(table_at))));
+    }
+    if keywords_at(&words, 0, &["DROP", "INDEX"]) {
+        let at = skip_optional(&words, 2, &[&["CONCURRENTLY"], &["IF", "EXISTS"]]);
+        let name = words.get(at)?;
+        let table = keywords_at(&words, at + 1, &["ON"])
+            .then(|| words.get(at + 2))
+            .flatten();
+        return Some((Op::Drop, key("index", name, table)));
+    }
+    if keywords_at(&words, 0, &["ALTER", "TABLE"]) && words.len() > 4 {
+        let table = &words[2];
+        let at = 3;
+        if keywords_at(&words, at, &["ADD", "CONSTRAINT"]) {
+            return Some((
+                Op::Create,
+                key("constraint", words.get(at + 2)?, Some(table)),
+            ));
+        }
+        let at_index = skip_optional(&words, at + 1, &[&["UNIQUE"]]);
+        if keywords_at(&words, at, &["ADD"])
+            && (keywords_at(&words, at_index, &["INDEX"])
+                || keywords_at(&words, at_index, &["KEY"]))
+        {
+            return Some((
+                Op::Create,
+                key("index", words.get(at_index + 1)?, Some(table)),
+            ));
+        }
+        if keywords_at(&words, at, &["DROP", "CONSTRAINT"]) {
+            let name_at = skip_optional(&words, at + 2, &[&["IF", "EXISTS"]]);
+            return Some((
+                Op::Drop,
+                key("constraint", words.get(name_at)?, Some(table)),
+            ));
+        }
+        if keywords_at(&words, at, &["DROP", "FOREIGN", "KEY"]) {
+            return Some((Op::Drop, key("constraint", words.get(at + 3)?, Some(table))));
+        }
+        if keywords_at(&words, at, &["DROP", "INDEX"]) || keywords_at(&words, at, &["DROP", "KEY"])
+        {
+            return Some((Op::Drop, key("index", words.get(at + 2)?, Some(table))));
+        }
+    }
+    None
+}
+
+/// Statement text with runs of whitespace collapsed, for spotting duplicates.
+fn normalized(statement: &str) -> String {
+    statement.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The items' SQL as the shortest equivalent list of statements, in order.
+///
+/// A create that is later dropped and created again is left out together with
+/// the drop, since only the last definition survives; identical statements run
+/// once. Operations that would fail against each other, such as creating the
+/// same object twice with no drop in between, are kept but logged as warnings.
+pub fn minimize_statements(items: &[DiffItem]) -> Vec<String> {
+    let statements: Vec<String> = items
+        .iter()
+        .flat_map(|item| item.sql_statements())
+        .collect();
+    let ops: Vec<Option<(Op, ObjectKey)>> = statements.iter().map(|s| object_op(s)).collect();
+
+    let mut removed = vec![false; statements.len()];
+    for (i, op) in ops.iter().enumerate() {
+        let Some((Op::Create, key)) = op else {
+            continue;
+        };
+        let mut later = ops
+            .iter()
+            .enumerate()
+            .skip(i + 1)
+            .filter_map(|(j, op)| match op {
+                Some((op, other)) if key.same_object(other) => Some((j, *op)),
+                _ => None,
+            });
+        if let (Some((drop_at, Op::Drop)), Some((_, Op::Create))) = (later.next(), later.next()) {
+            removed[i] = true;
+            removed[drop_at] = true;
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut kept: Vec<usize> = Vec::new();
+    for (i, statement) in statements.iter().enumerate() {
+        if !removed[i] && seen.insert(normalized(statement)) {
+            kept.push(i);
+        }
+    }
+
+    let mut last_op: Vec<(ObjectKey, Op)> = Vec::new();
+    for &i in &kept {
+        let Some((op, key)) = &ops[i] else {
+            continue;
+        };
+        match last_op.iter_mut().find(|(k, _)| k.same_object(key)) {
+            Some((_, previous)) => {
+                if *previous == *op {
+                    warn!(
+                        "Conflicting statements: {} {} is {} twice",
+                        key.kind,
+                        key.name,
+                        if *op == Op::Create {
+                            "created"
+                        } else {
+                            "dropped"
+                        }
+                    );
+                }
+                *previous = *op;
+            }
+            None => last_op.push((key.clone(), *op)),
+        }
+    }
+
+    kept.into_iter().map(|i| statements[i].clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::sql_item;
+    use crate::models::*;
+
+    #[test]
+    fn duplicated_drop_index_runs_once() {
+        let items = [
+            sql_item(
+                DiffType::IndexRemoved,
+                "users",
+                "DROP INDEX `idx_email` ON `users`;",
+            ),
+            sql_item(
+                DiffType::ColumnRemoved,
+                "users",
+                "ALTER TABLE `users` DROP COLUMN `email`;",
+            ),
+            sql_item(
+                DiffType::IndexRemoved,
+                "users",
+                "DROP INDEX `idx_email` ON `users`;",
+            ),
+        ];
+        assert_eq!(
+            minimize_statements(&items),
+            vec![
+                "DROP INDEX `idx_email` ON `users`;",
+                "ALTER TABLE `users` DROP COLUMN `email`;",
+            ]
+        );
+    }
+
+    #[test]
+    fn create_then_modify_keeps_only_the_final_definition() {
+        let items = [
+            sql_item(
+                DiffType::IndexAdded,
+                "users",
+                "CREATE INDEX \"idx_name\" ON \"users\" (\"name\");",
+            ),
+            sql_item(
+                DiffType::IndexModified,
+                "users",
+                "DROP INDEX \"idx_name\";\nCREATE INDEX \"idx_name\" ON \"users\" (\"name\", \"email\");",
+            ),
+        ];
+        assert_eq!(
+            minimize_statements(&items),
+            vec!["CREATE INDEX \"idx_name\" ON \"users\" (\"name\", \"email\");"]
+        );
+    }
+
+    #[test]
+    fn same_index_name_on_other_tables_is_not_collapsed() {
+        let items = [
+            sql_item(
+                DiffType::IndexAdded,
+                "users",
+                "CREATE INDEX `idx_a` ON `users` (`a`);",
+            ),
+            sql_item(
+                DiffType::IndexRemoved,
+                "users",
+                "DROP INDEX `idx_a` ON `orders`;",
+            ),
+            sql_item(
+                DiffType::IndexAdded,
+                "users",
+                "CREATE INDEX `idx_a` ON `orders` (`b`);",
+            ),
+        ];
+        assert_eq!(minimize_statements(&items).len(), 3);
+    }
+}
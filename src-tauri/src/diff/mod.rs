@@ -1,5 +1,42 @@
 pub mod comparator;
+pub mod compat;
+pub mod consistency;
 pub mod cross_compare;
+pub mod destructive;
+pub mod drift;
+pub mod estimate;
+#[cfg(test)]
+mod fixtures;
+pub mod grants;
+pub mod identifiers;
+pub mod impact;
+pub mod minimize;
+pub mod order;
+mod renames;
+pub mod report;
+pub mod routines;
+pub mod safe;
+pub mod script;
+pub mod undo;
 
-pub use comparator::compare_schemas;
-pub use cross_compare::compare_schemas_cross;
+pub use comparator::{
+    compare_schemas, compare_schemas_with_options, compare_snapshots, database_charset_warning,
+    duplicate_name_warnings, schemas_in_sync,
+};
+pub use compat::version_compatibility_warnings;
+pub use consistency::self_consistency_check;
+pub use cross_compare::{
+    compare_schemas_cross, compare_schemas_cross_with_options, cross_engine_warning,
+};
+pub use destructive::filter_destructive;
+pub use drift::{DriftDetector, schema_fingerprint};
+pub use estimate::estimate_sync;
+pub use grants::compare_grants;
+pub use impact::analyze_impact;
+pub use minimize::minimize_statements;
+pub use order::{drop_all_statements, order_for_execution};
+pub use report::{to_report, write_ndjson};
+pub use routines::compare_routines;
+pub use safe::select_safe_changes;
+pub use script::generate_script;
+pub use undo::undo_sql;
@@ -0,0 +1,411 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::db::SqlGenerator;
+use crate::models::*;
+
+/// Reorder diff items so that running their SQL top to bottom satisfies foreign key
+/// dependencies:
+///
+/// 1. foreign keys being removed,
+/// 2. new tables, referenced and inherited tables first,
+/// 3. everything else in its original order,
+/// 4. foreign keys being added or modified.
+///
+/// A new table's foreign keys to itself are fine inside its CREATE TABLE. When new
+/// tables reference each other in a cycle, the foreign keys that close the cycle are
+/// taken out of CREATE TABLE and appended as separate `ForeignKeyAdded` items.
+pub fn order_for_execution(
+    items: Vec<DiffItem>,
+    source: &[TableSchema],
+    sql_gen: &dyn SqlGenerator,
+) -> Vec<DiffItem> {
+    let source_map: HashMap<&str, &TableSchema> =
+        source.iter().map(|t| (t.name.as_str(), t)).collect();
+    let mut next_id = items
+        .iter()
+        .filter_map(|i| i.id.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+
+    let mut fk_drops = Vec::new();
+    let mut creates = Vec::new();
+    let mut others = Vec::new();
+    let mut fk_adds = Vec::new();
+    for item in items {
+        match item.diff_type {
+            DiffType::ForeignKeyRemoved => fk_drops.push(item),
+            DiffType::TableAdded => creates.push(item),
+            DiffType::ForeignKeyAdded | DiffType::ForeignKeyModified => fk_adds.push(item),
+            _ => others.push(item),
+        }
+    }
+
+    let (creates, deferred) = sort_creates(creates, |c| &c.table_name, &source_map);
+
+    let mut deferred_items = Vec::new();
+    for (mut create, fks) in creates.into_iter().zip(deferred) {
+        for fk in fks {
+            create.sql = strip_inline_foreign_key(&create.sql, &fk.name, sql_gen);
+            next_id += 1;
+            deferred_items.push(DiffItem {
+                id: next_id.to_string(),
+                diff_type: DiffType::ForeignKeyAdded,
+                table_name: create.table_name.clone(),
+                object_name: Some(fk.name.clone()),
+                source_def: Some(format!("-> {}", fk.ref_table)),
+                target_def: None,
+                sql: sql_gen.generate_add_foreign_key(&create.table_name, fk),
+                selected: create.selected,
+                warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
+            });
+        }
+        fk_drops.push(create);
+    }
+
+    fk_drops.extend(others);
+    fk_drops.extend(fk_adds);
+    fk_drops.extend(deferred_items);
+    // Creates may have lost inline foreign keys above.
+    super::comparator::finish_items(&mut fk_drops);
+    fk_drops
+}
+
+/// Statements that drop every table, routine and other object given without
+/// tripping over dependencies: the views that select from the tables first, then
+/// all foreign keys, then the tables, inheriting tables before their parents and
+/// referencing tables before the ones they reference, then the routines, and
+/// last the sequences and enum types the tables' columns may still have used.
+pub fn drop_all_statements(
+    tables: &[TableSchema],
+    routines: &[Routine],
+    objects: &SchemaObjects,
+    sql_gen: &dyn SqlGenerator,
+) -> Vec<String> {
+    let table_map: HashMap<&str, &TableSchema> =
+        tables.iter().map(|t| (t.name.as_str(), t)).collect();
+    let (mut creation_order, _) = sort_creates(tables.iter().collect(), |t| &t.name, &table_map);
+    creation_order.reverse();
+
+    let foreign_keys = creation_order.iter().flat_map(|t| {
+        t.foreign_keys
+            .iter()
+            .map(|fk| sql_gen.generate_drop_foreign_key(&t.name, &fk.name))
+    });
+    let table_drops = creation_order
+        .iter()
+        .map(|t| sql_gen.generate_drop_table(&t.name));
+    let routine_drops = routines.iter().map(|r| sql_gen.generate_drop_routine(r));
+    // One statement per kind, so views built on other views go in any order.
+    let drop_each = |kind: &str, names: &[String]| {
+        (!names.is_empty()).then(|| {
+            let names: Vec<String> = names.iter().map(|n| sql_gen.quote_identifier(n)).collect();
+            format!("DROP {} {};", kind, names.join(", "))
+        })
+    };
+    drop_each("VIEW", &objects.views)
+        .into_iter()
+        .chain(foreign_keys)
+        .chain(table_drops)
+        .chain(routine_drops)
+        .chain(drop_each("SEQUENCE", &objects.sequences))
+        .chain(drop_each("TYPE", &objects.enum_types))
+        .collect()
+}
+
+/// Topologically sort tables to create, each named by `table_name`. Returns the
+/// sorted items alongside, for each one, the foreign keys that had to be
+/// deferred to break a cycle.
+fn sort_creates<'a, T>(
+    creates: Vec<T>,
+    table_name: impl Fn(&T) -> &String,
+    source_map: &HashMap<&str, &'a TableSchema>,
+) -> (Vec<T>, Vec<Vec<&'a ForeignKey>>) {
+    let new_tables: HashSet<String> = creates.iter().map(|c| table_name(c).clone()).collect();
+
+    // Tables each new table must wait for (self-references don't count)
+    let mut waits_on: HashMap<String, HashSet<String>> = creates
+        .iter()
+        .map(|c| {
+            let deps = source_map
+                .get(table_name(c).as_str())
+                .map(|t| {
+                    t.foreign_keys
+                        .iter()
+                        .map(|fk| &fk.ref_table)
+                        .chain(&t.inherits)
+                        .filter(|r| *r != table_name(c) && new_tables.contains(*r))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default();
+            (table_name(c).clone(), deps)
+        })
+        .collect();
+    // A child can't be created before its parents, even to break a cycle.
+    let parents_done = |table: &str, done: &HashSet<String>| {
+        source_map.get(table).is_none_or(|t| {
+            t.inherits
+                .iter()
+                .all(|p| done.contains(p) || !new_tables.contains(p))
+        })
+    };
+
+    let mut remaining = creates;
+    let mut sorted = Vec::new();
+    let mut deferred = Vec::new();
+    let mut done: HashSet<String> = HashSet::new();
+
+    while !remaining.is_empty() {
+        let ready = remaining
+            .iter()
+            .position(|c| waits_on[table_name(c)].iter().all(|d| done.contains(d)));
+
+        let (pos, fks) = match ready {
+            Some(pos) => (pos, vec![]),
+            None => {
+                // Every remaining table waits on another: a cycle. Create the first one
+                // whose parents exist now and add its foreign keys to still-missing
+                // tables afterwards.
+                let pos = remaining
+                    .iter()
+                    .position(|c| parents_done(table_name(c), &done))
+                    .unwrap_or(0);
+                let table = table_name(&remaining[pos]);
+                let fks = source_map
+                    .get(table.as_str())
+                    .map(|t| {
+                        t.foreign_keys
+                            .iter()
+                            .filter(|fk| waits_on[table].contains(&fk.ref_table))
+                            .filter(|fk| !done.contains(&fk.ref_table))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (pos, fks)
+            }
+        };
+
+        let item = remaining.remove(pos);
+        waits_on.insert(table_name(&item).clone(), HashSet::new());
+        done.insert(table_name(&item).clone());
+        sorted.push(item);
+        deferred.push(fks);
+    }
+
+    (sorted, deferred)
+}
+
+/// Remove a `CONSTRAINT <name> FOREIGN KEY ...` line, and the comma that separates it
+/// from the previous column or constraint, from generated CREATE TABLE SQL.
+fn strip_inline_foreign_key(sql: &str, fk_name: &str, sql_gen: &dyn SqlGenerator) -> String {
+    let marker = format!(
+        "  CONSTRAINT {} FOREIGN KEY",
+        sql_gen.quote_identifier(fk_name)
+    );
+    let mut lines: Vec<String> = sql.lines().map(String::from).collect();
+    if let Some(pos) = lines.iter().position(|l| l.starts_with(&marker)) {
+        let trailing_comma = lines[pos].ends_with(',');
+        lines.remove(pos);
+        // The last constraint has no comma of its own; drop the one before it
+        if !trailing_comma && pos > 0 {
+            let prev = &mut lines[pos - 1];
+            if prev.ends_with(',') {
+                prev.pop();
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::PostgresSqlGenerator;
+    use crate::diff::fixtures::{column, numbered, sql_item, table};
+
+    fn fk(name: &str, column: &str, ref_table: &str) -> ForeignKey {
+        ForeignKey {
+            name: name.to_string(),
+            columns: vec![column.to_string()],
+            ref_table: ref_table.to_string(),
+            ref_columns: vec!["id".to_string()],
+            on_delete: "NO ACTION".to_string(),
+            on_update: "NO ACTION".to_string(),
+            deferrable: false,
+            initially_deferred: false,
+            match_type: None,
+        }
+    }
+
+    /// A table of `integer` columns with `foreign_keys`.
+    fn keyed_table(name: &str, columns: &[&str], foreign_keys: Vec<ForeignKey>) -> TableSchema {
+        TableSchema {
+            foreign_keys,
+            ..table(name, columns.iter().map(|c| column(c, "integer")).collect())
+        }
+    }
+
+    fn create_items(tables: &[TableSchema]) -> Vec<DiffItem> {
+        numbered(
+            tables
+                .iter()
+                .map(|t| {
+                    sql_item(
+                        DiffType::TableAdded,
+                        &t.name,
+                        &PostgresSqlGenerator::default().generate_create_table(t),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn names(items: &[DiffItem]) -> Vec<(&DiffType, &str)> {
+        items
+            .iter()
+            .map(|i| (&i.diff_type, i.table_name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn referenced_table_created_first() {
+        let tables = vec![
+            keyed_table(
+                "orders",
+                &["id", "user_id"],
+                vec![fk("fk_user", "user_id", "users")],
+            ),
+            keyed_table("users", &["id"], vec![]),
+        ];
+        let ordered = order_for_execution(
+            create_items(&tables),
+            &tables,
+            &PostgresSqlGenerator::default(),
+        );
+        assert_eq!(
+            names(&ordered),
+            vec![
+                (&DiffType::TableAdded, "users"),
+                (&DiffType::TableAdded, "orders")
+            ]
+        );
+        assert!(ordered[1].sql.contains("FOREIGN KEY"));
+    }
+
+    #[test]
+    fn self_referencing_table_keeps_inline_fk() {
+        let tables = vec![keyed_table(
+            "employees",
+            &["id", "manager_id"],
+            vec![fk("fk_manager", "manager_id", "employees")],
+        )];
+        let ordered = order_for_execution(
+            create_items(&tables),
+            &tables,
+            &PostgresSqlGenerator::default(),
+        );
+        assert_eq!(ordered.len(), 1);
+        assert!(
+            ordered[0]
+                .sql
+                .contains("CONSTRAINT \"fk_manager\" FOREIGN KEY")
+        );
+    }
+
+    #[test]
+    fn two_table_cycle_defers_one_fk() {
+        let tables = vec![
+            keyed_table("a", &["id", "b_id"], vec![fk("fk_a_b", "b_id", "b")]),
+            keyed_table("b", &["id", "a_id"], vec![fk("fk_b_a", "a_id", "a")]),
+        ];
+        let ordered = order_for_execution(
+            create_items(&tables),
+            &tables,
+            &PostgresSqlGenerator::default(),
+        );
+
+        assert_eq!(
+            names(&ordered),
+            vec![
+                (&DiffType::TableAdded, "a"),
+                (&DiffType::TableAdded, "b"),
+                (&DiffType::ForeignKeyAdded, "a"),
+            ]
+        );
+        // "a" is created without its FK to the not-yet-existing "b"
+        assert!(!ordered[0].sql.contains("FOREIGN KEY"));
+        assert!(ordered[0].sql.contains("\"b_id\" integer\n);"));
+        // "b" can reference "a" inline
+        assert!(ordered[1].sql.contains("CONSTRAINT \"fk_b_a\" FOREIGN KEY"));
+        assert_eq!(ordered[2].object_name.as_deref(), Some("fk_a_b"));
+        assert!(
+            ordered[2]
+                .sql
+                .starts_with("ALTER TABLE \"a\" ADD CONSTRAINT \"fk_a_b\"")
+        );
+        assert_eq!(ordered[2].id, "3");
+    }
+
+    #[test]
+    fn fk_drops_first_and_fk_adds_last() {
+        let tables = vec![keyed_table("users", &["id"], vec![])];
+        let mut items = create_items(&tables);
+        let other = |id: &str, diff_type| DiffItem::new(id, diff_type, "orders");
+        items.insert(0, other("2", DiffType::ForeignKeyAdded));
+        items.push(other("3", DiffType::ColumnAdded));
+        items.push(other("4", DiffType::ForeignKeyRemoved));
+
+        let ordered = order_for_execution(items, &tables, &PostgresSqlGenerator::default());
+        let types: Vec<&DiffType> = ordered.iter().map(|i| &i.diff_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                &DiffType::ForeignKeyRemoved,
+                &DiffType::TableAdded,
+                &DiffType::ColumnAdded,
+                &DiffType::ForeignKeyAdded,
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_all_drops_foreign_keys_before_their_tables() {
+        let mut invoices = keyed_table("invoices", &["id"], vec![]);
+        invoices.inherits = vec!["documents".to_string()];
+        let tables = vec![
+            keyed_table("users", &["id"], vec![]),
+            keyed_table(
+                "orders",
+                &["id", "user_id"],
+                vec![fk("fk_orders_user", "user_id", "users")],
+            ),
+            keyed_table("documents", &["id"], vec![]),
+            invoices,
+        ];
+
+        let objects = SchemaObjects {
+            views: vec!["active_users".to_string(), "user_orders".to_string()],
+            sequences: vec!["invoice_numbers".to_string()],
+            enum_types: vec!["order_status".to_string()],
+        };
+
+        let statements =
+            drop_all_statements(&tables, &[], &objects, &PostgresSqlGenerator::default());
+        assert_eq!(
+            statements,
+            vec![
+                "DROP VIEW \"active_users\", \"user_orders\";",
+                "ALTER TABLE \"orders\" DROP CONSTRAINT \"fk_orders_user\";",
+                "DROP TABLE \"invoices\";",
+                "DROP TABLE \"documents\";",
+                "DROP TABLE \"orders\";",
+                "DROP TABLE \"users\";",
+                "DROP SEQUENCE \"invoice_numbers\";",
+                "DROP TYPE \"order_status\";",
+            ]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::diff::destructive::{is_narrowing, type_lengths};
+use crate::models::{Column, TableSchema};
+
+/// Type families a column can move within without losing values, narrowest first.
+const TYPE_FAMILIES: &[&[&str]] = &[
+    &[
+        "tinyint",
+        "smallint",
+        "int2",
+        "mediumint",
+        "int",
+        "integer",
+        "int4",
+        "bigint",
+        "int8",
+    ],
+    &[
+        "char",
+        "character",
+        "varchar",
+        "tinytext",
+        "text",
+        "mediumtext",
+        "longtext",
+    ],
+    &["decimal", "numeric"],
+    &["float", "real", "float4", "double", "float8"],
+];
+
+/// Position of `base` in its family, with aliases (`int`/`integer`/`int4`)
+/// ranked together.
+fn family_rank(base: &str) -> Option<(usize, usize)> {
+    let base = match base {
+        "integer" | "int4" => "int",
+        "int2" => "smallint",
+        "int8" => "bigint",
+        "character" => "char",
+        "numeric" => "decimal",
+        "float4" => "real",
+        "float8" => "double",
+        other => other,
+    };
+    TYPE_FAMILIES
+        .iter()
+        .enumerate()
+        .find_map(|(family, types)| {
+            types
+                .iter()
+                .position(|t| *t == base)
+                .map(|rank| (family, rank))
+        })
+}
+
+/// Whether a column of type `from` can become `to` keeping every value: the
+/// same type, or a wider one in the same family (`int` to `bigint`,
+/// `varchar(50)` to `varchar(100)` or `text`).
+fn is_compatible_change(from: &str, to: &str) -> bool {
+    if from.eq_ignore_ascii_case(to) {
+        return true;
+    }
+    let (from_base, _) = type_lengths(from);
+    let (to_base, _) = type_lengths(to);
+    if from_base == to_base {
+        return !is_narrowing(from, to);
+    }
+    match (family_rank(&from_base), family_rank(&to_base)) {
+        (Some((from_family, from_rank)), Some((to_family, to_rank))) => {
+            from_family == to_family && to_rank >= from_rank
+        }
+        _ => false,
+    }
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Whether two column names plausibly name the same thing: one contains the
+/// other (`email` / `email_address`), or they differ in at most a third of
+/// their letters (`user_name` / `username`, `adress` / `address`). Case and
+/// underscores are ignored.
+fn similar_names(a: &str, b: &str) -> bool {
+    let normalize = |name: &str| -> Vec<char> {
+        name.chars()
+            .filter(|c| *c != '_')
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+    let (shorter, longer) = if a.len() <= b.len() {
+        (&a, &b)
+    } else {
+        (&b, &a)
+    };
+    if shorter.len() >= 2
+        && longer
+            .windows(shorter.len())
+            .any(|w| w == shorter.as_slice())
+    {
+        return true;
+    }
+    edit_distance(&a, &b) * 3 <= longer.len()
+}
+
+/// Columns that look renamed between `target` and `source`: a source-only and a
+/// target-only column whose types are compatible and whose names are similar
+/// or whose positions match. Similar names are paired first; each column is
+/// used once. Returns (source column, target column) pairs.
+pub(crate) fn column_renames<'a>(
+    source: &'a TableSchema,
+    target: &'a TableSchema,
+) -> Vec<(&'a Column, &'a Column)> {
+    let added: Vec<&Column> = source
+        .columns
+        .iter()
+        .filter(|c| !target.columns.iter().any(|t| t.name == c.name))
+        .collect();
+    let mut removed: Vec<&Column> = target
+        .columns
+        .iter()
+        .filter(|c| !source.columns.iter().any(|s| s.name == c.name))
+        .collect();
+
+    let mut renames = Vec::new();
+    let mut unmatched = Vec::new();
+    for col in added {
+        let found = removed.iter().position(|old| {
+            similar_names(&old.name, &col.name)
+                && is_compatible_change(&old.data_type, &col.data_type)
+        });
+        match found {
+            Some(pos) => renames.push((col, removed.remove(pos))),
+            None => unmatched.push(col),
+        }
+    }
+    for col in unmatched {
+        let found = removed.iter().position(|old| {
+            old.ordinal_position == col.ordinal_position
+                && is_compatible_change(&old.data_type, &col.data_type)
+        });
+        if let Some(pos) = found {
+            renames.push((col, removed.remove(pos)));
+        }
+    }
+    renames.sort_by_key(|(col, _)| col.ordinal_position);
+    renames
+}
@@ -0,0 +1,206 @@
+use std::io::{self, Write};
+
+use crate::models::*;
+
+/// Render a comparison result for other tools: pretty JSON, a Markdown checklist
+/// grouped by table (for PR comments and chat), or a plain-text summary.
+pub fn to_report(result: &DiffResult, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => serde_json::to_string_pretty(result).unwrap_or_default(),
+        ReportFormat::Markdown => markdown_report(result),
+        ReportFormat::PlainText => plain_text_report(result),
+    }
+}
+
+/// Write one `DiffItem` JSON object per line. Each item is serialized straight
+/// into `writer`, so wrapping a file in a `BufWriter` streams a large diff to
+/// disk without building the whole document in memory.
+pub fn write_ndjson<W: Write>(items: &[DiffItem], mut writer: W) -> io::Result<()> {
+    for item in items {
+        serde_json::to_writer(&mut writer, item)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// The `snake_case` name used for the diff type in JSON output.
+pub(crate) fn type_label(diff_type: &DiffType) -> String {
+    serde_json::to_value(diff_type)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn summary_line(result: &DiffResult) -> String {
+    format!(
+        "{} change(s): {} source table(s), {} target table(s)",
+        result.items.len(),
+        result.source_tables,
+        result.target_tables
+    )
+}
+
+/// Number of items per diff type, in order of first appearance.
+fn counts_by_type(items: &[DiffItem]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for item in items {
+        let label = type_label(&item.diff_type);
+        match counts.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((label, 1)),
+        }
+    }
+    counts
+}
+
+/// Items grouped by table, tables in order of first appearance and each
+/// table's items most severe first. Reports are for review; only the script
+/// keeps execution order.
+fn group_by_table(items: &[DiffItem]) -> Vec<(&str, Vec<&DiffItem>)> {
+    let mut groups: Vec<(&str, Vec<&DiffItem>)> = Vec::new();
+    for item in items {
+        match groups.iter_mut().find(|(t, _)| *t == item.table_name) {
+            Some((_, group)) => group.push(item),
+            None => groups.push((&item.table_name, vec![item])),
+        }
+    }
+    for (_, group) in &mut groups {
+        group.sort_by_key(|i| i.severity);
+    }
+    groups
+}
+
+fn item_line(item: &DiffItem) -> String {
+    let mut line = type_label(&item.diff_type);
+    if let Some(name) = &item.object_name {
+        line.push_str(&format!(" {}", name));
+    }
+    match (&item.target_def, &item.source_def) {
+        (Some(from), Some(to)) => line.push_str(&format!(": {} -> {}", from, to)),
+        (None, Some(def)) | (Some(def), None) => line.push_str(&format!(": {}", def)),
+        (None, None) => {}
+    }
+    line
+}
+
+fn markdown_report(result: &DiffResult) -> String {
+    let mut out = format!("# Schema diff\n\n{}\n", summary_line(result));
+
+    if !result.items.is_empty() {
+        out.push_str("\n| Change | Count |\n| --- | --- |\n");
+        for (label, count) in counts_by_type(&result.items) {
+            out.push_str(&format!("| {} | {} |\n", label, count));
+        }
+    }
+
+    for (table, items) in group_by_table(&result.items) {
+        out.push_str(&format!("\n## {}\n\n", table));
+        for item in items {
+            let check = if item.selected { "x" } else { " " };
+            out.push_str(&format!("- [{}] {}\n", check, item_line(item)));
+        }
+    }
+
+    if !result.warnings.is_empty() {
+        out.push_str("\n## Warnings\n\n");
+        for warning in &result.warnings {
+            out.push_str(&format!("- {}\n", warning));
+        }
+    }
+    out
+}
+
+fn plain_text_report(result: &DiffResult) -> String {
+    let mut out = format!("{}\n", summary_line(result));
+    for (table, items) in group_by_table(&result.items) {
+        out.push_str(&format!("\n{}:\n", table));
+        for item in items {
+            out.push_str(&format!("  {}\n", item_line(item)));
+        }
+    }
+    for warning in &result.warnings {
+        out.push_str(&format!("\nWarning: {}\n", warning));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::{item, numbered};
+
+    fn result() -> DiffResult {
+        DiffResult {
+            items: numbered(vec![
+                item(DiffType::TableAdded, "orders", None),
+                item(DiffType::ColumnAdded, "users", Some("email")),
+                DiffItem {
+                    selected: false,
+                    source_def: Some("varchar(50)".to_string()),
+                    ..item(DiffType::ColumnAdded, "users", Some("nickname"))
+                },
+            ]),
+            source_tables: 2,
+            target_tables: 1,
+            warnings: vec!["charset differs".to_string()],
+            cross_engine: false,
+            source_schema: None,
+            target_schema: None,
+        }
+    }
+
+    #[test]
+    fn markdown_has_a_row_per_item_and_counts() {
+        let md = to_report(&result(), ReportFormat::Markdown);
+
+        assert!(md.contains("3 change(s): 2 source table(s), 1 target table(s)"));
+        assert!(md.contains("| table_added | 1 |"));
+        assert!(md.contains("| column_added | 2 |"));
+
+        let rows: Vec<&str> = md.lines().filter(|l| l.starts_with("- [")).collect();
+        assert_eq!(
+            rows,
+            vec![
+                "- [x] table_added",
+                "- [x] column_added email",
+                "- [ ] column_added nickname: varchar(50)",
+            ]
+        );
+        let orders = md.find("## orders").unwrap();
+        let users = md.find("## users").unwrap();
+        assert!(orders < users);
+        assert!(md.contains("## Warnings\n\n- charset differs"));
+    }
+
+    #[test]
+    fn plain_text_groups_by_table() {
+        let text = to_report(&result(), ReportFormat::PlainText);
+        assert!(text.starts_with("3 change(s)"));
+        assert!(text.contains("users:\n  column_added email\n  column_added nickname"));
+        assert!(text.contains("Warning: charset differs"));
+    }
+
+    #[test]
+    fn ndjson_writes_one_item_per_line() {
+        let items = result().items;
+        let mut out = Vec::new();
+        write_ndjson(&items, &mut out).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), items.len());
+        for (line, item) in lines.iter().zip(&items) {
+            let parsed: DiffItem = serde_json::from_str(line).unwrap();
+            assert_eq!(parsed.id, item.id);
+            assert_eq!(parsed.object_name, item.object_name);
+        }
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let json = to_report(&result(), ReportFormat::Json);
+        let parsed: DiffResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.items.len(), 3);
+        assert_eq!(parsed.source_tables, 2);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::db::SqlGenerator;
+use crate::models::*;
+use std::collections::HashMap;
+
+/// Routine body with runs of whitespace collapsed, so re-indenting or
+/// re-wrapping a definition doesn't count as a behaviour change.
+fn normalized_body(definition: &str) -> String {
+    definition.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn routine_item(
+    id_counter: &mut u32,
+    diff_type: DiffType,
+    routine: &Routine,
+    source_def: Option<String>,
+    target_def: Option<String>,
+    sql: String,
+) -> DiffItem {
+    *id_counter += 1;
+    DiffItem {
+        id: id_counter.to_string(),
+        diff_type,
+        table_name: routine.name.clone(),
+        object_name: None,
+        source_def,
+        target_def,
+        sql,
+        selected: true,
+        warnings: vec![],
+        changes: vec![],
+        priority: DiffPriority::Structural,
+        severity: Severity::default(),
+    }
+}
+
+/// Compare stored procedures and functions. Routines are keyed by name (and
+/// argument list on Postgres), which goes in `table_name`; a routine whose kind
+/// changed is replaced like any other modification.
+pub fn compare_routines(
+    source: &[Routine],
+    target: &[Routine],
+    sql_gen: &dyn SqlGenerator,
+    id_counter: &mut u32,
+) -> Vec<DiffItem> {
+    let source_map: HashMap<&str, &Routine> = source.iter().map(|r| (r.name.as_str(), r)).collect();
+    let target_map: HashMap<&str, &Routine> = target.iter().map(|r| (r.name.as_str(), r)).collect();
+    let mut diffs = Vec::new();
+
+    for routine in source {
+        match target_map.get(routine.name.as_str()) {
+            None => diffs.push(routine_item(
+                id_counter,
+                DiffType::RoutineAdded,
+                routine,
+                Some(routine.definition.clone()),
+                None,
+                sql_gen.generate_create_routine(routine),
+            )),
+            Some(existing) if routine != *existing => {
+                let formatting_only = routine.kind == existing.kind
+                    && routine.language == existing.language
+                    && normalized_body(&routine.definition)
+                        == normalized_body(&existing.definition);
+                let mut item = routine_item(
+                    id_counter,
+                    DiffType::RoutineModified,
+                    routine,
+                    Some(routine.definition.clone()),
+                    Some(existing.definition.clone()),
+                    sql_gen.generate_replace_routine(routine),
+                );
+                // Formatting-only differences are shown but left unselected.
+                if formatting_only {
+                    item.priority = DiffPriority::Cosmetic;
+                    item.selected = false;
+                }
+                diffs.push(item);
+            }
+            Some(_) => {}
+        }
+    }
+
+    for routine in target {
+        if !source_map.contains_key(routine.name.as_str()) {
+            diffs.push(routine_item(
+                id_counter,
+                DiffType::RoutineRemoved,
+                routine,
+                None,
+                Some(routine.definition.clone()),
+                sql_gen.generate_drop_routine(routine),
+            ));
+        }
+    }
+
+    super::comparator::finish_items(&mut diffs);
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::PostgresSqlGenerator;
+
+    fn add_function(body: &str) -> Routine {
+        Routine {
+            name: "add(a integer, b integer)".to_string(),
+            kind: RoutineKind::Function,
+            definition: format!(
+                "CREATE OR REPLACE FUNCTION add(a integer, b integer)\n RETURNS integer\n LANGUAGE sql\nAS $function${}$function$\n",
+                body
+            ),
+            language: "sql".to_string(),
+        }
+    }
+
+    #[test]
+    fn changed_body_replaces_function() {
+        let mut id = 0;
+        let diffs = compare_routines(
+            &[add_function("SELECT a + b")],
+            &[add_function("SELECT a - b")],
+            &PostgresSqlGenerator::default(),
+            &mut id,
+        );
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].diff_type, DiffType::RoutineModified);
+        assert_eq!(diffs[0].priority, DiffPriority::Structural);
+        assert!(diffs[0].selected);
+        assert_eq!(
+            diffs[0].sql,
+            "CREATE OR REPLACE FUNCTION add(a integer, b integer)\n RETURNS integer\n LANGUAGE sql\nAS $function$SELECT a + b$function$;"
+        );
+    }
+
+    #[test]
+    fn whitespace_only_change_is_cosmetic() {
+        let mut id = 0;
+        let diffs = compare_routines(
+            &[add_function("SELECT a + b")],
+            &[add_function("SELECT  a +\n  b")],
+            &PostgresSqlGenerator::default(),
+            &mut id,
+        );
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].priority, DiffPriority::Cosmetic);
+        assert!(!diffs[0].selected);
+    }
+
+    #[test]
+    fn added_and_removed_routines() {
+        let mut id = 0;
+        let mut proc = add_function("SELECT 1");
+        proc.name = "cleanup()".to_string();
+        proc.kind = RoutineKind::Procedure;
+
+        let diffs = compare_routines(
+            &[add_function("SELECT a + b")],
+            &[proc],
+            &PostgresSqlGenerator::default(),
+            &mut id,
+        );
+
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].diff_type, DiffType::RoutineAdded);
+        assert_eq!(diffs[1].diff_type, DiffType::RoutineRemoved);
+        assert_eq!(diffs[1].sql, "DROP PROCEDURE IF EXISTS \"cleanup\"();");
+        assert_eq!(id, 2);
+    }
+}
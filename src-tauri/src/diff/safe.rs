@@ -0,0 +1,102 @@
+use crate::models::*;
+
+fn source_column<'a>(item: &DiffItem, source: &'a [TableSchema]) -> Option<&'a Column> {
+    let table = source.iter().find(|t| t.name == item.table_name)?;
+    let name = item.object_name.as_deref()?;
+    table.columns.iter().find(|c| c.name == name)
+}
+
+/// Select only additive changes that neither lock nor fail on existing data:
+/// new tables, indexes and foreign keys, and new columns that are nullable or
+/// have a default. Everything else is deselected. Each new NOT NULL column
+/// without a default is also noted in the result's warnings.
+pub fn select_safe_changes(result: &mut DiffResult, source: &[TableSchema]) {
+    for item in &mut result.items {
+        item.selected = match item.diff_type {
+            DiffType::TableAdded | DiffType::IndexAdded | DiffType::ForeignKeyAdded => true,
+            DiffType::ColumnAdded => match source_column(item, source) {
+                Some(col) if col.needs_backfill() => {
+                    result.warnings.push(format!(
+                        "Column {}.{} is NOT NULL without a default; adding it fails if the table has rows",
+                        item.table_name, col.name
+                    ));
+                    false
+                }
+                Some(_) => true,
+                None => false,
+            },
+            _ => false,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::{column, item, table};
+
+    fn result(items: Vec<DiffItem>) -> DiffResult {
+        DiffResult {
+            items,
+            source_tables: 1,
+            target_tables: 1,
+            warnings: vec![],
+            cross_engine: false,
+            source_schema: None,
+            target_schema: None,
+        }
+    }
+
+    #[test]
+    fn not_null_column_without_default_is_excluded() {
+        let source = vec![table(
+            "users",
+            vec![
+                column("age", "int"),
+                Column {
+                    nullable: false,
+                    default_value: Some("0".to_string()),
+                    ..column("status", "int")
+                },
+                Column {
+                    nullable: false,
+                    ..column("tenant_id", "int")
+                },
+            ],
+        )];
+        let mut diff = result(vec![
+            item(DiffType::ColumnAdded, "users", Some("age")),
+            item(DiffType::ColumnAdded, "users", Some("status")),
+            item(DiffType::ColumnAdded, "users", Some("tenant_id")),
+        ]);
+
+        select_safe_changes(&mut diff, &source);
+
+        let selected: Vec<bool> = diff.items.iter().map(|i| i.selected).collect();
+        assert_eq!(selected, vec![true, true, false]);
+        assert_eq!(
+            diff.warnings,
+            vec![
+                "Column users.tenant_id is NOT NULL without a default; adding it fails if the table has rows"
+            ]
+        );
+    }
+
+    #[test]
+    fn only_additive_changes_are_selected() {
+        let mut diff = result(vec![
+            item(DiffType::TableAdded, "users", None),
+            item(DiffType::IndexAdded, "users", Some("idx_email")),
+            item(DiffType::ForeignKeyAdded, "users", Some("fk_org")),
+            item(DiffType::ColumnRemoved, "users", Some("legacy")),
+            item(DiffType::ColumnModified, "users", Some("email")),
+            item(DiffType::IndexRemoved, "users", Some("idx_old")),
+        ]);
+
+        select_safe_changes(&mut diff, &[table("users", vec![])]);
+
+        let selected: Vec<bool> = diff.items.iter().map(|i| i.selected).collect();
+        assert_eq!(selected, vec![true, true, true, false, false, false]);
+        assert!(diff.warnings.is_empty());
+    }
+}
@@ -0,0 +1,512 @@
+use crate::db::split_statements;
+use crate::diff::report::type_label;
+use crate::models::*;
+use std::collections::HashMap;
+
+/// Render diff items as one SQL script, in the given order unless
+/// `options.group_by_table` asks for per-table sections.
+pub fn generate_script(items: &[DiffItem], options: &ScriptOptions) -> String {
+    let guarded: Vec<DiffItem>;
+    let items = if options.idempotent {
+        guarded = items
+            .iter()
+            .map(|item| match item.diff_type {
+                DiffType::ColumnAdded => {
+                    let sql = idempotent_column_add(&item.sql, options.server_version.as_deref());
+                    DiffItem {
+                        sql,
+                        ..item.clone()
+                    }
+                }
+                _ => item.clone(),
+            })
+            .collect();
+        &guarded
+    } else {
+        items
+    };
+    let items: Vec<&DiffItem> = items.iter().filter(|i| !i.sql.trim().is_empty()).collect();
+    let render = |items: &[&DiffItem]| {
+        if options.coalesce_alters {
+            coalesce_alters(items, options.annotate).join("\n\n")
+        } else if options.annotate {
+            items
+                .iter()
+                .map(|i| format!("{}\n{}", annotation(i), i.sql))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        } else {
+            items
+                .iter()
+                .map(|i| i.sql.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        }
+    };
+
+    if !options.group_by_table {
+        return render(&items);
+    }
+
+    // Tables come in the order they first appear, so a table created early keeps
+    // its section ahead of the tables whose foreign keys reference it. The sort is
+    // stable, so each table's statements keep their relative order.
+    let mut first_seen: HashMap<&str, usize> = HashMap::new();
+    for (position, item) in items.iter().enumerate() {
+        first_seen
+            .entry(item.table_name.as_str())
+            .or_insert(position);
+    }
+    let mut grouped = items.clone();
+    grouped.sort_by_key(|i| first_seen[i.table_name.as_str()]);
+
+    let mut sections: Vec<String> = Vec::new();
+    for chunk in grouped.chunk_by(|a, b| a.table_name == b.table_name) {
+        sections.push(format!(
+            "-- Table: {}\n{}",
+            chunk[0].table_name,
+            render(chunk)
+        ));
+    }
+    sections.join("\n\n")
+}
+
+/// A one-line SQL comment on what the item changes, e.g.
+/// `-- modify users.email: varchar(255) NULL -> varchar(100) NULL`, before
+/// (the target) first.
+fn annotation(item: &DiffItem) -> String {
+    let object = match &item.object_name {
+        Some(name) => format!("{}.{}", item.table_name, name),
+        None => item.table_name.clone(),
+    };
+    // Definitions can span lines; a line break would end the comment.
+    let flat = |def: &str| def.split_whitespace().collect::<Vec<_>>().join(" ");
+    let line = match (&item.target_def, &item.source_def) {
+        (Some(before), Some(after)) => {
+            format!("modify {}: {} -> {}", object, flat(before), flat(after))
+        }
+        (None, Some(def)) => format!("add {}: {}", object, flat(def)),
+        (Some(def), None) => format!("drop {}: {}", object, flat(def)),
+        (None, None) => format!("{} {}", type_label(&item.diff_type), object),
+    };
+    format!("-- {}", line)
+}
+
+/// Whether the server takes `ADD COLUMN IF NOT EXISTS`. MariaDB has it from
+/// 10.0.2; MySQL, up to 8.x at least, doesn't.
+fn supports_add_column_if_not_exists(server_version: &str) -> bool {
+    if !server_version.to_ascii_lowercase().contains("mariadb") {
+        return false;
+    }
+    let numbers: Vec<u32> = server_version
+        .split(|c: char| !c.is_ascii_digit())
+        .take(3)
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    numbers.as_slice() >= [10, 0, 2].as_slice()
+}
+
+/// `value` as a single-quoted MySQL string literal.
+fn mysql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// An added column's SQL, made safe to run again once the column exists.
+/// Postgres and MariaDB get `IF NOT EXISTS`; on MySQL the `ALTER TABLE` is
+/// prepared and run only when `information_schema.columns` doesn't list the
+/// column yet.
+fn idempotent_column_add(sql: &str, server_version: Option<&str>) -> String {
+    let mysql_guard = !server_version.is_some_and(supports_add_column_if_not_exists);
+    split_statements(sql)
+        .iter()
+        .map(|statement| {
+            let Some((target, clause)) = split_alter(statement) else {
+                return format!("{};", statement);
+            };
+            let Some(column_def) = clause.strip_prefix("ADD COLUMN ") else {
+                return format!("{};", statement);
+            };
+            if !(mysql_guard && target.starts_with('`')) {
+                return format!("ALTER TABLE {} ADD COLUMN IF NOT EXISTS {};", target, column_def);
+            }
+            let unquote = |name: &str| name.trim_matches('`').replace("``", "`");
+            let column = column_def
+                .strip_prefix('`')
+                .and_then(|rest| rest.find("` ").map(|end| &column_def[..end + 2]))
+                .unwrap_or_default();
+            format!(
+                "SET @ddl = IF((SELECT COUNT(*) FROM information_schema.columns WHERE table_schema = DATABASE() AND table_name = {} AND column_name = {}) = 0, {}, 'DO 0');\nPREPARE stmt FROM @ddl;\nEXECUTE stmt;\nDEALLOCATE PREPARE stmt;",
+                mysql_string(&unquote(target)),
+                mysql_string(&unquote(column)),
+                mysql_string(statement)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The items' SQL with runs of consecutive `ALTER TABLE` statements on one
+/// table merged into a single statement. Nothing is reordered; a statement
+/// that can't be merged ends the run, as does a clause on a constraint or index
+/// the run already drops or adds: MySQL rejects `DROP FOREIGN KEY x, ADD
+/// CONSTRAINT x` in one statement. With `annotate`, a merged statement is
+/// preceded by the annotations of every item in it.
+fn coalesce_alters(items: &[&DiffItem], annotate: bool) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut run: Option<(String, Vec<String>)> = None;
+    // Constraint and index names the current run touches.
+    let mut touched: Vec<String> = Vec::new();
+    let mut notes: Vec<String> = Vec::new();
+    let flush = |run: &mut Option<(String, Vec<String>)>,
+                 notes: &mut Vec<String>,
+                 out: &mut Vec<String>| {
+        if let Some((target, clauses)) = run.take() {
+            let statement = match clauses.as_slice() {
+                [clause] => format!("ALTER TABLE {} {};", target, clause),
+                _ => format!("ALTER TABLE {}\n  {};", target, clauses.join(",\n  ")),
+            };
+            notes.push(statement);
+            out.push(notes.join("\n"));
+            notes.clear();
+        }
+    };
+
+    for item in items {
+        let statements = item.sql_statements();
+        let clauses: Option<Vec<(&str, &str)>> = statements
+            .iter()
+            .map(|s| alter_clause(s.trim_end_matches(';')))
+            .collect();
+        let Some(clauses) = clauses else {
+            flush(&mut run, &mut notes, &mut out);
+            out.push(if annotate {
+                format!("{}\n{}", annotation(item), item.sql)
+            } else {
+                item.sql.clone()
+            });
+            continue;
+        };
+        for (i, (target, clause)) in clauses.into_iter().enumerate() {
+            let name = constraint_name(clause);
+            let clashes = name
+                .as_ref()
+                .is_some_and(|n| touched.iter().any(|t| t.eq_ignore_ascii_case(n)));
+            match &mut run {
+                Some((current, pending)) if current == target && !clashes => {
+                    pending.push(clause.to_string())
+                }
+                _ => {
+                    flush(&mut run, &mut notes, &mut out);
+                    run = Some((target.to_string(), vec![clause.to_string()]));
+                    touched.clear();
+                }
+            }
+            touched.extend(name);
+            if annotate && i == 0 {
+                notes.push(annotation(item));
+            }
+        }
+    }
+    flush(&mut run, &mut notes, &mut out);
+    out
+}
+
+/// The constraint or index an `ADD`/`DROP` clause names, unquoted; `PRIMARY`
+/// for an unnamed primary key. `None` for clauses on columns and unnamed
+/// constraints.
+fn constraint_name(clause: &str) -> Option<String> {
+    let mut words = clause.split_whitespace();
+    let action = words.next()?.to_ascii_uppercase();
+    if action != "ADD" && action != "DROP" {
+        return None;
+    }
+    let mut keyword = false;
+    let mut primary = false;
+    for word in words {
+        match word.to_ascii_uppercase().as_str() {
+            "CONSTRAINT" | "FOREIGN" | "KEY" | "INDEX" | "UNIQUE" | "CHECK" => keyword = true,
+            "PRIMARY" => primary = true,
+            "IF" | "NOT" | "EXISTS" => {}
+            _ if word.starts_with('(') => break,
+            _ => return keyword.then(|| super::minimize::unquote(word)),
+        }
+    }
+    primary.then(|| "PRIMARY".to_string())
+}
+
+/// Split `ALTER TABLE <table> <clause>` into the (quoted) table and the
+/// clause, if the clause can share a statement with others. Renames,
+/// constraint validation and MySQL's `ALGORITHM=`/`LOCK=` options have to run
+/// on their own.
+fn alter_clause(statement: &str) -> Option<(&str, &str)> {
+    let (target, clause) = split_alter(statement)?;
+    let upper = clause.to_ascii_uppercase();
+    let standalone = ["ONLY", "IF"]
+        .iter()
+        .any(|kw| target.eq_ignore_ascii_case(kw))
+        || upper.starts_with("RENAME")
+        || upper.contains("VALIDATE CONSTRAINT")
+        || upper.contains("ALGORITHM=");
+    (!standalone && !clause.is_empty()).then_some((target, clause))
+}
+
+/// Split `ALTER TABLE <table> <rest>` into the (quoted) table and the rest.
+fn split_alter(statement: &str) -> Option<(&str, &str)> {
+    let statement = statement.trim();
+    let prefix = statement.get(..12)?;
+    if !prefix.eq_ignore_ascii_case("ALTER TABLE ") {
+        return None;
+    }
+    let rest = &statement[12..];
+    let end = match rest.chars().next()? {
+        quote @ ('`' | '"') => {
+            // Doubled quotes are part of the name.
+            let mut chars = rest.char_indices().skip(1).peekable();
+            loop {
+                let (i, c) = chars.next()?;
+                if c == quote {
+                    if chars.peek().is_some_and(|&(_, next)| next == quote) {
+                        chars.next();
+                    } else {
+                        break i + 1;
+                    }
+                }
+            }
+        }
+        _ => rest.find(char::is_whitespace)?,
+    };
+    let (target, clause) = rest.split_at(end);
+    Some((target, clause.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::fixtures::sql_item;
+
+    fn category_ordered_items() -> Vec<DiffItem> {
+        vec![
+            sql_item(DiffType::TableAdded, "users", "CREATE TABLE users;"),
+            sql_item(DiffType::ColumnAdded, "orders", "ALTER TABLE orders ADD a;"),
+            sql_item(DiffType::ColumnAdded, "users", "ALTER TABLE users ADD b;"),
+            sql_item(DiffType::IndexAdded, "orders", "CREATE INDEX i ON orders;"),
+            sql_item(DiffType::IndexAdded, "users", "CREATE INDEX j ON users;"),
+        ]
+    }
+
+    #[test]
+    fn ungrouped_keeps_input_order() {
+        let script = generate_script(&category_ordered_items(), &ScriptOptions::default());
+        assert_eq!(
+            script,
+            "CREATE TABLE users;\n\nALTER TABLE orders ADD a;\n\nALTER TABLE users ADD b;\n\nCREATE INDEX i ON orders;\n\nCREATE INDEX j ON users;"
+        );
+        assert!(!script.contains("-- Table:"));
+    }
+
+    #[test]
+    fn grouped_items_for_a_table_are_consecutive() {
+        let options = ScriptOptions {
+            group_by_table: true,
+            ..Default::default()
+        };
+        let mut items = category_ordered_items();
+        items.push(sql_item(DiffType::ForeignKeyAdded,
+            "orders",
+            "ALTER TABLE orders ADD CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id);",
+        ));
+        let script = generate_script(&items, &options);
+        let lines: Vec<&str> = script.lines().filter(|l| !l.is_empty()).collect();
+        // users is created before the orders foreign key that references it.
+        assert_eq!(
+            lines,
+            vec![
+                "-- Table: users",
+                "CREATE TABLE users;",
+                "ALTER TABLE users ADD b;",
+                "CREATE INDEX j ON users;",
+                "-- Table: orders",
+                "ALTER TABLE orders ADD a;",
+                "CREATE INDEX i ON orders;",
+                "ALTER TABLE orders ADD CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id);",
+            ]
+        );
+    }
+
+    #[test]
+    fn idempotent_column_add_is_guarded_on_older_mysql() {
+        let items = [sql_item(
+            DiffType::ColumnAdded,
+            "users",
+            "ALTER TABLE `users` ADD COLUMN `nick` varchar(20) NULL DEFAULT 'n/a';",
+        )];
+        let options = ScriptOptions {
+            idempotent: true,
+            server_version: Some("5.7.44".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_script(&items, &options),
+            "SET @ddl = IF((SELECT COUNT(*) FROM information_schema.columns WHERE table_schema = DATABASE() AND table_name = 'users' AND column_name = 'nick') = 0, 'ALTER TABLE `users` ADD COLUMN `nick` varchar(20) NULL DEFAULT ''n/a''', 'DO 0');\nPREPARE stmt FROM @ddl;\nEXECUTE stmt;\nDEALLOCATE PREPARE stmt;"
+        );
+
+        let mariadb = ScriptOptions {
+            server_version: Some("10.11.6-MariaDB".to_string()),
+            ..options
+        };
+        assert_eq!(
+            generate_script(&items, &mariadb),
+            "ALTER TABLE `users` ADD COLUMN IF NOT EXISTS `nick` varchar(20) NULL DEFAULT 'n/a';"
+        );
+    }
+
+    #[test]
+    fn skips_items_without_sql() {
+        let items = vec![
+            sql_item(DiffType::ColumnAdded, "users", ""),
+            sql_item(DiffType::ColumnAdded, "users", "ALTER TABLE users ADD b;"),
+        ];
+        let options = ScriptOptions {
+            group_by_table: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_script(&items, &options),
+            "-- Table: users\nALTER TABLE users ADD b;"
+        );
+    }
+
+    #[test]
+    fn coalesces_column_changes_on_one_table() {
+        let items = vec![
+            sql_item(
+                DiffType::ColumnAdded,
+                "users",
+                "ALTER TABLE `users` ADD COLUMN `age` int;",
+            ),
+            sql_item(
+                DiffType::ColumnModified,
+                "users",
+                "ALTER TABLE `users` MODIFY COLUMN `name` varchar(100) NOT NULL;",
+            ),
+            sql_item(
+                DiffType::ColumnRemoved,
+                "users",
+                "ALTER TABLE `users` DROP COLUMN `legacy`;",
+            ),
+        ];
+        let options = ScriptOptions {
+            coalesce_alters: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_script(&items, &options),
+            "ALTER TABLE `users`\n  ADD COLUMN `age` int,\n  MODIFY COLUMN `name` varchar(100) NOT NULL,\n  DROP COLUMN `legacy`;"
+        );
+    }
+
+    #[test]
+    fn coalescing_keeps_order_across_other_statements() {
+        let items = vec![
+            sql_item(
+                DiffType::ColumnAdded,
+                "my \"users\"",
+                "ALTER TABLE \"my \"\"users\"\"\" ADD COLUMN a int;",
+            ),
+            sql_item(
+                DiffType::ColumnModified,
+                "my \"users\"",
+                "ALTER TABLE \"my \"\"users\"\"\" ALTER COLUMN b TYPE bigint;\nALTER TABLE \"my \"\"users\"\"\" ALTER COLUMN b SET NOT NULL;",
+            ),
+            sql_item(
+                DiffType::IndexAdded,
+                "orders",
+                "CREATE INDEX i ON orders (a);",
+            ),
+            sql_item(
+                DiffType::ForeignKeyAdded,
+                "orders",
+                "ALTER TABLE orders ADD CONSTRAINT fk FOREIGN KEY (a) REFERENCES x (id) NOT VALID;\nALTER TABLE orders VALIDATE CONSTRAINT fk;",
+            ),
+            sql_item(
+                DiffType::ColumnAdded,
+                "orders",
+                "ALTER TABLE orders ADD COLUMN c int;",
+            ),
+        ];
+        let options = ScriptOptions {
+            coalesce_alters: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_script(&items, &options),
+            "ALTER TABLE \"my \"\"users\"\"\"\n  ADD COLUMN a int,\n  ALTER COLUMN b TYPE bigint,\n  ALTER COLUMN b SET NOT NULL;\n\n\
+             CREATE INDEX i ON orders (a);\n\n\
+             ALTER TABLE orders ADD CONSTRAINT fk FOREIGN KEY (a) REFERENCES x (id) NOT VALID;\nALTER TABLE orders VALIDATE CONSTRAINT fk;\n\n\
+             ALTER TABLE orders ADD COLUMN c int;"
+        );
+    }
+
+    #[test]
+    fn drop_and_add_of_one_constraint_stay_separate() {
+        let items = vec![
+            sql_item(
+                DiffType::ForeignKeyModified,
+                "orders",
+                "ALTER TABLE `orders` DROP FOREIGN KEY `fk_user`;\nALTER TABLE `orders` ADD CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`);",
+            ),
+            sql_item(
+                DiffType::ColumnAdded,
+                "orders",
+                "ALTER TABLE `orders` ADD COLUMN `note` text;",
+            ),
+        ];
+        let options = ScriptOptions {
+            coalesce_alters: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_script(&items, &options),
+            "ALTER TABLE `orders` DROP FOREIGN KEY `fk_user`;\n\n\
+             ALTER TABLE `orders`\n  ADD CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`),\n  ADD COLUMN `note` text;"
+        );
+    }
+
+    #[test]
+    fn annotated_statements_show_both_definitions() {
+        let mut modified = sql_item(
+            DiffType::ColumnModified,
+            "users",
+            "ALTER TABLE `users` MODIFY COLUMN `email` varchar(100) NULL;",
+        );
+        modified.object_name = Some("email".to_string());
+        modified.target_def = Some("varchar(255) NULL".to_string());
+        modified.source_def = Some("varchar(100) NULL".to_string());
+        let mut removed = sql_item(
+            DiffType::ColumnRemoved,
+            "users",
+            "ALTER TABLE `users` DROP COLUMN `legacy`;",
+        );
+        removed.object_name = Some("legacy".to_string());
+        removed.target_def = Some("int".to_string());
+        let options = ScriptOptions {
+            annotate: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_script(&[modified.clone(), removed.clone()], &options),
+            "-- modify users.email: varchar(255) NULL -> varchar(100) NULL\nALTER TABLE `users` MODIFY COLUMN `email` varchar(100) NULL;\n\n-- drop users.legacy: int\nALTER TABLE `users` DROP COLUMN `legacy`;"
+        );
+
+        // Merged statements keep every item's note.
+        let options = ScriptOptions {
+            annotate: true,
+            coalesce_alters: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_script(&[modified, removed], &options),
+            "-- modify users.email: varchar(255) NULL -> varchar(100) NULL\n-- drop users.legacy: int\nALTER TABLE `users`\n  MODIFY COLUMN `email` varchar(100) NULL,\n  DROP COLUMN `legacy`;"
+        );
+    }
+}
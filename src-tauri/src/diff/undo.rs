@@ -0,0 +1,204 @@
+use crate::db::SqlGenerator;
+use crate::models::*;
+
+/// SQL that takes the target back to how it was before the first `ran`
+/// statements of `item` were applied, built from `target`, the target's tables as
+/// read before the sync. `ran` is less than the item's statement count when it
+/// failed partway: a modified index or constraint whose drop ran but whose
+/// re-creation failed is compensated by re-creating the original alone.
+///
+/// `None` when the change can't be reversed from the schema alone, e.g. a
+/// routine change, or when the object it touched isn't in `target`. Undoing a
+/// removal recreates the structure, not the data it held.
+pub fn undo_sql(
+    item: &DiffItem,
+    ran: usize,
+    target: &[TableSchema],
+    sql_gen: &dyn SqlGenerator,
+) -> Option<String> {
+    let table_name = item.table_name.as_str();
+    let table = target.iter().find(|t| t.name == table_name);
+    let object = item.object_name.as_deref();
+    let partial = ran < item.sql_statements().len();
+    let sql =
+        match item.diff_type {
+            DiffType::TableAdded => sql_gen.generate_drop_table(table_name),
+            DiffType::TableRemoved => sql_gen.generate_create_table(table?),
+            DiffType::ColumnAdded => sql_gen.generate_drop_column(table_name, object?),
+            DiffType::ColumnRemoved => sql_gen
+                .generate_add_column(table_name, find(&table?.columns, object?, |c| &c.name)?),
+            DiffType::ColumnModified => sql_gen
+                .generate_modify_column(table_name, find(&table?.columns, object?, |c| &c.name)?),
+            DiffType::IndexAdded => sql_gen.generate_drop_index(table_name, object?),
+            DiffType::IndexRemoved => {
+                sql_gen.generate_add_index(table_name, find(&table?.indexes, object?, |i| &i.name)?)
+            }
+            DiffType::IndexModified => {
+                let index = find(&table?.indexes, object?, |i| &i.name)?;
+                let add = sql_gen.generate_add_index(table_name, index);
+                if partial {
+                    add
+                } else {
+                    format!(
+                        "{}\n{}",
+                        sql_gen.generate_drop_index(table_name, &index.name),
+                        add
+                    )
+                }
+            }
+            DiffType::ForeignKeyAdded => sql_gen.generate_drop_foreign_key(table_name, object?),
+            DiffType::ForeignKeyRemoved => sql_gen.generate_add_foreign_key(
+                table_name,
+                find(&table?.foreign_keys, object?, |fk| &fk.name)?,
+            ),
+            DiffType::ForeignKeyModified => {
+                let fk = find(&table?.foreign_keys, object?, |fk| &fk.name)?;
+                let add = sql_gen.generate_add_foreign_key(table_name, fk);
+                if partial {
+                    add
+                } else {
+                    format!(
+                        "{}\n{}",
+                        sql_gen.generate_drop_foreign_key(table_name, &fk.name),
+                        add
+                    )
+                }
+            }
+            DiffType::UniqueConstraintAdded => sql_gen.generate_drop_unique(table_name, object?),
+            DiffType::UniqueConstraintRemoved => sql_gen.generate_add_unique(
+                table_name,
+                find(&table?.unique_constraints, object?, |uc| &uc.name)?,
+            ),
+            DiffType::UniqueConstraintModified => {
+                let uc = find(&table?.unique_constraints, object?, |uc| &uc.name)?;
+                let add = sql_gen.generate_add_unique(table_name, uc);
+                if partial {
+                    add
+                } else {
+                    format!(
+                        "{}\n{}",
+                        sql_gen.generate_drop_unique(table_name, &uc.name),
+                        add
+                    )
+                }
+            }
+            DiffType::CheckConstraintAdded => sql_gen.generate_drop_check(table_name, object?),
+            DiffType::CheckConstraintRemoved => sql_gen.generate_add_check(
+                table_name,
+                find(&table?.check_constraints, object?, |c| &c.name)?,
+            ),
+            DiffType::CheckConstraintModified => {
+                let check = find(&table?.check_constraints, object?, |c| &c.name)?;
+                let add = sql_gen.generate_add_check(table_name, check);
+                if partial {
+                    add
+                } else {
+                    format!(
+                        "{}\n{}",
+                        sql_gen.generate_drop_check(table_name, &check.name),
+                        add
+                    )
+                }
+            }
+            _ => return None,
+        };
+    Some(sql)
+}
+
+fn find<'a, T>(items: &'a [T], name: &str, name_of: impl Fn(&T) -> &String) -> Option<&'a T> {
+    items.iter().find(|item| name_of(item) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MySqlSqlGenerator;
+    use crate::diff::fixtures::{column, sql_item, table};
+
+    fn users() -> TableSchema {
+        TableSchema {
+            indexes: vec![Index {
+                name: "idx_email".to_string(),
+                columns: vec!["email".to_string()],
+                unique: false,
+                index_type: "BTREE".to_string(),
+                tablespace: None,
+                visible: true,
+                expression: None,
+                storage_params: vec![],
+                comment: None,
+            }],
+            ..table("users", vec![column("email", "varchar(100)")])
+        }
+    }
+
+    #[test]
+    fn additions_are_undone_by_dropping_and_changes_by_restoring_the_target() {
+        let target = vec![users()];
+        let sql_gen = MySqlSqlGenerator;
+        let undo = |diff_type, object: &str| {
+            let item = DiffItem {
+                object_name: Some(object.to_string()),
+                ..sql_item(diff_type, "users", "x;")
+            };
+            undo_sql(&item, 1, &target, &sql_gen)
+        };
+
+        assert_eq!(
+            undo(DiffType::ColumnAdded, "nick"),
+            Some(sql_gen.generate_drop_column("users", "nick"))
+        );
+        assert_eq!(
+            undo(DiffType::ColumnModified, "email"),
+            Some(sql_gen.generate_modify_column("users", &target[0].columns[0]))
+        );
+        assert_eq!(
+            undo(DiffType::ColumnRemoved, "email"),
+            Some(sql_gen.generate_add_column("users", &target[0].columns[0]))
+        );
+    }
+
+    #[test]
+    fn partly_applied_modification_only_restores_what_was_dropped() {
+        let target = vec![users()];
+        let sql_gen = MySqlSqlGenerator;
+        let modified = DiffItem {
+            object_name: Some("idx_email".to_string()),
+            ..sql_item(
+                DiffType::IndexModified,
+                "users",
+                "DROP INDEX `idx_email` ON `users`;\nCREATE INDEX `idx_email` ON `users` (`nick`);",
+            )
+        };
+        let original = sql_gen.generate_add_index("users", &target[0].indexes[0]);
+
+        assert_eq!(
+            undo_sql(&modified, 1, &target, &sql_gen),
+            Some(original.clone())
+        );
+        assert_eq!(
+            undo_sql(&modified, 2, &target, &sql_gen),
+            Some(format!(
+                "{}\n{}",
+                sql_gen.generate_drop_index("users", "idx_email"),
+                original
+            ))
+        );
+    }
+
+    #[test]
+    fn irreversible_or_unknown_changes_have_no_undo() {
+        let target = vec![users()];
+        let sql_gen = MySqlSqlGenerator;
+        let undo = |diff_type, object: &str| {
+            let item = DiffItem {
+                object_name: Some(object.to_string()),
+                ..sql_item(diff_type, "users", "x;")
+            };
+            undo_sql(&item, 1, &target, &sql_gen)
+        };
+
+        assert_eq!(undo(DiffType::RoutineModified, "f"), None);
+        assert_eq!(undo(DiffType::ColumnRemoved, "missing"), None);
+    }
+}
@@ -1,27 +1,39 @@
 use log::{error, info};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
-use database_structure_sync_lib::db::{MySqlDriver, PostgresDriver, SchemaReader, SqlGenerator};
+use database_structure_sync_lib::db::{
+    ConnectionCache, GeneratorRegistry, MySqlDriver, PostgresDriver, SchemaFile, SchemaReader,
+    SqlExecutor, SqlGenerator, statement_timeout_params,
+};
 use database_structure_sync_lib::error::{AppError, AppResult};
-use database_structure_sync_lib::models::{Connection, DbType};
+use database_structure_sync_lib::models::{Connection, ConnectionSecrets, DbType};
 use database_structure_sync_lib::ssh::SshTunnel;
 use database_structure_sync_lib::storage::ConfigStore;
 use database_structure_sync_lib::types::{
     MariaDbTypeMapper, MySqlTypeMapper, PostgresTypeMapper, TypeMapper,
 };
 
+/// How long an unused cached driver keeps its pool open.
+pub(crate) const DRIVER_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
 pub struct AppState {
     pub config_store: Arc<Mutex<ConfigStore>>,
-    pub active_tunnels: Arc<Mutex<Vec<SshTunnel>>>,
+    pub driver_cache: ConnectionCache<DatabaseDriver>,
+    /// SQL generator for each dialect; used for every diff the app produces.
+    pub generators: GeneratorRegistry,
+    /// Running drift monitors by id, aborted by `stop_drift_monitor`.
+    pub drift_monitors: Arc<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>>,
 }
 
 /// Resolve connection host and port, applying SSH tunnel if configured.
-/// When SSH is enabled, creates a local tunnel and returns `("127.0.0.1", local_port)`.
+/// When SSH is enabled, creates a local tunnel and returns `("127.0.0.1", local_port)`
+/// along with the tunnel, which stays open until it is dropped.
 pub(crate) async fn resolve_connection_endpoint(
     conn: &Connection,
-    tunnels: &Arc<Mutex<Vec<SshTunnel>>>,
-) -> Result<(String, u16), AppError> {
+) -> Result<(String, u16, Option<SshTunnel>), AppError> {
     if let Some(ssh) = &conn.ssh_config {
         if ssh.enabled {
             info!("Creating SSH tunnel for connection: {}", conn.name);
@@ -29,29 +41,53 @@ pub(crate) async fn resolve_connection_endpoint(
                 .await
                 .map_err(|e| AppError::Connection(format!("SSH tunnel failed: {}", e)))?;
             let local_port = tunnel.local_port();
-            tunnels.lock().await.push(tunnel);
             info!("SSH tunnel established on 127.0.0.1:{}", local_port);
-            return Ok(("127.0.0.1".to_string(), local_port));
+            return Ok(("127.0.0.1".to_string(), local_port, Some(tunnel)));
         }
     }
-    Ok((conn.host.clone(), conn.port))
+    Ok((conn.host.clone(), conn.port, None))
+}
+
+/// Database driver that implements both SchemaReader and SqlGenerator.
+/// Holds the SSH tunnel its pool connects through, so the tunnel closes once
+/// the driver and any `use_database` drivers made from it are dropped (e.g.
+/// evicted from or invalidated in the cache).
+pub(crate) struct DatabaseDriver {
+    backend: Backend,
+    tunnel: Option<Arc<SshTunnel>>,
 }
 
-/// Database driver that implements both SchemaReader and SqlGenerator
-pub(crate) enum DatabaseDriver {
+enum Backend {
     MySql(MySqlDriver),
     Postgres(PostgresDriver),
+    File(SchemaFile),
 }
 
 impl DatabaseDriver {
+    /// Connect with `conn`, taking its password and SSH secrets from `secrets`
+    /// when it has `prompt_for_secrets` set and failing when they're missing.
     pub(crate) async fn create(
         conn: &Connection,
-        tunnels: &Arc<Mutex<Vec<SshTunnel>>>,
+        secrets: Option<&ConnectionSecrets>,
     ) -> AppResult<Self> {
-        let (host, port) = resolve_connection_endpoint(conn, tunnels).await?;
+        if let Some(path) = &conn.schema_file {
+            info!("Loading schema file for: {}", conn.name);
+            let file = SchemaFile::load(path, &conn.db_type)
+                .map_err(|e| AppError::Connection(format!("{:#}", e)))?;
+            return Ok(DatabaseDriver {
+                backend: Backend::File(file),
+                tunnel: None,
+            });
+        }
+        let conn = &conn.with_secrets(secrets)?.resolve_secrets()?;
+        let (host, port, tunnel) = resolve_connection_endpoint(conn).await?;
         let ssl_config = conn.ssl_config.as_ref();
+        let mut session_params = conn.session_params.clone();
+        if let Some(secs) = conn.statement_timeout_secs {
+            session_params.extend(statement_timeout_params(&conn.db_type, secs));
+        }
 
-        match conn.db_type {
+        let backend = match conn.db_type {
             DbType::MySQL | DbType::MariaDB => {
                 info!("Creating MySQL/MariaDB driver for: {}", conn.name);
                 let driver = MySqlDriver::new_with_ssl(
@@ -61,10 +97,11 @@ impl DatabaseDriver {
                     &conn.password,
                     &conn.database,
                     ssl_config,
+                    &session_params,
                 )
                 .await
                 .map_err(|e| AppError::Connection(e.to_string()))?;
-                Ok(DatabaseDriver::MySql(driver))
+                Backend::MySql(driver)
             }
             DbType::PostgreSQL => {
                 info!("Creating PostgreSQL driver for: {}", conn.name);
@@ -75,25 +112,89 @@ impl DatabaseDriver {
                     &conn.password,
                     &conn.database,
                     ssl_config,
+                    &session_params,
                 )
                 .await
                 .map_err(|e| AppError::Connection(e.to_string()))?;
-                Ok(DatabaseDriver::Postgres(driver))
+                Backend::Postgres(driver)
+            }
+        };
+        Ok(DatabaseDriver {
+            backend,
+            tunnel: tunnel.map(Arc::new),
+        })
+    }
+
+    /// A driver for reading several databases of this server in turn through
+    /// one connection and this driver's SSH tunnel; point it at each database
+    /// with `use_database`.
+    pub(crate) async fn server_session(self: &Arc<Self>) -> AppResult<Arc<DatabaseDriver>> {
+        match &self.backend {
+            Backend::MySql(d) => {
+                let driver = d
+                    .single_connection()
+                    .await
+                    .map_err(|e| AppError::Connection(e.to_string()))?;
+                Ok(Arc::new(DatabaseDriver {
+                    backend: Backend::MySql(driver),
+                    tunnel: self.tunnel.clone(),
+                }))
+            }
+            Backend::Postgres(_) | Backend::File(_) => Ok(Arc::clone(self)),
+        }
+    }
+
+    /// Point a `server_session` driver at `database`. MySQL switches its one
+    /// connection with `USE`; Postgres can't change the database of an open
+    /// connection, so it gets a connection to `database` over the same tunnel.
+    /// A schema file holds a single schema and is returned as is.
+    pub(crate) async fn use_database(
+        self: &Arc<Self>,
+        database: &str,
+    ) -> AppResult<Arc<DatabaseDriver>> {
+        match &self.backend {
+            Backend::MySql(d) => {
+                d.use_database(database)
+                    .await
+                    .map_err(|e| AppError::Connection(e.to_string()))?;
+                Ok(Arc::clone(self))
+            }
+            Backend::Postgres(d) => {
+                let driver = d
+                    .connect_to_database(database)
+                    .await
+                    .map_err(|e| AppError::Connection(e.to_string()))?;
+                Ok(Arc::new(DatabaseDriver {
+                    backend: Backend::Postgres(driver),
+                    tunnel: self.tunnel.clone(),
+                }))
             }
+            Backend::File(_) => Ok(Arc::clone(self)),
+        }
+    }
+
+    /// Whether the underlying pool can still hand out connections.
+    pub(crate) fn is_healthy(&self) -> bool {
+        match &self.backend {
+            Backend::MySql(d) => !d.pool().is_closed(),
+            Backend::Postgres(d) => !d.pool().is_closed(),
+            Backend::File(_) => true,
         }
     }
 
     pub(crate) fn as_reader(&self) -> &dyn SchemaReader {
-        match self {
-            DatabaseDriver::MySql(d) => d,
-            DatabaseDriver::Postgres(d) => d,
+        match &self.backend {
+            Backend::MySql(d) => d,
+            Backend::Postgres(d) => d,
+            Backend::File(f) => f,
         }
     }
 
     pub(crate) fn as_sql_generator(&self) -> &dyn SqlGenerator {
-        match self {
-            DatabaseDriver::MySql(d) => d,
-            DatabaseDriver::Postgres(d) => d,
+        match &self.backend {
+            Backend::MySql(d) => d,
+            Backend::Postgres(d) => d,
+            Backend::File(f) => f.sql_generator(),
         }
     }
 
@@ -105,23 +206,12 @@ impl DatabaseDriver {
         }
     }
 
-    pub(crate) async fn execute_sql(&self, sql: &str) -> Result<(), sqlx::Error> {
-        for stmt in sql.split(';') {
-            let stmt = stmt.trim();
-            if stmt.is_empty() {
-                continue;
-            }
-            let full = format!("{};", stmt);
-            match self {
-                DatabaseDriver::MySql(d) => {
-                    sqlx::query(&full).execute(d.pool()).await?;
-                }
-                DatabaseDriver::Postgres(d) => {
-                    sqlx::query(&full).execute(d.pool()).await?;
-                }
-            }
+    pub(crate) fn as_executor(&self) -> &dyn SqlExecutor {
+        match &self.backend {
+            Backend::MySql(d) => d,
+            Backend::Postgres(d) => d,
+            Backend::File(f) => f,
         }
-        Ok(())
     }
 }
 
@@ -141,17 +231,35 @@ pub(crate) async fn load_connection(
         })
 }
 
-/// Create a DatabaseDriver for a connection, optionally overriding the database name.
+/// Get a DatabaseDriver for a connection, optionally overriding the database name.
+/// Reuses a cached driver for the same connection and database when still healthy;
+/// schema files are re-read every time so edits to the file are picked up.
+/// `secrets` holds, by connection ID, the secrets of connections that don't store them.
 pub(crate) async fn create_driver(
+    state: &AppState,
     conn: &mut Connection,
     database_override: Option<String>,
-    tunnels: &Arc<Mutex<Vec<SshTunnel>>>,
-) -> Result<DatabaseDriver, String> {
+    secrets: Option<&HashMap<String, ConnectionSecrets>>,
+) -> Result<Arc<DatabaseDriver>, String> {
     if let Some(db) = database_override {
         conn.database = db;
     }
-    DatabaseDriver::create(conn, tunnels).await.map_err(|e| {
-        error!("Failed to connect ({}): {}", conn.name, e);
-        e.to_string()
-    })
+    let conn = &*conn;
+    let secrets = secrets.and_then(|s| s.get(&conn.id));
+    if conn.schema_file.is_some() {
+        return DatabaseDriver::create(conn, secrets)
+            .await
+            .map(Arc::new)
+            .map_err(|e| e.to_string());
+    }
+    state
+        .driver_cache
+        .get_or_create(&conn.id, &conn.database, DatabaseDriver::is_healthy, || {
+            DatabaseDriver::create(conn, secrets)
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to connect ({}): {}", conn.name, e);
+            e.to_string()
+        })
 }
@@ -1,6 +1,7 @@
 pub mod db;
 pub mod diff;
 pub mod error;
+pub mod logging;
 pub mod models;
 pub mod ssh;
 pub mod storage;
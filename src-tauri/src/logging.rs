@@ -0,0 +1,34 @@
+use log::LevelFilter;
+
+use crate::error::{AppError, AppResult};
+
+/// Level the app starts at. The log plugin itself is built at `Trace`, so
+/// `set_log_level` can raise verbosity later without a restart.
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// Change the global log filter at runtime, e.g. to `"debug"` while reproducing a bug.
+pub fn set_log_level(level: &str) -> AppResult<LevelFilter> {
+    let filter: LevelFilter = level
+        .trim()
+        .parse()
+        .map_err(|_| AppError::Validation(format!("Unknown log level: {}", level)))?;
+    log::set_max_level(filter);
+    Ok(filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_log_level_changes_max_level() {
+        assert_eq!(set_log_level("debug").unwrap(), LevelFilter::Debug);
+        assert_eq!(log::max_level(), LevelFilter::Debug);
+
+        assert!(set_log_level("chatty").is_err());
+        assert_eq!(log::max_level(), LevelFilter::Debug);
+
+        set_log_level(" INFO ").unwrap();
+        assert_eq!(log::max_level(), DEFAULT_LOG_LEVEL);
+    }
+}
@@ -5,12 +5,15 @@ mod commands;
 mod driver;
 
 use log::info;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::Manager;
 use tokio::sync::Mutex;
 
+use database_structure_sync_lib::db::{ConnectionCache, GeneratorRegistry};
+use database_structure_sync_lib::logging::DEFAULT_LOG_LEVEL;
 use database_structure_sync_lib::storage::ConfigStore;
-use driver::AppState;
+use driver::{AppState, DRIVER_IDLE_TIMEOUT};
 
 fn main() {
     tauri::Builder::default()
@@ -27,10 +30,13 @@ fn main() {
                 .target(tauri_plugin_log::Target::new(
                     tauri_plugin_log::TargetKind::Stdout,
                 ))
-                .level(log::LevelFilter::Info)
+                // The plugin passes everything through; the effective level is
+                // log::max_level(), which `set_log_level` adjusts at runtime.
+                .level(log::LevelFilter::Trace)
                 .build(),
         )
         .setup(|app| {
+            log::set_max_level(DEFAULT_LOG_LEVEL);
             info!("Starting Database Structure Sync application");
             let app_data_dir = app
                 .path()
@@ -43,7 +49,9 @@ fn main() {
                     .expect("Failed to initialize config store");
                 app.manage(AppState {
                     config_store: Arc::new(Mutex::new(config_store)),
-                    active_tunnels: Arc::new(Mutex::new(Vec::new())),
+                    driver_cache: ConnectionCache::new(DRIVER_IDLE_TIMEOUT),
+                    generators: GeneratorRegistry::default(),
+                    drift_monitors: Arc::new(Mutex::new(HashMap::new())),
                 });
             });
             info!("Application setup complete");
@@ -56,10 +64,40 @@ fn main() {
             commands::update_connection,
             commands::delete_connection,
             commands::test_connection,
+            commands::test_ssh_tunnel,
+            commands::ping_connection,
             commands::list_databases,
+            commands::list_tables,
+            commands::self_consistency_check,
+            commands::save_schema_snapshot,
+            commands::compare_against_snapshot,
+            commands::generate_drop_all,
+            commands::get_database_info,
             commands::compare_databases,
+            commands::compare_databases_safe,
+            commands::compare_multi_database,
+            commands::save_compare_preset,
+            commands::list_compare_presets,
+            commands::delete_compare_preset,
+            commands::compare_tables_subset,
+            commands::are_schemas_in_sync,
+            commands::start_drift_monitor,
+            commands::stop_drift_monitor,
             commands::execute_sync,
-            commands::save_sql_file
+            commands::execute_sync_report,
+            commands::apply_diff_items_with_rollback,
+            commands::apply_diff_item,
+            commands::compare_schema_snapshots,
+            commands::generate_sql_script,
+            commands::minimize_sql_statements,
+            commands::filter_destructive_diffs,
+            commands::format_diff_report,
+            commands::get_native_create_table,
+            commands::analyze_diff_item,
+            commands::estimate_sync,
+            commands::set_log_level,
+            commands::save_sql_file,
+            commands::export_diffs_ndjson
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::error::{AppError, AppResult};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum DbType {
@@ -71,10 +73,148 @@ pub struct Connection {
     pub database: String,
     pub ssh_config: Option<SshConfig>,
     pub ssl_config: Option<SslConfig>,
+    /// Session variables set on every new connection, e.g. `("search_path", "app, public")`.
+    #[serde(default)]
+    pub session_params: Vec<(String, String)>,
+    /// Path of a schema dump read in place of a live database. Such a
+    /// connection can be compared against but never synced to.
+    #[serde(default)]
+    pub schema_file: Option<String>,
+    /// Abort any statement running longer than this, so a stuck sync fails
+    /// instead of holding locks indefinitely.
+    #[serde(default)]
+    pub statement_timeout_secs: Option<u32>,
+    /// Keep no password or SSH secret in the keyring; each connect has to be
+    /// given them (see `with_secrets`).
+    #[serde(default)]
+    pub prompt_for_secrets: bool,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Replace a value of the form `${NAME}` with `lookup(NAME)`; anything else is
+/// returned unchanged.
+fn resolve_placeholder(value: &str, lookup: &impl Fn(&str) -> Option<String>) -> AppResult<String> {
+    match value
+        .trim()
+        .strip_prefix("${")
+        .and_then(|v| v.strip_suffix('}'))
+    {
+        Some(name) => {
+            lookup(name).ok_or_else(|| AppError::Validation(format!("env var {} not set", name)))
+        }
+        None => Ok(value.to_string()),
+    }
+}
+
+fn env_lookup(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+impl SshConfig {
+    /// A copy with `${NAME}` password and passphrase placeholders resolved.
+    pub fn resolve_secrets_with(
+        &self,
+        lookup: &impl Fn(&str) -> Option<String>,
+    ) -> AppResult<SshConfig> {
+        let auth_method = match &self.auth_method {
+            SshAuthMethod::Password { password } => SshAuthMethod::Password {
+                password: resolve_placeholder(password, lookup)?,
+            },
+            SshAuthMethod::PrivateKey {
+                private_key_path,
+                passphrase,
+            } => SshAuthMethod::PrivateKey {
+                private_key_path: private_key_path.clone(),
+                passphrase: passphrase
+                    .as_deref()
+                    .map(|p| resolve_placeholder(p, lookup))
+                    .transpose()?,
+            },
+        };
+        Ok(SshConfig {
+            auth_method,
+            ..self.clone()
+        })
+    }
+
+    /// Resolve `${NAME}` placeholders from the process environment.
+    pub fn resolve_secrets(&self) -> AppResult<SshConfig> {
+        self.resolve_secrets_with(&env_lookup)
+    }
+}
+
+impl Connection {
+    /// A copy with `${NAME}` placeholders in the password and SSH secrets
+    /// resolved through `lookup`.
+    pub fn resolve_secrets_with(
+        &self,
+        lookup: &impl Fn(&str) -> Option<String>,
+    ) -> AppResult<Connection> {
+        Ok(Connection {
+            password: resolve_placeholder(&self.password, lookup)?,
+            ssh_config: self
+                .ssh_config
+                .as_ref()
+                .map(|ssh| ssh.resolve_secrets_with(lookup))
+                .transpose()?,
+            ..self.clone()
+        })
+    }
+
+    /// Resolve `${NAME}` placeholders from the process environment, so CI can
+    /// pass credentials without storing them in the keyring.
+    pub fn resolve_secrets(&self) -> AppResult<Connection> {
+        self.resolve_secrets_with(&env_lookup)
+    }
+
+    /// A copy with the password and SSH secrets taken from `secrets` when the
+    /// connection has `prompt_for_secrets` set; otherwise the stored ones are
+    /// kept. The database password, and the SSH password under password auth,
+    /// must be supplied. A key passphrase is optional, since keys may have none.
+    pub fn with_secrets(&self, secrets: Option<&ConnectionSecrets>) -> AppResult<Connection> {
+        if !self.prompt_for_secrets {
+            return Ok(self.clone());
+        }
+        let missing = |what: &str| {
+            AppError::Validation(format!("{} needs its {} to connect", self.name, what))
+        };
+        let secrets = secrets.ok_or_else(|| missing("password"))?;
+        let password = secrets
+            .password
+            .clone()
+            .ok_or_else(|| missing("password"))?;
+        let ssh_config = match &self.ssh_config {
+            Some(ssh) if ssh.enabled => {
+                let auth_method = match &ssh.auth_method {
+                    SshAuthMethod::Password { .. } => SshAuthMethod::Password {
+                        password: secrets
+                            .ssh_password
+                            .clone()
+                            .ok_or_else(|| missing("SSH password"))?,
+                    },
+                    SshAuthMethod::PrivateKey {
+                        private_key_path, ..
+                    } => SshAuthMethod::PrivateKey {
+                        private_key_path: private_key_path.clone(),
+                        passphrase: secrets.ssh_passphrase.clone(),
+                    },
+                };
+                Some(SshConfig {
+                    auth_method,
+                    ..ssh.clone()
+                })
+            }
+            other => other.clone(),
+        };
+        Ok(Connection {
+            password,
+            ssh_config,
+            ..self.clone()
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionInput {
     #[serde(default)]
@@ -89,4 +229,32 @@ pub struct ConnectionInput {
     pub database: String,
     pub ssh_config: Option<SshConfig>,
     pub ssl_config: Option<SslConfig>,
+    #[serde(default)]
+    pub session_params: Vec<(String, String)>,
+    #[serde(default)]
+    pub schema_file: Option<String>,
+    #[serde(default)]
+    pub statement_timeout_secs: Option<u32>,
+    #[serde(default)]
+    pub prompt_for_secrets: bool,
+}
+
+/// Secrets supplied at connect time for a connection that doesn't store them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionSecrets {
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub ssh_password: Option<String>,
+    #[serde(default)]
+    pub ssh_passphrase: Option<String>,
+}
+
+/// Result of a quick liveness check against a saved connection.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PingResult {
+    pub ok: bool,
+    /// Round trip of a `SELECT 1`, in milliseconds.
+    pub latency_ms: u64,
+    pub server_version: Option<String>,
 }
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::{DbType, TableSchema};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum DiffType {
@@ -8,6 +10,7 @@ pub enum DiffType {
     ColumnAdded,
     ColumnRemoved,
     ColumnModified,
+    ColumnRenamed,
     IndexAdded,
     IndexRemoved,
     IndexModified,
@@ -17,6 +20,27 @@ pub enum DiffType {
     UniqueConstraintAdded,
     UniqueConstraintRemoved,
     UniqueConstraintModified,
+    ExclusionConstraintAdded,
+    ExclusionConstraintRemoved,
+    ExclusionConstraintModified,
+    CheckConstraintAdded,
+    CheckConstraintRemoved,
+    CheckConstraintModified,
+    PrimaryKeyModified,
+    RoutineAdded,
+    RoutineRemoved,
+    RoutineModified,
+    TableRebuilt,
+    TablespaceModified,
+    RowSecurityModified,
+    PolicyAdded,
+    PolicyRemoved,
+    PolicyModified,
+    PartitioningModified,
+    TableOptionsModified,
+    InheritanceModified,
+    GrantAdded,
+    GrantRemoved,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -35,6 +59,107 @@ pub struct TypeWarning {
     pub severity: WarningSeverity,
 }
 
+/// A single column attribute that differs between source and target.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnChangeKind {
+    DataType,
+    /// The type is written differently but means the same, e.g. `INT(11)` and `int`.
+    DataTypeSpelling,
+    Nullable,
+    Default,
+    Comment,
+    AutoIncrement,
+    Visibility,
+    OnUpdate,
+    Sequence,
+    Generated,
+    Srid,
+}
+
+/// How urgent a diff is: structural changes affect behaviour, cosmetic ones
+/// (comments and other metadata) can be batched or skipped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffPriority {
+    #[default]
+    Structural,
+    Cosmetic,
+}
+
+impl DiffPriority {
+    /// Classify a `ColumnModified` diff by the attributes that changed. Only a
+    /// comment or the spelling of the type leaves the column behaving the same.
+    pub fn for_column_changes(changes: &[ColumnChangeKind]) -> Self {
+        let cosmetic = |c: &ColumnChangeKind| {
+            matches!(
+                c,
+                ColumnChangeKind::Comment | ColumnChangeKind::DataTypeSpelling
+            )
+        };
+        if !changes.is_empty() && changes.iter().all(cosmetic) {
+            DiffPriority::Cosmetic
+        } else {
+            DiffPriority::Structural
+        }
+    }
+}
+
+/// How closely a reviewer should look at a diff. Variants sort most severe first.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Loses data or changes what the constraints let in.
+    High,
+    /// Adds or reshapes structure without touching existing data.
+    #[default]
+    Medium,
+    /// Cosmetic: comments and other metadata.
+    Low,
+}
+
+impl Severity {
+    /// Classify an item by its type, the column attributes it changes and its priority.
+    pub fn for_item(item: &DiffItem) -> Self {
+        if item.priority == DiffPriority::Cosmetic {
+            return Severity::Low;
+        }
+        match item.diff_type {
+            DiffType::TableRemoved
+            | DiffType::ColumnRemoved
+            | DiffType::ColumnRenamed
+            | DiffType::TableRebuilt
+            | DiffType::PartitioningModified
+            | DiffType::ForeignKeyAdded
+            | DiffType::ForeignKeyRemoved
+            | DiffType::ForeignKeyModified
+            | DiffType::UniqueConstraintAdded
+            | DiffType::UniqueConstraintRemoved
+            | DiffType::UniqueConstraintModified
+            | DiffType::ExclusionConstraintAdded
+            | DiffType::ExclusionConstraintRemoved
+            | DiffType::ExclusionConstraintModified
+            | DiffType::CheckConstraintAdded
+            | DiffType::CheckConstraintRemoved
+            | DiffType::CheckConstraintModified
+            | DiffType::PrimaryKeyModified
+            | DiffType::RowSecurityModified
+            | DiffType::InheritanceModified
+            | DiffType::PolicyAdded
+            | DiffType::PolicyRemoved
+            | DiffType::PolicyModified => Severity::High,
+            DiffType::ColumnModified
+                if item.changes.iter().any(|c| {
+                    matches!(c, ColumnChangeKind::DataType | ColumnChangeKind::Nullable)
+                }) =>
+            {
+                Severity::High
+            }
+            _ => Severity::Medium,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiffItem {
     pub id: String,
@@ -47,6 +172,192 @@ pub struct DiffItem {
     pub selected: bool,
     #[serde(default)]
     pub warnings: Vec<TypeWarning>,
+    /// For `ColumnModified`, which column attributes differ.
+    #[serde(default)]
+    pub changes: Vec<ColumnChangeKind>,
+    #[serde(default)]
+    pub priority: DiffPriority,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+impl DiffItem {
+    /// A selected item of `diff_type` on `table_name` with no SQL, definitions
+    /// or warnings yet; fill in the rest with struct update syntax.
+    pub fn new(id: &str, diff_type: DiffType, table_name: &str) -> Self {
+        DiffItem {
+            id: id.to_string(),
+            diff_type,
+            table_name: table_name.to_string(),
+            object_name: None,
+            source_def: None,
+            target_def: None,
+            sql: String::new(),
+            selected: true,
+            warnings: vec![],
+            changes: vec![],
+            priority: DiffPriority::default(),
+            severity: Severity::default(),
+        }
+    }
+
+    /// `sql` as separate statements, in order, each with its terminator. Split
+    /// from `sql` on every call, so it always matches what will run.
+    pub fn sql_statements(&self) -> Vec<String> {
+        crate::db::terminated_statements(&self.sql)
+    }
+}
+
+/// Options that tune how two schemas are compared.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompareOptions {
+    /// Fix a table's column order when new or reordered columns would leave it
+    /// different from the source. MySQL moves the columns in place (`MODIFY COLUMN
+    /// ... AFTER`); Postgres rebuilds the table (create/copy/drop/rename). Either
+    /// way the table is rewritten and locked.
+    pub rebuild_for_column_order: bool,
+    /// Treat unique constraints covering the same columns as equal even when their
+    /// names differ (e.g. Postgres `users_email_key` vs MySQL `email`).
+    pub match_unique_by_columns: bool,
+    /// Match indexes, foreign keys and unique constraints by what they cover rather
+    /// than by name, so the same index under another generated name is not reported
+    /// as removed and added. Implies `match_unique_by_columns`; the same as
+    /// `name_sensitivity: IgnoreAll`.
+    pub match_by_structure: bool,
+    /// Which index, foreign key and unique constraint name differences count as
+    /// changes.
+    pub name_sensitivity: NameSensitivity,
+    /// Add foreign keys as `NOT VALID` and validate them in a second statement where
+    /// the dialect supports it (Postgres), so existing rows are checked without
+    /// holding an exclusive lock.
+    pub validate_foreign_keys_separately: bool,
+    /// Leave AUTO_INCREMENT/identity, and the Postgres sequence behind it, out of
+    /// column comparison, e.g. when checking a restored backup against a live database.
+    pub ignore_auto_increment: bool,
+    /// Match source table and column names to a Postgres target's lowercase names,
+    /// as Postgres folds unquoted identifiers (source `Users` is target `users`).
+    pub fold_identifier_case: bool,
+    /// Ask MySQL to add columns and secondary indexes in place without locking
+    /// (`ALGORITHM=INPLACE, LOCK=NONE`) where the operation supports it.
+    pub online_ddl: bool,
+    /// Build and drop Postgres indexes on existing tables `CONCURRENTLY`, so
+    /// writes aren't blocked while they build. Those statements must run outside
+    /// a transaction.
+    pub concurrent_indexes: bool,
+    /// Give added NOT NULL columns without a default the value MySQL fills in
+    /// implicitly (`''` for strings, `0` for numbers), so the add succeeds on a
+    /// table that already has rows.
+    pub fill_implicit_defaults: bool,
+    /// Report a target-only and a source-only column as one renamed column when
+    /// their types are compatible and their names are similar or their positions
+    /// match, so the column is renamed (and widened) instead of dropped and
+    /// added with its data lost.
+    pub detect_column_renames: bool,
+    /// Leave column comments out of column comparison.
+    pub ignore_comments: bool,
+    /// Also compare table-level GRANTs. Off by default: reading privileges needs
+    /// extra rights and is slow on servers with many users.
+    pub include_grants: bool,
+    /// Bundle of options applied on top of the individual ones.
+    pub preset: Option<ComparePreset>,
+}
+
+/// How much index and constraint names matter when comparing. An object that
+/// only differs in a name that doesn't matter is matched by what it covers
+/// instead of being reported as removed and added.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NameSensitivity {
+    /// Every name difference is a change.
+    #[default]
+    Strict,
+    /// Only names the engine generated (`orders_ibfk_1`, `orders_user_id_fkey`,
+    /// `users_email_key`) are ignored; deliberate names are still compared.
+    IgnoreGenerated,
+    /// No name difference is a change.
+    IgnoreAll,
+}
+
+/// Named combinations of compare options for common cases.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparePreset {
+    /// Ignore drift that doesn't change a table's structure, as restored backups
+    /// commonly show: column comments, column order and AUTO_INCREMENT/identity.
+    IgnoreCosmetic,
+}
+
+impl CompareOptions {
+    /// These options with the preset, if any, folded into the individual flags.
+    pub fn resolved(&self) -> CompareOptions {
+        let mut options = self.clone();
+        if self.match_by_structure {
+            options.match_unique_by_columns = true;
+            options.name_sensitivity = NameSensitivity::IgnoreAll;
+        }
+        match self.preset {
+            Some(ComparePreset::IgnoreCosmetic) => {
+                options.ignore_comments = true;
+                options.ignore_auto_increment = true;
+                options.rebuild_for_column_order = false;
+            }
+            None => {}
+        }
+        options
+    }
+}
+
+/// Compare options saved under a name, to repeat a comparison without setting
+/// them up again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedComparePreset {
+    pub id: String,
+    pub name: String,
+    pub options: CompareOptions,
+    /// Select only the safe, additive changes, as `compare_databases_safe` does.
+    #[serde(default)]
+    pub safe_only: bool,
+    pub created_at: String,
+}
+
+/// A database's tables saved under a name, to diff a live database against
+/// later as a baseline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaSnapshot {
+    pub id: String,
+    pub name: String,
+    pub db_type: DbType,
+    pub tables: Vec<TableSchema>,
+    pub created_at: String,
+}
+
+/// Options for rendering diff items as a single SQL script.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptOptions {
+    /// Keep each table's statements together under a `-- Table: name` header.
+    pub group_by_table: bool,
+    /// Merge consecutive `ALTER TABLE` statements on the same table into one
+    /// statement with comma-separated clauses, so MySQL rewrites the table once.
+    pub coalesce_alters: bool,
+    /// Write added columns so the script can run again after they exist.
+    pub idempotent: bool,
+    /// Target server version as `ping` reports it, e.g. `8.0.36` or
+    /// `10.11.6-MariaDB`; decides how idempotent column adds are written.
+    pub server_version: Option<String>,
+    /// Precede each statement with a `-- ` comment naming the object and its
+    /// definition before and after, so a saved script documents itself.
+    pub annotate: bool,
+}
+
+/// Output format for a human- or tool-readable diff report.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Json,
+    Markdown,
+    PlainText,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,4 +365,118 @@ pub struct DiffResult {
     pub items: Vec<DiffItem>,
     pub source_tables: usize,
     pub target_tables: usize,
+    /// Comparison-wide notes that don't belong to a single diff item.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Source and target are different engines, so types were translated and the
+    /// SQL needs a closer review.
+    #[serde(default)]
+    pub cross_engine: bool,
+    /// Tables read from the source, when the comparison was asked to include them.
+    #[serde(default)]
+    pub source_schema: Option<Vec<TableSchema>>,
+    /// Tables read from the target, when the comparison was asked to include them.
+    #[serde(default)]
+    pub target_schema: Option<Vec<TableSchema>>,
+}
+
+impl DiffResult {
+    /// Attach the tables both sides were read as, for callers building their
+    /// own reports from the same read.
+    pub fn with_schemas(mut self, source: Vec<TableSchema>, target: Vec<TableSchema>) -> Self {
+        self.source_schema = Some(source);
+        self.target_schema = Some(target);
+        self
+    }
+}
+
+/// Payload of the `drift-detected` event: a drift monitor found its target out
+/// of step with its source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftEvent {
+    pub monitor_id: String,
+    pub source_id: String,
+    pub target_id: String,
+    pub result: DiffResult,
+}
+
+/// The comparison of one source/target database pair on the same two servers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabasePairResult {
+    pub source_db: String,
+    pub target_db: String,
+    pub result: DiffResult,
+}
+
+/// What applying a single diff item would affect on the target, for review
+/// before running it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImpactReport {
+    /// Applying the item can discard data (dropped tables/columns, rebuilds,
+    /// type changes).
+    pub destructive: bool,
+    /// Estimated rows in the affected table, if the server has statistics.
+    pub row_count: Option<u64>,
+    /// Foreign keys and indexes that depend on the affected table or column.
+    pub referenced_by: Vec<String>,
+    pub summary: String,
+}
+
+/// Rough duration of applying a set of diff items, for planning a maintenance
+/// window. Heuristic: only operations that rewrite or scan a table are costed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncEstimate {
+    pub min_seconds: f64,
+    pub max_seconds: f64,
+    pub statements: Vec<StatementEstimate>,
+}
+
+/// Estimated duration of one diff item that rewrites or scans its table.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatementEstimate {
+    pub item_id: String,
+    pub table_name: String,
+    /// Estimated rows in the table; `None` when the server has no statistics,
+    /// in which case the item is costed at zero.
+    pub row_count: Option<u64>,
+    pub min_seconds: f64,
+    pub max_seconds: f64,
+}
+
+/// Outcome of one statement in a best-effort sync run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatementResult {
+    /// Position in the submitted list, starting at 0.
+    pub index: usize,
+    pub sql: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// Outcome of applying diff items so that a failure leaves the target as it was.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RollbackReport {
+    /// One result per item attempted, in order; only the last can have failed.
+    /// A batch rolled back as one transaction has a single failed result.
+    pub results: Vec<StatementResult>,
+    /// Undo statements run after a failure, latest applied statement first.
+    pub undo: Vec<StatementResult>,
+    /// Ids of items left fully or partly applied: no undo could be generated
+    /// for them, or it failed.
+    pub left_applied: Vec<String>,
+    /// Whether the target is back where it started. True when nothing failed.
+    pub clean_rollback: bool,
+}
+
+/// A table or one of its objects that came back different after generating its
+/// `CREATE TABLE` and parsing that DDL again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Inconsistency {
+    pub table_name: String,
+    pub object_name: Option<String>,
+    pub diff_type: DiffType,
+    /// The object as read from the database.
+    pub expected: Option<String>,
+    /// The object as parsed from the generated DDL.
+    pub actual: Option<String>,
 }
@@ -9,6 +9,86 @@ pub struct Column {
     pub auto_increment: bool,
     pub comment: Option<String>,
     pub ordinal_position: u32,
+    /// MySQL 8 `INVISIBLE` columns are hidden from `SELECT *`; always true on Postgres.
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    /// MySQL `ON UPDATE` expression, e.g. `CURRENT_TIMESTAMP(3)`; always `None` on Postgres.
+    #[serde(default)]
+    pub on_update: Option<String>,
+    /// Postgres sequence behind a `nextval()` default, e.g. `users_id_seq`. The
+    /// default itself is dropped in favour of `auto_increment`; always `None` on MySQL.
+    #[serde(default)]
+    pub sequence: Option<String>,
+    /// Set for generated (computed) columns; such columns have no default.
+    #[serde(default)]
+    pub generated: Option<GeneratedColumn>,
+    /// MySQL 8 spatial reference system of a geometry column, e.g. 4326; `None`
+    /// when unrestricted and always on Postgres.
+    #[serde(default)]
+    pub srid: Option<u32>,
+    /// MySQL maximum length in bytes of a character column, e.g. 1020 for a
+    /// utf8mb4 `varchar(255)`. Depends on the column's character set, so it is
+    /// informational and left out of equality; always `None` on Postgres.
+    #[serde(default)]
+    pub octet_length: Option<u64>,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+/// Whether a generated column is computed on read or materialized on write.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GeneratedStorage {
+    Virtual,
+    Stored,
+}
+
+impl GeneratedStorage {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            GeneratedStorage::Virtual => "VIRTUAL",
+            GeneratedStorage::Stored => "STORED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GeneratedColumn {
+    pub expression: String,
+    pub storage: GeneratedStorage,
+}
+
+impl GeneratedColumn {
+    /// The `GENERATED ALWAYS AS (...) STORAGE` clause, without a leading space.
+    pub fn clause(&self) -> String {
+        format!(
+            "GENERATED ALWAYS AS ({}) {}",
+            self.expression,
+            self.storage.keyword()
+        )
+    }
+}
+
+impl Column {
+    /// The default value, with an explicit `NULL` (optionally cast, as Postgres
+    /// reports it: `NULL::character varying`) treated the same as no default.
+    pub fn effective_default(&self) -> Option<&str> {
+        self.default_value.as_deref().filter(|d| {
+            let head = d.split("::").next().unwrap_or(d).trim();
+            !head.eq_ignore_ascii_case("NULL")
+        })
+    }
+
+    /// NOT NULL with nothing to fill existing rows: adding the column fails on a
+    /// non-empty table.
+    pub fn needs_backfill(&self) -> bool {
+        !self.nullable
+            && self.effective_default().is_none()
+            && !self.auto_increment
+            && self.generated.is_none()
+    }
 }
 
 impl PartialEq for Column {
@@ -16,9 +96,14 @@ impl PartialEq for Column {
         self.name == other.name
             && self.data_type == other.data_type
             && self.nullable == other.nullable
-            && self.default_value == other.default_value
+            && self.effective_default() == other.effective_default()
             && self.auto_increment == other.auto_increment
             && self.comment == other.comment
+            && self.visible == other.visible
+            && self.on_update == other.on_update
+            && self.sequence == other.sequence
+            && self.generated == other.generated
+            && self.srid == other.srid
     }
 }
 
@@ -34,6 +119,24 @@ pub struct Index {
     pub columns: Vec<String>,
     pub unique: bool,
     pub index_type: String,
+    #[serde(default)]
+    pub tablespace: Option<String>,
+    /// MySQL 8 invisible indexes are ignored by the optimizer; always true on Postgres.
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    /// Full `CREATE INDEX` statement for Postgres indexes that `columns` can't
+    /// describe (expression keys, `DESC` / `NULLS FIRST|LAST` ordering, a per-column
+    /// `COLLATE`, partial indexes). Such indexes are compared and regenerated from
+    /// this definition; `columns` is empty.
+    #[serde(default)]
+    pub expression: Option<String>,
+    /// Postgres storage parameters as `pg_class.reloptions` lists them
+    /// (`fillfactor=70`); always empty on MySQL.
+    #[serde(default)]
+    pub storage_params: Vec<String>,
+    /// MySQL `INDEX ... COMMENT '...'`; always `None` on Postgres.
+    #[serde(default)]
+    pub comment: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -44,6 +147,15 @@ pub struct ForeignKey {
     pub ref_columns: Vec<String>,
     pub on_delete: String,
     pub on_update: String,
+    /// Postgres only: whether the check can be deferred to commit time.
+    #[serde(default)]
+    pub deferrable: bool,
+    /// Postgres only: whether a deferrable check is deferred by default.
+    #[serde(default)]
+    pub initially_deferred: bool,
+    /// Postgres `MATCH` type (`FULL` or `PARTIAL`); `None` is the default `SIMPLE`.
+    #[serde(default)]
+    pub match_type: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -52,7 +164,67 @@ pub struct UniqueConstraint {
     pub columns: Vec<String>,
 }
 
+/// Postgres `EXCLUDE` constraint, kept as the server-rendered definition
+/// (e.g. `EXCLUDE USING gist (room WITH =, during WITH &&)`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExclusionConstraint {
+    pub name: String,
+    pub definition: String,
+}
+
+/// `CHECK` constraint. The expression is as the server reports it, e.g.
+/// `(`age` > 0)` on MySQL or `(age > 0)` on Postgres.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CheckConstraint {
+    pub name: String,
+    pub expression: String,
+    /// `false` for a MySQL `NOT ENFORCED` check; always true on Postgres.
+    #[serde(default = "default_enforced")]
+    pub enforced: bool,
+}
+
+fn default_enforced() -> bool {
+    true
+}
+
+/// Postgres row-level security policy. The expressions are as `pg_policies`
+/// deparses them, e.g. `(tenant_id = current_setting('app.tenant'::text))`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Policy {
+    pub name: String,
+    pub table: String,
+    /// `ALL`, `SELECT`, `INSERT`, `UPDATE` or `DELETE`.
+    pub command: String,
+    /// `false` for a `RESTRICTIVE` policy.
+    pub permissive: bool,
+    /// Roles the policy applies to; `public` means every role.
+    pub roles: Vec<String>,
+    pub using_expr: Option<String>,
+    pub check_expr: Option<String>,
+}
+
+/// How a table is split into partitions (MySQL `PARTITION BY`, Postgres
+/// declarative partitioning).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PartitionSpec {
+    /// `RANGE`, `LIST`, `HASH`, or MySQL's `RANGE COLUMNS`, `KEY`, ...
+    pub method: String,
+    /// Partition key as written inside `PARTITION BY method (...)`.
+    pub expression: String,
+    pub partitions: Vec<Partition>,
+}
+
+/// One partition of a partitioned table. `bound` is the dialect's own clause:
+/// `VALUES LESS THAN (2024)` on MySQL (empty for HASH/KEY), or
+/// `FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')` on Postgres, where each
+/// partition is a table of its own.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Partition {
+    pub name: String,
+    pub bound: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct TableSchema {
     pub name: String,
     pub columns: Vec<Column>,
@@ -60,4 +232,103 @@ pub struct TableSchema {
     pub indexes: Vec<Index>,
     pub foreign_keys: Vec<ForeignKey>,
     pub unique_constraints: Vec<UniqueConstraint>,
+    /// Non-default tablespace the table lives in; `None` means the server default.
+    #[serde(default)]
+    pub tablespace: Option<String>,
+    /// Always empty on MySQL, which has no exclusion constraints.
+    #[serde(default)]
+    pub exclusion_constraints: Vec<ExclusionConstraint>,
+    #[serde(default)]
+    pub check_constraints: Vec<CheckConstraint>,
+    /// Whether row-level security is enabled; always `false` on MySQL.
+    #[serde(default)]
+    pub row_security: bool,
+    /// Row-level security policies; always empty on MySQL.
+    #[serde(default)]
+    pub policies: Vec<Policy>,
+    #[serde(default)]
+    pub partitioning: Option<PartitionSpec>,
+    /// Storage options; always unset on Postgres.
+    #[serde(default)]
+    pub options: TableOptions,
+    /// Postgres parent tables this table `INHERITS` from, in declaration order;
+    /// always empty on MySQL.
+    #[serde(default)]
+    pub inherits: Vec<String>,
+}
+
+/// MySQL table options. `None` means the option wasn't read (e.g. a dump that
+/// leaves it out), and such an option is not compared.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TableOptions {
+    pub engine: Option<String>,
+    pub charset: Option<String>,
+    pub row_format: Option<String>,
+}
+
+impl TableOptions {
+    pub fn is_empty(&self) -> bool {
+        self.engine.is_none() && self.charset.is_none() && self.row_format.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutineKind {
+    Procedure,
+    Function,
+}
+
+impl RoutineKind {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            RoutineKind::Procedure => "PROCEDURE",
+            RoutineKind::Function => "FUNCTION",
+        }
+    }
+}
+
+/// A stored procedure or function. `definition` is the full `CREATE` statement
+/// as the server reports it, without the MySQL `DEFINER` clause or the Postgres
+/// `public.` qualifier. On Postgres `name` includes the argument list, e.g.
+/// `add(a integer, b integer)`, so overloads stay distinct.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Routine {
+    pub name: String,
+    pub kind: RoutineKind,
+    pub definition: String,
+    pub language: String,
+}
+
+/// One table-level privilege held by a user or role. `grantee` is as the server
+/// reports it: `'app'@'%'` on MySQL, the role name on Postgres.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Grant {
+    pub grantee: String,
+    pub privilege: String,
+    /// The table or view the privilege is on.
+    pub object: String,
+}
+
+/// Objects besides tables and routines that dropping everything has to remove
+/// too, each list sorted by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SchemaObjects {
+    pub views: Vec<String>,
+    /// Sequences no column owns; the ones behind serial and identity columns
+    /// go with their table.
+    pub sequences: Vec<String>,
+    /// Postgres enum types; always empty on MySQL.
+    pub enum_types: Vec<String>,
+}
+
+/// Database-level defaults that column and table settings inherit from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DatabaseInfo {
+    /// MySQL default character set, or Postgres LC_CTYPE.
+    pub charset: Option<String>,
+    /// MySQL default collation, or Postgres LC_COLLATE.
+    pub collation: Option<String>,
+    /// Server-side encoding name (same as `charset` on MySQL).
+    pub encoding: Option<String>,
 }
@@ -1,3 +1,3 @@
 pub mod tunnel;
 
-pub use tunnel::SshTunnel;
+pub use tunnel::{SshTunnel, test_ssh_tunnel};
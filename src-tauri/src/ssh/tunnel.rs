@@ -6,9 +6,10 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::Mutex;
 
+use crate::error::{AppError, AppResult};
 use crate::models::{SshAuthMethod, SshConfig};
 
 /// Get the path to the user's known_hosts file.
@@ -175,7 +176,7 @@ impl client::Handler for SshClient {
 
 pub struct SshTunnel {
     local_port: u16,
-    _handle: tokio::task::JoinHandle<()>,
+    handle: tokio::task::JoinHandle<()>,
 }
 
 impl SshTunnel {
@@ -273,13 +274,112 @@ impl SshTunnel {
             }
         });
 
-        Ok(Self {
-            local_port,
-            _handle: handle,
-        })
+        Ok(Self { local_port, handle })
     }
 
     pub fn local_port(&self) -> u16 {
         self.local_port
     }
+
+    /// Stop accepting connections and drop the SSH session.
+    pub fn close(self) {
+        drop(self);
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Turn a tunnel setup failure into an `AppError::SshTunnel` saying which part
+/// of the SSH hop failed.
+fn classify_tunnel_error(err: anyhow::Error, ssh_config: &SshConfig) -> AppError {
+    let server = format!("{}:{}", ssh_config.host, ssh_config.port);
+    let unreachable = matches!(
+        err.downcast_ref::<russh::Error>(),
+        Some(russh::Error::IO(_) | russh::Error::ConnectionTimeout)
+    );
+    let message = if unreachable {
+        format!("Cannot reach SSH server {}: {}", server, err)
+    } else if err.downcast_ref::<russh_keys::Error>().is_some() {
+        format!("Cannot load private key: {}", err)
+    } else if err.to_string().contains("authentication failed") {
+        format!(
+            "Authentication failed for {}@{}",
+            ssh_config.username, server
+        )
+    } else {
+        format!("SSH connection to {} failed: {}", server, err)
+    };
+    AppError::SshTunnel(message)
+}
+
+/// Open a tunnel to `remote_host:remote_port`, check its local end accepts a
+/// connection, then close it. Returns the local port the tunnel listened on, so
+/// SSH problems can be told apart from database ones.
+pub async fn test_ssh_tunnel(
+    ssh_config: &SshConfig,
+    remote_host: &str,
+    remote_port: u16,
+) -> AppResult<u16> {
+    let tunnel = SshTunnel::new(ssh_config, remote_host, remote_port)
+        .await
+        .map_err(|e| classify_tunnel_error(e, ssh_config))?;
+    let local_port = tunnel.local_port();
+    let probe = TcpStream::connect(("127.0.0.1", local_port)).await;
+    tunnel.close();
+    probe.map_err(|e| {
+        AppError::SshTunnel(format!(
+            "Tunnel opened but local port {} refused connections: {}",
+            local_port, e
+        ))
+    })?;
+    Ok(local_port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ssh_config(port: u16) -> SshConfig {
+        SshConfig {
+            enabled: true,
+            host: "127.0.0.1".to_string(),
+            port,
+            username: "deploy".to_string(),
+            auth_method: SshAuthMethod::Password {
+                password: "wrong".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn rejected_credentials_are_an_authentication_error() {
+        let err = classify_tunnel_error(anyhow!("SSH authentication failed"), &ssh_config(22));
+        assert_eq!(
+            err.to_string(),
+            "SSH tunnel error: Authentication failed for deploy@127.0.0.1:22"
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_is_reported_before_authentication() {
+        // A port that was just free; nothing listens on it any more.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let err = test_ssh_tunnel(&ssh_config(port), "db", 5432)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Cannot reach SSH server"),
+            "{}",
+            err
+        );
+    }
 }
@@ -1,8 +1,13 @@
 use anyhow::Result;
+use log::warn;
 use sqlx::{Pool, Sqlite, sqlite::SqlitePoolOptions};
 use std::path::PathBuf;
 
-use crate::models::{Connection, ConnectionInput, DbType, SshAuthMethod, SshConfig, SslConfig};
+use crate::error::AppError;
+use crate::models::{
+    CompareOptions, Connection, ConnectionInput, DbType, SavedComparePreset, SchemaSnapshot,
+    SshAuthMethod, SshConfig, SslConfig, TableSchema,
+};
 use crate::storage::crypto;
 
 pub struct ConfigStore {
@@ -42,17 +47,149 @@ impl ConfigStore {
                 ssl_client_key_path TEXT,
                 ssl_verify_server INTEGER DEFAULT 1,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                session_params TEXT,
+                schema_file TEXT,
+                statement_timeout_secs INTEGER,
+                prompt_for_secrets INTEGER NOT NULL DEFAULT 0
             )
             "#,
         )
         .execute(&pool)
         .await?;
 
+        // Databases created before session parameters existed lack the column
+        let (has_session_params,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'session_params'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if has_session_params == 0 {
+            sqlx::query("ALTER TABLE connections ADD COLUMN session_params TEXT")
+                .execute(&pool)
+                .await?;
+        }
+        let (has_schema_file,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'schema_file'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if has_schema_file == 0 {
+            sqlx::query("ALTER TABLE connections ADD COLUMN schema_file TEXT")
+                .execute(&pool)
+                .await?;
+        }
+        let (has_statement_timeout,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'statement_timeout_secs'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if has_statement_timeout == 0 {
+            sqlx::query("ALTER TABLE connections ADD COLUMN statement_timeout_secs INTEGER")
+                .execute(&pool)
+                .await?;
+        }
+        let (has_prompt_for_secrets,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'prompt_for_secrets'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if has_prompt_for_secrets == 0 {
+            sqlx::query(
+                "ALTER TABLE connections ADD COLUMN prompt_for_secrets INTEGER NOT NULL DEFAULT 0",
+            )
+            .execute(&pool)
+            .await?;
+        }
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS compare_presets (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                options TEXT NOT NULL,
+                safe_only INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_snapshots (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                db_type TEXT NOT NULL,
+                tables_json TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Older databases may already hold duplicate names; those keep working,
+        // and save/update still reject new duplicates.
+        if let Err(e) = sqlx::query(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_connections_name ON connections (name COLLATE NOCASE)",
+        )
+        .execute(&pool)
+        .await
+        {
+            warn!("Connection names are not unique, skipping the unique index: {}", e);
+        }
+
         Ok(Self { pool })
     }
 
+    fn duplicate_name_error(name: &str) -> anyhow::Error {
+        AppError::Validation(format!("a connection named {} already exists", name)).into()
+    }
+
+    /// Reject a statement timeout no dialect would accept as a session parameter.
+    fn ensure_valid_timeout(input: &ConnectionInput) -> Result<()> {
+        match input.statement_timeout_secs {
+            Some(secs) if secs > crate::db::MAX_STATEMENT_TIMEOUT_SECS => {
+                Err(AppError::Validation(format!(
+                    "statement timeout must be at most {} seconds",
+                    crate::db::MAX_STATEMENT_TIMEOUT_SECS
+                ))
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject `name` when another connection (ignoring `exclude_id`) already uses
+    /// it, case-insensitively.
+    async fn ensure_unique_name(&self, name: &str, exclude_id: Option<&str>) -> Result<()> {
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM connections WHERE name = ? COLLATE NOCASE AND id IS NOT ?",
+        )
+        .bind(name)
+        .bind(exclude_id)
+        .fetch_one(&self.pool)
+        .await?;
+        if count > 0 {
+            return Err(Self::duplicate_name_error(name));
+        }
+        Ok(())
+    }
+
+    /// Turn a unique-index violation, from a save racing another, into the same
+    /// validation error `ensure_unique_name` gives.
+    fn map_write_error(err: sqlx::Error, name: &str) -> anyhow::Error {
+        match err.as_database_error() {
+            Some(db_err) if db_err.is_unique_violation() => Self::duplicate_name_error(name),
+            _ => err.into(),
+        }
+    }
+
     fn fetch_connection_passwords(row: &ConnectionRow) -> (String, Option<String>, Option<String>) {
+        if row.prompt_for_secrets == 1 {
+            return (String::new(), None, None);
+        }
         let password = crypto::get_password(&row.id).unwrap_or_default();
         let ssh_password =
             if row.ssh_enabled == 1 && row.ssh_auth_method.as_deref() == Some("password") {
@@ -97,24 +234,37 @@ impl ConfigStore {
         }))
     }
 
-    fn store_connection_passwords(id: &str, input: &ConnectionInput) -> Result<()> {
+    /// Keyring entries to write for `input`, as (entry name, secret) pairs:
+    /// none when the connection prompts for its secrets instead.
+    fn passwords_to_store(id: &str, input: &ConnectionInput) -> Vec<(String, String)> {
+        if input.prompt_for_secrets {
+            return vec![];
+        }
+        let mut entries = Vec::new();
         // Store SSH passwords first (matching original order for atomicity)
         if let Some(ssh) = &input.ssh_config {
             if ssh.enabled {
                 match &ssh.auth_method {
                     SshAuthMethod::Password { password } => {
-                        crypto::store_password(&format!("{}_ssh", id), password)?;
+                        entries.push((format!("{}_ssh", id), password.clone()));
                     }
                     SshAuthMethod::PrivateKey { passphrase, .. } => {
                         if let Some(pp) = passphrase {
-                            crypto::store_password(&format!("{}_ssh_passphrase", id), pp)?;
+                            entries.push((format!("{}_ssh_passphrase", id), pp.clone()));
                         }
                     }
                 }
             }
         }
         // Then main password
-        crypto::store_password(id, &input.password)?;
+        entries.push((id.to_string(), input.password.clone()));
+        entries
+    }
+
+    fn store_connection_passwords(id: &str, input: &ConnectionInput) -> Result<()> {
+        for (entry, secret) in Self::passwords_to_store(id, input) {
+            crypto::store_password(&entry, &secret)?;
+        }
         Ok(())
     }
 
@@ -125,11 +275,7 @@ impl ConfigStore {
     }
 
     fn flatten_input(input: &ConnectionInput) -> FlatConnectionFields {
-        let db_type_str = match input.db_type {
-            DbType::MySQL => "mysql".to_string(),
-            DbType::PostgreSQL => "postgresql".to_string(),
-            DbType::MariaDB => "mariadb".to_string(),
-        };
+        let db_type_str = db_type_name(&input.db_type).to_string();
 
         let (ssh_enabled, ssh_host, ssh_port, ssh_username, ssh_auth_method, ssh_private_key_path) =
             match &input.ssh_config {
@@ -163,6 +309,13 @@ impl ConfigStore {
             _ => (0, None, None, None, 1),
         };
 
+        // Stored as a JSON array of [name, value] pairs; NULL when there are none
+        let session_params = if input.session_params.is_empty() {
+            None
+        } else {
+            serde_json::to_string(&input.session_params).ok()
+        };
+
         FlatConnectionFields {
             db_type_str,
             ssh_enabled,
@@ -176,6 +329,7 @@ impl ConfigStore {
             ssl_cert,
             ssl_key,
             ssl_verify,
+            session_params,
         }
     }
 
@@ -184,7 +338,8 @@ impl ConfigStore {
         let now = chrono::Utc::now().to_rfc3339();
         let f = Self::flatten_input(&input);
 
-        Self::store_connection_passwords(&id, &input)?;
+        Self::ensure_valid_timeout(&input)?;
+        self.ensure_unique_name(&input.name, None).await?;
 
         sqlx::query(
             r#"
@@ -192,8 +347,9 @@ impl ConfigStore {
                 id, name, db_type, host, port, username, database_name,
                 ssh_enabled, ssh_host, ssh_port, ssh_username, ssh_auth_method, ssh_private_key_path,
                 ssl_enabled, ssl_ca_cert_path, ssl_client_cert_path, ssl_client_key_path, ssl_verify_server,
-                created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                created_at, updated_at, session_params, schema_file, statement_timeout_secs,
+                prompt_for_secrets
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&id)
@@ -216,9 +372,31 @@ impl ConfigStore {
         .bind(f.ssl_verify)
         .bind(&now)
         .bind(&now)
+        .bind(&f.session_params)
+        .bind(&input.schema_file)
+        .bind(input.statement_timeout_secs.map(i64::from))
+        .bind(input.prompt_for_secrets)
         .execute(&self.pool)
-        .await?;
+        .await
+        .map_err(|e| Self::map_write_error(e, &input.name))?;
+
+        // Secrets go in only once the row exists, so a rejected save leaves no
+        // keyring entries behind; a failed keyring write takes the row back out.
+        if let Err(e) = Self::store_connection_passwords(&id, &input) {
+            Self::delete_connection_passwords(&id);
+            sqlx::query("DELETE FROM connections WHERE id = ?")
+                .bind(&id)
+                .execute(&self.pool)
+                .await?;
+            return Err(e);
+        }
 
+        // As from get_connection, a prompting connection comes back without one.
+        let password = if input.prompt_for_secrets {
+            String::new()
+        } else {
+            input.password
+        };
         Ok(Connection {
             id,
             name: input.name,
@@ -226,10 +404,14 @@ impl ConfigStore {
             host: input.host,
             port: input.port,
             username: input.username,
-            password: input.password,
+            password,
             database: input.database,
             ssh_config: input.ssh_config,
             ssl_config: input.ssl_config,
+            session_params: input.session_params,
+            schema_file: input.schema_file,
+            statement_timeout_secs: input.statement_timeout_secs,
+            prompt_for_secrets: input.prompt_for_secrets,
             created_at: now.clone(),
             updated_at: now,
         })
@@ -239,15 +421,16 @@ impl ConfigStore {
         let now = chrono::Utc::now().to_rfc3339();
         let f = Self::flatten_input(&input);
 
-        Self::delete_connection_passwords(id);
-        Self::store_connection_passwords(id, &input)?;
+        Self::ensure_valid_timeout(&input)?;
+        self.ensure_unique_name(&input.name, Some(id)).await?;
 
         let rows_affected = sqlx::query(
             r#"UPDATE connections SET
                 name = ?, db_type = ?, host = ?, port = ?, username = ?, database_name = ?,
                 ssh_enabled = ?, ssh_host = ?, ssh_port = ?, ssh_username = ?, ssh_auth_method = ?, ssh_private_key_path = ?,
                 ssl_enabled = ?, ssl_ca_cert_path = ?, ssl_client_cert_path = ?, ssl_client_key_path = ?, ssl_verify_server = ?,
-                updated_at = ?
+                updated_at = ?, session_params = ?, schema_file = ?,
+                statement_timeout_secs = ?, prompt_for_secrets = ?
             WHERE id = ?"#,
         )
         .bind(&input.name).bind(&f.db_type_str).bind(&input.host)
@@ -255,14 +438,20 @@ impl ConfigStore {
         .bind(f.ssh_enabled).bind(&f.ssh_host).bind(f.ssh_port).bind(&f.ssh_username)
         .bind(&f.ssh_auth_method).bind(&f.ssh_private_key_path)
         .bind(f.ssl_enabled).bind(&f.ssl_ca).bind(&f.ssl_cert).bind(&f.ssl_key).bind(f.ssl_verify)
-        .bind(&now).bind(id)
+        .bind(&now).bind(&f.session_params).bind(&input.schema_file)
+        .bind(input.statement_timeout_secs.map(i64::from)).bind(input.prompt_for_secrets).bind(id)
         .execute(&self.pool)
-        .await?
+        .await
+        .map_err(|e| Self::map_write_error(e, &input.name))?
         .rows_affected();
 
         if rows_affected == 0 {
             anyhow::bail!("Connection not found: {}", id);
         }
+        // Replaced only once the update went through, so a rejected one keeps
+        // the old secrets.
+        Self::delete_connection_passwords(id);
+        Self::store_connection_passwords(id, &input)?;
 
         let conn = self
             .get_connection(id)
@@ -282,6 +471,170 @@ impl ConfigStore {
 
         Ok(())
     }
+
+    /// Save `options` under `name`, replacing a preset of the same name.
+    pub async fn save_compare_preset(
+        &self,
+        name: &str,
+        options: &CompareOptions,
+        safe_only: bool,
+    ) -> Result<SavedComparePreset> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO compare_presets (id, name, options, safe_only, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET options = excluded.options,
+                safe_only = excluded.safe_only
+            "#,
+        )
+        .bind(&id)
+        .bind(name)
+        .bind(serde_json::to_string(options)?)
+        .bind(safe_only)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        let (id,): (String,) = sqlx::query_as("SELECT id FROM compare_presets WHERE name = ?")
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await?;
+        self.get_compare_preset(&id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Compare preset not found after save: {}", name))
+    }
+
+    pub async fn list_compare_presets(&self) -> Result<Vec<SavedComparePreset>> {
+        let rows: Vec<PresetRow> = sqlx::query_as(
+            "SELECT id, name, options, safe_only, created_at FROM compare_presets ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(PresetRow::into_preset).collect()
+    }
+
+    pub async fn get_compare_preset(&self, id: &str) -> Result<Option<SavedComparePreset>> {
+        let row: Option<PresetRow> = sqlx::query_as(
+            "SELECT id, name, options, safe_only, created_at FROM compare_presets WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(PresetRow::into_preset).transpose()
+    }
+
+    pub async fn delete_compare_preset(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM compare_presets WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Save `tables` as a snapshot under `name`, replacing a snapshot of the
+    /// same name.
+    pub async fn save_schema_snapshot(
+        &self,
+        name: &str,
+        db_type: &DbType,
+        tables: &[TableSchema],
+    ) -> Result<SchemaSnapshot> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO schema_snapshots (id, name, db_type, tables_json, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET db_type = excluded.db_type,
+                tables_json = excluded.tables_json, created_at = excluded.created_at
+            "#,
+        )
+        .bind(&id)
+        .bind(name)
+        .bind(db_type_name(db_type))
+        .bind(serde_json::to_string(tables)?)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        let (id,): (String,) = sqlx::query_as("SELECT id FROM schema_snapshots WHERE name = ?")
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await?;
+        self.get_schema_snapshot(&id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Schema snapshot not found after save: {}", name))
+    }
+
+    pub async fn get_schema_snapshot(&self, id: &str) -> Result<Option<SchemaSnapshot>> {
+        let row: Option<SnapshotRow> = sqlx::query_as(
+            "SELECT id, name, db_type, tables_json, created_at FROM schema_snapshots WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(SnapshotRow::into_snapshot).transpose()
+    }
+}
+
+fn db_type_name(db_type: &DbType) -> &'static str {
+    match db_type {
+        DbType::MySQL => "mysql",
+        DbType::PostgreSQL => "postgresql",
+        DbType::MariaDB => "mariadb",
+    }
+}
+
+fn db_type_from_name(name: &str) -> DbType {
+    match name {
+        "postgresql" => DbType::PostgreSQL,
+        "mariadb" => DbType::MariaDB,
+        _ => DbType::MySQL,
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SnapshotRow {
+    id: String,
+    name: String,
+    db_type: String,
+    tables_json: String,
+    created_at: String,
+}
+
+impl SnapshotRow {
+    fn into_snapshot(self) -> Result<SchemaSnapshot> {
+        Ok(SchemaSnapshot {
+            id: self.id,
+            name: self.name,
+            db_type: db_type_from_name(&self.db_type),
+            tables: serde_json::from_str(&self.tables_json)?,
+            created_at: self.created_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PresetRow {
+    id: String,
+    name: String,
+    options: String,
+    safe_only: bool,
+    created_at: String,
+}
+
+impl PresetRow {
+    fn into_preset(self) -> Result<SavedComparePreset> {
+        Ok(SavedComparePreset {
+            id: self.id,
+            name: self.name,
+            options: serde_json::from_str(&self.options)?,
+            safe_only: self.safe_only,
+            created_at: self.created_at,
+        })
+    }
 }
 
 struct FlatConnectionFields {
@@ -297,6 +650,7 @@ struct FlatConnectionFields {
     ssl_cert: Option<String>,
     ssl_key: Option<String>,
     ssl_verify: i32,
+    session_params: Option<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -321,6 +675,10 @@ struct ConnectionRow {
     ssl_verify_server: i32,
     created_at: String,
     updated_at: String,
+    session_params: Option<String>,
+    schema_file: Option<String>,
+    statement_timeout_secs: Option<i64>,
+    prompt_for_secrets: i32,
 }
 
 impl ConnectionRow {
@@ -330,12 +688,7 @@ impl ConnectionRow {
         ssh_password: Option<String>,
         ssh_passphrase: Option<String>,
     ) -> Connection {
-        let db_type = match self.db_type.as_str() {
-            "mysql" => DbType::MySQL,
-            "postgresql" => DbType::PostgreSQL,
-            "mariadb" => DbType::MariaDB,
-            _ => DbType::MySQL,
-        };
+        let db_type = db_type_from_name(&self.db_type);
 
         let ssh_config = if self.ssh_enabled == 1 {
             let auth_method = match self.ssh_auth_method.as_deref() {
@@ -384,6 +737,15 @@ impl ConnectionRow {
             database: self.database_name,
             ssh_config,
             ssl_config,
+            session_params: self
+                .session_params
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            schema_file: self.schema_file,
+            statement_timeout_secs: self
+                .statement_timeout_secs
+                .and_then(|secs| u32::try_from(secs).ok()),
+            prompt_for_secrets: self.prompt_for_secrets == 1,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
@@ -22,6 +22,10 @@ fn base_row() -> ConnectionRow {
         ssl_verify_server: 1,
         created_at: "2025-01-01".to_string(),
         updated_at: "2025-01-01".to_string(),
+        session_params: None,
+        schema_file: None,
+        statement_timeout_secs: None,
+        prompt_for_secrets: 0,
     }
 }
 
@@ -307,3 +311,350 @@ fn into_connection_ssl_verify_server_nonzero_nonone_is_false() {
     // Production code: `self.ssl_verify_server == 1`, so 2 maps to false
     assert!(!ssl.verify_server);
 }
+
+// ========================================================================
+// session params
+// ========================================================================
+
+#[test]
+fn into_connection_parses_session_params() {
+    let mut row = base_row();
+    row.session_params = Some(r#"[["search_path","app, public"]]"#.to_string());
+    let conn = row.into_connection(String::new(), None, None);
+    assert_eq!(
+        conn.session_params,
+        vec![("search_path".to_string(), "app, public".to_string())]
+    );
+}
+
+#[test]
+fn into_connection_missing_or_invalid_session_params_is_empty() {
+    let conn = base_row().into_connection(String::new(), None, None);
+    assert!(conn.session_params.is_empty());
+
+    let mut row = base_row();
+    row.session_params = Some("not json".to_string());
+    let conn = row.into_connection(String::new(), None, None);
+    assert!(conn.session_params.is_empty());
+}
+
+#[test]
+fn flatten_input_serializes_session_params() {
+    let mut input = ConnectionInput {
+        id: None,
+        name: "Test".to_string(),
+        db_type: DbType::PostgreSQL,
+        host: "localhost".to_string(),
+        port: 5432,
+        username: "postgres".to_string(),
+        password: String::new(),
+        database: "app".to_string(),
+        ssh_config: None,
+        ssl_config: None,
+        session_params: vec![],
+        schema_file: None,
+        statement_timeout_secs: None,
+        prompt_for_secrets: false,
+    };
+    assert!(ConfigStore::flatten_input(&input).session_params.is_none());
+
+    input.session_params = vec![("search_path".to_string(), "app".to_string())];
+    assert_eq!(
+        ConfigStore::flatten_input(&input).session_params.as_deref(),
+        Some(r#"[["search_path","app"]]"#)
+    );
+}
+
+#[test]
+fn into_connection_keeps_schema_file() {
+    assert!(
+        base_row()
+            .into_connection(String::new(), None, None)
+            .schema_file
+            .is_none()
+    );
+
+    let mut row = base_row();
+    row.schema_file = Some("/tmp/schema.sql".to_string());
+    let conn = row.into_connection(String::new(), None, None);
+    assert_eq!(conn.schema_file.as_deref(), Some("/tmp/schema.sql"));
+}
+
+#[test]
+fn into_connection_keeps_statement_timeout() {
+    let mut row = base_row();
+    row.statement_timeout_secs = Some(30);
+    let conn = row.into_connection(String::new(), None, None);
+    assert_eq!(conn.statement_timeout_secs, Some(30));
+
+    row = base_row();
+    row.statement_timeout_secs = Some(-1);
+    let conn = row.into_connection(String::new(), None, None);
+    assert_eq!(conn.statement_timeout_secs, None);
+}
+
+// ========================================================================
+// Name uniqueness
+// ========================================================================
+
+/// A store in a fresh temp directory, which is removed again on drop.
+struct TempStore {
+    store: ConfigStore,
+    dir: std::path::PathBuf,
+}
+
+impl std::ops::Deref for TempStore {
+    type Target = ConfigStore;
+
+    fn deref(&self) -> &ConfigStore {
+        &self.store
+    }
+}
+
+impl Drop for TempStore {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+async fn temp_store() -> TempStore {
+    let dir = std::env::temp_dir().join(format!("config-{}", uuid::Uuid::new_v4()));
+    TempStore {
+        store: ConfigStore::new(dir.clone()).await.unwrap(),
+        dir,
+    }
+}
+
+async fn insert_named(store: &ConfigStore, id: &str, name: &str) -> sqlx::Result<()> {
+    sqlx::query(
+        "INSERT INTO connections (id, name, db_type, host, port, username, database_name, created_at, updated_at)
+         VALUES (?, ?, 'mysql', 'localhost', 3306, 'root', 'app', '', '')",
+    )
+    .bind(id)
+    .bind(name)
+    .execute(&store.pool)
+    .await
+    .map(|_| ())
+}
+
+#[tokio::test]
+async fn save_connection_rejects_duplicate_name_case_insensitively() {
+    let store = temp_store().await;
+    insert_named(&store, "existing", "Production")
+        .await
+        .unwrap();
+
+    let input = ConnectionInput {
+        id: None,
+        name: "production".to_string(),
+        db_type: DbType::MySQL,
+        host: "localhost".to_string(),
+        port: 3306,
+        username: "root".to_string(),
+        password: String::new(),
+        database: "app".to_string(),
+        ssh_config: None,
+        ssl_config: None,
+        session_params: vec![],
+        schema_file: None,
+        statement_timeout_secs: None,
+        prompt_for_secrets: false,
+    };
+    let err = store.save_connection(input.clone()).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Validation error: a connection named production already exists"
+    );
+
+    // Keeping its own name is not a collision.
+    store
+        .ensure_unique_name("Production", Some("existing"))
+        .await
+        .unwrap();
+    let err = store.update_connection("other", input).await.unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+}
+
+#[tokio::test]
+async fn save_connection_rejects_out_of_range_statement_timeout() {
+    let store = temp_store().await;
+    let mut input = ConnectionInput {
+        id: None,
+        name: "slow".to_string(),
+        db_type: DbType::PostgreSQL,
+        host: "localhost".to_string(),
+        port: 5432,
+        username: "postgres".to_string(),
+        password: String::new(),
+        database: "app".to_string(),
+        ssh_config: None,
+        ssl_config: None,
+        session_params: vec![],
+        schema_file: None,
+        statement_timeout_secs: Some(u32::MAX),
+        prompt_for_secrets: false,
+    };
+    let err = store.save_connection(input.clone()).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Validation error: statement timeout must be at most 2147483 seconds"
+    );
+
+    input.statement_timeout_secs = Some(crate::db::MAX_STATEMENT_TIMEOUT_SECS);
+    let saved = store.save_connection(input.clone()).await.unwrap();
+    input.statement_timeout_secs = Some(crate::db::MAX_STATEMENT_TIMEOUT_SECS + 1);
+    assert!(store.update_connection(&saved.id, input).await.is_err());
+}
+
+#[tokio::test]
+async fn unique_index_violation_is_a_validation_error() {
+    let store = temp_store().await;
+    insert_named(&store, "a", "Staging").await.unwrap();
+    let err = insert_named(&store, "b", "STAGING").await.unwrap_err();
+
+    let mapped = ConfigStore::map_write_error(err, "STAGING");
+    assert!(matches!(
+        mapped.downcast_ref::<AppError>(),
+        Some(AppError::Validation(_))
+    ));
+}
+
+// ========================================================================
+// Prompted secrets
+// ========================================================================
+
+#[tokio::test]
+async fn prompting_connection_stores_no_keyring_entry() {
+    let mut input = ConnectionInput {
+        id: None,
+        name: "Locked down".to_string(),
+        db_type: DbType::MySQL,
+        host: "db.internal".to_string(),
+        port: 3306,
+        username: "app".to_string(),
+        password: "s3cret".to_string(),
+        database: "app".to_string(),
+        ssh_config: Some(SshConfig {
+            enabled: true,
+            host: "bastion".to_string(),
+            port: 22,
+            username: "jump".to_string(),
+            auth_method: SshAuthMethod::Password {
+                password: "tunnel".to_string(),
+            },
+        }),
+        ssl_config: None,
+        session_params: vec![],
+        schema_file: None,
+        statement_timeout_secs: None,
+        prompt_for_secrets: false,
+    };
+    assert_eq!(ConfigStore::passwords_to_store("c1", &input).len(), 2);
+
+    input.prompt_for_secrets = true;
+    assert!(ConfigStore::passwords_to_store("c1", &input).is_empty());
+
+    // Nothing touches the keyring, so this saves even where there is none.
+    let store = temp_store().await;
+    let saved = store.save_connection(input).await.unwrap();
+    let loaded = store.get_connection(&saved.id).await.unwrap().unwrap();
+    assert!(loaded.prompt_for_secrets);
+    assert_eq!(loaded.password, "");
+}
+
+// ========================================================================
+// Compare presets
+// ========================================================================
+
+#[tokio::test]
+async fn compare_preset_round_trips() {
+    let store = temp_store().await;
+    let options = CompareOptions {
+        match_by_structure: true,
+        ignore_comments: true,
+        ..Default::default()
+    };
+
+    let saved = store
+        .save_compare_preset("nightly", &options, true)
+        .await
+        .unwrap();
+    let loaded = store.get_compare_preset(&saved.id).await.unwrap().unwrap();
+    assert_eq!(loaded.name, "nightly");
+    assert!(loaded.safe_only);
+    assert!(loaded.options.match_by_structure);
+    assert!(loaded.options.ignore_comments);
+    assert!(!loaded.options.online_ddl);
+
+    // Saving under the same name replaces the options but keeps the preset.
+    let resaved = store
+        .save_compare_preset("nightly", &CompareOptions::default(), false)
+        .await
+        .unwrap();
+    assert_eq!(resaved.id, saved.id);
+    assert!(!resaved.safe_only);
+    assert_eq!(store.list_compare_presets().await.unwrap().len(), 1);
+
+    store.delete_compare_preset(&saved.id).await.unwrap();
+    assert!(store.list_compare_presets().await.unwrap().is_empty());
+}
+
+// ========================================================================
+// Schema snapshots
+// ========================================================================
+
+fn snapshot_table(columns: &[&str]) -> TableSchema {
+    TableSchema {
+        name: "users".to_string(),
+        columns: columns
+            .iter()
+            .enumerate()
+            .map(|(i, name)| crate::models::Column {
+                name: name.to_string(),
+                data_type: "int".to_string(),
+                nullable: true,
+                default_value: None,
+                auto_increment: false,
+                comment: None,
+                ordinal_position: i as u32 + 1,
+                visible: true,
+                on_update: None,
+                sequence: None,
+                generated: None,
+                srid: None,
+                octet_length: None,
+            })
+            .collect(),
+        options: Default::default(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn schema_snapshot_round_trips_as_a_baseline() {
+    let store = temp_store().await;
+    let baseline = vec![snapshot_table(&["id", "email"])];
+
+    let saved = store
+        .save_schema_snapshot("prod-baseline", &DbType::PostgreSQL, &baseline)
+        .await
+        .unwrap();
+    let loaded = store.get_schema_snapshot(&saved.id).await.unwrap().unwrap();
+    assert_eq!(loaded.name, "prod-baseline");
+    assert_eq!(loaded.db_type, DbType::PostgreSQL);
+    assert_eq!(loaded.tables, baseline);
+
+    // The live database lost a column since the baseline was taken.
+    let live = vec![snapshot_table(&["id"])];
+    let diffs = crate::diff::compare_snapshots(&loaded.tables, &live, loaded.db_type);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, crate::models::DiffType::ColumnAdded);
+    assert_eq!(diffs[0].object_name.as_deref(), Some("email"));
+    assert!(
+        store
+            .get_schema_snapshot("missing")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
@@ -1,5 +1,10 @@
-use database_structure_sync_lib::db::SqlGenerator;
-use database_structure_sync_lib::diff::compare_schemas;
+use database_structure_sync_lib::db::{
+    self, MySqlSqlGenerator, SqlGenerator, session_set_statements, statement_timeout_params,
+};
+use database_structure_sync_lib::diff::{
+    compare_schemas, compare_schemas_with_options, compare_snapshots, database_charset_warning,
+    duplicate_name_warnings, generate_script, schemas_in_sync, to_report,
+};
 use database_structure_sync_lib::error::AppError;
 use database_structure_sync_lib::models::*;
 
@@ -36,6 +41,10 @@ impl SqlGenerator for MockSqlGen {
         )
     }
 
+    fn generate_add_column_online(&self, table_name: &str, column: &Column) -> String {
+        format!("{} ONLINE", self.generate_add_column(table_name, column))
+    }
+
     fn generate_modify_column(&self, table_name: &str, column: &Column) -> String {
         format!(
             "ALTER TABLE \"{}\" MODIFY COLUMN \"{}\" {}",
@@ -43,6 +52,17 @@ impl SqlGenerator for MockSqlGen {
         )
     }
 
+    fn generate_alter_column(&self, table_name: &str, source: &Column, _target: &Column) -> String {
+        self.generate_modify_column(table_name, source)
+    }
+
+    fn generate_rename_column(&self, table_name: &str, source: &Column, target: &Column) -> String {
+        format!(
+            "ALTER TABLE \"{}\" RENAME COLUMN \"{}\" TO \"{}\"",
+            table_name, target.name, source.name
+        )
+    }
+
     fn generate_add_index(&self, table_name: &str, index: &Index) -> String {
         let idx_type = if index.unique {
             "UNIQUE INDEX"
@@ -58,6 +78,10 @@ impl SqlGenerator for MockSqlGen {
         )
     }
 
+    fn generate_add_index_online(&self, table_name: &str, index: &Index) -> String {
+        format!("{} ONLINE", self.generate_add_index(table_name, index))
+    }
+
     fn generate_drop_index(&self, table_name: &str, index_name: &str) -> String {
         format!("DROP INDEX \"{}\" ON \"{}\"", index_name, table_name)
     }
@@ -73,6 +97,17 @@ impl SqlGenerator for MockSqlGen {
         )
     }
 
+    fn generate_add_foreign_key_deferred_validation(
+        &self,
+        table_name: &str,
+        fk: &ForeignKey,
+    ) -> String {
+        format!(
+            "{} NOT VALID",
+            self.generate_add_foreign_key(table_name, fk)
+        )
+    }
+
     fn generate_drop_foreign_key(&self, table_name: &str, fk_name: &str) -> String {
         format!(
             "ALTER TABLE \"{}\" DROP FOREIGN KEY \"{}\"",
@@ -95,6 +130,145 @@ impl SqlGenerator for MockSqlGen {
             table_name, unique_name
         )
     }
+
+    fn generate_alter_primary_key(
+        &self,
+        table_name: &str,
+        _current: Option<&PrimaryKey>,
+        wanted: Option<&PrimaryKey>,
+    ) -> String {
+        match wanted {
+            Some(pk) => format!(
+                "ALTER TABLE \"{}\" PRIMARY KEY ({})",
+                table_name,
+                pk.columns.join(", ")
+            ),
+            None => format!("ALTER TABLE \"{}\" DROP PRIMARY KEY", table_name),
+        }
+    }
+
+    fn generate_add_exclusion(&self, table_name: &str, ec: &ExclusionConstraint) -> String {
+        format!(
+            "ALTER TABLE \"{}\" ADD CONSTRAINT \"{}\" {}",
+            table_name, ec.name, ec.definition
+        )
+    }
+
+    fn generate_drop_exclusion(&self, table_name: &str, ec_name: &str) -> String {
+        format!(
+            "ALTER TABLE \"{}\" DROP CONSTRAINT \"{}\"",
+            table_name, ec_name
+        )
+    }
+
+    fn generate_add_check(&self, table_name: &str, check: &CheckConstraint) -> String {
+        format!(
+            "ALTER TABLE \"{}\" ADD CONSTRAINT \"{}\" CHECK ({})",
+            table_name, check.name, check.expression
+        )
+    }
+
+    fn generate_drop_check(&self, table_name: &str, check_name: &str) -> String {
+        format!(
+            "ALTER TABLE \"{}\" DROP CONSTRAINT \"{}\"",
+            table_name, check_name
+        )
+    }
+
+    fn generate_create_policy(&self, table_name: &str, policy: &Policy) -> String {
+        format!(
+            "CREATE POLICY \"{}\" ON \"{}\" FOR {}",
+            policy.name, table_name, policy.command
+        )
+    }
+
+    fn generate_alter_policy(&self, table_name: &str, policy: &Policy) -> String {
+        format!("ALTER POLICY \"{}\" ON \"{}\"", policy.name, table_name)
+    }
+
+    fn generate_drop_policy(&self, table_name: &str, policy_name: &str) -> String {
+        format!("DROP POLICY \"{}\" ON \"{}\"", policy_name, table_name)
+    }
+
+    fn generate_set_row_security(&self, table_name: &str, enabled: bool) -> String {
+        format!(
+            "ALTER TABLE \"{}\" {} ROW LEVEL SECURITY",
+            table_name,
+            if enabled { "ENABLE" } else { "DISABLE" }
+        )
+    }
+
+    fn generate_create_routine(&self, routine: &Routine) -> String {
+        format!("{};", routine.definition)
+    }
+
+    fn generate_replace_routine(&self, routine: &Routine) -> String {
+        format!("{};", routine.definition)
+    }
+
+    fn generate_drop_routine(&self, routine: &Routine) -> String {
+        format!("DROP {} {}", routine.kind.keyword(), routine.name)
+    }
+
+    fn generate_rebuild_table(&self, source: &TableSchema, _target: &TableSchema) -> String {
+        format!("REBUILD TABLE \"{}\"", source.name)
+    }
+
+    fn generate_move_column(
+        &self,
+        _table: &str,
+        _column: &Column,
+        _after: Option<&str>,
+    ) -> Option<String> {
+        None
+    }
+
+    fn generate_alter_partitioning(
+        &self,
+        _table: &str,
+        _partitioning: Option<&PartitionSpec>,
+    ) -> Option<String> {
+        None
+    }
+
+    fn generate_set_tablespace(&self, table: &str, tablespace: Option<&str>) -> String {
+        format!(
+            "ALTER TABLE \"{}\" SET TABLESPACE \"{}\"",
+            table,
+            tablespace.unwrap_or("default")
+        )
+    }
+
+    fn generate_set_table_options(&self, table: &str, options: &TableOptions) -> String {
+        format!(
+            "ALTER TABLE \"{}\" ENGINE={}",
+            table,
+            options.engine.as_deref().unwrap_or("default")
+        )
+    }
+
+    fn generate_set_inherits(&self, table: &str, add: &[String], remove: &[String]) -> String {
+        format!(
+            "ALTER TABLE \"{}\" INHERIT {} NO INHERIT {}",
+            table,
+            add.join(","),
+            remove.join(",")
+        )
+    }
+
+    fn generate_grant(&self, grant: &Grant) -> String {
+        format!(
+            "GRANT {} ON \"{}\" TO {}",
+            grant.privilege, grant.object, grant.grantee
+        )
+    }
+
+    fn generate_revoke(&self, grant: &Grant) -> String {
+        format!(
+            "REVOKE {} ON \"{}\" FROM {}",
+            grant.privilege, grant.object, grant.grantee
+        )
+    }
 }
 
 // ============================================================================
@@ -116,6 +290,12 @@ fn create_column(
         auto_increment,
         comment: None,
         ordinal_position: position,
+        visible: true,
+        on_update: None,
+        sequence: None,
+        generated: None,
+        srid: None,
+        octet_length: None,
     }
 }
 
@@ -134,6 +314,12 @@ fn create_column_with_default(
         auto_increment: false,
         comment: None,
         ordinal_position: position,
+        visible: true,
+        on_update: None,
+        sequence: None,
+        generated: None,
+        srid: None,
+        octet_length: None,
     }
 }
 
@@ -143,6 +329,11 @@ fn create_index(name: &str, columns: Vec<&str>, unique: bool) -> Index {
         columns: columns.iter().map(|s| s.to_string()).collect(),
         unique,
         index_type: "BTREE".to_string(),
+        tablespace: None,
+        visible: true,
+        expression: None,
+        storage_params: vec![],
+        comment: None,
     }
 }
 
@@ -159,6 +350,9 @@ fn create_foreign_key(
         ref_columns: ref_columns.iter().map(|s| s.to_string()).collect(),
         on_delete: "CASCADE".to_string(),
         on_update: "CASCADE".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
     }
 }
 
@@ -173,10 +367,7 @@ fn create_table(name: &str, columns: Vec<Column>) -> TableSchema {
     TableSchema {
         name: name.to_string(),
         columns,
-        primary_key: None,
-        indexes: vec![],
-        foreign_keys: vec![],
-        unique_constraints: vec![],
+        ..Default::default()
     }
 }
 
@@ -247,6 +438,38 @@ fn test_identical_tables_no_diff() {
     assert!(diffs.is_empty());
 }
 
+#[test]
+fn test_reordered_columns_no_diff() {
+    let source = vec![create_table(
+        "users",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("name", "VARCHAR(255)", true, false, 2),
+            create_column("email", "VARCHAR(255)", true, false, 3),
+        ],
+    )];
+    let target = vec![create_table(
+        "users",
+        vec![
+            create_column("email", "VARCHAR(255)", true, false, 1),
+            create_column("id", "INT", false, true, 2),
+            create_column("name", "VARCHAR(255)", true, false, 3),
+        ],
+    )];
+
+    assert!(compare_schemas(&source, &target, &MockSqlGen).is_empty());
+    assert!(schemas_in_sync(&source, &target));
+
+    // Positional sync still rebuilds when asked for.
+    let options = CompareOptions {
+        rebuild_for_column_order: true,
+        ..Default::default()
+    };
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::TableRebuilt);
+}
+
 // ============================================================================
 // Column Level Tests
 // ============================================================================
@@ -373,6 +596,188 @@ fn test_detect_modified_column_default() {
     assert!(column_modified.is_some());
 }
 
+#[test]
+fn test_modified_column_lists_changed_attributes() {
+    let source = vec![create_table(
+        "users",
+        vec![create_column("email", "VARCHAR(255)", false, false, 1)],
+    )];
+
+    let target = vec![create_table(
+        "users",
+        vec![create_column("email", "VARCHAR(100)", true, false, 1)],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+
+    let diff = diffs
+        .iter()
+        .find(|d| d.diff_type == DiffType::ColumnModified)
+        .unwrap();
+    assert_eq!(
+        diff.changes,
+        vec![ColumnChangeKind::DataType, ColumnChangeKind::Nullable]
+    );
+}
+
+#[test]
+fn test_comment_only_change_is_cosmetic() {
+    let mut commented = create_column("email", "VARCHAR(255)", false, false, 1);
+    commented.comment = Some("Login email".to_string());
+    let source = vec![
+        create_table("users", vec![commented]),
+        create_table(
+            "orders",
+            vec![create_column("total", "DECIMAL(10,2)", false, false, 1)],
+        ),
+    ];
+    let target = vec![
+        create_table(
+            "users",
+            vec![create_column("email", "VARCHAR(255)", false, false, 1)],
+        ),
+        create_table(
+            "orders",
+            vec![create_column("total", "INT", false, false, 1)],
+        ),
+    ];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+
+    let priority_of = |table: &str| {
+        diffs
+            .iter()
+            .find(|d| d.table_name == table && d.diff_type == DiffType::ColumnModified)
+            .unwrap()
+            .priority
+            .clone()
+    };
+    assert_eq!(priority_of("users"), DiffPriority::Cosmetic);
+    assert_eq!(priority_of("orders"), DiffPriority::Structural);
+}
+
+#[test]
+fn test_type_spelling_only_change_is_cosmetic() {
+    let source = vec![create_table(
+        "users",
+        vec![
+            create_column("id", "INT(11) UNSIGNED", false, false, 1),
+            create_column("email", "VARCHAR(255)", false, false, 2),
+        ],
+    )];
+    let target = vec![create_table(
+        "users",
+        vec![
+            create_column("id", "int  unsigned", false, false, 1),
+            create_column("email", "varchar(100)", false, false, 2),
+        ],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+
+    let diff_of = |column: &str| {
+        diffs
+            .iter()
+            .find(|d| d.object_name.as_deref() == Some(column))
+            .unwrap()
+    };
+    assert_eq!(
+        diff_of("id").changes,
+        vec![ColumnChangeKind::DataTypeSpelling]
+    );
+    assert_eq!(diff_of("id").priority, DiffPriority::Cosmetic);
+    assert_eq!(diff_of("email").changes, vec![ColumnChangeKind::DataType]);
+    assert_eq!(diff_of("email").priority, DiffPriority::Structural);
+}
+
+#[test]
+fn test_removed_column_is_high_severity_and_comment_change_low() {
+    let mut commented = create_column("email", "VARCHAR(255)", false, false, 1);
+    commented.comment = Some("Login email".to_string());
+    let source = vec![create_table("users", vec![commented])];
+    let target = vec![create_table(
+        "users",
+        vec![
+            create_column("email", "VARCHAR(255)", false, false, 1),
+            create_column("legacy_id", "INT", true, false, 2),
+        ],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    let severity_of = |diff_type: DiffType| {
+        diffs
+            .iter()
+            .find(|d| d.diff_type == diff_type)
+            .unwrap()
+            .severity
+    };
+    assert_eq!(severity_of(DiffType::ColumnModified), Severity::Low);
+    assert_eq!(severity_of(DiffType::ColumnRemoved), Severity::High);
+
+    // Reviewers see the drop first; the script keeps execution order.
+    let result = DiffResult {
+        items: diffs.clone(),
+        source_tables: 1,
+        target_tables: 1,
+        warnings: vec![],
+        cross_engine: false,
+        source_schema: None,
+        target_schema: None,
+    };
+    let report = to_report(&result, ReportFormat::PlainText);
+    let drop_line = report.find("column_removed").unwrap();
+    assert!(
+        drop_line < report.find("column_modified").unwrap(),
+        "{}",
+        report
+    );
+    let in_order: Vec<&str> = diffs
+        .iter()
+        .map(|d| d.sql.as_str())
+        .filter(|sql| !sql.trim().is_empty())
+        .collect();
+    assert_eq!(
+        generate_script(&diffs, &ScriptOptions::default()),
+        in_order.join("\n\n")
+    );
+}
+
+#[test]
+fn test_explicit_null_default_equals_no_default() {
+    let mut explicit_null = create_column("nickname", "varchar(50)", true, false, 1);
+    explicit_null.default_value = Some("NULL::character varying".to_string());
+    let source = vec![create_table("users", vec![explicit_null])];
+    let target = vec![create_table(
+        "users",
+        vec![create_column("nickname", "varchar(50)", true, false, 1)],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    assert!(diffs.is_empty(), "unexpected diffs: {:?}", diffs);
+}
+
+#[test]
+fn test_null_default_to_real_default_is_modified() {
+    let mut explicit_null = create_column("status", "varchar(20)", true, false, 1);
+    explicit_null.default_value = Some("NULL".to_string());
+    let source = vec![create_table(
+        "users",
+        vec![create_column_with_default(
+            "status",
+            "varchar(20)",
+            true,
+            "'active'::character varying",
+            1,
+        )],
+    )];
+    let target = vec![create_table("users", vec![explicit_null])];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Default]);
+}
+
 #[test]
 fn test_multiple_column_changes() {
     let source = vec![create_table(
@@ -490,6 +895,30 @@ fn test_detect_modified_index() {
     assert!(diff.sql.contains("CREATE INDEX"));
 }
 
+#[test]
+fn test_modified_index_exposes_drop_and_create_statements() {
+    let columns = vec![
+        create_column("email", "VARCHAR(255)", true, false, 1),
+        create_column("name", "VARCHAR(255)", true, false, 2),
+    ];
+    let mut source_table = create_table("users", columns.clone());
+    source_table.indexes = vec![create_index("idx_search", vec!["email", "name"], false)];
+    let mut target_table = create_table("users", columns);
+    target_table.indexes = vec![create_index("idx_search", vec!["email"], false)];
+
+    let diffs = compare_schemas(&[source_table], &[target_table], &MySqlSqlGenerator);
+
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::IndexModified);
+    assert_eq!(
+        diffs[0].sql_statements(),
+        vec![
+            "DROP INDEX `idx_search` ON `users`;",
+            "CREATE INDEX `idx_search` ON `users` (`email`, `name`);",
+        ]
+    );
+}
+
 #[test]
 fn test_detect_unique_index_added() {
     let mut source_table = create_table(
@@ -664,20 +1093,556 @@ fn test_composite_unique_constraint() {
         .find(|d| d.diff_type == DiffType::UniqueConstraintAdded);
     assert!(uc_added.is_some());
     assert!(
-        uc_added
-            .unwrap()
-            .source_def
-            .as_ref()
-            .unwrap()
-            .contains("order_id")
+        uc_added
+            .unwrap()
+            .source_def
+            .as_ref()
+            .unwrap()
+            .contains("order_id")
+    );
+    assert!(
+        uc_added
+            .unwrap()
+            .source_def
+            .as_ref()
+            .unwrap()
+            .contains("product_id")
+    );
+}
+
+// ============================================================================
+// Sync Status Tests
+// ============================================================================
+
+#[test]
+fn test_schemas_in_sync_when_identical() {
+    let mut users = create_table(
+        "users",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("email", "VARCHAR(255)", false, false, 2),
+        ],
+    );
+    users.indexes = vec![
+        create_index("idx_email", vec!["email"], true),
+        create_index("idx_id", vec!["id"], false),
+    ];
+    let source = vec![users.clone(), create_table("logs", vec![])];
+    let mut reordered = users;
+    reordered.indexes.reverse();
+    let target = vec![create_table("logs", vec![]), reordered];
+
+    assert!(schemas_in_sync(&source, &target));
+    assert!(compare_schemas(&source, &target, &MockSqlGen).is_empty());
+}
+
+#[test]
+fn test_schemas_not_in_sync_when_primary_key_differs() {
+    let mut source_users = create_table(
+        "users",
+        vec![
+            create_column("id", "INT", false, false, 1),
+            create_column("tenant_id", "INT", false, false, 2),
+        ],
+    );
+    let mut target_users = source_users.clone();
+    source_users.primary_key = Some(PrimaryKey {
+        name: None,
+        columns: vec!["tenant_id".to_string(), "id".to_string()],
+    });
+    target_users.primary_key = Some(PrimaryKey {
+        name: Some("users_pkey".to_string()),
+        columns: vec!["id".to_string()],
+    });
+    let source = vec![source_users];
+    let target = vec![target_users];
+
+    assert!(!schemas_in_sync(&source, &target));
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::PrimaryKeyModified);
+    assert_eq!(diffs[0].source_def.as_deref(), Some("tenant_id, id"));
+    assert_eq!(diffs[0].target_def.as_deref(), Some("id"));
+    assert_eq!(diffs[0].severity, Severity::High);
+
+    let postgres = db::PostgresSqlGenerator::default();
+    assert_eq!(
+        postgres.generate_alter_primary_key(
+            "users",
+            target[0].primary_key.as_ref(),
+            source[0].primary_key.as_ref()
+        ),
+        "ALTER TABLE \"users\" DROP CONSTRAINT IF EXISTS \"users_pkey\";\n\
+         ALTER TABLE \"users\" ADD CONSTRAINT \"users_pkey\" PRIMARY KEY (\"tenant_id\", \"id\");"
+    );
+    assert_eq!(
+        MySqlSqlGenerator.generate_alter_primary_key(
+            "users",
+            target[0].primary_key.as_ref(),
+            source[0].primary_key.as_ref()
+        ),
+        "ALTER TABLE `users` DROP PRIMARY KEY, ADD PRIMARY KEY (`tenant_id`, `id`);"
+    );
+}
+
+#[test]
+fn test_schemas_not_in_sync_when_column_differs() {
+    let source = vec![create_table(
+        "users",
+        vec![create_column("email", "VARCHAR(255)", false, false, 1)],
+    )];
+    let target = vec![create_table(
+        "users",
+        vec![create_column("email", "VARCHAR(100)", false, false, 1)],
+    )];
+
+    assert!(!schemas_in_sync(&source, &target));
+    assert!(!schemas_in_sync(&source, &[]));
+}
+
+#[test]
+fn test_database_charset_warning() {
+    let utf8mb4 = DatabaseInfo {
+        charset: Some("utf8mb4".to_string()),
+        collation: Some("utf8mb4_0900_ai_ci".to_string()),
+        encoding: Some("utf8mb4".to_string()),
+    };
+    let latin1 = DatabaseInfo {
+        charset: Some("latin1".to_string()),
+        collation: Some("latin1_swedish_ci".to_string()),
+        encoding: Some("latin1".to_string()),
+    };
+
+    let warning = database_charset_warning(&utf8mb4, &latin1).unwrap();
+    assert!(warning.contains("utf8mb4") && warning.contains("latin1"));
+    assert!(database_charset_warning(&utf8mb4, &utf8mb4.clone()).is_none());
+    assert!(database_charset_warning(&utf8mb4, &DatabaseInfo::default()).is_none());
+}
+
+#[test]
+fn test_duplicate_table_names_are_warned_about() {
+    let id = create_column("id", "int", false, false, 1);
+    let tables = vec![
+        create_table("users", vec![id.clone()]),
+        create_table("orders", vec![id.clone(), id.clone()]),
+        create_table("users", vec![id]),
+    ];
+
+    let warnings = duplicate_name_warnings("Source", &tables);
+    assert_eq!(
+        warnings,
+        vec![
+            "Source schema has more than one table named users",
+            "Source table orders has more than one column named id",
+        ]
+    );
+    assert!(duplicate_name_warnings("Source", &tables[..1]).is_empty());
+}
+
+#[test]
+fn test_utf8_alias_matches_utf8mb3_but_not_utf8mb4() {
+    let with_charset = |charset: &str| DatabaseInfo {
+        charset: Some(charset.to_string()),
+        ..DatabaseInfo::default()
+    };
+
+    assert!(database_charset_warning(&with_charset("utf8"), &with_charset("utf8mb3")).is_none());
+    assert!(database_charset_warning(&with_charset("UTF8MB3"), &with_charset("utf8")).is_none());
+    let warning =
+        database_charset_warning(&with_charset("utf8mb3"), &with_charset("utf8mb4")).unwrap();
+    assert!(warning.contains("utf8mb3") && warning.contains("utf8mb4"));
+}
+
+// ============================================================================
+// Column Order Rebuild Tests
+// ============================================================================
+
+#[test]
+fn test_rebuild_for_mid_inserted_column() {
+    let source = vec![create_table(
+        "users",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("email", "VARCHAR(255)", false, false, 2),
+            create_column("name", "VARCHAR(100)", true, false, 3),
+        ],
+    )];
+    let target = vec![create_table(
+        "users",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("name", "VARCHAR(100)", true, false, 2),
+        ],
+    )];
+    let options = CompareOptions {
+        rebuild_for_column_order: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::TableRebuilt);
+    assert_eq!(diffs[0].table_name, "users");
+    assert_eq!(diffs[0].source_def, Some("id, email, name".to_string()));
+    assert_eq!(diffs[0].target_def, Some("id, name".to_string()));
+    assert_eq!(diffs[0].sql, "REBUILD TABLE \"users\"");
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnAdded);
+}
+
+#[test]
+fn test_no_rebuild_when_column_appended() {
+    let source = vec![create_table(
+        "users",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("name", "VARCHAR(100)", true, false, 2),
+        ],
+    )];
+    let target = vec![create_table(
+        "users",
+        vec![create_column("id", "INT", false, true, 1)],
+    )];
+    let options = CompareOptions {
+        rebuild_for_column_order: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnAdded);
+}
+
+#[test]
+fn test_ignore_auto_increment_option() {
+    let source = vec![create_table(
+        "users",
+        vec![create_column("id", "INT", false, true, 1)],
+    )];
+    let target = vec![create_table(
+        "users",
+        vec![create_column("id", "INT", false, false, 1)],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::AutoIncrement]);
+
+    let options = CompareOptions {
+        ignore_auto_increment: true,
+        ..Default::default()
+    };
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert!(diffs.is_empty());
+}
+
+#[test]
+fn test_ignore_auto_increment_keeps_other_changes() {
+    let source = vec![create_table(
+        "users",
+        vec![create_column("id", "BIGINT", false, true, 1)],
+    )];
+    let target = vec![create_table(
+        "users",
+        vec![create_column("id", "INT", false, false, 1)],
+    )];
+    let options = CompareOptions {
+        ignore_auto_increment: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::DataType]);
+}
+
+#[test]
+fn test_ignore_cosmetic_preset_skips_comments_and_positions() {
+    let mut id = create_column("id", "INT", false, true, 1);
+    id.comment = Some("primary key".to_string());
+    let source = vec![create_table(
+        "users",
+        vec![id, create_column("name", "VARCHAR(100)", true, false, 2)],
+    )];
+    let target = vec![create_table(
+        "users",
+        vec![
+            create_column("name", "VARCHAR(100)", true, false, 1),
+            create_column("id", "INT", false, true, 2),
+        ],
+    )];
+    let mut options = CompareOptions {
+        rebuild_for_column_order: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::TableRebuilt);
+    options.rebuild_for_column_order = false;
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Comment]);
+
+    options.preset = Some(ComparePreset::IgnoreCosmetic);
+    options.rebuild_for_column_order = true;
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert!(diffs.is_empty(), "{:?}", diffs);
+}
+
+#[test]
+fn test_fold_identifier_case_matches_postgres_lowercase_names() {
+    let mut orders = create_table(
+        "Orders",
+        vec![
+            create_column("Id", "INT", false, true, 1),
+            create_column("UserId", "INT", false, false, 2),
+        ],
+    );
+    orders.foreign_keys = vec![create_foreign_key(
+        "fk_orders_user",
+        vec!["UserId"],
+        "Users",
+        vec!["Id"],
+    )];
+    let source = vec![
+        create_table(
+            "Users",
+            vec![
+                create_column("Id", "INT", false, true, 1),
+                create_column("email", "VARCHAR(255)", false, false, 2),
+            ],
+        ),
+        orders,
+    ];
+
+    let mut target_orders = create_table(
+        "orders",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("userid", "INT", false, false, 2),
+        ],
+    );
+    target_orders.foreign_keys = vec![create_foreign_key(
+        "fk_orders_user",
+        vec!["userid"],
+        "users",
+        vec!["id"],
+    )];
+    let target = vec![
+        create_table(
+            "users",
+            vec![
+                create_column("id", "INT", false, true, 1),
+                create_column("email", "VARCHAR(255)", false, false, 2),
+            ],
+        ),
+        target_orders,
+    ];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    assert!(diffs.iter().any(|d| d.diff_type == DiffType::TableAdded));
+    assert!(diffs.iter().any(|d| d.diff_type == DiffType::TableRemoved));
+
+    let options = CompareOptions {
+        fold_identifier_case: true,
+        ..Default::default()
+    };
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert!(diffs.is_empty(), "{:?}", diffs);
+}
+
+#[test]
+fn test_fold_identifier_case_keeps_casing_of_new_tables() {
+    let source = vec![create_table(
+        "AuditLog",
+        vec![create_column("Id", "INT", false, true, 1)],
+    )];
+    let options = CompareOptions {
+        fold_identifier_case: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(&source, &[], &MockSqlGen, &options);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].table_name, "AuditLog");
+}
+
+#[test]
+fn test_unique_constraint_matched_by_columns() {
+    let mut source_table = create_table(
+        "users",
+        vec![create_column("email", "VARCHAR(255)", false, false, 1)],
+    );
+    source_table.unique_constraints = vec![create_unique_constraint("email", vec!["email"])];
+    let mut target_table = source_table.clone();
+    target_table.unique_constraints =
+        vec![create_unique_constraint("users_email_key", vec!["email"])];
+    let source = vec![source_table];
+    let target = vec![target_table];
+
+    // By name: the differing generated names show up as a drop + add pair
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    assert_eq!(diffs.len(), 2);
+
+    let options = CompareOptions {
+        match_unique_by_columns: true,
+        ..Default::default()
+    };
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert!(diffs.is_empty(), "unexpected diffs: {:?}", diffs);
+}
+
+#[test]
+fn test_match_by_structure_ignores_index_and_fk_names() {
+    let mut source_table = create_table(
+        "orders",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("user_id", "INT", false, false, 2),
+            create_column("email", "VARCHAR(255)", false, false, 3),
+        ],
+    );
+    source_table.indexes = vec![
+        create_index("idx_user", vec!["user_id"], false),
+        create_index("idx_email", vec!["email"], true),
+    ];
+    source_table.foreign_keys = vec![create_foreign_key(
+        "fk_user",
+        vec!["user_id"],
+        "users",
+        vec!["id"],
+    )];
+    source_table.unique_constraints = vec![create_unique_constraint("email", vec!["email"])];
+    let mut target_table = source_table.clone();
+    target_table.indexes = vec![
+        create_index("orders_user_id_idx", vec!["user_id"], false),
+        create_index("orders_email_idx", vec!["email"], true),
+    ];
+    target_table.foreign_keys = vec![create_foreign_key(
+        "orders_user_id_fkey",
+        vec!["user_id"],
+        "users",
+        vec!["id"],
+    )];
+    target_table.unique_constraints =
+        vec![create_unique_constraint("orders_email_key", vec!["email"])];
+    let source = vec![source_table];
+    let target = vec![target_table];
+
+    assert_eq!(compare_schemas(&source, &target, &MockSqlGen).len(), 8);
+
+    let options = CompareOptions {
+        match_by_structure: true,
+        ..Default::default()
+    };
+    let diffs = compare_schemas_with_options(&source, &target, &MockSqlGen, &options);
+    assert!(diffs.is_empty(), "unexpected diffs: {:?}", diffs);
+}
+
+#[test]
+fn test_match_by_structure_still_reports_changed_index() {
+    let mut source_table = create_table(
+        "orders",
+        vec![create_column("user_id", "INT", false, false, 1)],
+    );
+    source_table.indexes = vec![create_index("idx_user", vec!["user_id"], true)];
+    let mut target_table = source_table.clone();
+    target_table.indexes = vec![create_index("orders_user_id_idx", vec!["user_id"], false)];
+
+    let options = CompareOptions {
+        match_by_structure: true,
+        ..Default::default()
+    };
+    let diffs =
+        compare_schemas_with_options(&[source_table], &[target_table], &MockSqlGen, &options);
+    let types: Vec<_> = diffs.iter().map(|d| d.diff_type.clone()).collect();
+    assert_eq!(types, vec![DiffType::IndexAdded, DiffType::IndexRemoved]);
+}
+
+#[test]
+fn test_name_sensitivity_modes() {
+    let mut source_table = create_table(
+        "orders",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("user_id", "INT", false, false, 2),
+            create_column("created_at", "DATETIME", false, false, 3),
+        ],
+    );
+    source_table.indexes = vec![create_index(
+        "idx_orders_created",
+        vec!["created_at"],
+        false,
+    )];
+    source_table.foreign_keys = vec![create_foreign_key(
+        "orders_ibfk_1",
+        vec!["user_id"],
+        "users",
+        vec!["id"],
+    )];
+    let mut target_table = source_table.clone();
+    // A deliberately renamed index and an FK MySQL numbered differently.
+    target_table.indexes = vec![create_index("ix_created", vec!["created_at"], false)];
+    target_table.foreign_keys = vec![create_foreign_key(
+        "orders_ibfk_2",
+        vec!["user_id"],
+        "users",
+        vec!["id"],
+    )];
+    let source = vec![source_table];
+    let target = vec![target_table];
+
+    let diff_types = |name_sensitivity| {
+        let options = CompareOptions {
+            name_sensitivity,
+            ..Default::default()
+        };
+        compare_schemas_with_options(&source, &target, &MockSqlGen, &options)
+            .into_iter()
+            .map(|d| d.diff_type)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(diff_types(NameSensitivity::Strict).len(), 4);
+    assert_eq!(
+        diff_types(NameSensitivity::IgnoreGenerated),
+        vec![DiffType::IndexAdded, DiffType::IndexRemoved]
+    );
+    assert!(diff_types(NameSensitivity::IgnoreAll).is_empty());
+}
+
+#[test]
+fn test_unique_constraint_match_by_columns_still_detects_new_columns() {
+    let mut source_table = create_table(
+        "users",
+        vec![
+            create_column("email", "VARCHAR(255)", false, false, 1),
+            create_column("tenant", "INT", false, false, 2),
+        ],
+    );
+    source_table.unique_constraints =
+        vec![create_unique_constraint("email", vec!["tenant", "email"])];
+    let mut target_table = source_table.clone();
+    target_table.unique_constraints =
+        vec![create_unique_constraint("users_email_key", vec!["email"])];
+
+    let options = CompareOptions {
+        match_unique_by_columns: true,
+        ..Default::default()
+    };
+    let diffs =
+        compare_schemas_with_options(&[source_table], &[target_table], &MockSqlGen, &options);
+    assert!(
+        diffs
+            .iter()
+            .any(|d| d.diff_type == DiffType::UniqueConstraintAdded)
     );
     assert!(
-        uc_added
-            .unwrap()
-            .source_def
-            .as_ref()
-            .unwrap()
-            .contains("product_id")
+        diffs
+            .iter()
+            .any(|d| d.diff_type == DiffType::UniqueConstraintRemoved)
     );
 }
 
@@ -842,6 +1807,53 @@ fn test_multiple_tables_mixed_changes() {
     assert!(!diffs.iter().any(|d| d.table_name == "table_a"));
 }
 
+#[test]
+fn test_subset_compare_only_reports_named_tables() {
+    let source = vec![
+        create_table("users", vec![create_column("id", "INT", false, true, 1)]),
+        create_table(
+            "orders",
+            vec![create_column("id", "BIGINT", false, true, 1)],
+        ),
+        create_table("audit", vec![create_column("id", "INT", false, true, 1)]),
+    ];
+    let target = vec![
+        create_table(
+            "users",
+            vec![
+                create_column("id", "INT", false, true, 1),
+                create_column("legacy", "TEXT", true, false, 2),
+            ],
+        ),
+        create_table("orders", vec![create_column("id", "INT", false, true, 1)]),
+        create_table("sessions", vec![create_column("id", "INT", false, true, 1)]),
+    ];
+
+    // What a reader returns for get_tables_named(["users", "orders"]).
+    let named = ["users", "orders"];
+    let pick = |tables: &[TableSchema]| -> Vec<TableSchema> {
+        tables
+            .iter()
+            .filter(|t| named.contains(&t.name.as_str()))
+            .cloned()
+            .collect()
+    };
+
+    let diffs = compare_schemas(&pick(&source), &pick(&target), &MockSqlGen);
+
+    assert!(
+        diffs
+            .iter()
+            .any(|d| d.diff_type == DiffType::ColumnRemoved && d.table_name == "users")
+    );
+    assert!(
+        diffs
+            .iter()
+            .any(|d| d.diff_type == DiffType::ColumnModified && d.table_name == "orders")
+    );
+    assert!(diffs.iter().all(|d| named.contains(&d.table_name.as_str())));
+}
+
 // ============================================================================
 // Model Tests
 // ============================================================================
@@ -881,6 +1893,9 @@ fn test_diff_type_variants() {
         DiffType::UniqueConstraintAdded,
         DiffType::UniqueConstraintRemoved,
         DiffType::UniqueConstraintModified,
+        DiffType::TableRebuilt,
+        DiffType::TablespaceModified,
+        DiffType::TableOptionsModified,
     ];
 
     for i in 0..types.len() {
@@ -1045,6 +2060,54 @@ fn test_db_type_deserialize_lowercase() {
 // Connection Serialization Tests
 // ============================================================================
 
+#[test]
+fn test_session_params_become_set_statements_run_on_connect() {
+    let params = vec![
+        ("search_path".to_string(), "app, public".to_string()),
+        ("sql_mode".to_string(), "'STRICT_TRANS_TABLES'".to_string()),
+    ];
+    assert_eq!(
+        session_set_statements(&params).unwrap(),
+        vec![
+            "SET search_path = app, public",
+            "SET sql_mode = 'STRICT_TRANS_TABLES'",
+        ]
+    );
+
+    let injected = vec![("x; DROP TABLE users".to_string(), "1".to_string())];
+    assert!(session_set_statements(&injected).is_err());
+    let chained = vec![(
+        "search_path".to_string(),
+        "app; DROP TABLE users".to_string(),
+    )];
+    assert!(session_set_statements(&chained).is_err());
+}
+
+#[test]
+fn test_statement_timeout_becomes_dialect_session_params() {
+    let set = |db_type| session_set_statements(&statement_timeout_params(&db_type, 30)).unwrap();
+    assert_eq!(
+        set(DbType::PostgreSQL),
+        vec!["SET statement_timeout = 30000"]
+    );
+    assert_eq!(
+        set(DbType::MySQL),
+        vec![
+            "SET max_execution_time = 30000",
+            "SET lock_wait_timeout = 30"
+        ]
+    );
+    assert_eq!(
+        set(DbType::MariaDB),
+        vec!["SET max_statement_time = 30", "SET lock_wait_timeout = 30"]
+    );
+    // Milliseconds are counted past u32.
+    assert_eq!(
+        statement_timeout_params(&DbType::MySQL, u32::MAX)[0].1,
+        "4294967295000"
+    );
+}
+
 #[test]
 fn test_connection_serialize_skips_password() {
     let conn = Connection {
@@ -1058,6 +2121,10 @@ fn test_connection_serialize_skips_password() {
         database: "testdb".to_string(),
         ssh_config: None,
         ssl_config: None,
+        session_params: vec![],
+        schema_file: None,
+        statement_timeout_secs: None,
+        prompt_for_secrets: false,
         created_at: "2025-01-01T00:00:00Z".to_string(),
         updated_at: "2025-01-01T00:00:00Z".to_string(),
     };
@@ -1102,6 +2169,10 @@ fn test_connection_input_serialize_deserialize() {
         database: "app".to_string(),
         ssh_config: None,
         ssl_config: None,
+        session_params: vec![],
+        schema_file: None,
+        statement_timeout_secs: None,
+        prompt_for_secrets: false,
     };
 
     let json = serde_json::to_string(&input).unwrap();
@@ -1267,6 +2338,10 @@ fn test_connection_with_ssh_config_serialize() {
             },
         }),
         ssl_config: None,
+        session_params: vec![],
+        schema_file: None,
+        statement_timeout_secs: None,
+        prompt_for_secrets: false,
         created_at: "2025-01-01".to_string(),
         updated_at: "2025-01-01".to_string(),
     };
@@ -1278,6 +2353,119 @@ fn test_connection_with_ssh_config_serialize() {
     assert!(!json.contains("\"pw\""));
 }
 
+fn placeholder_connection() -> Connection {
+    Connection {
+        id: "conn-ci".to_string(),
+        name: "CI DB".to_string(),
+        db_type: DbType::PostgreSQL,
+        host: "db.internal".to_string(),
+        port: 5432,
+        username: "ci".to_string(),
+        password: "${DB_PASSWORD}".to_string(),
+        database: "app".to_string(),
+        ssh_config: Some(SshConfig {
+            enabled: true,
+            host: "jump.example.com".to_string(),
+            port: 22,
+            username: "jumpuser".to_string(),
+            auth_method: SshAuthMethod::PrivateKey {
+                private_key_path: "~/.ssh/id_ed25519".to_string(),
+                passphrase: Some("${SSH_PASSPHRASE}".to_string()),
+            },
+        }),
+        ssl_config: None,
+        session_params: vec![],
+        schema_file: None,
+        statement_timeout_secs: None,
+        prompt_for_secrets: false,
+        created_at: String::new(),
+        updated_at: String::new(),
+    }
+}
+
+#[test]
+fn test_connection_resolves_env_placeholders() {
+    let lookup = |name: &str| match name {
+        "DB_PASSWORD" => Some("s3cret".to_string()),
+        "SSH_PASSPHRASE" => Some("unlock".to_string()),
+        _ => None,
+    };
+
+    let resolved = placeholder_connection()
+        .resolve_secrets_with(&lookup)
+        .unwrap();
+    assert_eq!(resolved.password, "s3cret");
+    match resolved.ssh_config.unwrap().auth_method {
+        SshAuthMethod::PrivateKey {
+            private_key_path,
+            passphrase,
+        } => {
+            assert_eq!(private_key_path, "~/.ssh/id_ed25519");
+            assert_eq!(passphrase.as_deref(), Some("unlock"));
+        }
+        other => panic!("unexpected auth method: {:?}", other),
+    }
+
+    // Plain values pass through untouched.
+    let mut plain = placeholder_connection();
+    plain.password = "literal$".to_string();
+    plain.ssh_config = None;
+    assert_eq!(
+        plain.resolve_secrets_with(&lookup).unwrap().password,
+        "literal$"
+    );
+}
+
+#[test]
+fn test_prompting_connection_requires_supplied_secrets() {
+    let mut conn = placeholder_connection();
+    conn.password = String::new();
+    conn.prompt_for_secrets = true;
+    if let Some(ssh) = conn.ssh_config.as_mut() {
+        ssh.auth_method = SshAuthMethod::Password {
+            password: String::new(),
+        };
+    }
+
+    let err = conn.with_secrets(None).unwrap_err();
+    assert!(
+        matches!(err, AppError::Validation(ref msg) if msg == "CI DB needs its password to connect")
+    );
+    let only_password = ConnectionSecrets {
+        password: Some("s3cret".to_string()),
+        ..Default::default()
+    };
+    let err = conn.with_secrets(Some(&only_password)).unwrap_err();
+    assert!(
+        matches!(err, AppError::Validation(ref msg) if msg == "CI DB needs its SSH password to connect")
+    );
+
+    let secrets = ConnectionSecrets {
+        ssh_password: Some("tunnel".to_string()),
+        ..only_password
+    };
+    let resolved = conn.with_secrets(Some(&secrets)).unwrap();
+    assert_eq!(resolved.password, "s3cret");
+    match resolved.ssh_config.unwrap().auth_method {
+        SshAuthMethod::Password { password } => assert_eq!(password, "tunnel"),
+        other => panic!("unexpected auth method: {:?}", other),
+    }
+
+    // Connections that store their secrets ignore the supplied ones.
+    conn.prompt_for_secrets = false;
+    assert_eq!(conn.with_secrets(Some(&secrets)).unwrap().password, "");
+}
+
+#[test]
+fn test_connection_missing_env_placeholder_is_validation_error() {
+    let err = placeholder_connection()
+        .resolve_secrets_with(&|name: &str| (name == "DB_PASSWORD").then(|| "pw".to_string()))
+        .unwrap_err();
+    assert!(
+        matches!(err, AppError::Validation(ref msg) if msg == "env var SSH_PASSPHRASE not set")
+    );
+}
+
 // ============================================================================
 // DiffType Serialization Tests (snake_case)
 // ============================================================================
@@ -1370,6 +2558,9 @@ fn test_diff_item_serialize_deserialize() {
         sql: "ALTER TABLE users ADD COLUMN email VARCHAR(255)".to_string(),
         selected: true,
         warnings: vec![],
+        changes: vec![],
+        priority: DiffPriority::Structural,
+        severity: Severity::default(),
     };
 
     let json = serde_json::to_string(&item).unwrap();
@@ -1398,6 +2589,9 @@ fn test_diff_item_with_none_fields() {
         sql: "CREATE TABLE orders".to_string(),
         selected: false,
         warnings: vec![],
+        changes: vec![],
+        priority: DiffPriority::Structural,
+        severity: Severity::default(),
     };
 
     let json = serde_json::to_string(&item).unwrap();
@@ -1426,6 +2620,9 @@ fn test_diff_result_serialize_deserialize() {
                 sql: "CREATE TABLE users".to_string(),
                 selected: true,
                 warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             },
             DiffItem {
                 id: "2".to_string(),
@@ -1437,10 +2634,17 @@ fn test_diff_result_serialize_deserialize() {
                 sql: "ALTER TABLE orders DROP COLUMN old_col".to_string(),
                 selected: true,
                 warnings: vec![],
+                changes: vec![],
+                priority: DiffPriority::Structural,
+                severity: Severity::default(),
             },
         ],
         source_tables: 5,
         target_tables: 3,
+        warnings: vec![],
+        cross_engine: false,
+        source_schema: None,
+        target_schema: None,
     };
 
     let json = serde_json::to_string(&result).unwrap();
@@ -1452,12 +2656,77 @@ fn test_diff_result_serialize_deserialize() {
     assert_eq!(deserialized.items[1].diff_type, DiffType::ColumnRemoved);
 }
 
+#[test]
+fn test_diff_result_carries_schemas_when_requested() {
+    let result = DiffResult {
+        items: vec![],
+        source_tables: 1,
+        target_tables: 0,
+        warnings: vec![],
+        cross_engine: false,
+        source_schema: None,
+        target_schema: None,
+    };
+    let json = serde_json::to_value(&result).unwrap();
+    assert!(json["source_schema"].is_null());
+
+    let users = create_table("users", vec![create_column("id", "INT", false, true, 1)]);
+    let result = result.with_schemas(vec![users], vec![]);
+    let json = serde_json::to_string(&result).unwrap();
+    let deserialized: DiffResult = serde_json::from_str(&json).unwrap();
+    let source = deserialized.source_schema.unwrap();
+    assert_eq!(source[0].name, "users");
+    assert_eq!(source[0].columns[0].name, "id");
+    assert_eq!(deserialized.target_schema, Some(vec![]));
+}
+
+#[test]
+fn test_database_pair_results_keep_each_pair() {
+    let source = vec![create_table(
+        "users",
+        vec![create_column("id", "INT", false, true, 1)],
+    )];
+    let results: Vec<DatabasePairResult> = [("tenant1", 0), ("tenant2", 1)]
+        .iter()
+        .map(|(db, target_count)| {
+            let target = &source[..*target_count];
+            DatabasePairResult {
+                source_db: format!("{}_source", db),
+                target_db: format!("{}_target", db),
+                result: DiffResult {
+                    items: compare_schemas(&source, target, &MockSqlGen),
+                    source_tables: source.len(),
+                    target_tables: target.len(),
+                    warnings: vec![],
+                    cross_engine: false,
+                    source_schema: None,
+                    target_schema: None,
+                },
+            }
+        })
+        .collect();
+
+    let json = serde_json::to_value(&results).unwrap();
+    assert_eq!(json[0]["source_db"], "tenant1_source");
+    assert_eq!(json[0]["target_db"], "tenant1_target");
+    assert_eq!(json[0]["result"]["items"][0]["diff_type"], "table_added");
+    assert_eq!(json[1]["source_db"], "tenant2_source");
+    assert_eq!(json[1]["result"]["items"], serde_json::json!([]));
+
+    let parsed: Vec<DatabasePairResult> = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.len(), 2);
+}
+
 #[test]
 fn test_diff_result_empty() {
     let result = DiffResult {
         items: vec![],
         source_tables: 0,
         target_tables: 0,
+        warnings: vec![],
+        cross_engine: false,
+        source_schema: None,
+        target_schema: None,
     };
 
     let json = serde_json::to_string(&result).unwrap();
@@ -1481,6 +2750,12 @@ fn test_column_serialize_deserialize() {
         auto_increment: false,
         comment: Some("User email address".to_string()),
         ordinal_position: 3,
+        visible: true,
+        on_update: None,
+        sequence: None,
+        generated: None,
+        srid: None,
+        octet_length: None,
     };
 
     let json = serde_json::to_string(&col).unwrap();
@@ -1504,6 +2779,12 @@ fn test_column_serialize_none_optionals() {
         auto_increment: true,
         comment: None,
         ordinal_position: 1,
+        visible: true,
+        on_update: None,
+        sequence: None,
+        generated: None,
+        srid: None,
+        octet_length: None,
     };
 
     let json = serde_json::to_string(&col).unwrap();
@@ -1546,6 +2827,11 @@ fn test_index_serialize_deserialize() {
         columns: vec!["email".to_string()],
         unique: true,
         index_type: "BTREE".to_string(),
+        tablespace: None,
+        visible: true,
+        expression: None,
+        storage_params: vec![],
+        comment: None,
     };
 
     let json = serde_json::to_string(&idx).unwrap();
@@ -1562,6 +2848,11 @@ fn test_index_multi_column() {
         columns: vec!["last_name".to_string(), "first_name".to_string()],
         unique: false,
         index_type: "HASH".to_string(),
+        tablespace: None,
+        visible: true,
+        expression: None,
+        storage_params: vec![],
+        comment: None,
     };
 
     let json = serde_json::to_string(&idx).unwrap();
@@ -1580,6 +2871,9 @@ fn test_foreign_key_serialize_deserialize() {
         ref_columns: vec!["id".to_string()],
         on_delete: "CASCADE".to_string(),
         on_update: "SET NULL".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
     };
 
     let json = serde_json::to_string(&fk).unwrap();
@@ -1601,6 +2895,9 @@ fn test_foreign_key_composite() {
         ref_columns: vec!["oid".to_string(), "pid".to_string()],
         on_delete: "RESTRICT".to_string(),
         on_update: "NO ACTION".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
     };
 
     let json = serde_json::to_string(&fk).unwrap();
@@ -1647,6 +2944,12 @@ fn test_table_schema_serialize_deserialize() {
                 auto_increment: true,
                 comment: None,
                 ordinal_position: 1,
+                visible: true,
+                on_update: None,
+                sequence: None,
+                generated: None,
+                srid: None,
+                octet_length: None,
             },
             Column {
                 name: "email".to_string(),
@@ -1656,6 +2959,12 @@ fn test_table_schema_serialize_deserialize() {
                 auto_increment: false,
                 comment: Some("email".to_string()),
                 ordinal_position: 2,
+                visible: true,
+                on_update: None,
+                sequence: None,
+                generated: None,
+                srid: None,
+                octet_length: None,
             },
         ],
         primary_key: Some(PrimaryKey {
@@ -1667,12 +2976,17 @@ fn test_table_schema_serialize_deserialize() {
             columns: vec!["email".to_string()],
             unique: true,
             index_type: "BTREE".to_string(),
+            tablespace: None,
+            visible: true,
+            expression: None,
+            storage_params: vec![],
+            comment: None,
         }],
-        foreign_keys: vec![],
         unique_constraints: vec![UniqueConstraint {
             name: "uq_email".to_string(),
             columns: vec!["email".to_string()],
         }],
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&table).unwrap();
@@ -1689,11 +3003,7 @@ fn test_table_schema_serialize_deserialize() {
 fn test_table_schema_minimal() {
     let table = TableSchema {
         name: "empty_table".to_string(),
-        columns: vec![],
-        primary_key: None,
-        indexes: vec![],
-        foreign_keys: vec![],
-        unique_constraints: vec![],
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&table).unwrap();
@@ -1703,6 +3013,62 @@ fn test_table_schema_minimal() {
     assert!(deserialized.primary_key.is_none());
 }
 
+#[test]
+fn test_table_schema_tablespace_round_trip() {
+    let mut table = create_table("events", vec![create_column("id", "INT", false, true, 1)]);
+    table.tablespace = Some("fast_ssd".to_string());
+    let mut idx = create_index("idx_id", vec!["id"], false);
+    idx.tablespace = Some("index_space".to_string());
+    table.indexes.push(idx);
+
+    let json = serde_json::to_string(&table).unwrap();
+    let deserialized: TableSchema = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, table);
+    assert_eq!(deserialized.tablespace, Some("fast_ssd".to_string()));
+
+    // Tables serialized before tablespaces existed still deserialize
+    let legacy = r#"{"name":"t","columns":[],"primary_key":null,"indexes":[],"foreign_keys":[],"unique_constraints":[]}"#;
+    let deserialized: TableSchema = serde_json::from_str(legacy).unwrap();
+    assert!(deserialized.tablespace.is_none());
+}
+
+#[test]
+fn test_detect_tablespace_change() {
+    let mut source_table = create_table("events", vec![create_column("id", "INT", false, true, 1)]);
+    source_table.tablespace = Some("fast_ssd".to_string());
+    let target_table = create_table("events", vec![create_column("id", "INT", false, true, 1)]);
+
+    let diffs = compare_schemas(&[source_table.clone()], &[target_table], &MockSqlGen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::TablespaceModified);
+    assert_eq!(diffs[0].source_def, Some("fast_ssd".to_string()));
+    assert_eq!(diffs[0].target_def, None);
+
+    let diffs = compare_schemas(&[source_table.clone()], &[source_table], &MockSqlGen);
+    assert!(diffs.is_empty());
+}
+
+#[test]
+fn test_visibility_defaults_and_change_detection() {
+    // Schemas serialized before visibility existed are treated as visible
+    let legacy = r#"{"name":"idx","columns":["a"],"unique":false,"index_type":"BTREE"}"#;
+    let idx: Index = serde_json::from_str(legacy).unwrap();
+    assert!(idx.visible);
+
+    let mut hidden = create_column("secret", "INT", true, false, 1);
+    hidden.visible = false;
+    let source = vec![create_table("users", vec![hidden])];
+    let target = vec![create_table(
+        "users",
+        vec![create_column("secret", "INT", true, false, 1)],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &MockSqlGen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Visibility]);
+}
+
 // ============================================================================
 // Schema Model Equality Tests
 // ============================================================================
@@ -1820,6 +3186,9 @@ fn test_detect_modified_foreign_key_on_delete() {
         ref_columns: vec!["id".to_string()],
         on_delete: "SET NULL".to_string(),
         on_update: "CASCADE".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
     }];
 
     let mut target_table = create_table(
@@ -1836,6 +3205,9 @@ fn test_detect_modified_foreign_key_on_delete() {
         ref_columns: vec!["id".to_string()],
         on_delete: "CASCADE".to_string(),
         on_update: "CASCADE".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
     }];
 
     let diffs = compare_schemas(&vec![source_table], &vec![target_table], &MockSqlGen);
@@ -1844,6 +3216,78 @@ fn test_detect_modified_foreign_key_on_delete() {
     assert_eq!(diffs[0].diff_type, DiffType::ForeignKeyModified);
 }
 
+#[test]
+fn test_engine_only_difference_is_one_table_options_diff() {
+    let mut source_table = create_table(
+        "orders",
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("note", "VARCHAR(255)", true, false, 2),
+        ],
+    );
+    source_table.options = TableOptions {
+        engine: Some("InnoDB".to_string()),
+        charset: Some("utf8".to_string()),
+        row_format: Some("DYNAMIC".to_string()),
+    };
+    let mut target_table = source_table.clone();
+    target_table.options.engine = Some("MyISAM".to_string());
+    target_table.options.charset = Some("utf8mb3".to_string());
+    target_table.options.row_format = None;
+
+    let diffs = compare_schemas(&[source_table], &[target_table], &MySqlSqlGenerator);
+
+    assert_eq!(diffs.len(), 1, "unexpected diffs: {:?}", diffs);
+    assert_eq!(diffs[0].diff_type, DiffType::TableOptionsModified);
+    assert_eq!(diffs[0].source_def.as_deref(), Some("ENGINE=InnoDB"));
+    assert_eq!(diffs[0].target_def.as_deref(), Some("ENGINE=MyISAM"));
+    assert_eq!(diffs[0].sql, "ALTER TABLE `orders` ENGINE=InnoDB;");
+}
+
+#[test]
+fn test_charset_upgrade_is_a_table_options_diff() {
+    let mut source_table = create_table("orders", vec![create_column("id", "INT", false, true, 1)]);
+    source_table.options.charset = Some("utf8mb4".to_string());
+    let mut target_table = source_table.clone();
+    target_table.options.charset = Some("utf8mb3".to_string());
+
+    let source = vec![source_table];
+    let target = vec![target_table];
+    assert!(!schemas_in_sync(&source, &target));
+    let diffs = compare_schemas(&source, &target, &MySqlSqlGenerator);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE `orders` DEFAULT CHARSET=utf8mb4;"
+    );
+}
+
+#[test]
+fn test_detect_modified_foreign_key_deferrability() {
+    let columns = || {
+        vec![
+            create_column("id", "INT", false, true, 1),
+            create_column("user_id", "INT", false, false, 2),
+        ]
+    };
+    let mut source_table = create_table("orders", columns());
+    let mut fk = create_foreign_key("fk_user", vec!["user_id"], "users", vec!["id"]);
+    fk.deferrable = true;
+    source_table.foreign_keys = vec![fk];
+    let mut target_table = create_table("orders", columns());
+    target_table.foreign_keys = vec![create_foreign_key(
+        "fk_user",
+        vec!["user_id"],
+        "users",
+        vec!["id"],
+    )];
+
+    let diffs = compare_schemas(&[source_table], &[target_table], &MockSqlGen);
+
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ForeignKeyModified);
+}
+
 #[test]
 fn test_detect_modified_unique_constraint() {
     let mut source_table = create_table(
@@ -1997,6 +3441,12 @@ fn test_modified_column_comment_change() {
             auto_increment: false,
             comment: Some("full name".to_string()),
             ordinal_position: 1,
+            visible: true,
+            on_update: None,
+            sequence: None,
+            generated: None,
+            srid: None,
+            octet_length: None,
         }],
     )];
 
@@ -2010,6 +3460,12 @@ fn test_modified_column_comment_change() {
             auto_increment: false,
             comment: None,
             ordinal_position: 1,
+            visible: true,
+            on_update: None,
+            sequence: None,
+            generated: None,
+            srid: None,
+            octet_length: None,
         }],
     )];
 
@@ -2089,3 +3545,205 @@ fn test_column_with_default_value_serialize() {
     assert_eq!(deserialized.default_value, Some("0".to_string()));
     assert!(!deserialized.auto_increment);
 }
+
+// ============================================================================
+// Schema Assembly Tests
+// ============================================================================
+
+fn scrambled_rows() -> (
+    Vec<db::TableRow>,
+    Vec<db::ColumnRow>,
+    Vec<db::IndexRow>,
+    Vec<db::FkRow>,
+) {
+    let tables = ["orders", "users", "audit"]
+        .iter()
+        .map(|t| db::TableRow {
+            table_name: t.to_string(),
+            tablespace: None,
+            options: TableOptions::default(),
+        })
+        .collect();
+    let columns = [
+        ("orders", "id", 1),
+        ("orders", "user_id", 2),
+        ("users", "id", 1),
+    ]
+    .iter()
+    .map(|(t, c, pos)| db::ColumnRow {
+        table_name: t.to_string(),
+        name: c.to_string(),
+        data_type: "int".to_string(),
+        nullable: false,
+        default_value: None,
+        auto_increment: false,
+        comment: None,
+        ordinal_position: *pos,
+        visible: true,
+        on_update: None,
+        sequence: None,
+        generated: None,
+        srid: None,
+        octet_length: None,
+    })
+    .collect();
+    let indexes = [
+        ("orders", "idx_z", "user_id", 1),
+        ("orders", "idx_a", "user_id", 1),
+        ("orders", "idx_a", "id", 2),
+        ("orders", "idx_m", "id", 1),
+    ]
+    .iter()
+    .map(|(t, i, c, seq)| db::IndexRow {
+        table_name: t.to_string(),
+        index_name: i.to_string(),
+        column_name: c.to_string(),
+        is_unique: false,
+        index_type: "BTREE".to_string(),
+        tablespace: None,
+        visible: true,
+        seq_in_index: *seq,
+        expression: None,
+        storage_params: vec![],
+        comment: None,
+    })
+    .collect();
+    let fks = [
+        ("fk_z", "user_id", "id"),
+        ("fk_b", "user_id", "id"),
+        ("fk_b", "id", "tenant_id"),
+    ]
+    .iter()
+    .map(|(name, col, ref_col)| db::FkRow {
+        table_name: "orders".to_string(),
+        constraint_name: name.to_string(),
+        column_name: col.to_string(),
+        ref_table: "users".to_string(),
+        ref_column: ref_col.to_string(),
+        on_delete: "CASCADE".to_string(),
+        on_update: "NO ACTION".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
+    })
+    .collect();
+    (tables, columns, indexes, fks)
+}
+
+fn assemble_scrambled() -> Vec<TableSchema> {
+    let (tables, columns, indexes, fks) = scrambled_rows();
+    db::assemble_schemas(tables, columns, vec![], indexes, fks, vec![], vec![])
+}
+
+#[test]
+fn test_assembled_schema_order_is_deterministic() {
+    let first = assemble_scrambled();
+    for _ in 0..10 {
+        assert_eq!(assemble_scrambled(), first);
+    }
+
+    let names: Vec<&str> = first.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["audit", "orders", "users"]);
+
+    let orders = &first[1];
+    let index_names: Vec<&str> = orders.indexes.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(index_names, vec!["idx_a", "idx_m", "idx_z"]);
+    assert_eq!(orders.indexes[0].columns, vec!["user_id", "id"]);
+
+    let fk_names: Vec<&str> = orders
+        .foreign_keys
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect();
+    assert_eq!(fk_names, vec!["fk_b", "fk_z"]);
+    assert_eq!(orders.foreign_keys[0].columns, vec!["user_id", "id"]);
+    assert_eq!(orders.foreign_keys[0].ref_columns, vec!["id", "tenant_id"]);
+}
+
+#[test]
+fn test_composite_index_keeps_key_order() {
+    // Rows arrive out of key order, as they could from an unordered query.
+    let indexes = [("c", 3), ("a", 1), ("b", 2)]
+        .iter()
+        .map(|(col, seq)| db::IndexRow {
+            table_name: "events".to_string(),
+            index_name: "idx_tenant_kind_at".to_string(),
+            column_name: col.to_string(),
+            is_unique: false,
+            index_type: "BTREE".to_string(),
+            tablespace: None,
+            visible: true,
+            seq_in_index: *seq,
+            expression: None,
+            storage_params: vec![],
+            comment: None,
+        })
+        .collect();
+    let tables = vec![db::TableRow {
+        table_name: "events".to_string(),
+        tablespace: None,
+        options: TableOptions::default(),
+    }];
+
+    let schemas = db::assemble_schemas(tables, vec![], vec![], indexes, vec![], vec![], vec![]);
+    assert_eq!(schemas[0].indexes.len(), 1);
+    assert_eq!(schemas[0].indexes[0].columns, vec!["a", "b", "c"]);
+}
+
+// ============================================================================
+// Snapshot Comparison Tests
+// ============================================================================
+
+/// A one-table snapshot as the UI would serialize it.
+fn users_snapshot(email_type: &str) -> Vec<TableSchema> {
+    let json = format!(
+        r#"[{{
+            "name": "users",
+            "columns": [
+                {{"name": "id", "data_type": "int", "nullable": false, "default_value": null,
+                  "auto_increment": true, "comment": null, "ordinal_position": 1}},
+                {{"name": "email", "data_type": "{}", "nullable": false, "default_value": null,
+                  "auto_increment": false, "comment": null, "ordinal_position": 2}}
+            ],
+            "primary_key": {{"name": null, "columns": ["id"]}},
+            "indexes": [],
+            "foreign_keys": [],
+            "unique_constraints": []
+        }}]"#,
+        email_type
+    );
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn test_compare_snapshots_mysql() {
+    let diffs = compare_snapshots(
+        &users_snapshot("varchar(255)"),
+        &users_snapshot("varchar(100)"),
+        DbType::MySQL,
+    );
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE `users` MODIFY COLUMN `email` varchar(255) NOT NULL;"
+    );
+}
+
+#[test]
+fn test_compare_snapshots_postgres() {
+    let diffs = compare_snapshots(
+        &users_snapshot("varchar(255)"),
+        &users_snapshot("varchar(100)"),
+        DbType::PostgreSQL,
+    );
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert!(
+        diffs[0]
+            .sql
+            .starts_with("ALTER TABLE \"users\" ALTER COLUMN \"email\" TYPE varchar(255)"),
+        "unexpected SQL: {}",
+        diffs[0].sql
+    );
+}
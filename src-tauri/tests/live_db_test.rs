@@ -0,0 +1,280 @@
+//! Tests against a real database server. They're ignored by default; point
+//! `MYSQL_TEST_URL` / `POSTGRES_TEST_URL` at a scratch database and run
+//! `cargo test -- --ignored` to include them.
+
+use database_structure_sync_lib::db::{
+    MySqlDriver, PostgresDriver, SchemaReader, SqlExecutor, ping, session_set_statements,
+    statement_timeout_params,
+};
+use database_structure_sync_lib::models::DbType;
+use sqlx::mysql::MySqlPoolOptions;
+use sqlx::postgres::PgPoolOptions;
+
+fn test_url(var: &str) -> String {
+    std::env::var(var).unwrap_or_else(|_| panic!("{} must be set for live tests", var))
+}
+
+#[tokio::test]
+#[ignore = "needs MYSQL_TEST_URL"]
+async fn mysql_native_create_table_lists_columns() {
+    let pool = MySqlPoolOptions::new()
+        .connect(&test_url("MYSQL_TEST_URL"))
+        .await
+        .unwrap();
+    sqlx::raw_sql("DROP TABLE IF EXISTS native_ddl_probe; CREATE TABLE native_ddl_probe (id INT PRIMARY KEY, email VARCHAR(255) NOT NULL)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let ddl = MySqlDriver::from_pool(pool.clone())
+        .native_create_table("native_ddl_probe")
+        .await
+        .unwrap();
+    assert!(
+        ddl.starts_with("CREATE TABLE `native_ddl_probe`"),
+        "{}",
+        ddl
+    );
+    assert!(ddl.contains("`email` varchar(255) NOT NULL"), "{}", ddl);
+
+    sqlx::raw_sql("DROP TABLE native_ddl_probe")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs POSTGRES_TEST_URL"]
+async fn postgres_native_create_table_lists_columns() {
+    let pool = PgPoolOptions::new()
+        .connect(&test_url("POSTGRES_TEST_URL"))
+        .await
+        .unwrap();
+    sqlx::raw_sql("DROP TABLE IF EXISTS native_ddl_probe; CREATE TABLE native_ddl_probe (id integer PRIMARY KEY, email varchar(255) NOT NULL)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let ddl = PostgresDriver::from_pool(pool.clone())
+        .native_create_table("native_ddl_probe")
+        .await
+        .unwrap();
+    assert!(
+        ddl.starts_with("CREATE TABLE \"native_ddl_probe\""),
+        "{}",
+        ddl
+    );
+    assert!(
+        ddl.contains("\"email\" character varying(255) NOT NULL"),
+        "{}",
+        ddl
+    );
+    assert!(ddl.contains("PRIMARY KEY (id)"), "{}", ddl);
+
+    sqlx::raw_sql("DROP TABLE native_ddl_probe")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs POSTGRES_TEST_URL"]
+async fn postgres_ping_reports_latency_and_version() {
+    let pool = PgPoolOptions::new()
+        .connect(&test_url("POSTGRES_TEST_URL"))
+        .await
+        .unwrap();
+
+    let result = ping(&PostgresDriver::from_pool(pool)).await;
+    assert!(result.ok);
+    assert!(result.latency_ms < 10_000);
+    assert!(result.server_version.is_some());
+}
+
+#[tokio::test]
+#[ignore = "needs MYSQL_TEST_URL"]
+async fn mysql_list_tables_returns_names_only() {
+    let pool = MySqlPoolOptions::new()
+        .connect(&test_url("MYSQL_TEST_URL"))
+        .await
+        .unwrap();
+    sqlx::raw_sql("DROP TABLE IF EXISTS list_probe_a, list_probe_b; CREATE TABLE list_probe_b (id INT); CREATE TABLE list_probe_a (id INT, name VARCHAR(50))")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let names = MySqlDriver::from_pool(pool.clone())
+        .list_tables()
+        .await
+        .unwrap();
+    let probes: Vec<&str> = names
+        .iter()
+        .map(String::as_str)
+        .filter(|n| n.starts_with("list_probe_"))
+        .collect();
+    assert_eq!(probes, vec!["list_probe_a", "list_probe_b"]);
+
+    sqlx::raw_sql("DROP TABLE list_probe_a, list_probe_b")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs MYSQL_TEST_URL"]
+async fn mysql_use_database_switches_the_single_connection() {
+    let pool = MySqlPoolOptions::new()
+        .connect(&test_url("MYSQL_TEST_URL"))
+        .await
+        .unwrap();
+    let session = MySqlDriver::from_pool(pool)
+        .single_connection()
+        .await
+        .unwrap();
+
+    session.use_database("information_schema").await.unwrap();
+    let (current,): (String,) = sqlx::query_as("SELECT CAST(DATABASE() AS CHAR)")
+        .fetch_one(session.pool())
+        .await
+        .unwrap();
+    assert_eq!(current, "information_schema");
+    assert_eq!(session.pool().options().get_max_connections(), 1);
+}
+
+#[tokio::test]
+#[ignore = "needs POSTGRES_TEST_URL"]
+async fn postgres_list_tables_returns_names_only() {
+    let pool = PgPoolOptions::new()
+        .connect(&test_url("POSTGRES_TEST_URL"))
+        .await
+        .unwrap();
+    sqlx::raw_sql("DROP TABLE IF EXISTS list_probe_a, list_probe_b; CREATE TABLE list_probe_b (id integer); CREATE TABLE list_probe_a (id integer, name varchar(50))")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let names = PostgresDriver::from_pool(pool.clone())
+        .list_tables()
+        .await
+        .unwrap();
+    let probes: Vec<&str> = names
+        .iter()
+        .map(String::as_str)
+        .filter(|n| n.starts_with("list_probe_"))
+        .collect();
+    assert_eq!(probes, vec!["list_probe_a", "list_probe_b"]);
+
+    sqlx::raw_sql("DROP TABLE list_probe_a, list_probe_b")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs POSTGRES_TEST_URL"]
+async fn postgres_reads_array_and_jsonb_types() {
+    let pool = PgPoolOptions::new()
+        .connect(&test_url("POSTGRES_TEST_URL"))
+        .await
+        .unwrap();
+    sqlx::raw_sql("DROP TABLE IF EXISTS type_probe; CREATE TABLE type_probe (ids integer[] NOT NULL DEFAULT '{}'::integer[], tags varchar(20)[], doc jsonb DEFAULT '{}'::jsonb)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let tables = PostgresDriver::from_pool(pool.clone())
+        .get_tables_named(&["type_probe".to_string()])
+        .await
+        .unwrap();
+    let columns: Vec<(&str, &str, Option<&str>)> = tables[0]
+        .columns
+        .iter()
+        .map(|c| {
+            (
+                c.name.as_str(),
+                c.data_type.as_str(),
+                c.default_value.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        columns,
+        vec![
+            ("ids", "integer[]", Some("'{}'::integer[]")),
+            ("tags", "varchar(20)[]", None),
+            ("doc", "jsonb", Some("'{}'::jsonb")),
+        ]
+    );
+
+    sqlx::raw_sql("DROP TABLE type_probe")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs POSTGRES_TEST_URL"]
+async fn postgres_statement_timeout_aborts_slow_statement() {
+    let statements =
+        session_set_statements(&statement_timeout_params(&DbType::PostgreSQL, 1)).unwrap();
+    let pool = PgPoolOptions::new()
+        .after_connect(move |conn, _meta| {
+            let statements = statements.clone();
+            Box::pin(async move {
+                for stmt in &statements {
+                    sqlx::query(stmt).execute(&mut *conn).await?;
+                }
+                Ok(())
+            })
+        })
+        .connect(&test_url("POSTGRES_TEST_URL"))
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let err = PostgresDriver::from_pool(pool)
+        .execute_sql("SELECT pg_sleep(10)")
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Database error: statement timed out");
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test]
+#[ignore = "needs POSTGRES_TEST_URL"]
+async fn postgres_unique_constraint_is_not_also_read_as_index() {
+    let pool = PgPoolOptions::new()
+        .connect(&test_url("POSTGRES_TEST_URL"))
+        .await
+        .unwrap();
+    sqlx::raw_sql(
+        "DROP TABLE IF EXISTS unique_probe; \
+         CREATE TABLE unique_probe (id integer, email text, code text, CONSTRAINT unique_probe_email_key UNIQUE (email)); \
+         CREATE UNIQUE INDEX unique_probe_code_idx ON unique_probe (code)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let tables = PostgresDriver::from_pool(pool.clone())
+        .get_tables_named(&["unique_probe".to_string()])
+        .await
+        .unwrap();
+    let constraints: Vec<&str> = tables[0]
+        .unique_constraints
+        .iter()
+        .map(|uc| uc.name.as_str())
+        .collect();
+    let indexes: Vec<(&str, bool)> = tables[0]
+        .indexes
+        .iter()
+        .map(|idx| (idx.name.as_str(), idx.unique))
+        .collect();
+    assert_eq!(constraints, vec!["unique_probe_email_key"]);
+    assert_eq!(indexes, vec![("unique_probe_code_idx", true)]);
+
+    sqlx::raw_sql("DROP TABLE unique_probe")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
@@ -1,4 +1,7 @@
-use database_structure_sync_lib::db::{MySqlSqlGenerator, PostgresSqlGenerator, SqlGenerator};
+use database_structure_sync_lib::db::{
+    MySqlSqlGenerator, PostgresSqlGenerator, SqlGenerator, runs_outside_transaction,
+};
+use database_structure_sync_lib::diff::{compare_schemas, compare_schemas_with_options};
 use database_structure_sync_lib::models::*;
 
 // ============================================================================
@@ -14,6 +17,12 @@ fn col(name: &str, data_type: &str, nullable: bool, auto_increment: bool, pos: u
         auto_increment,
         comment: None,
         ordinal_position: pos,
+        visible: true,
+        on_update: None,
+        sequence: None,
+        generated: None,
+        srid: None,
+        octet_length: None,
     }
 }
 
@@ -34,6 +43,12 @@ fn col_full(
         auto_increment,
         comment: comment.map(|s| s.to_string()),
         ordinal_position: pos,
+        visible: true,
+        on_update: None,
+        sequence: None,
+        generated: None,
+        srid: None,
+        octet_length: None,
     }
 }
 
@@ -41,10 +56,7 @@ fn make_table(name: &str, columns: Vec<Column>) -> TableSchema {
     TableSchema {
         name: name.to_string(),
         columns,
-        primary_key: None,
-        indexes: vec![],
-        foreign_keys: vec![],
-        unique_constraints: vec![],
+        ..Default::default()
     }
 }
 
@@ -54,6 +66,11 @@ fn make_index(name: &str, columns: Vec<&str>, unique: bool) -> Index {
         columns: columns.iter().map(|s| s.to_string()).collect(),
         unique,
         index_type: "BTREE".to_string(),
+        tablespace: None,
+        visible: true,
+        expression: None,
+        storage_params: vec![],
+        comment: None,
     }
 }
 
@@ -65,6 +82,9 @@ fn make_fk(name: &str, columns: Vec<&str>, ref_table: &str, ref_columns: Vec<&st
         ref_columns: ref_columns.iter().map(|s| s.to_string()).collect(),
         on_delete: "CASCADE".to_string(),
         on_update: "SET NULL".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
     }
 }
 
@@ -103,6 +123,30 @@ fn mysql_create_table_minimal() {
     assert_eq!(sql, "CREATE TABLE `t` (\n  `id` INT NOT NULL\n);");
 }
 
+#[test]
+fn table_without_columns_is_skipped_not_broken() {
+    let table = make_table("empty", vec![]);
+    assert_eq!(
+        PostgresSqlGenerator::default().generate_create_table(&table),
+        "CREATE TABLE \"empty\" ();"
+    );
+    let mysql = MySqlSqlGenerator.generate_create_table(&table);
+    assert!(mysql.starts_with("-- "), "{}", mysql);
+    assert!(!mysql.contains("CREATE TABLE"), "{}", mysql);
+
+    for sqlgen in [
+        &MySqlSqlGenerator as &dyn SqlGenerator,
+        &PostgresSqlGenerator::default(),
+    ] {
+        let diffs = compare_schemas(std::slice::from_ref(&table), &[], sqlgen);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].diff_type, DiffType::TableAdded);
+        assert!(diffs[0].sql.is_empty());
+        assert!(!diffs[0].selected);
+        assert_eq!(diffs[0].warnings[0].severity, WarningSeverity::Skipped);
+    }
+}
+
 #[test]
 fn mysql_create_table_with_pk() {
     let sqlgen = MySqlSqlGenerator;
@@ -226,6 +270,54 @@ fn mysql_add_column_default() {
     assert!(sql.contains("DEFAULT 0"));
 }
 
+#[test]
+fn mysql_add_column_expression_default() {
+    let sqlgen = MySqlSqlGenerator;
+    let c = col_full("token", "char(36)", false, Some("(uuid())"), false, None, 2);
+    assert_eq!(
+        sqlgen.generate_add_column("users", &c),
+        "ALTER TABLE `users` ADD COLUMN `token` char(36) NOT NULL DEFAULT (uuid());"
+    );
+}
+
+#[test]
+fn mysql_literal_to_expression_default_is_modified() {
+    let sqlgen = MySqlSqlGenerator;
+    let source = make_table(
+        "users",
+        vec![col_full(
+            "token",
+            "char(36)",
+            false,
+            Some("(uuid())"),
+            false,
+            None,
+            1,
+        )],
+    );
+    let target = make_table(
+        "users",
+        vec![col_full(
+            "token",
+            "char(36)",
+            false,
+            Some("uuid()"),
+            false,
+            None,
+            1,
+        )],
+    );
+
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Default]);
+    assert!(
+        diffs[0].sql.contains("DEFAULT (uuid())"),
+        "{}",
+        diffs[0].sql
+    );
+}
+
 #[test]
 fn mysql_add_column_auto_increment() {
     let sqlgen = MySqlSqlGenerator;
@@ -309,6 +401,76 @@ fn mysql_modify_column_all_options() {
     assert!(sql.contains("COMMENT 'PK'"));
 }
 
+// ============================================================================
+// MySQL: ENUM / SET value lists
+// ============================================================================
+
+#[test]
+fn mysql_enum_value_added_modifies_with_full_list() {
+    let sqlgen = MySqlSqlGenerator;
+    let source = vec![make_table(
+        "orders",
+        vec![col(
+            "status",
+            "enum('pending','paid','shipped','refunded')",
+            false,
+            false,
+            1,
+        )],
+    )];
+    let target = vec![make_table(
+        "orders",
+        vec![col(
+            "status",
+            "enum('pending','paid','shipped')",
+            false,
+            false,
+            1,
+        )],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE `orders` MODIFY COLUMN `status` enum('pending','paid','shipped','refunded') NOT NULL;"
+    );
+}
+
+#[test]
+fn mysql_set_value_added_modifies_with_full_list() {
+    let sqlgen = MySqlSqlGenerator;
+    let flags: Vec<String> = (0..40).map(|i| format!("'flag_{}'", i)).collect();
+    let long_set = format!("set({})", flags.join(","));
+    let short_set = format!("set({})", flags[..39].join(","));
+    let source = vec![make_table(
+        "features",
+        vec![col("flags", &long_set, true, false, 1)],
+    )];
+    let target = vec![make_table(
+        "features",
+        vec![col("flags", &short_set, true, false, 1)],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert!(
+        diffs[0].sql.contains(&long_set),
+        "MODIFY must carry every set member, got: {}",
+        diffs[0].sql
+    );
+}
+
+#[test]
+fn mysql_enum_values_with_quotes_are_preserved() {
+    let sqlgen = MySqlSqlGenerator;
+    let c = col("size", "enum('it''s','x,y','z')", true, false, 1);
+    let sql = sqlgen.generate_modify_column("items", &c);
+    assert!(sql.contains("enum('it''s','x,y','z')"), "got: {}", sql);
+}
+
 // ============================================================================
 // MySQL: generate_add_index
 // ============================================================================
@@ -426,13 +588,13 @@ fn mysql_drop_unique() {
 
 #[test]
 fn pg_quote_identifier_plain() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     assert_eq!(sqlgen.quote_identifier("users"), "\"users\"");
 }
 
 #[test]
 fn pg_quote_identifier_with_double_quote() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     assert_eq!(sqlgen.quote_identifier("user\"name"), "\"user\"\"name\"");
 }
 
@@ -442,7 +604,7 @@ fn pg_quote_identifier_with_double_quote() {
 
 #[test]
 fn pg_add_column_auto_increment_becomes_serial() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("id", "INT", false, true, 1);
     let sql = sqlgen.generate_add_column("users", &c);
     assert!(sql.contains("SERIAL"));
@@ -451,7 +613,7 @@ fn pg_add_column_auto_increment_becomes_serial() {
 
 #[test]
 fn pg_add_column_serial_skips_not_null() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("id", "INT", false, true, 1);
     let sql = sqlgen.generate_add_column("users", &c);
     assert!(!sql.contains("NOT NULL"));
@@ -459,7 +621,7 @@ fn pg_add_column_serial_skips_not_null() {
 
 #[test]
 fn pg_add_column_basic() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("email", "VARCHAR(255)", false, false, 2);
     let sql = sqlgen.generate_add_column("users", &c);
     assert_eq!(
@@ -472,9 +634,45 @@ fn pg_add_column_basic() {
 // PostgreSQL: generate_modify_column (ALTER COLUMN TYPE syntax)
 // ============================================================================
 
+#[test]
+fn pg_add_column_skips_explicit_null_default() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let c = col_full(
+        "nickname",
+        "varchar(50)",
+        true,
+        Some("NULL::character varying"),
+        false,
+        None,
+        2,
+    );
+    let sql = sqlgen.generate_add_column("users", &c);
+    assert_eq!(
+        sql,
+        "ALTER TABLE \"users\" ADD COLUMN \"nickname\" varchar(50);"
+    );
+}
+
+#[test]
+fn pg_modify_column_explicit_null_default_drops_default() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let c = col_full(
+        "nickname",
+        "varchar(50)",
+        true,
+        Some("NULL"),
+        false,
+        None,
+        2,
+    );
+    let sql = sqlgen.generate_modify_column("users", &c);
+    assert!(sql.contains("DROP DEFAULT"), "got: {}", sql);
+    assert!(!sql.contains("SET DEFAULT NULL"), "got: {}", sql);
+}
+
 #[test]
 fn pg_modify_column_type_syntax() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("name", "VARCHAR(500)", true, false, 2);
     let sql = sqlgen.generate_modify_column("users", &c);
     assert!(sql.contains("ALTER TABLE \"users\" ALTER COLUMN \"name\" TYPE VARCHAR(500);"));
@@ -485,7 +683,7 @@ fn pg_modify_column_type_syntax() {
 
 #[test]
 fn pg_modify_column_no_serial_for_auto_increment() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("id", "INT", false, true, 1);
     let sql = sqlgen.generate_modify_column("users", &c);
     // Should NOT use SERIAL pseudo-type for ALTER COLUMN
@@ -495,16 +693,128 @@ fn pg_modify_column_no_serial_for_auto_increment() {
 
 #[test]
 fn pg_modify_column_emits_not_null() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("email", "VARCHAR(255)", false, false, 2);
     let sql = sqlgen.generate_modify_column("users", &c);
     assert!(sql.contains("SET NOT NULL"));
     assert!(!sql.contains("DROP NOT NULL"));
 }
 
+#[test]
+fn pg_nullable_to_not_null_diff_sets_not_null() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let source = vec![make_table(
+        "users",
+        vec![col_full(
+            "email",
+            "VARCHAR(255)",
+            false,
+            Some("''"),
+            false,
+            None,
+            1,
+        )],
+    )];
+    let target = vec![make_table(
+        "users",
+        vec![col("email", "VARCHAR(255)", true, false, 1)],
+    )];
+
+    let diffs = compare_schemas(&source, &target, &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    let stmts: Vec<&str> = diffs[0].sql.lines().collect();
+    assert!(stmts.contains(&"ALTER TABLE \"users\" ALTER COLUMN \"email\" SET NOT NULL;"));
+    assert!(stmts.contains(&"ALTER TABLE \"users\" ALTER COLUMN \"email\" SET DEFAULT '';"));
+}
+
+#[test]
+fn pg_alter_column_type_nullability_and_default_together() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let source = col_full(
+        "status",
+        "VARCHAR(20)",
+        false,
+        Some("'active'"),
+        false,
+        None,
+        1,
+    );
+    let target = col_full("status", "VARCHAR(10)", true, Some("'new'"), false, None, 1);
+
+    assert_eq!(
+        sqlgen.generate_alter_column("users", &source, &target),
+        "ALTER TABLE \"users\" ALTER COLUMN \"status\" TYPE VARCHAR(20);\n\
+         ALTER TABLE \"users\" ALTER COLUMN \"status\" SET NOT NULL;\n\
+         ALTER TABLE \"users\" ALTER COLUMN \"status\" SET DEFAULT 'active';"
+    );
+}
+
+#[test]
+fn pg_alter_column_emits_only_changed_aspects() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let source = col_full("note", "TEXT", true, None, false, Some("Free text"), 1);
+    let mut target = source.clone();
+    target.nullable = false;
+    target.default_value = Some("''".to_string());
+    target.comment = None;
+
+    assert_eq!(
+        sqlgen.generate_alter_column("users", &source, &target),
+        "ALTER TABLE \"users\" ALTER COLUMN \"note\" DROP NOT NULL;\n\
+         ALTER TABLE \"users\" ALTER COLUMN \"note\" DROP DEFAULT;\n\
+         COMMENT ON COLUMN \"users\".\"note\" IS 'Free text';"
+    );
+}
+
+#[test]
+fn pg_alter_column_dropping_serial_drops_default() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let source = col("id", "integer", false, false, 1);
+    let target = col("id", "integer", false, true, 1);
+    assert_eq!(
+        sqlgen.generate_alter_column("users", &source, &target),
+        "ALTER TABLE \"users\" ALTER COLUMN \"id\" DROP DEFAULT;"
+    );
+}
+
+#[test]
+fn pg_serial_columns_on_different_sequences_are_reported() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source_id = col("id", "integer", false, true, 1);
+    source_id.sequence = Some("users_id_seq".to_string());
+    let mut target_id = source_id.clone();
+    target_id.sequence = Some("legacy_users_seq".to_string());
+
+    let diffs = compare_schemas(
+        &[make_table("users", vec![source_id])],
+        &[make_table("users", vec![target_id])],
+        &sqlgen,
+    );
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Sequence]);
+    assert_eq!(
+        diffs[0].sql,
+        "CREATE SEQUENCE IF NOT EXISTS \"users_id_seq\";\n\
+         ALTER TABLE \"users\" ALTER COLUMN \"id\" SET DEFAULT nextval('users_id_seq');\n\
+         ALTER SEQUENCE \"users_id_seq\" OWNED BY \"users\".\"id\";"
+    );
+}
+
+#[test]
+fn mysql_alter_column_restates_definition() {
+    let sqlgen = MySqlSqlGenerator;
+    let source = col("email", "VARCHAR(255)", false, false, 1);
+    let target = col("email", "VARCHAR(100)", true, false, 1);
+    assert_eq!(
+        sqlgen.generate_alter_column("users", &source, &target),
+        sqlgen.generate_modify_column("users", &source)
+    );
+}
+
 #[test]
 fn pg_modify_column_emits_drop_not_null() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("email", "VARCHAR(255)", true, false, 2);
     let sql = sqlgen.generate_modify_column("users", &c);
     assert!(sql.contains("DROP NOT NULL"));
@@ -513,7 +823,7 @@ fn pg_modify_column_emits_drop_not_null() {
 
 #[test]
 fn pg_modify_column_emits_set_default() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col_full("status", "INT", false, Some("0"), false, None, 2);
     let sql = sqlgen.generate_modify_column("users", &c);
     assert!(sql.contains("SET DEFAULT 0"));
@@ -522,7 +832,7 @@ fn pg_modify_column_emits_set_default() {
 
 #[test]
 fn pg_modify_column_emits_drop_default() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("status", "INT", false, false, 2);
     let sql = sqlgen.generate_modify_column("users", &c);
     assert!(sql.contains("DROP DEFAULT"));
@@ -535,7 +845,7 @@ fn pg_modify_column_emits_drop_default() {
 
 #[test]
 fn pg_create_table_serial_column() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let table = make_table("users", vec![col("id", "INT", false, true, 1)]);
     let sql = sqlgen.generate_create_table(&table);
     assert!(sql.contains("SERIAL"));
@@ -544,7 +854,7 @@ fn pg_create_table_serial_column() {
 
 #[test]
 fn pg_create_table_indexes_outside() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let mut table = make_table("users", vec![col("email", "VARCHAR(255)", false, false, 1)]);
     table.indexes = vec![make_index("idx_email", vec!["email"], false)];
     let sql = sqlgen.generate_create_table(&table);
@@ -559,25 +869,102 @@ fn pg_create_table_indexes_outside() {
     assert!(after_create.contains("CREATE INDEX \"idx_email\" ON \"users\" (\"email\");"));
 }
 
+#[test]
+fn pg_expression_index_round_trip() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let definition = "CREATE UNIQUE INDEX idx_email ON users USING btree (lower((email)::text))";
+    let mut lower_email = make_index("idx_email", vec![], true);
+    lower_email.expression = Some(definition.to_string());
+
+    let mut source = make_table("users", vec![col("email", "varchar(255)", false, false, 1)]);
+    source.indexes = vec![lower_email];
+    assert_eq!(
+        sqlgen.generate_add_index("users", &source.indexes[0]),
+        format!("{};", definition)
+    );
+    assert!(
+        sqlgen
+            .generate_create_table(&source)
+            .ends_with(&format!("{};", definition))
+    );
+
+    let same = compare_schemas(
+        std::slice::from_ref(&source),
+        std::slice::from_ref(&source),
+        &sqlgen,
+    );
+    assert!(same.is_empty());
+
+    // A plain column index of the same name is a different index.
+    let mut target = source.clone();
+    target.indexes = vec![make_index("idx_email", vec!["email"], true)];
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::IndexModified);
+    assert_eq!(
+        diffs[0].sql,
+        format!("DROP INDEX \"idx_email\";\n{};", definition)
+    );
+}
+
 // ============================================================================
 // PostgreSQL: generate_drop_index (no ON table)
 // ============================================================================
 
 #[test]
 fn pg_drop_index_no_on_table() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let sql = sqlgen.generate_drop_index("users", "idx_email");
     assert_eq!(sql, "DROP INDEX \"idx_email\";");
     assert!(!sql.contains("ON"));
 }
 
+#[test]
+fn pg_concurrent_index_statements_run_outside_transaction() {
+    let sqlgen = PostgresSqlGenerator {
+        concurrent_indexes: true,
+    };
+    let create = sqlgen.generate_add_index("users", &make_index("idx_email", vec!["email"], true));
+    assert_eq!(
+        create,
+        "CREATE UNIQUE INDEX CONCURRENTLY \"idx_email\" ON \"users\" (\"email\");"
+    );
+    let drop = sqlgen.generate_drop_index("users", "idx_email");
+    assert_eq!(drop, "DROP INDEX CONCURRENTLY \"idx_email\";");
+    assert!(runs_outside_transaction(&create));
+    assert!(runs_outside_transaction(&drop));
+
+    let plain = PostgresSqlGenerator::default();
+    assert!(!runs_outside_transaction(
+        &plain.generate_drop_index("users", "idx_email")
+    ));
+}
+
+#[test]
+fn pg_add_fk_keeps_match_type_and_deferrability() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut fk = make_fk("fk_user", vec!["user_id"], "users", vec!["id"]);
+    fk.deferrable = true;
+    fk.initially_deferred = true;
+    fk.match_type = Some("FULL".to_string());
+    let sql = sqlgen.generate_add_foreign_key("orders", &fk);
+    assert_eq!(
+        sql,
+        "ALTER TABLE \"orders\" ADD CONSTRAINT \"fk_user\" FOREIGN KEY (\"user_id\") REFERENCES \"users\" (\"id\") MATCH FULL ON DELETE CASCADE ON UPDATE SET NULL DEFERRABLE INITIALLY DEFERRED;"
+    );
+
+    let mysql = MySqlSqlGenerator.generate_add_foreign_key("orders", &fk);
+    assert!(!mysql.contains("DEFERRABLE"));
+    assert!(!mysql.contains("MATCH"));
+}
+
 // ============================================================================
 // PostgreSQL: generate_drop_foreign_key (DROP CONSTRAINT syntax)
 // ============================================================================
 
 #[test]
 fn pg_drop_fk_constraint_syntax() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let sql = sqlgen.generate_drop_foreign_key("orders", "fk_user");
     assert_eq!(sql, "ALTER TABLE \"orders\" DROP CONSTRAINT \"fk_user\";");
     assert!(!sql.contains("FOREIGN KEY"));
@@ -589,7 +976,7 @@ fn pg_drop_fk_constraint_syntax() {
 
 #[test]
 fn pg_drop_unique_constraint_syntax() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let sql = sqlgen.generate_drop_unique("users", "uq_email");
     assert_eq!(sql, "ALTER TABLE \"users\" DROP CONSTRAINT \"uq_email\";");
     assert!(!sql.contains("DROP INDEX"));
@@ -602,7 +989,7 @@ fn pg_drop_unique_constraint_syntax() {
 #[test]
 fn cross_gen_different_quote_styles() {
     let mysql = MySqlSqlGenerator;
-    let pg = PostgresSqlGenerator;
+    let pg = PostgresSqlGenerator::default();
     assert_eq!(mysql.quote_identifier("users"), "`users`");
     assert_eq!(pg.quote_identifier("users"), "\"users\"");
 }
@@ -610,7 +997,7 @@ fn cross_gen_different_quote_styles() {
 #[test]
 fn cross_gen_drop_index_syntax_difference() {
     let mysql = MySqlSqlGenerator;
-    let pg = PostgresSqlGenerator;
+    let pg = PostgresSqlGenerator::default();
 
     let mysql_sql = mysql.generate_drop_index("users", "idx_email");
     let pg_sql = pg.generate_drop_index("users", "idx_email");
@@ -631,6 +1018,9 @@ fn mysql_fk_invalid_action_falls_back() {
         ref_columns: vec!["id".to_string()],
         on_delete: "DROP TABLE".to_string(),
         on_update: "INVALID".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
     };
     let sql = sqlgen.generate_add_foreign_key("orders", &fk);
     assert!(sql.contains("ON DELETE NO ACTION"));
@@ -639,7 +1029,7 @@ fn mysql_fk_invalid_action_falls_back() {
 
 #[test]
 fn pg_fk_invalid_action_falls_back() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let fk = ForeignKey {
         name: "fk_bad".to_string(),
         columns: vec!["user_id".to_string()],
@@ -647,6 +1037,9 @@ fn pg_fk_invalid_action_falls_back() {
         ref_columns: vec!["id".to_string()],
         on_delete: "'; DROP TABLE users; --".to_string(),
         on_update: "WHATEVER".to_string(),
+        deferrable: false,
+        initially_deferred: false,
+        match_type: None,
     };
     let sql = sqlgen.generate_add_foreign_key("orders", &fk);
     assert!(sql.contains("ON DELETE NO ACTION"));
@@ -656,7 +1049,7 @@ fn pg_fk_invalid_action_falls_back() {
 #[test]
 fn cross_gen_drop_fk_syntax_difference() {
     let mysql = MySqlSqlGenerator;
-    let pg = PostgresSqlGenerator;
+    let pg = PostgresSqlGenerator::default();
 
     let mysql_sql = mysql.generate_drop_foreign_key("orders", "fk_user");
     let pg_sql = pg.generate_drop_foreign_key("orders", "fk_user");
@@ -673,7 +1066,7 @@ fn cross_gen_drop_fk_syntax_difference() {
 
 #[test]
 fn pg_bigint_auto_increment_becomes_bigserial() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("id", "bigint", false, true, 1);
     let sql = sqlgen.generate_add_column("users", &c);
     assert!(
@@ -689,7 +1082,7 @@ fn pg_bigint_auto_increment_becomes_bigserial() {
 
 #[test]
 fn pg_smallint_auto_increment_becomes_smallserial() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("id", "smallint", false, true, 1);
     let sql = sqlgen.generate_add_column("users", &c);
     assert!(
@@ -701,7 +1094,7 @@ fn pg_smallint_auto_increment_becomes_smallserial() {
 
 #[test]
 fn pg_int_auto_increment_becomes_serial() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("id", "integer", false, true, 1);
     let sql = sqlgen.generate_add_column("users", &c);
     assert!(
@@ -716,7 +1109,7 @@ fn pg_int_auto_increment_becomes_serial() {
 
 #[test]
 fn pg_modify_column_auto_increment_creates_sequence() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let c = col("id", "integer", false, true, 1);
     let sql = sqlgen.generate_modify_column("users", &c);
     assert!(
@@ -730,7 +1123,7 @@ fn pg_modify_column_auto_increment_creates_sequence() {
 
 #[test]
 fn pg_create_table_bigint_auto_increment_uses_bigserial() {
-    let sqlgen = PostgresSqlGenerator;
+    let sqlgen = PostgresSqlGenerator::default();
     let table = TableSchema {
         name: "events".to_string(),
         columns: vec![col("id", "bigint", false, true, 1)],
@@ -738,9 +1131,7 @@ fn pg_create_table_bigint_auto_increment_uses_bigserial() {
             name: Some("events_pkey".to_string()),
             columns: vec!["id".to_string()],
         }),
-        indexes: vec![],
-        foreign_keys: vec![],
-        unique_constraints: vec![],
+        ..Default::default()
     };
     let sql = sqlgen.generate_create_table(&table);
     assert!(
@@ -749,3 +1140,948 @@ fn pg_create_table_bigint_auto_increment_uses_bigserial() {
         sql
     );
 }
+
+#[test]
+fn pg_rebuild_table_preserves_source_column_order() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source = make_table(
+        "users",
+        vec![
+            col("id", "integer", false, true, 1),
+            col("email", "varchar(255)", false, false, 2),
+            col("name", "varchar(100)", true, false, 3),
+        ],
+    );
+    source.primary_key = Some(PrimaryKey {
+        name: Some("users_pkey".to_string()),
+        columns: vec!["id".to_string()],
+    });
+    source.indexes = vec![make_index("idx_email", vec!["email"], false)];
+    let target = make_table(
+        "users",
+        vec![
+            col("id", "integer", false, true, 1),
+            col("name", "varchar(100)", true, false, 2),
+        ],
+    );
+
+    let sql = sqlgen.generate_rebuild_table(&source, &target);
+    let lines: Vec<&str> = sql.lines().collect();
+    assert!(lines[0].starts_with("CREATE TABLE \"users__rebuild\""));
+    let insert = lines
+        .iter()
+        .position(|l| {
+            *l == "INSERT INTO \"users__rebuild\" (\"id\", \"name\") SELECT \"id\", \"name\" FROM ONLY \"users\";"
+        })
+        .expect("should copy shared columns, without inheriting tables' rows");
+    let drop = lines
+        .iter()
+        .position(|l| *l == "DROP TABLE \"users\";")
+        .expect("should drop the original table");
+    let rename = lines
+        .iter()
+        .position(|l| *l == "ALTER TABLE \"users__rebuild\" RENAME TO \"users\";")
+        .expect("should rename the rebuilt table");
+    let sequence = lines
+        .iter()
+        .position(|l| *l == "ALTER SEQUENCE \"users__rebuild_id_seq\" RENAME TO \"users_id_seq\";")
+        .expect("should give the new sequence the old one's name");
+    assert!(insert < drop && drop < rename && rename < sequence);
+    assert!(sql.contains("setval(pg_get_serial_sequence('\"users__rebuild\"', 'id')"));
+    assert!(sql.contains("CREATE INDEX \"idx_email\" ON \"users\""));
+}
+
+#[test]
+fn pg_rebuild_keeps_inbound_foreign_keys_and_primary_key_name() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut users = make_table(
+        "users",
+        vec![
+            col("id", "integer", false, false, 1),
+            col("email", "varchar(255)", false, false, 2),
+            col("name", "varchar(100)", true, false, 3),
+        ],
+    );
+    // Read back without a name: the implicit users_pkey.
+    users.primary_key = Some(PrimaryKey {
+        name: None,
+        columns: vec!["id".to_string()],
+    });
+    let mut orders = make_table(
+        "orders",
+        vec![
+            col("id", "integer", false, false, 1),
+            col("user_id", "integer", false, false, 2),
+        ],
+    );
+    orders.foreign_keys = vec![make_fk("fk_user", vec!["user_id"], "users", vec!["id"])];
+    let mut old_users = users.clone();
+    old_users.columns = vec![users.columns[0].clone(), users.columns[2].clone()];
+    let options = CompareOptions {
+        rebuild_for_column_order: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(
+        &[users, orders.clone()],
+        &[old_users, orders],
+        &sqlgen,
+        &options,
+    );
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::TableRebuilt);
+    let statements = &diffs[0].sql_statements();
+    assert_eq!(
+        statements[0],
+        "ALTER TABLE \"orders\" DROP CONSTRAINT \"fk_user\";"
+    );
+    let drop = statements
+        .iter()
+        .position(|s| s == "DROP TABLE \"users\";")
+        .unwrap();
+    assert!(drop > 0);
+    assert!(
+        statements.contains(
+            &"ALTER TABLE \"users\" RENAME CONSTRAINT \"users__rebuild_pkey\" TO \"users_pkey\";"
+                .to_string()
+        ),
+        "{:?}",
+        statements
+    );
+    assert!(
+        statements.last().unwrap().starts_with(
+            "ALTER TABLE \"orders\" ADD CONSTRAINT \"fk_user\" FOREIGN KEY (\"user_id\")"
+        ),
+        "{:?}",
+        statements
+    );
+}
+
+#[test]
+fn mysql_column_order_is_fixed_in_place() {
+    let sqlgen = MySqlSqlGenerator;
+    let source = make_table(
+        "users",
+        vec![
+            col("id", "int", false, false, 1),
+            col("email", "varchar(255)", false, false, 2),
+            col("name", "varchar(100)", true, false, 3),
+        ],
+    );
+    let target = make_table(
+        "users",
+        vec![
+            col("id", "int", false, false, 1),
+            col("name", "varchar(100)", true, false, 2),
+        ],
+    );
+    let options = CompareOptions {
+        rebuild_for_column_order: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(&[source], &[target], &sqlgen, &options);
+    let types: Vec<&DiffType> = diffs.iter().map(|d| &d.diff_type).collect();
+    assert_eq!(types, [&DiffType::ColumnAdded, &DiffType::TableRebuilt]);
+    assert_eq!(
+        diffs[1].sql,
+        "ALTER TABLE `users` MODIFY COLUMN `email` varchar(255) NOT NULL AFTER `id`;"
+    );
+}
+
+#[test]
+fn pg_create_table_with_tablespace() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut table = make_table("events", vec![col("id", "integer", false, false, 1)]);
+    table.tablespace = Some("fast_ssd".to_string());
+    let mut idx = make_index("idx_id", vec!["id"], false);
+    idx.tablespace = Some("index_space".to_string());
+    table.indexes = vec![idx];
+
+    let sql = sqlgen.generate_create_table(&table);
+    assert!(sql.contains(") TABLESPACE \"fast_ssd\";"), "got: {}", sql);
+    assert!(
+        sql.contains("CREATE INDEX \"idx_id\" ON \"events\" (\"id\") TABLESPACE \"index_space\";"),
+        "got: {}",
+        sql
+    );
+}
+
+#[test]
+fn pg_partial_index_tablespace_precedes_predicate() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut idx = make_index("idx_active", vec![], false);
+    idx.tablespace = Some("index_space".to_string());
+    idx.expression = Some(
+        "CREATE INDEX idx_active ON events USING btree (lower(name)) WITH (fillfactor='70') WHERE active"
+            .to_string(),
+    );
+    assert_eq!(
+        sqlgen.generate_add_index("events", &idx),
+        "CREATE INDEX idx_active ON events USING btree (lower(name)) WITH (fillfactor='70') \
+         TABLESPACE \"index_space\" WHERE active;"
+    );
+
+    // Not repeated when the definition already names it.
+    idx.expression = Some(
+        "CREATE INDEX idx_active ON events USING btree (lower(name)) TABLESPACE index_space WHERE active"
+            .to_string(),
+    );
+    assert_eq!(
+        sqlgen.generate_add_index("events", &idx),
+        "CREATE INDEX idx_active ON events USING btree (lower(name)) TABLESPACE index_space WHERE active;"
+    );
+}
+
+#[test]
+fn pg_set_tablespace_defaults_to_pg_default() {
+    let sqlgen = PostgresSqlGenerator::default();
+    assert_eq!(
+        sqlgen.generate_set_tablespace("events", None),
+        "ALTER TABLE \"events\" SET TABLESPACE \"pg_default\";"
+    );
+}
+
+#[test]
+fn mysql_create_table_with_tablespace() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut table = make_table("events", vec![col("id", "INT", false, false, 1)]);
+    table.tablespace = Some("ts1".to_string());
+    let sql = sqlgen.generate_create_table(&table);
+    assert!(sql.ends_with(") TABLESPACE `ts1`;"), "got: {}", sql);
+}
+
+#[test]
+fn mysql_invisible_index_round_trip() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut idx = make_index("idx_email", vec!["email"], false);
+    idx.visible = false;
+    let mut table = make_table("users", vec![col("email", "VARCHAR(255)", false, false, 1)]);
+    table.indexes = vec![idx.clone()];
+
+    let create = sqlgen.generate_create_table(&table);
+    assert!(
+        create.contains("  INDEX `idx_email` (`email`) /*!80000 INVISIBLE */"),
+        "got: {}",
+        create
+    );
+    assert_eq!(
+        sqlgen.generate_add_index("users", &idx),
+        "CREATE INDEX `idx_email` ON `users` (`email`) /*!80000 INVISIBLE */;"
+    );
+
+    // Making a visible index invisible is a modification that keeps the attribute
+    let mut target = table.clone();
+    target.indexes[0].visible = true;
+    let diffs = compare_schemas(&[table], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::IndexModified);
+    assert!(
+        diffs[0].sql.ends_with("/*!80000 INVISIBLE */;"),
+        "got: {}",
+        diffs[0].sql
+    );
+}
+
+#[test]
+fn mysql_invisible_column() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut column = col("secret", "INT", true, false, 1);
+    column.visible = false;
+    assert_eq!(
+        sqlgen.generate_add_column("users", &column),
+        "ALTER TABLE `users` ADD COLUMN `secret` INT NULL /*!80023 INVISIBLE */;"
+    );
+    assert!(
+        sqlgen
+            .generate_modify_column("users", &column)
+            .ends_with("DEFAULT NULL /*!80023 INVISIBLE */;")
+    );
+}
+
+#[test]
+fn mysql_on_update_current_timestamp_round_trip() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut updated_at = col_full(
+        "updated_at",
+        "timestamp(3)",
+        false,
+        Some("CURRENT_TIMESTAMP(3)"),
+        false,
+        None,
+        1,
+    );
+    updated_at.on_update = Some("CURRENT_TIMESTAMP(3)".to_string());
+    let source = make_table("audit", vec![updated_at.clone()]);
+
+    assert!(sqlgen.generate_create_table(&source).contains(
+        "`updated_at` timestamp(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3) ON UPDATE CURRENT_TIMESTAMP(3)"
+    ));
+
+    let mut target = source.clone();
+    target.columns[0].on_update = None;
+    let diffs = compare_schemas(std::slice::from_ref(&source), &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::OnUpdate]);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE `audit` MODIFY COLUMN `updated_at` timestamp(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3) ON UPDATE CURRENT_TIMESTAMP(3);"
+    );
+
+    let source = [source];
+    assert!(compare_schemas(&source, &source, &sqlgen).is_empty());
+}
+
+#[test]
+fn pg_fk_change_with_deferred_validation() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source = make_table("orders", vec![col("user_id", "integer", false, false, 1)]);
+    source.foreign_keys = vec![make_fk("fk_user", vec!["user_id"], "users", vec!["id"])];
+    let mut target = source.clone();
+    target.foreign_keys[0].on_delete = "RESTRICT".to_string();
+
+    let options = CompareOptions {
+        validate_foreign_keys_separately: true,
+        ..Default::default()
+    };
+    let diffs = compare_schemas_with_options(&[source], &[target], &sqlgen, &options);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ForeignKeyModified);
+    let stmts: Vec<&str> = diffs[0].sql.lines().collect();
+    assert_eq!(
+        stmts,
+        vec![
+            "ALTER TABLE \"orders\" DROP CONSTRAINT \"fk_user\";",
+            "ALTER TABLE \"orders\" ADD CONSTRAINT \"fk_user\" FOREIGN KEY (\"user_id\") REFERENCES \"users\" (\"id\") ON DELETE CASCADE ON UPDATE SET NULL NOT VALID;",
+            "ALTER TABLE \"orders\" VALIDATE CONSTRAINT \"fk_user\";",
+        ]
+    );
+}
+
+#[test]
+fn mysql_deferred_validation_falls_back_to_plain_add() {
+    let sqlgen = MySqlSqlGenerator;
+    let fk = make_fk("fk_user", vec!["user_id"], "users", vec!["id"]);
+    assert_eq!(
+        sqlgen.generate_add_foreign_key_deferred_validation("orders", &fk),
+        sqlgen.generate_add_foreign_key("orders", &fk)
+    );
+}
+
+#[test]
+fn pg_added_exclusion_constraint_detected_and_generated() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let target = make_table(
+        "bookings",
+        vec![
+            col("room", "integer", false, false, 1),
+            col("during", "tsrange", false, false, 2),
+        ],
+    );
+    let mut source = target.clone();
+    source.exclusion_constraints = vec![ExclusionConstraint {
+        name: "no_overlap".to_string(),
+        definition: "EXCLUDE USING gist (room WITH =, during WITH &&)".to_string(),
+    }];
+
+    let diffs = compare_schemas(std::slice::from_ref(&source), &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ExclusionConstraintAdded);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE \"bookings\" ADD CONSTRAINT \"no_overlap\" EXCLUDE USING gist (room WITH =, during WITH &&);"
+    );
+    assert!(
+        sqlgen.generate_create_table(&source).contains(
+            "  CONSTRAINT \"no_overlap\" EXCLUDE USING gist (room WITH =, during WITH &&)"
+        )
+    );
+}
+
+#[test]
+fn mysql_routine_replace_drops_then_creates() {
+    let sqlgen = MySqlSqlGenerator;
+    let routine = Routine {
+        name: "touch".to_string(),
+        kind: RoutineKind::Procedure,
+        definition: "CREATE PROCEDURE `touch`()\nBEGIN\n  UPDATE t SET a = 1;\nEND".to_string(),
+        language: "SQL".to_string(),
+    };
+
+    let sql = sqlgen.generate_replace_routine(&routine);
+    assert_eq!(
+        sql,
+        "DROP PROCEDURE IF EXISTS `touch`;\nCREATE PROCEDURE `touch`()\nBEGIN\n  UPDATE t SET a = 1;\nEND;"
+    );
+    assert_eq!(
+        database_structure_sync_lib::db::split_statements(&sql),
+        vec![
+            "DROP PROCEDURE IF EXISTS `touch`",
+            "CREATE PROCEDURE `touch`()\nBEGIN\n  UPDATE t SET a = 1;\nEND",
+        ]
+    );
+}
+
+#[test]
+fn mysql_generated_column_storage_change_recreates_column() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut source_total = col("total", "decimal(10,2)", true, false, 3);
+    source_total.generated = Some(GeneratedColumn {
+        expression: "(`price` * `qty`)".to_string(),
+        storage: GeneratedStorage::Stored,
+    });
+    let mut target_total = source_total.clone();
+    target_total.generated = Some(GeneratedColumn {
+        expression: "(`price` * `qty`)".to_string(),
+        storage: GeneratedStorage::Virtual,
+    });
+
+    let diffs = compare_schemas(
+        &[make_table("orders", vec![source_total])],
+        &[make_table("orders", vec![target_total])],
+        &sqlgen,
+    );
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Generated]);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE `orders` DROP COLUMN `total`;\n\
+         ALTER TABLE `orders` ADD COLUMN `total` decimal(10,2) GENERATED ALWAYS AS ((`price` * `qty`)) STORED NULL;"
+    );
+}
+
+#[test]
+fn mysql_generated_expression_change_modifies_in_place() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut source = col("total", "int", true, false, 1);
+    source.generated = Some(GeneratedColumn {
+        expression: "(`a` + `b`)".to_string(),
+        storage: GeneratedStorage::Virtual,
+    });
+    let mut target = source.clone();
+    target.generated = Some(GeneratedColumn {
+        expression: "(`a` - `b`)".to_string(),
+        storage: GeneratedStorage::Virtual,
+    });
+
+    assert_eq!(
+        sqlgen.generate_alter_column("orders", &source, &target),
+        "ALTER TABLE `orders` MODIFY COLUMN `total` int GENERATED ALWAYS AS ((`a` + `b`)) VIRTUAL NULL;"
+    );
+}
+
+#[test]
+fn pg_generated_expression_change_recreates_column() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source_total = col("total", "integer", true, false, 1);
+    source_total.generated = Some(GeneratedColumn {
+        expression: "(a + b)".to_string(),
+        storage: GeneratedStorage::Stored,
+    });
+    let mut target_total = source_total.clone();
+    target_total.generated = Some(GeneratedColumn {
+        expression: "(a - b)".to_string(),
+        storage: GeneratedStorage::Stored,
+    });
+
+    let diffs = compare_schemas(
+        &[make_table("orders", vec![source_total.clone()])],
+        &[make_table("orders", vec![target_total])],
+        &sqlgen,
+    );
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Generated]);
+    assert!(!diffs[0].sql.is_empty());
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE \"orders\" DROP COLUMN \"total\";\n\
+         ALTER TABLE \"orders\" ADD COLUMN \"total\" integer GENERATED ALWAYS AS ((a + b)) STORED;"
+    );
+
+    let mut plain = source_total.clone();
+    plain.generated = None;
+    assert_eq!(
+        sqlgen.generate_alter_column("orders", &plain, &source_total),
+        "ALTER TABLE \"orders\" ALTER COLUMN \"total\" DROP EXPRESSION;"
+    );
+}
+
+#[test]
+fn mysql_srid_only_change_modifies_column() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut source_location = col("location", "point", false, false, 2);
+    source_location.srid = Some(4326);
+    let mut target_location = source_location.clone();
+    target_location.srid = Some(3857);
+
+    let diffs = compare_schemas(
+        &[make_table("places", vec![source_location])],
+        &[make_table("places", vec![target_location])],
+        &sqlgen,
+    );
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::Srid]);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE `places` MODIFY COLUMN `location` point NOT NULL /*!80003 SRID 4326 */;"
+    );
+}
+
+fn pg_definition_index(name: &str, definition: &str) -> Index {
+    Index {
+        columns: vec![],
+        expression: Some(definition.to_string()),
+        storage_params: vec![],
+        ..make_index(name, vec![], false)
+    }
+}
+
+#[test]
+fn pg_nulls_last_index_is_regenerated_and_modified() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let nulls_last = pg_definition_index(
+        "idx_tasks_due",
+        "CREATE INDEX idx_tasks_due ON tasks USING btree (due_at NULLS LAST)",
+    );
+    assert_eq!(
+        sqlgen.generate_add_index("tasks", &nulls_last),
+        "CREATE INDEX idx_tasks_due ON tasks USING btree (due_at NULLS LAST);"
+    );
+
+    // The target's index was built with the default ordering.
+    let mut source = make_table("tasks", vec![col("due_at", "timestamp", true, false, 1)]);
+    source.indexes = vec![nulls_last];
+    let mut target = source.clone();
+    target.indexes = vec![make_index("idx_tasks_due", vec!["due_at"], false)];
+
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::IndexModified);
+    assert!(diffs[0].sql.contains("(due_at NULLS LAST)"));
+}
+
+#[test]
+fn pg_collated_index_round_trips() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let collated = pg_definition_index(
+        "idx_users_name_c",
+        "CREATE INDEX idx_users_name_c ON users USING btree (name COLLATE \"C\")",
+    );
+    assert_eq!(
+        sqlgen.generate_add_index("users", &collated),
+        "CREATE INDEX idx_users_name_c ON users USING btree (name COLLATE \"C\");"
+    );
+
+    let mut table = make_table("users", vec![col("name", "text", false, false, 1)]);
+    table.indexes = vec![collated];
+    let mut plain = table.clone();
+    plain.indexes = vec![make_index("idx_users_name_c", vec!["name"], false)];
+
+    assert!(
+        compare_schemas(
+            std::slice::from_ref(&table),
+            std::slice::from_ref(&table),
+            &sqlgen
+        )
+        .is_empty()
+    );
+    let diffs = compare_schemas(&[table], &[plain], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::IndexModified);
+}
+
+#[test]
+fn mysql_removing_auto_increment_redefines_column_and_keeps_pk() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut source = make_table("users", vec![col("id", "int", false, false, 1)]);
+    source.primary_key = Some(PrimaryKey {
+        name: Some("PRIMARY".to_string()),
+        columns: vec!["id".to_string()],
+    });
+    let mut target = source.clone();
+    target.columns[0].auto_increment = true;
+
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnModified);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::AutoIncrement]);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE `users` MODIFY COLUMN `id` int NOT NULL;"
+    );
+}
+
+fn online_ddl() -> CompareOptions {
+    CompareOptions {
+        online_ddl: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn mysql_online_ddl_hint_on_added_index_and_nullable_column() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut source = make_table(
+        "users",
+        vec![
+            col("id", "int", false, true, 1),
+            col("email", "varchar(255)", true, false, 2),
+        ],
+    );
+    source.indexes = vec![make_index("idx_email", vec!["email"], false)];
+    let target = make_table("users", vec![col("id", "int", false, true, 1)]);
+
+    let diffs = compare_schemas_with_options(&[source], &[target], &sqlgen, &online_ddl());
+    let sql: Vec<&str> = diffs.iter().map(|d| d.sql.as_str()).collect();
+    assert_eq!(
+        sql,
+        vec![
+            "ALTER TABLE `users` ADD COLUMN `email` varchar(255) NULL, ALGORITHM=INPLACE, LOCK=NONE;",
+            "CREATE INDEX `idx_email` ON `users` (`email`) ALGORITHM=INPLACE LOCK=NONE;",
+        ]
+    );
+}
+
+#[test]
+fn mysql_online_ddl_hint_omitted_for_primary_key_column() {
+    let sqlgen = MySqlSqlGenerator;
+    // A new AUTO_INCREMENT primary key column makes InnoDB rebuild the table.
+    let mut source = make_table(
+        "events",
+        vec![
+            col("id", "bigint", false, true, 1),
+            col("name", "varchar(50)", true, false, 2),
+        ],
+    );
+    source.primary_key = Some(PrimaryKey {
+        name: Some("PRIMARY".to_string()),
+        columns: vec!["id".to_string()],
+    });
+    let target = make_table("events", vec![col("name", "varchar(50)", true, false, 2)]);
+
+    let diffs = compare_schemas_with_options(&[source], &[target], &sqlgen, &online_ddl());
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnAdded);
+    assert!(!diffs[0].sql.contains("ALGORITHM"), "{}", diffs[0].sql);
+    assert_eq!(diffs[1].diff_type, DiffType::PrimaryKeyModified);
+    assert_eq!(diffs[1].sql, "ALTER TABLE `events` ADD PRIMARY KEY (`id`);");
+
+    let mut fulltext = make_index("ft_name", vec!["name"], false);
+    fulltext.index_type = "FULLTEXT".to_string();
+    assert!(
+        !sqlgen
+            .generate_add_index_online("events", &fulltext)
+            .contains("ALGORITHM")
+    );
+}
+
+#[test]
+fn not_null_column_without_default_gets_implicit_default_when_asked() {
+    let sqlgen = MySqlSqlGenerator;
+    let source = make_table(
+        "users",
+        vec![
+            col("id", "int", false, true, 1),
+            col("nickname", "varchar(50)", false, false, 2),
+            col("score", "int unsigned", false, false, 3),
+        ],
+    );
+    let target = make_table("users", vec![col("id", "int", false, true, 1)]);
+    let options = CompareOptions {
+        fill_implicit_defaults: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(&[source], &[target], &sqlgen, &options);
+    let sql: Vec<&str> = diffs.iter().map(|d| d.sql.as_str()).collect();
+    assert_eq!(
+        sql,
+        vec![
+            "ALTER TABLE `users` ADD COLUMN `nickname` varchar(50) NOT NULL DEFAULT '';",
+            "ALTER TABLE `users` ADD COLUMN `score` int unsigned NOT NULL DEFAULT 0;",
+        ]
+    );
+    assert!(diffs[0].warnings[0].message.contains("DEFAULT ''"));
+    assert!(diffs[1].warnings[0].message.contains("DEFAULT 0"));
+}
+
+#[test]
+fn not_null_column_without_default_is_warned_by_default() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let source = make_table(
+        "users",
+        vec![
+            col("id", "integer", false, true, 1),
+            col("nickname", "varchar(50)", false, false, 2),
+            col("score", "numeric(10,2)", false, false, 3),
+        ],
+    );
+    let target = make_table("users", vec![col("id", "integer", false, true, 1)]);
+
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 2);
+    for diff in &diffs {
+        assert!(!diff.sql.contains("DEFAULT"), "{}", diff.sql);
+        assert_eq!(
+            diff.warnings[0].message,
+            "NOT NULL without a default; adding it fails if the table has rows"
+        );
+    }
+}
+
+fn make_policy(name: &str, using_expr: &str) -> Policy {
+    Policy {
+        name: name.to_string(),
+        table: "orders".to_string(),
+        command: "ALL".to_string(),
+        permissive: true,
+        roles: vec!["public".to_string()],
+        using_expr: Some(using_expr.to_string()),
+        check_expr: None,
+    }
+}
+
+#[test]
+fn pg_added_policy_and_row_security_are_detected() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source = make_table("orders", vec![col("id", "integer", false, true, 1)]);
+    source.row_security = true;
+    source.policies = vec![make_policy(
+        "tenant_isolation",
+        "(tenant_id = current_setting('app.tenant')::integer)",
+    )];
+    let target = make_table("orders", vec![col("id", "integer", false, true, 1)]);
+
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    let kinds: Vec<_> = diffs.iter().map(|d| d.diff_type.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![DiffType::RowSecurityModified, DiffType::PolicyAdded]
+    );
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE \"orders\" ENABLE ROW LEVEL SECURITY;"
+    );
+    assert_eq!(
+        diffs[1].sql,
+        "CREATE POLICY \"tenant_isolation\" ON \"orders\" FOR ALL TO PUBLIC \
+         USING ((tenant_id = current_setting('app.tenant')::integer));"
+    );
+}
+
+#[test]
+fn pg_changed_policy_expression_is_altered_in_place() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source = make_table("orders", vec![col("id", "integer", false, true, 1)]);
+    source.policies = vec![make_policy("owner_only", "(owner = CURRENT_USER)")];
+    let mut target = source.clone();
+    target.policies = vec![make_policy("owner_only", "true")];
+
+    let diffs = compare_schemas(&[source.clone()], &[target.clone()], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::PolicyModified);
+    assert!(
+        diffs[0]
+            .sql
+            .starts_with("ALTER POLICY \"owner_only\" ON \"orders\""),
+        "{}",
+        diffs[0].sql
+    );
+
+    // A different command can't be altered; the policy is recreated.
+    target.policies[0].command = "SELECT".to_string();
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    assert!(diffs[0].sql.contains("DROP POLICY"), "{}", diffs[0].sql);
+    assert!(diffs[0].sql.contains("CREATE POLICY"), "{}", diffs[0].sql);
+}
+
+#[test]
+fn partitioning_change_rebuilds_table() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source = make_table(
+        "events",
+        vec![
+            col("id", "integer", false, false, 1),
+            col("created", "date", false, false, 2),
+        ],
+    );
+    source.partitioning = Some(PartitionSpec {
+        method: "RANGE".to_string(),
+        expression: "created".to_string(),
+        partitions: vec![Partition {
+            name: "events_2024".to_string(),
+            bound: "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')".to_string(),
+        }],
+    });
+    let mut target = source.clone();
+    target.partitioning = None;
+
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::PartitioningModified);
+    assert_eq!(diffs[0].target_def, None);
+    let sql = &diffs[0].sql;
+    assert!(sql.contains(") PARTITION BY RANGE (created);"), "{}", sql);
+    assert!(
+        sql.contains(
+            "CREATE TABLE \"events_2024__rebuild\" PARTITION OF \"events__rebuild\" \
+             FOR VALUES FROM ('2024-01-01') TO ('2025-01-01');"
+        ),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.ends_with("ALTER TABLE \"events_2024__rebuild\" RENAME TO \"events_2024\";"),
+        "{}",
+        sql
+    );
+}
+
+#[test]
+fn pg_partitioning_rebuild_lists_other_changes_unselected() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source = make_table(
+        "events",
+        vec![
+            col("id", "integer", false, false, 1),
+            col("created", "date", false, false, 2),
+        ],
+    );
+    source.partitioning = Some(PartitionSpec {
+        method: "RANGE".to_string(),
+        expression: "created".to_string(),
+        partitions: vec![],
+    });
+    let mut target = make_table("events", vec![col("id", "integer", false, false, 1)]);
+    target.partitioning = None;
+
+    let diffs = compare_schemas(&[source], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].diff_type, DiffType::PartitioningModified);
+    assert!(diffs[0].selected);
+    assert_eq!(diffs[1].diff_type, DiffType::ColumnAdded);
+    assert!(!diffs[1].selected, "the rebuild already adds the column");
+}
+
+#[test]
+fn mysql_partitioning_change_alters_table_in_place() {
+    let sqlgen = MySqlSqlGenerator;
+    let mut source = make_table(
+        "events",
+        vec![
+            col("id", "int", false, false, 1),
+            col("created", "date", false, false, 2),
+        ],
+    );
+    source.partitioning = Some(PartitionSpec {
+        method: "RANGE".to_string(),
+        expression: "YEAR(created)".to_string(),
+        partitions: vec![Partition {
+            name: "p2024".to_string(),
+            bound: "VALUES LESS THAN (2025)".to_string(),
+        }],
+    });
+    let target = make_table("events", vec![col("id", "int", false, false, 1)]);
+
+    // Partitioning is added once the rest of the table matches.
+    let diffs = compare_schemas(
+        std::slice::from_ref(&source),
+        std::slice::from_ref(&target),
+        &sqlgen,
+    );
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnAdded);
+    assert!(diffs[0].selected);
+    assert_eq!(diffs[1].diff_type, DiffType::PartitioningModified);
+    assert_eq!(
+        diffs[1].sql,
+        "ALTER TABLE `events` PARTITION BY RANGE (YEAR(created))\n\
+         (PARTITION `p2024` VALUES LESS THAN (2025));"
+    );
+
+    // ...and removed before anything else changes.
+    let diffs = compare_schemas(&[target], &[source], &sqlgen);
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].sql, "ALTER TABLE `events` REMOVE PARTITIONING;");
+    assert_eq!(diffs[1].diff_type, DiffType::ColumnRemoved);
+}
+
+#[test]
+fn rename_with_widening_type_is_one_change_not_drop_and_add() {
+    let source = make_table(
+        "users",
+        vec![
+            col("id", "int", false, true, 1),
+            col("email_address", "varchar(255)", false, false, 2),
+        ],
+    );
+    let target = make_table(
+        "users",
+        vec![
+            col("id", "int", false, true, 1),
+            col("email", "varchar(100)", false, false, 2),
+        ],
+    );
+    let renames = CompareOptions {
+        detect_column_renames: true,
+        ..Default::default()
+    };
+
+    let diffs = compare_schemas_with_options(
+        std::slice::from_ref(&source),
+        std::slice::from_ref(&target),
+        &MySqlSqlGenerator,
+        &renames,
+    );
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::ColumnRenamed);
+    assert_eq!(diffs[0].changes, vec![ColumnChangeKind::DataType]);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE `users` CHANGE COLUMN `email` `email_address` varchar(255) NOT NULL;"
+    );
+
+    let diffs = compare_schemas_with_options(
+        std::slice::from_ref(&source),
+        std::slice::from_ref(&target),
+        &PostgresSqlGenerator::default(),
+        &renames,
+    );
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE \"users\" RENAME COLUMN \"email\" TO \"email_address\";\n\
+         ALTER TABLE \"users\" ALTER COLUMN \"email_address\" TYPE varchar(255);"
+    );
+
+    // Without the flag the column is dropped and added.
+    let diffs = compare_schemas(&[source], &[target], &MySqlSqlGenerator);
+    let types: Vec<DiffType> = diffs.into_iter().map(|d| d.diff_type).collect();
+    assert_eq!(types, vec![DiffType::ColumnAdded, DiffType::ColumnRemoved]);
+}
+
+#[test]
+fn pg_inheritance_change_moves_the_table_between_parents() {
+    let sqlgen = PostgresSqlGenerator::default();
+    let mut source = make_table("invoices", vec![col("id", "integer", false, false, 1)]);
+    source.inherits = vec!["documents".to_string()];
+    let mut target = source.clone();
+    target.inherits = vec!["records".to_string()];
+
+    let diffs = compare_schemas(&[source.clone()], &[target], &sqlgen);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].diff_type, DiffType::InheritanceModified);
+    assert_eq!(diffs[0].severity, Severity::High);
+    assert_eq!(
+        diffs[0].sql,
+        "ALTER TABLE \"invoices\" NO INHERIT \"records\";\n\
+         ALTER TABLE \"invoices\" INHERIT \"documents\";"
+    );
+
+    let mut reordered = source.clone();
+    source.inherits.push("audited".to_string());
+    reordered.inherits.insert(0, "audited".to_string());
+    assert!(compare_schemas(&[source], &[reordered], &sqlgen).is_empty());
+}